@@ -4,17 +4,25 @@
 // @author Dennis Kuhnert <dennis.kuhnert@campus.tu-berlin.de>
 // @date 2017
 
-use bincode::{deserialize_from, serialize_into, Infinite};
+mod lsp;
+
+use bincode::{serialize_into, Infinite};
 use clap::{App, AppSettings, Arg, SubCommand};
 use serde_json::Value;
 use std::env;
+use std::fs;
 use std::fs::File;
-use std::io::{stdin, BufReader, BufWriter, Read, Write};
+use std::io::{stdin, BufRead, BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
 use std::string::String;
-use zokrates_core::compile::compile;
+use zokrates_core::commitment::commitment;
+use zokrates_core::compile::{compile, compile_with_config, compile_with_warnings, CompileConfig};
+use zokrates_core::diagnostics::Diagnostic;
+use zokrates_core::differential::run_differential;
+use zokrates_core::format;
 use zokrates_core::ir;
 use zokrates_core::proof_system::*;
+use zokrates_core::testing::run_tests;
 use zokrates_field::field::{Field, FieldPrime};
 use zokrates_fs_resolver::resolve as fs_resolve;
 
@@ -25,6 +33,50 @@ fn main() {
     })
 }
 
+// formats `diagnostic`, quoting `source`, the code of the program at `path`, if it points to it,
+// or the code of the imported file it points to
+fn format_diagnostic(
+    diagnostic: Diagnostic,
+    path: &Path,
+    location: &String,
+    source: &str,
+    json: bool,
+) -> String {
+    // the compiler refers to the program by the location its imports are resolved from
+    let (diagnostic, source) = match diagnostic.file.as_ref() == Some(location) {
+        true => (
+            Diagnostic {
+                file: Some(path.display().to_string()),
+                ..diagnostic
+            },
+            Some(source.to_string()),
+        ),
+        false => {
+            let source = match json {
+                true => None,
+                false => diagnostic
+                    .file
+                    .as_ref()
+                    .and_then(|file| read_import(location, file)),
+            };
+            (diagnostic, source)
+        }
+    };
+
+    match json {
+        true => serde_json::to_string(&diagnostic).unwrap(),
+        false => diagnostic.render(source.as_ref().map(String::as_str)),
+    }
+}
+
+// reads the code of the file imported as `file` by the program at `location`, if it can be found
+fn read_import(location: &String, file: &String) -> Option<String> {
+    let (mut reader, _, _) = fs_resolve(&Some(location.clone()), file).ok()?;
+    let mut source = String::new();
+    reader.read_to_string(&mut source).ok()?;
+    Some(source)
+}
+
 fn cli() -> Result<(), String> {
     const FLATTENED_CODE_DEFAULT_PATH: &str = "out";
     const VERIFICATION_KEY_DEFAULT_PATH: &str = "verification.key";
@@ -32,6 +84,8 @@ fn cli() -> Result<(), String> {
     const VERIFICATION_CONTRACT_DEFAULT_PATH: &str = "verifier.sol";
     const WITNESS_DEFAULT_PATH: &str = "witness";
     const JSON_PROOF_PATH: &str = "proof.json";
+    const PROOFS_DEFAULT_PATH: &str = "proofs";
+    const SNARKJS_DEFAULT_PATH: &str = "snarkjs";
     let default_scheme = env::var("ZOKRATES_PROVING_SCHEME").unwrap_or(String::from("g16"));
 
     // cli specification using clap library
@@ -61,6 +115,22 @@ fn cli() -> Result<(), String> {
             .long("light")
             .help("Skip logs and human readable output")
             .required(false)
+        ).arg(Arg::with_name("commit-public-inputs")
+            .long("commit-public-inputs")
+            .help("Replace the public inputs and outputs of the program by a single sha256 commitment to them, which verifiers compute from the original values. Only supported by G16")
+            .required(false)
+        ).arg(Arg::with_name("strip-logs")
+            .long("strip-logs")
+            .help("Remove the log statements of the program, which are otherwise printed when computing a witness")
+            .required(false)
+        ).arg(Arg::with_name("error-format")
+            .long("error-format")
+            .help("Format in which errors and warnings are printed. In json, each of them is printed to stderr as an object on its own line")
+            .value_name("FORMAT")
+            .takes_value(true)
+            .possible_values(&["human", "json"])
+            .default_value("human")
+            .required(false)
         )
      )
     .subcommand(SubCommand::with_name("setup")
@@ -104,6 +174,13 @@ fn cli() -> Result<(), String> {
             .long("light")
             .help("Skip logs and human readable output")
             .required(false)
+        ).arg(Arg::with_name("insecure-seed")
+            .long("insecure-seed")
+            .help("INSECURE, for tests only: derive all randomness from this seed, so that the same keys are generated on every run. Anyone knowing the seed can forge proofs")
+            .hidden(true)
+            .value_name("SEED")
+            .takes_value(true)
+            .required(false)
         )
     )
     .subcommand(SubCommand::with_name("export-verifier")
@@ -128,11 +205,19 @@ fn cli() -> Result<(), String> {
         ).arg(Arg::with_name("proving-scheme")
             .short("s")
             .long("proving-scheme")
-            .help("Proving scheme to use to export the verifier. Available options are G16 (default), PGHR13 and GM17")
+            .help("Proving scheme to use to export the verifier. Available options are G16, PGHR13 and GM17. Defaults to the scheme of the verification key")
             .value_name("FILE")
             .takes_value(true)
             .required(false)
-            .default_value(&default_scheme)
+        ).arg(Arg::with_name("target")
+            .short("t")
+            .long("target")
+            .help("Flavour of the verifier: `abiv2` takes the proof as a struct, `optimized` hard-codes the verification key and takes calldata, `library` shares the verification logic in a library. Only `default` is available for PGHR13 and GM17")
+            .value_name("TARGET")
+            .takes_value(true)
+            .possible_values(&SolidityTarget::VALUES)
+            .required(false)
+            .default_value("default")
         )
     )
     .subcommand(SubCommand::with_name("compute-witness")
@@ -164,6 +249,11 @@ fn cli() -> Result<(), String> {
             .long("light")
             .help("Skip logs and human readable output")
             .required(false)
+        ).arg(Arg::with_name("verbose")
+            .short("v")
+            .long("verbose")
+            .help("Print the whole witness, with the names of the variables in the source")
+            .required(false)
         )
     )
     .subcommand(SubCommand::with_name("generate-proof")
@@ -208,6 +298,302 @@ fn cli() -> Result<(), String> {
             .takes_value(true)
             .required(false)
             .default_value(&default_scheme)
+        ).arg(Arg::with_name("insecure-seed")
+            .long("insecure-seed")
+            .help("INSECURE, for tests only: derive all randomness from this seed, so that the same proof is generated on every run. Anyone knowing the seed can recover the witness")
+            .hidden(true)
+            .value_name("SEED")
+            .takes_value(true)
+            .required(false)
+        )
+    )
+    .subcommand(SubCommand::with_name("generate-proofs")
+        .about("Calculates one proof per witness, loading the compiled program and the proving key only once.")
+        .arg(Arg::with_name("witnesses")
+            .short("w")
+            .long("witnesses")
+            .help("Path of a directory of witness files, or of a file with one JSON witness per line. Use '-' to read JSON witnesses from stdin")
+            .value_name("PATH")
+            .takes_value(true)
+            .required(true)
+        ).arg(Arg::with_name("provingkey")
+            .short("p")
+            .long("provingkey")
+            .help("Path of the proving key file")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(PROVING_KEY_DEFAULT_PATH)
+        ).arg(Arg::with_name("output")
+            .short("o")
+            .long("output")
+            .help("Path of the directory the JSON proof files are written to")
+            .value_name("DIRECTORY")
+            .takes_value(true)
+            .required(false)
+            .default_value(PROOFS_DEFAULT_PATH)
+        ).arg(Arg::with_name("input")
+            .short("i")
+            .long("input")
+            .help("Path of compiled code")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(FLATTENED_CODE_DEFAULT_PATH)
+        ).arg(Arg::with_name("threads")
+            .short("t")
+            .long("threads")
+            .help("Number of proofs to generate in parallel")
+            .value_name("COUNT")
+            .takes_value(true)
+            .required(false)
+            .default_value("1")
+        ).arg(Arg::with_name("proving-scheme")
+            .short("s")
+            .long("proving-scheme")
+            .help("Proving scheme to use to generate the proofs. Available options are G16 (default), PGHR13 and GM17")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(&default_scheme)
+        ).arg(Arg::with_name("insecure-seed")
+            .long("insecure-seed")
+            .help("INSECURE, for tests only: derive all randomness from this seed, so that the same proofs are generated on every run. Anyone knowing the seed can recover the witnesses")
+            .hidden(true)
+            .value_name("SEED")
+            .takes_value(true)
+            .required(false)
+        )
+    )
+    .subcommand(SubCommand::with_name("verify")
+        .about("Verifies a given proof with the given verification key")
+        .arg(Arg::with_name("verification-key-path")
+            .short("v")
+            .long("verification-key-path")
+            .help("Path of the verification key file")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(VERIFICATION_KEY_DEFAULT_PATH)
+        ).arg(Arg::with_name("proofpath")
+            .short("j")
+            .long("proofpath")
+            .help("Path of the JSON proof file")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(JSON_PROOF_PATH)
+        ).arg(Arg::with_name("proving-scheme")
+            .short("s")
+            .long("proving-scheme")
+            .help("Proving scheme of the proof. Available options are G16, PGHR13 and GM17. Defaults to the scheme of the verification key")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+        ).arg(Arg::with_name("rerandomization-of")
+            .long("rerandomization-of")
+            .help("Path of another G16 JSON proof. Also checks whether both proofs are different valid proofs of the same public inputs, as re-randomizations of each other are")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+        )
+    )
+    .subcommand(SubCommand::with_name("rerandomize")
+        .about("Turns a G16 proof into a fresh proof of the same public inputs which cannot be linked to it")
+        .arg(Arg::with_name("verification-key-path")
+            .short("v")
+            .long("verification-key-path")
+            .help("Path of the verification key file")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(VERIFICATION_KEY_DEFAULT_PATH)
+        ).arg(Arg::with_name("proofpath")
+            .short("j")
+            .long("proofpath")
+            .help("Path of the JSON proof file")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(JSON_PROOF_PATH)
+        ).arg(Arg::with_name("output")
+            .short("o")
+            .long("output")
+            .help("Path of the re-randomized JSON proof file")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(JSON_PROOF_PATH)
+        )
+    )
+    .subcommand(SubCommand::with_name("export-r1cs")
+        .about("Exports the constraint system of a compiled program in the R1CS formats of the circom ecosystem")
+        .arg(Arg::with_name("input")
+            .short("i")
+            .long("input")
+            .help("Path of compiled code")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(FLATTENED_CODE_DEFAULT_PATH)
+        ).arg(Arg::with_name("output")
+            .short("o")
+            .long("output")
+            .help("Base path of the exported files, to which the .r1cs, .json and .wtns extensions are added")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(FLATTENED_CODE_DEFAULT_PATH)
+        ).arg(Arg::with_name("witness")
+            .short("w")
+            .long("witness")
+            .help("Path of a witness file to export in the .wtns format")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+        )
+    )
+    .subcommand(SubCommand::with_name("export-snarkjs")
+        .about("Converts a G16 verification key and proof to the snarkjs format")
+        .arg(Arg::with_name("verification-key-path")
+            .short("v")
+            .long("verification-key-path")
+            .help("Path of the verification key file")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(VERIFICATION_KEY_DEFAULT_PATH)
+        ).arg(Arg::with_name("proofpath")
+            .short("j")
+            .long("proofpath")
+            .help("Path of the JSON proof file")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(JSON_PROOF_PATH)
+        ).arg(Arg::with_name("output")
+            .short("o")
+            .long("output")
+            .help("Directory to write verification_key.json, proof.json and public.json to")
+            .value_name("DIRECTORY")
+            .takes_value(true)
+            .required(false)
+            .default_value(SNARKJS_DEFAULT_PATH)
+        )
+    )
+    .subcommand(SubCommand::with_name("import-snarkjs")
+        .about("Converts a snarkjs Groth16 verification key and proof to the G16 format")
+        .arg(Arg::with_name("input")
+            .short("i")
+            .long("input")
+            .help("Directory to read verification_key.json, proof.json and public.json from")
+            .value_name("DIRECTORY")
+            .takes_value(true)
+            .required(false)
+            .default_value(SNARKJS_DEFAULT_PATH)
+        ).arg(Arg::with_name("verification-key-path")
+            .short("v")
+            .long("verification-key-path")
+            .help("Path of the generated verification key file")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(VERIFICATION_KEY_DEFAULT_PATH)
+        ).arg(Arg::with_name("proofpath")
+            .short("j")
+            .long("proofpath")
+            .help("Path of the generated JSON proof file")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(false)
+            .default_value(JSON_PROOF_PATH)
+        )
+    )
+     .subcommand(SubCommand::with_name("lsp")
+        .about("Starts a language server for ZoKrates source files, communicating over stdin and stdout")
+    )
+     .subcommand(SubCommand::with_name("fmt")
+        .about("Formats a source file in place")
+        .arg(Arg::with_name("input")
+            .short("i")
+            .long("input")
+            .help("Path of the source code")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(true)
+        ).arg(Arg::with_name("check")
+            .long("check")
+            .help("Fail if the file is not formatted, without changing it")
+            .required(false)
+        )
+    )
+     .subcommand(SubCommand::with_name("test")
+        .about("Runs the test functions of a source file, or of all source files in a directory")
+        .arg(Arg::with_name("input")
+            .short("i")
+            .long("input")
+            .help("Path of the source code, or of a directory containing it")
+            .value_name("PATH")
+            .takes_value(true)
+            .required(true)
+        )
+    )
+     .subcommand(SubCommand::with_name("differential")
+        .about("Runs a program on random inputs both as written and as compiled, reporting the inputs on which they disagree")
+        .arg(Arg::with_name("input")
+            .short("i")
+            .long("input")
+            .help("Path of the source code")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(true)
+        ).arg(Arg::with_name("runs")
+            .short("n")
+            .long("runs")
+            .help("Number of random inputs to run the program on")
+            .value_name("COUNT")
+            .takes_value(true)
+            .required(false)
+            .default_value("100")
+        ).arg(Arg::with_name("seed")
+            .long("seed")
+            .help("Seed of the random inputs, which makes them reproducible")
+            .value_name("SEED")
+            .takes_value(true)
+            .required(false)
+            .default_value("0")
+        )
+    )
+     .subcommand(SubCommand::with_name("debug")
+        .about("Runs a program one statement at a time, printing the statements at the breakpoints and stopping at the first failing constraint")
+        .arg(Arg::with_name("input")
+            .short("i")
+            .long("input")
+            .help("Path of the source code")
+            .value_name("FILE")
+            .takes_value(true)
+            .required(true)
+        ).arg(Arg::with_name("arguments")
+            .short("a")
+            .long("arguments")
+            .help("Arguments for the program's main method as a space separated list")
+            .takes_value(true)
+            .multiple(true) // allows multiple values
+            .required(false)
+        ).arg(Arg::with_name("break")
+            .short("b")
+            .long("break")
+            .help("Line of the source code at which the statements are printed")
+            .value_name("LINE")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .required(false)
+        ).arg(Arg::with_name("step")
+            .short("s")
+            .long("step")
+            .help("Print every statement of the source code")
+            .required(false)
         )
     )
      .subcommand(SubCommand::with_name("print-proof")
@@ -252,13 +638,48 @@ fn cli() -> Result<(), String> {
 
             let hr_output_path = bin_output_path.to_path_buf().with_extension("code");
 
-            let file = File::open(path.clone()).unwrap();
+            let mut source = String::new();
+            File::open(path.clone())
+                .and_then(|mut file| file.read_to_string(&mut source))
+                .map_err(|why| format!("couldn't read {}: {}", path.display(), why))?;
 
-            let mut reader = BufReader::new(file);
+            let mut reader = BufReader::new(source.as_bytes());
 
-            let program_flattened: ir::Prog<FieldPrime> =
-                compile(&mut reader, Some(location), Some(fs_resolve))
-                    .map_err(|e| format!("Compilation failed:\n\n {}", e))?;
+            let config = CompileConfig {
+                commit_public_inputs: sub_matches.is_present("commit-public-inputs"),
+                strip_logs: sub_matches.is_present("strip-logs"),
+                ..CompileConfig::default()
+            };
+
+            let json = sub_matches.value_of("error-format").unwrap() == "json";
+
+            let (program_flattened, warnings): (ir::Prog<FieldPrime>, _) = compile_with_warnings(
+                &mut reader,
+                Some(location.clone()),
+                Some(fs_resolve),
+                config,
+            )
+            .map_err(|e| {
+                let errors = e
+                    .diagnostics()
+                    .into_iter()
+                    .map(|d| format_diagnostic(d, &path, &location, &source, json))
+                    .collect::<Vec<_>>();
+                match json {
+                    true => {
+                        eprintln!("{}", errors.join("\n"));
+                        format!("Compilation failed")
+                    }
+                    false => format!("Compilation failed:\n\n{}", errors.join("\n\n")),
+                }
+            })?;
+
+            for warning in warnings {
+                eprintln!(
+                    "{}",
+                    format_diagnostic(warning, &path, &location, &source, json)
+                );
+            }
 
             // number of constraints the flattened program will translate to.
             let num_constraints = program_flattened.constraint_count();
@@ -309,8 +730,7 @@ fn cli() -> Result<(), String> {
 
             let mut reader = BufReader::new(file);
 
-            let program_ast: ir::Prog<FieldPrime> =
-                deserialize_from(&mut reader, Infinite).map_err(|why| why.to_string())?;
+            let program_ast: ir::Prog<FieldPrime> = ir::Prog::read(&mut reader)?;
 
             // print deserialized flattened program
             if !sub_matches.is_present("light") {
@@ -359,10 +779,16 @@ fn cli() -> Result<(), String> {
             }
 
             let witness = program_ast
-                .execute(&arguments)
+                .execute_with_log(&arguments, |message| println!("log: {}", message))
                 .map_err(|e| format!("Execution failed: {}", e))?;
 
-            println!("\nWitness: \n\n{}", witness.format_outputs());
+            match sub_matches.is_present("verbose") {
+                true => println!(
+                    "\nWitness: \n\n{}",
+                    witness.format_with_names(&program_ast.names)
+                ),
+                false => println!("\nWitness: \n\n{}", witness.format_outputs()),
+            }
 
             // write witness to file
             let output_path = Path::new(sub_matches.value_of("output").unwrap());
@@ -386,34 +812,49 @@ fn cli() -> Result<(), String> {
 
             let mut reader = BufReader::new(file);
 
-            let program: ir::Prog<FieldPrime> =
-                deserialize_from(&mut reader, Infinite).map_err(|why| format!("{:?}", why))?;
+            let program: ir::Prog<FieldPrime> = ir::Prog::read(&mut reader)?;
 
             // print deserialized flattened program
             if !sub_matches.is_present("light") {
                 println!("{}", program);
             }
 
+            let scheme_name = sub_matches.value_of("proving-scheme").unwrap();
+            if !program.committed.is_empty() && scheme_name.to_lowercase() != "g16" {
+                return Err(format!(
+                    "Commitments to the public inputs are not supported by {}",
+                    scheme_name
+                ));
+            }
+
             // get paths for proving and verification keys
             let pk_path = sub_matches.value_of("proving-key-path").unwrap();
             let vk_path = sub_matches.value_of("verification-key-path").unwrap();
 
+            let seed = insecure_seed(sub_matches, scheme)?;
+
             // run setup phase
-            scheme.setup(program, pk_path, vk_path);
+            scheme.setup(program, pk_path, vk_path, seed);
         }
         ("export-verifier", Some(sub_matches)) => {
             {
-                let scheme = get_scheme(sub_matches.value_of("proving-scheme").unwrap())?;
+                // read vk file
+                let input_path = Path::new(sub_matches.value_of("input").unwrap());
+
+                let scheme = detect_scheme(&input_path, sub_matches.value_of("proving-scheme"))?;
 
                 println!("Exporting verifier...");
 
-                // read vk file
-                let input_path = Path::new(sub_matches.value_of("input").unwrap());
                 let input_file = File::open(&input_path)
                     .map_err(|why| format!("couldn't open {}: {}", input_path.display(), why))?;
                 let reader = BufReader::new(input_file);
 
-                let verifier = scheme.export_solidity_verifier(reader);
+                let target = sub_matches
+                    .value_of("target")
+                    .unwrap()
+                    .parse::<SolidityTarget>()?;
+
+                let verifier = scheme.export_solidity_verifier_for(reader, target)?;
 
                 //write output file
                 let output_path = Path::new(sub_matches.value_of("output").unwrap());
@@ -425,7 +866,22 @@ fn cli() -> Result<(), String> {
                 writer
                     .write_all(&verifier.as_bytes())
                     .map_err(|_| "Failed writing output to file.".to_string())?;
-                println!("Finished exporting verifier.");
+
+                // write the ABI of the verifier and a module encoding proofs as calls to it
+                let header = read_vk_header(&input_path)?;
+                let inputs = header.committed_inputs.unwrap_or(header.inputs);
+                let interface = scheme.verifier_interface(inputs, target)?;
+
+                let abi_path = output_path.with_extension("json");
+                write_file(&abi_path, &interface.abi())?;
+                let module_path = output_path.with_extension("rs");
+                write_file(&module_path, &interface.rust_module())?;
+
+                println!(
+                    "Finished exporting verifier. ABI written to {}, calldata encoder to {}",
+                    abi_path.display(),
+                    module_path.display()
+                );
             }
         }
         ("generate-proof", Some(sub_matches)) => {
@@ -452,13 +908,257 @@ fn cli() -> Result<(), String> {
 
             let mut reader = BufReader::new(program_file);
 
-            let program: ir::Prog<FieldPrime> =
-                deserialize_from(&mut reader, Infinite).map_err(|why| format!("{:?}", why))?;
+            let program: ir::Prog<FieldPrime> = ir::Prog::read(&mut reader)?;
+
+            let seed = insecure_seed(sub_matches, scheme)?;
 
             println!(
                 "generate-proof successful: {:?}",
-                scheme.generate_proof(program, witness, pk_path, proof_path)
+                scheme.generate_proof(program, witness, pk_path, proof_path, seed)
+            );
+        }
+        ("generate-proofs", Some(sub_matches)) => {
+            println!("Generating proofs...");
+
+            let scheme = get_scheme(sub_matches.value_of("proving-scheme").unwrap())?;
+
+            let threads: usize = sub_matches
+                .value_of("threads")
+                .unwrap()
+                .parse()
+                .map_err(|_| "Invalid number of threads".to_string())?;
+
+            let output_dir = Path::new(sub_matches.value_of("output").unwrap());
+            fs::create_dir_all(&output_dir)
+                .map_err(|why| format!("couldn't create {}: {}", output_dir.display(), why))?;
+
+            // collect (witness, name, proof path) jobs
+            let witnesses_path = sub_matches.value_of("witnesses").unwrap();
+            let jobs = match witnesses_path {
+                "-" => read_json_witnesses(BufReader::new(stdin()), &output_dir)?,
+                path if Path::new(path).is_dir() => read_witness_dir(Path::new(path), &output_dir)?,
+                path => {
+                    let file = File::open(&path)
+                        .map_err(|why| format!("couldn't open {}: {}", path, why))?;
+                    read_json_witnesses(BufReader::new(file), &output_dir)?
+                }
+            };
+
+            let pk_path = sub_matches.value_of("provingkey").unwrap();
+
+            let program_path = Path::new(sub_matches.value_of("input").unwrap());
+            let program_file = File::open(&program_path)
+                .map_err(|why| format!("couldn't open {}: {}", program_path.display(), why))?;
+
+            let mut reader = BufReader::new(program_file);
+
+            let program: ir::Prog<FieldPrime> = ir::Prog::read(&mut reader)?;
+
+            let seed = insecure_seed(sub_matches, scheme)?;
+
+            let count = jobs.len();
+
+            scheme.generate_proofs(program, jobs, pk_path, threads, seed)?;
+
+            println!(
+                "{} proof{} written to '{}'",
+                count,
+                if count == 1 { "" } else { "s" },
+                output_dir.display()
+            );
+        }
+        ("verify", Some(sub_matches)) => {
+            let vk_path = Path::new(sub_matches.value_of("verification-key-path").unwrap());
+
+            let scheme = detect_scheme(&vk_path, sub_matches.value_of("proving-scheme"))?;
+
+            if sub_matches.is_present("rerandomization-of") {
+                check_rerandomization_scheme(vk_path)?;
+            }
+
+            println!("Verifying proof...");
+
+            let vk_file = File::open(&vk_path)
+                .map_err(|why| format!("couldn't open {}: {}", vk_path.display(), why))?;
+
+            let proof_path = Path::new(sub_matches.value_of("proofpath").unwrap());
+            let proof_file = File::open(&proof_path)
+                .map_err(|why| format!("couldn't open {}: {}", proof_path.display(), why))?;
+
+            match scheme.verify(BufReader::new(vk_file), BufReader::new(proof_file))? {
+                true => println!("PASSED"),
+                false => Err("FAILED".to_string())?,
+            }
+
+            if let Some(other_path) = sub_matches.value_of("rerandomization-of") {
+                let other_path = Path::new(other_path);
+                let open = |path: &Path| {
+                    File::open(path)
+                        .map(BufReader::new)
+                        .map_err(|why| format!("couldn't open {}: {}", path.display(), why))
+                };
+
+                let rerandomization = G16 {}.is_rerandomization(
+                    open(vk_path)?,
+                    open(proof_path)?,
+                    open(other_path)?,
+                )?;
+
+                match rerandomization {
+                    true => println!(
+                        "{} and {} are different valid proofs of the same public inputs: they may be re-randomizations of each other",
+                        proof_path.display(),
+                        other_path.display()
+                    ),
+                    false => Err(format!(
+                        "{} and {} are not re-randomizations of each other",
+                        proof_path.display(),
+                        other_path.display()
+                    ))?,
+                }
+            }
+        }
+        ("rerandomize", Some(sub_matches)) => {
+            let vk_path = Path::new(sub_matches.value_of("verification-key-path").unwrap());
+            let proof_path = Path::new(sub_matches.value_of("proofpath").unwrap());
+            let output_path = Path::new(sub_matches.value_of("output").unwrap());
+
+            check_rerandomization_scheme(vk_path)?;
+
+            println!("Re-randomizing proof...");
+
+            let vk_file = File::open(&vk_path)
+                .map_err(|why| format!("couldn't open {}: {}", vk_path.display(), why))?;
+            let proof_file = File::open(&proof_path)
+                .map_err(|why| format!("couldn't open {}: {}", proof_path.display(), why))?;
+
+            let proof = G16 {}.rerandomize(BufReader::new(vk_file), BufReader::new(proof_file))?;
+            write_file(output_path, &proof)?;
+
+            println!("Re-randomized proof written to {}", output_path.display());
+        }
+        ("export-r1cs", Some(sub_matches)) => {
+            println!("Exporting constraint system...");
+
+            let path = Path::new(sub_matches.value_of("input").unwrap());
+            let file = File::open(&path)
+                .map_err(|why| format!("couldn't open {}: {}", path.display(), why))?;
+
+            let mut reader = BufReader::new(file);
+
+            let program: ir::Prog<FieldPrime> = ir::Prog::read(&mut reader)?;
+
+            let r1cs = ir::R1cs::from(program);
+
+            let output_path = Path::new(sub_matches.value_of("output").unwrap());
+            let r1cs_path = output_path.with_extension("r1cs");
+            let json_path = output_path.with_extension("json");
+
+            let r1cs_file = File::create(&r1cs_path)
+                .map_err(|why| format!("couldn't create {}: {}", r1cs_path.display(), why))?;
+            r1cs.write_binary(BufWriter::new(r1cs_file))
+                .map_err(|why| format!("couldn't write {}: {}", r1cs_path.display(), why))?;
+
+            let json_file = File::create(&json_path)
+                .map_err(|why| format!("couldn't create {}: {}", json_path.display(), why))?;
+            r1cs.write_json(BufWriter::new(json_file))
+                .map_err(|why| format!("couldn't write {}: {}", json_path.display(), why))?;
+
+            println!(
+                "Constraint system written to {} and {}",
+                r1cs_path.display(),
+                json_path.display()
             );
+
+            if let Some(witness_path) = sub_matches.value_of("witness") {
+                let witness_path = Path::new(witness_path);
+                let witness_file = File::open(&witness_path)
+                    .map_err(|why| format!("couldn't open {}: {}", witness_path.display(), why))?;
+                let witness = ir::Witness::read(witness_file)
+                    .map_err(|why| format!("could not load witness: {:?}", why))?;
+
+                let wtns_path = output_path.with_extension("wtns");
+                let wtns_file = File::create(&wtns_path)
+                    .map_err(|why| format!("couldn't create {}: {}", wtns_path.display(), why))?;
+                r1cs.write_witness(&witness, BufWriter::new(wtns_file))
+                    .map_err(|why| format!("couldn't write {}: {}", wtns_path.display(), why))?;
+
+                println!("Witness written to {}", wtns_path.display());
+            }
+        }
+        ("export-snarkjs", Some(sub_matches)) => {
+            let vk_path = Path::new(sub_matches.value_of("verification-key-path").unwrap());
+            let proof_path = Path::new(sub_matches.value_of("proofpath").unwrap());
+            let output_dir = Path::new(sub_matches.value_of("output").unwrap());
+
+            if !vk_path.is_file() && !proof_path.is_file() {
+                return Err(format!(
+                    "Neither {} nor {} exist",
+                    vk_path.display(),
+                    proof_path.display()
+                ));
+            }
+
+            fs::create_dir_all(output_dir)
+                .map_err(|why| format!("couldn't create {}: {}", output_dir.display(), why))?;
+
+            if vk_path.is_file() {
+                let vk_file = File::open(&vk_path)
+                    .map_err(|why| format!("couldn't open {}: {}", vk_path.display(), why))?;
+                let vk = G16 {}.export_snarkjs_vk(BufReader::new(vk_file))?;
+                write_file(&output_dir.join("verification_key.json"), &vk)?;
+            }
+
+            if proof_path.is_file() {
+                let proof_file = File::open(&proof_path)
+                    .map_err(|why| format!("couldn't open {}: {}", proof_path.display(), why))?;
+                let (proof, public) = G16 {}.export_snarkjs_proof(BufReader::new(proof_file))?;
+                write_file(&output_dir.join("proof.json"), &proof)?;
+                write_file(&output_dir.join("public.json"), &public)?;
+            }
+
+            println!("snarkjs files written to {}", output_dir.display());
+        }
+        ("import-snarkjs", Some(sub_matches)) => {
+            let input_dir = Path::new(sub_matches.value_of("input").unwrap());
+            let vk_path = Path::new(sub_matches.value_of("verification-key-path").unwrap());
+            let proof_path = Path::new(sub_matches.value_of("proofpath").unwrap());
+
+            let snarkjs_vk_path = input_dir.join("verification_key.json");
+            let snarkjs_proof_path = input_dir.join("proof.json");
+            let snarkjs_public_path = input_dir.join("public.json");
+
+            if !snarkjs_vk_path.is_file() && !snarkjs_proof_path.is_file() {
+                return Err(format!(
+                    "Neither {} nor {} exist",
+                    snarkjs_vk_path.display(),
+                    snarkjs_proof_path.display()
+                ));
+            }
+
+            if snarkjs_vk_path.is_file() {
+                let vk_file = File::open(&snarkjs_vk_path).map_err(|why| {
+                    format!("couldn't open {}: {}", snarkjs_vk_path.display(), why)
+                })?;
+                let vk = G16 {}.import_snarkjs_vk(BufReader::new(vk_file))?;
+                write_file(&vk_path, &vk)?;
+                println!("Verification key written to {}", vk_path.display());
+            }
+
+            if snarkjs_proof_path.is_file() {
+                let proof_file = File::open(&snarkjs_proof_path).map_err(|why| {
+                    format!("couldn't open {}: {}", snarkjs_proof_path.display(), why)
+                })?;
+                let public_file = File::open(&snarkjs_public_path).map_err(|why| {
+                    format!("couldn't open {}: {}", snarkjs_public_path.display(), why)
+                })?;
+                let proof = G16 {}.import_snarkjs_proof(
+                    BufReader::new(proof_file),
+                    BufReader::new(public_file),
+                )?;
+                write_file(&proof_path, &proof)?;
+                println!("Proof written to {}", proof_path.display());
+            }
         }
         ("print-proof", Some(sub_matches)) => {
             let format = sub_matches.value_of("format").unwrap();
@@ -471,13 +1171,15 @@ fn cli() -> Result<(), String> {
             let proof_object: Value =
                 serde_json::from_reader(file).map_err(|why| format!("{:?}", why))?;
 
+            let inputs = verifier_inputs(&proof_object)?;
+
             match format {
                 "json" => {
                     println!("~~~~~~~~ Copy the output below for valid ABIv2 format ~~~~~~~~");
                     println!();
                     print!("{}", proof_object["proof"]);
                     print!(",");
-                    println!("{}", proof_object["inputs"]);
+                    println!("{}", inputs);
                     println!();
                     println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
                 }
@@ -490,7 +1192,7 @@ fn cli() -> Result<(), String> {
                         print!(",");
                     }
 
-                    println!("{}", proof_object["inputs"]);
+                    println!("{}", inputs);
                     println!();
                     println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
                 }
@@ -500,22 +1202,489 @@ fn cli() -> Result<(), String> {
                         print!("{}", value);
                         print!(",");
                     }
-                    println!("{}", proof_object["inputs"]);
+                    println!("{}", inputs);
                 }
                 "testingV2" => {
                     //used by testing pipeline to generate arguments for contract call
                     print!("{}", proof_object["proof"]);
                     print!(",");
-                    println!("{}", proof_object["inputs"]);
+                    println!("{}", inputs);
                 }
                 _ => unreachable!(),
             }
         }
+        ("lsp", Some(_)) => {
+            lsp::run()?;
+        }
+        ("test", Some(sub_matches)) => {
+            let input = Path::new(sub_matches.value_of("input").unwrap());
+
+            let mut paths = vec![];
+            collect_sources(input, &mut paths)?;
+
+            let (mut passed, mut failed) = (0, 0);
+
+            for path in paths {
+                let location = path
+                    .parent()
+                    .unwrap()
+                    .to_path_buf()
+                    .into_os_string()
+                    .into_string()
+                    .unwrap();
+
+                let source = fs::read_to_string(&path)
+                    .map_err(|why| format!("couldn't read {}: {}", path.display(), why))?;
+
+                let reports = run_tests::<FieldPrime, _, _, _>(
+                    &mut BufReader::new(source.as_bytes()),
+                    Some(location.clone()),
+                    Some(fs_resolve),
+                )
+                .map_err(|e| {
+                    let errors = e
+                        .diagnostics()
+                        .into_iter()
+                        .map(|d| format_diagnostic(d, &path, &location, &source, false))
+                        .collect::<Vec<_>>();
+                    format!(
+                        "Compilation of {} failed:\n\n{}",
+                        path.display(),
+                        errors.join("\n\n")
+                    )
+                })?;
+
+                if reports.is_empty() {
+                    continue;
+                }
+
+                println!("Running {} tests in {}", reports.len(), path.display());
+
+                for report in reports {
+                    let constraints = match report.constraint_count {
+                        Some(count) => format!(" ({} constraints)", count),
+                        None => String::new(),
+                    };
+                    match (report.passed(), report.should_fail) {
+                        (true, false) => println!("test {} ... ok{}", report.name, constraints),
+                        (true, true) => println!(
+                            "test {} ... ok, failed as expected{}",
+                            report.name, constraints
+                        ),
+                        (false, _) => println!(
+                            "test {} ... FAILED{}\n\t{}",
+                            report.name,
+                            constraints,
+                            report.failure.unwrap()
+                        ),
+                    }
+                    match report.passed() {
+                        true => passed += 1,
+                        false => failed += 1,
+                    }
+                }
+                println!();
+            }
+
+            println!("{} passed, {} failed", passed, failed);
+
+            if failed > 0 {
+                return Err(format!(
+                    "{} test{} failed",
+                    failed,
+                    if failed == 1 { "" } else { "s" }
+                ));
+            }
+        }
+        ("differential", Some(sub_matches)) => {
+            let path = Path::new(sub_matches.value_of("input").unwrap());
+
+            let runs: usize = sub_matches
+                .value_of("runs")
+                .unwrap()
+                .parse()
+                .map_err(|_| "Invalid number of runs".to_string())?;
+
+            let seed: u64 = sub_matches
+                .value_of("seed")
+                .unwrap()
+                .parse()
+                .map_err(|_| "Invalid seed".to_string())?;
+
+            let location = path
+                .parent()
+                .unwrap()
+                .to_path_buf()
+                .into_os_string()
+                .into_string()
+                .unwrap();
+
+            let source = fs::read_to_string(path)
+                .map_err(|why| format!("couldn't read {}: {}", path.display(), why))?;
+
+            let report = run_differential::<FieldPrime, _, _, _>(
+                &mut BufReader::new(source.as_bytes()),
+                Some(location.clone()),
+                Some(fs_resolve),
+                runs,
+                seed,
+            )
+            .map_err(|e| {
+                let errors = e
+                    .diagnostics()
+                    .into_iter()
+                    .map(|d| format_diagnostic(d, path, &location, &source, false))
+                    .collect::<Vec<_>>();
+                format!("Compilation failed:\n\n{}", errors.join("\n\n"))
+            })?;
+
+            for mismatch in &report.mismatches {
+                println!("Mismatch on {}\n", mismatch);
+            }
+
+            println!(
+                "{} runs, {} failing in both versions, {} mismatches",
+                report.runs,
+                report.failures,
+                report.mismatches.len()
+            );
+
+            if !report.mismatches.is_empty() {
+                return Err(format!(
+                    "The compiled program disagrees with the source program on {} input{}",
+                    report.mismatches.len(),
+                    if report.mismatches.len() == 1 {
+                        ""
+                    } else {
+                        "s"
+                    }
+                ));
+            }
+        }
+        ("debug", Some(sub_matches)) => {
+            let path = Path::new(sub_matches.value_of("input").unwrap());
+
+            let location = path
+                .parent()
+                .unwrap()
+                .to_path_buf()
+                .into_os_string()
+                .into_string()
+                .unwrap();
+
+            let source = fs::read_to_string(path)
+                .map_err(|why| format!("couldn't read {}: {}", path.display(), why))?;
+
+            let config = CompileConfig {
+                debug: true,
+                ..CompileConfig::default()
+            };
+
+            let program: ir::Prog<FieldPrime> = compile_with_config(
+                &mut BufReader::new(source.as_bytes()),
+                Some(location.clone()),
+                Some(fs_resolve),
+                config,
+            )
+            .map_err(|e| {
+                let errors = e
+                    .diagnostics()
+                    .into_iter()
+                    .map(|d| format_diagnostic(d, path, &location, &source, false))
+                    .collect::<Vec<_>>();
+                format!("Compilation failed:\n\n{}", errors.join("\n\n"))
+            })?;
+
+            let arguments: Vec<_> = sub_matches
+                .values_of("arguments")
+                .map(|a| {
+                    a.map(|x| FieldPrime::try_from_dec_str(x).map_err(|_| x.to_string()))
+                        .collect()
+                })
+                .unwrap_or(Ok(vec![]))
+                .map_err(|e| format!("Could not parse argument: {}", e))?;
+
+            let breakpoints: Vec<usize> = sub_matches
+                .values_of("break")
+                .map(|b| {
+                    b.map(|l| l.parse().map_err(|_| format!("Invalid line: {}", l)))
+                        .collect()
+                })
+                .unwrap_or(Ok(vec![]))?;
+
+            let step = sub_matches.is_present("step");
+
+            let mut debugger = ir::Debugger::new(&program, &arguments)
+                .map_err(|e| format!("Execution failed: {}", e))?;
+
+            // whether the statements being executed are printed
+            let mut stopped = false;
+
+            while let Some(s) = debugger.step(|message| println!("log: {}", message)) {
+                match s {
+                    ir::Step::Location(position) => {
+                        stopped = step || breakpoints.contains(&position.line);
+                        if stopped {
+                            println!(
+                                "\n{}",
+                                format_location(path, &source, position.line, position.col)
+                            );
+                        }
+                    }
+                    ir::Step::Executed(statement) => {
+                        if stopped {
+                            print_statement(&debugger, statement);
+                        }
+                    }
+                    ir::Step::Failed(statement, error) => {
+                        match debugger.location() {
+                            Some(position) => println!(
+                                "\nExecution stopped at {}",
+                                format_location(path, &source, position.line, position.col)
+                            ),
+                            None => println!("\nExecution stopped"),
+                        };
+                        print_statement(&debugger, statement);
+                        return Err(format!("Execution failed: {}", error));
+                    }
+                }
+            }
+
+            println!("\nWitness: \n\n{}", debugger.witness().format_outputs());
+        }
+        ("fmt", Some(sub_matches)) => {
+            let path = Path::new(sub_matches.value_of("input").unwrap());
+
+            let source = fs::read_to_string(path)
+                .map_err(|why| format!("couldn't read {}: {}", path.display(), why))?;
+
+            let formatted = format::format(&source).map_err(|e| {
+                let errors = e
+                    .diagnostics()
+                    .into_iter()
+                    .map(|d| {
+                        d.with_file(Some(path.display().to_string()))
+                            .render(Some(&source))
+                    })
+                    .collect::<Vec<_>>();
+                format!("Formatting failed:\n\n{}", errors.join("\n\n"))
+            })?;
+
+            match (formatted == source, sub_matches.is_present("check")) {
+                (true, _) => println!("{} is formatted", path.display()),
+                (false, true) => return Err(format!("{} is not formatted", path.display())),
+                (false, false) => {
+                    write_file(path, &formatted)?;
+                    println!("Formatted {}", path.display());
+                }
+            }
+        }
         _ => unreachable!(),
     }
     Ok(())
 }
 
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+    File::create(path)
+        .map_err(|why| format!("couldn't create {}: {}", path.display(), why))?
+        .write_all(content.as_bytes())
+        .map_err(|why| format!("couldn't write {}: {}", path.display(), why))
+}
+
+// the location `line:col` in the source code at `path`, followed by the line it points to
+fn format_location(path: &Path, source: &str, line: usize, col: usize) -> String {
+    format!(
+        "{}:{}:{}\n{:>5} | {}",
+        path.display(),
+        line,
+        col,
+        line,
+        source.lines().nth(line - 1).unwrap_or("")
+    )
+}
+
+// print a statement executed by the debugger, followed by the values of its named variables
+fn print_statement(debugger: &ir::Debugger<FieldPrime>, statement: &ir::Statement<FieldPrime>) {
+    println!("        {}", statement);
+    for (name, value) in debugger.named_values(statement) {
+        match value {
+            Some(value) => println!("            {} = {}", name, value),
+            None => println!("            {} = ?", name),
+        }
+    }
+}
+
+// the source files at `path`, which is either a file or a directory searched recursively
+fn collect_sources(path: &Path, sources: &mut Vec<PathBuf>) -> Result<(), String> {
+    if !path.is_dir() {
+        sources.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    let mut entries = fs::read_dir(path)
+        .and_then(|entries| {
+            entries
+                .map(|e| e.map(|e| e.path()))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|why| format!("couldn't read {}: {}", path.display(), why))?;
+    entries.sort();
+
+    for entry in entries {
+        match entry.is_dir() {
+            true => collect_sources(&entry, sources)?,
+            false if entry.extension().map(|e| e == "code").unwrap_or(false) => sources.push(entry),
+            false => {}
+        }
+    }
+
+    Ok(())
+}
+
+// read every witness file in `dir`, mapping `dir/<name>` to `output_dir/<name>.json`
+fn read_witness_dir(
+    dir: &Path,
+    output_dir: &Path,
+) -> Result<Vec<(ir::Witness<FieldPrime>, String, String)>, String> {
+    let mut paths = fs::read_dir(dir)
+        .map_err(|why| format!("couldn't read {}: {}", dir.display(), why))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|why| format!("couldn't read {}: {}", dir.display(), why))?;
+
+    paths.retain(|p| p.is_file());
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let file = File::open(&path)
+                .map_err(|why| format!("couldn't open {}: {}", path.display(), why))?;
+            let witness = ir::Witness::read(file)
+                .map_err(|why| format!("could not load witness {}: {:?}", path.display(), why))?;
+            // append the extension rather than replacing it, as `witness.1` and `witness.2` would
+            // otherwise both be proven to `witness.json`
+            let mut file_name = path.file_name().unwrap().to_os_string();
+            file_name.push(".json");
+            let proof_path = output_dir.join(file_name);
+            Ok((
+                witness,
+                format!("witness {}", path.display()),
+                proof_path.to_str().unwrap().to_string(),
+            ))
+        })
+        .collect()
+}
+
+// read one JSON witness per non-empty line, mapping line `n` to `output_dir/proof_<n>.json`
+fn read_json_witnesses<R: BufRead>(
+    reader: R,
+    output_dir: &Path,
+) -> Result<Vec<(ir::Witness<FieldPrime>, String, String)>, String> {
+    reader
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| match line {
+            Ok(l) => !l.trim().is_empty(),
+            Err(_) => true,
+        })
+        .map(|(index, line)| {
+            let number = index + 1;
+            let line = line.map_err(|why| format!("couldn't read witnesses: {}", why))?;
+            let witness = ir::Witness::read_json(line.as_bytes())
+                .map_err(|why| format!("could not load witness on line {}: {:?}", number, why))?;
+            let proof_path = output_dir.join(format!("proof_{}.json", number));
+            Ok((
+                witness,
+                format!("witness on line {}", number),
+                proof_path.to_str().unwrap().to_string(),
+            ))
+        })
+        .collect()
+}
+
+// the inputs of `verifyTx`, which are the committed inputs if the proof has a commitment to them
+fn verifier_inputs(proof_object: &Value) -> Result<Value, String> {
+    match proof_object.get("committed_inputs") {
+        Some(committed) => {
+            let values = committed
+                .as_array()
+                .ok_or("Invalid committed inputs")?
+                .iter()
+                .map(hex_field_element)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let expected = hex_field_element(&proof_object["inputs"][0])?;
+
+            if commitment(&values) != expected {
+                return Err(String::from(
+                    "The committed inputs of the proof do not match its public input",
+                ));
+            }
+
+            Ok(committed.clone())
+        }
+        None => Ok(proof_object["inputs"].clone()),
+    }
+}
+
+fn hex_field_element(v: &Value) -> Result<FieldPrime, String> {
+    v.as_str()
+        .ok_or_else(|| format!("Invalid input {}, expected a hexadecimal string", v))
+        .and_then(field_from_hex)
+}
+
+// read the seed making setup or proving deterministic, which is only meant for tests
+fn insecure_seed(
+    sub_matches: &clap::ArgMatches,
+    scheme: &dyn ProofSystem,
+) -> Result<Option<Seed>, String> {
+    match sub_matches.value_of("insecure-seed") {
+        None => Ok(None),
+        Some(_) if !scheme.supports_seed() => Err(String::from(
+            "Seeds are not supported by this proving scheme",
+        )),
+        Some(seed) => {
+            println!("WARNING: Using a fixed seed. The output is NOT secure and must only be used in tests.");
+            Ok(Some(seed.parse()?))
+        }
+    }
+}
+
+fn read_vk_header(vk_path: &Path) -> Result<VerificationKeyHeader, String> {
+    let file = File::open(&vk_path)
+        .map_err(|why| format!("couldn't open {}: {}", vk_path.display(), why))?;
+
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(|why| format!("Invalid verification key {}: {}", vk_path.display(), why))
+}
+
+// only G16 proofs can be re-randomized
+fn check_rerandomization_scheme(vk_path: &Path) -> Result<(), String> {
+    match read_vk_header(vk_path)?.scheme.as_ref() {
+        "g16" => Ok(()),
+        _ => Err(String::from("Re-randomization is only supported for G16")),
+    }
+}
+
+// read the proving scheme from a JSON verification key, checking it against the scheme requested by the user
+fn detect_scheme(
+    vk_path: &Path,
+    requested: Option<&str>,
+) -> Result<&'static dyn ProofSystem, String> {
+    let header = read_vk_header(vk_path)?;
+
+    match requested {
+        Some(scheme) if scheme.to_lowercase() != header.scheme => Err(format!(
+            "Verification key {} was generated for {}, not {}",
+            vk_path.display(),
+            header.scheme,
+            scheme
+        )),
+        _ => get_scheme(&header.scheme),
+    }
+}
+
 fn get_scheme(scheme_str: &str) -> Result<&'static dyn ProofSystem, String> {
     match scheme_str.to_lowercase().as_ref() {
         #[cfg(feature = "libsnark")]
@@ -532,6 +1701,7 @@ mod tests {
     extern crate glob;
     use self::glob::glob;
     use super::*;
+    use zokrates_core::flat_absy::FlatVariable;
 
     #[test]
     fn examples() {
@@ -563,6 +1733,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn imported_diagnostic_source() {
+        let path = Path::new("./examples/error/imported_error.code");
+        let location = path.parent().unwrap().display().to_string();
+        let source = fs::read_to_string(path).unwrap();
+
+        let res: Result<ir::Prog<FieldPrime>, _> = compile(
+            &mut BufReader::new(source.as_bytes()),
+            Some(location.clone()),
+            Some(fs_resolve),
+        );
+        let diagnostic = res.unwrap_err().diagnostics().remove(0);
+
+        // the line of the imported file the error points to is quoted
+        assert_eq!(
+            format_diagnostic(diagnostic, path, &location, &source, false),
+            "error[E0100]: Identifier \"a\" is undefined\n --> ./undefined_identifier.code:2:9\n  |\n2 | \treturn a\n  | \t       ^"
+        );
+    }
+
     #[test]
     fn examples_with_input_success() {
         //these examples should compile and run
@@ -625,4 +1815,88 @@ mod tests {
                 .unwrap();
         }
     }
+
+    #[test]
+    fn unit_tests() {
+        let path = Path::new("./examples/book/unit_tests.code");
+        let mut reader = BufReader::new(File::open(path).unwrap());
+
+        let reports = run_tests::<FieldPrime, _, _, _>(
+            &mut reader,
+            Some(String::from("./examples/book")),
+            Some(fs_resolve),
+        )
+        .unwrap();
+
+        let names: Vec<_> = reports.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["test_square", "test_fail_square"]);
+        assert!(reports.iter().all(|r| r.passed()));
+    }
+
+    #[test]
+    fn witness_dir_keeps_extensions() {
+        let dir = tempdir::TempDir::new("witnesses").unwrap();
+        let witness = ir::Witness(
+            vec![(FlatVariable::one(), FieldPrime::from(1))]
+                .into_iter()
+                .collect(),
+        );
+        for name in &["witness.1", "witness.2"] {
+            witness
+                .write(File::create(dir.path().join(name)).unwrap())
+                .unwrap();
+        }
+
+        let proofs: Vec<_> = read_witness_dir(dir.path(), Path::new("proofs"))
+            .unwrap()
+            .into_iter()
+            .map(|(_, _, path)| path)
+            .collect();
+
+        assert_eq!(
+            proofs,
+            vec![
+                Path::new("proofs").join("witness.1.json").to_str().unwrap(),
+                Path::new("proofs").join("witness.2.json").to_str().unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn json_witnesses_numbered_by_line() {
+        let witnesses = "{\"~one\": \"1\"}\n\n{\"~one\": \"1\"}\n";
+
+        let jobs = read_json_witnesses(witnesses.as_bytes(), Path::new("proofs")).unwrap();
+
+        assert_eq!(
+            jobs.into_iter()
+                .map(|(_, name, path)| (name, path))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    String::from("witness on line 1"),
+                    Path::new("proofs")
+                        .join("proof_1.json")
+                        .to_str()
+                        .unwrap()
+                        .to_string()
+                ),
+                (
+                    String::from("witness on line 3"),
+                    Path::new("proofs")
+                        .join("proof_3.json")
+                        .to_str()
+                        .unwrap()
+                        .to_string()
+                )
+            ]
+        );
+
+        let error = read_json_witnesses(
+            "{\"~one\": \"1\"}\n{\"bug\": \"1\"}".as_bytes(),
+            Path::new("proofs"),
+        )
+        .unwrap_err();
+        assert!(error.contains("line 2"));
+    }
 }
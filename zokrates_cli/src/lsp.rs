@@ -0,0 +1,587 @@
+//
+// @file lsp.rs
+//
+// A language server for ZoKrates, speaking the Language Server Protocol over stdin and stdout.
+// Documents are checked as they are edited, without being flattened.
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use zokrates_core::compile::{analyse, Analysis};
+use zokrates_core::diagnostics::{Diagnostic, Severity};
+use zokrates_core::symbols::{Position, Symbol, SymbolIndex, SymbolKind};
+use zokrates_field::field::FieldPrime;
+use zokrates_fs_resolver::{resolve as fs_resolve, resolve_path};
+
+const METHOD_NOT_FOUND: i64 = -32601;
+
+struct Document {
+    text: String,
+    // the symbols of the last version of the document which could be parsed
+    symbols: SymbolIndex,
+}
+
+#[derive(Default)]
+struct Server {
+    documents: HashMap<String, Document>,
+}
+
+/// Serves requests read from stdin until the client exits
+pub fn run() -> Result<(), String> {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+
+    let mut server = Server::default();
+
+    while let Some(message) = read_message(&mut input)? {
+        if message["method"] == "exit" {
+            break;
+        }
+
+        for response in server.handle(&message) {
+            write_message(&mut output, &response)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn read_message<R: BufRead>(input: &mut R) -> Result<Option<Value>, String> {
+    let mut length = None;
+
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header).map_err(|e| e.to_string())? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        let mut parts = header.splitn(2, ':');
+        if let (Some("Content-Length"), Some(value)) = (parts.next(), parts.next()) {
+            length = Some(
+                value
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|e| format!("Invalid Content-Length header: {}", e))?,
+            );
+        }
+    }
+
+    let length = length.ok_or("Missing Content-Length header")?;
+    let mut content = vec![0; length];
+    input.read_exact(&mut content).map_err(|e| e.to_string())?;
+
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| format!("Invalid message: {}", e))
+}
+
+fn write_message<W: Write>(output: &mut W, message: &Value) -> Result<(), String> {
+    let content = message.to_string();
+    write!(
+        output,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )
+    .and_then(|_| output.flush())
+    .map_err(|e| e.to_string())
+}
+
+impl Server {
+    // returns the messages to send back to the client
+    fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let id = &message["id"];
+        let params = &message["params"];
+
+        let result = match message["method"].as_str().unwrap_or("") {
+            "initialize" => json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "completionProvider": {
+                        "triggerCharacters": ["\"", "/"]
+                    }
+                },
+                "serverInfo": {
+                    "name": "zokrates",
+                    "version": env!("CARGO_PKG_VERSION")
+                }
+            }),
+            "shutdown" => Value::Null,
+            "textDocument/didOpen" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                let text = params["textDocument"]["text"].as_str().unwrap_or("");
+                return vec![self.update(uri, text.to_string())];
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                // documents are synchronised in full, so the last change is the whole text
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+                return match text {
+                    Some(text) => vec![self.update(uri, text.to_string())],
+                    None => vec![],
+                };
+            }
+            "textDocument/didSave" => {
+                // the imported files may have changed
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                let text = self.documents.get(uri).map(|d| d.text.clone());
+                return match text {
+                    Some(text) => vec![self.update(uri, text)],
+                    None => vec![],
+                };
+            }
+            "textDocument/didClose" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                self.documents.remove(uri);
+                return vec![publish_diagnostics(uri, vec![])];
+            }
+            "textDocument/hover" => self.hover(params),
+            "textDocument/definition" => self.definition(params),
+            "textDocument/completion" => self.completion(params),
+            method => {
+                // notifications which are not supported are ignored
+                if id.is_null() {
+                    return vec![];
+                }
+                return vec![json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {
+                        "code": METHOD_NOT_FOUND,
+                        "message": format!("Unsupported method {}", method)
+                    }
+                })];
+            }
+        };
+
+        match id.is_null() {
+            true => vec![],
+            false => vec![json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": result
+            })],
+        }
+    }
+
+    // checks the new text of a document, returning the diagnostics to publish
+    fn update(&mut self, uri: &str, text: String) -> Value {
+        let path = uri_to_path(uri);
+        let location = location(&path);
+        let analysis = analyse_source(&text, &location);
+
+        let diagnostics = analysis
+            .diagnostics
+            .iter()
+            .map(|d| diagnostic_to_lsp(d, uri, &location))
+            .collect();
+
+        // the symbols are not found while the document cannot be parsed, so we keep the previous ones
+        let symbols = match (
+            analysis.symbols.symbols.is_empty(),
+            self.documents.remove(uri),
+        ) {
+            (true, Some(previous)) => previous.symbols,
+            _ => analysis.symbols,
+        };
+
+        self.documents
+            .insert(uri.to_string(), Document { text, symbols });
+
+        publish_diagnostics(uri, diagnostics)
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        match self.symbol_at(params) {
+            Some((_, symbol)) => json!({
+                "contents": {
+                    "kind": "markdown",
+                    "value": format!("```zokrates\n{}\n```", symbol.detail)
+                }
+            }),
+            None => Value::Null,
+        }
+    }
+
+    fn definition(&self, params: &Value) -> Value {
+        let (uri, symbol) = match self.symbol_at(params) {
+            Some(res) => res,
+            None => return Value::Null,
+        };
+
+        // imported symbols are looked up in the file they are imported from, if it can be read
+        if let Some(source) = &symbol.import {
+            let location = location(&uri_to_path(uri));
+            if let Some(location) = imported_definition(symbol, source, &location) {
+                return location;
+            }
+        }
+
+        match symbol.pos {
+            Some(pos) => json!({ "uri": uri, "range": range(pos) }),
+            None => Value::Null,
+        }
+    }
+
+    fn completion(&self, params: &Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+        let document = match self.documents.get(uri) {
+            Some(document) => document,
+            None => return json!([]),
+        };
+        let position = &params["position"];
+        let line = position["line"].as_u64().unwrap_or(0) as usize;
+        let character = position["character"].as_u64().unwrap_or(0) as usize;
+
+        let prefix: String = document
+            .text
+            .lines()
+            .nth(line)
+            .unwrap_or("")
+            .chars()
+            .take(character)
+            .collect();
+
+        // inside the path of an import, complete with the files of the standard library
+        if prefix.trim_start().starts_with("import") && prefix.matches('"').count() == 1 {
+            let start = prefix.chars().take_while(|c| *c != '"').count() + 1;
+            let edit_range = json!({
+                "start": { "line": line, "character": start },
+                "end": { "line": line, "character": character }
+            });
+            return Value::Array(
+                stdlib_paths()
+                    .into_iter()
+                    .map(|path| {
+                        json!({
+                            "label": path,
+                            "kind": 17,
+                            "textEdit": { "range": edit_range, "newText": path }
+                        })
+                    })
+                    .collect(),
+            );
+        }
+
+        let pos = Position {
+            line: line + 1,
+            col: character + 1,
+        };
+
+        Value::Array(
+            document
+                .symbols
+                .visible_at(pos)
+                .into_iter()
+                .map(|symbol| {
+                    json!({
+                        "label": symbol.id,
+                        "kind": completion_kind(symbol.kind),
+                        "detail": symbol.detail
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    // the document and the symbol at the position of a request
+    fn symbol_at<'a>(&'a self, params: &'a Value) -> Option<(&'a str, &'a Symbol)> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let position = &params["position"];
+        let pos = Position {
+            line: position["line"].as_u64()? as usize + 1,
+            col: position["character"].as_u64()? as usize + 1,
+        };
+
+        self.documents
+            .get(uri)
+            .and_then(|d| d.symbols.symbol_at(pos))
+            .map(|symbol| (uri, symbol))
+    }
+}
+
+fn analyse_source(text: &str, location: &String) -> Analysis {
+    let mut reader = text.as_bytes();
+    analyse::<FieldPrime, _, _, _>(&mut reader, Some(location.clone()), Some(fs_resolve))
+}
+
+// imported functions are the `main` function of the imported file, constants keep their name
+fn imported_definition(symbol: &Symbol, source: &String, location: &String) -> Option<Value> {
+    let path = resolve_path(location, source).ok()?;
+    let text = fs::read_to_string(&path).ok()?;
+    let analysis = analyse_source(&text, &self::location(&path));
+
+    let id = match symbol.kind {
+        SymbolKind::Function => "main",
+        _ => symbol.id.as_str(),
+    };
+
+    let index = analysis.symbols.find_global(id, symbol.kind)?;
+    let pos = analysis.symbols.symbols[index].pos?;
+
+    Some(json!({ "uri": path_to_uri(&path), "range": range(pos) }))
+}
+
+// the location imports are resolved from, which is the directory of the file
+fn location(path: &Path) -> String {
+    path.parent()
+        .map(|parent| parent.to_string_lossy().to_string())
+        .unwrap_or(String::from("."))
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics }
+    })
+}
+
+fn diagnostic_to_lsp(diagnostic: &Diagnostic, uri: &str, location: &String) -> Value {
+    let severity = match diagnostic.severity {
+        Severity::Error => 1,
+        Severity::Warning => 2,
+    };
+
+    // diagnostics of imported files are shown at the top of the document
+    let (message, pos) = match diagnostic.file.as_ref() == Some(location) {
+        true => (diagnostic.message.clone(), diagnostic.pos),
+        false => (
+            match &diagnostic.file {
+                Some(file) => format!("{}: {}", file, diagnostic.message),
+                None => diagnostic.message.clone(),
+            },
+            None,
+        ),
+    };
+
+    let related: Vec<_> = diagnostic
+        .notes
+        .iter()
+        .filter_map(|note| {
+            note.pos.filter(|_| pos.is_some()).map(|note_pos| {
+                json!({
+                    "location": { "uri": uri, "range": range(note_pos) },
+                    "message": note.message
+                })
+            })
+        })
+        .collect();
+
+    json!({
+        "range": pos.map(range).unwrap_or(json!({
+            "start": { "line": 0, "character": 0 },
+            "end": { "line": 0, "character": 0 }
+        })),
+        "severity": severity,
+        "code": diagnostic.code.as_str(),
+        "source": "zokrates",
+        "message": message,
+        "relatedInformation": related
+    })
+}
+
+// positions are 1-based in the compiler and 0-based in the protocol
+fn range((start, end): (Position, Position)) -> Value {
+    json!({
+        "start": { "line": start.line - 1, "character": start.col - 1 },
+        "end": { "line": end.line - 1, "character": end.col - 1 }
+    })
+}
+
+fn completion_kind(kind: SymbolKind) -> u8 {
+    match kind {
+        SymbolKind::Function => 3,
+        SymbolKind::Variable => 6,
+        SymbolKind::Constant => 21,
+    }
+}
+
+// the paths of the files of the standard library, as they are imported
+fn stdlib_paths() -> Vec<String> {
+    fn visit(dir: &Path, base: &Path, paths: &mut Vec<String>) {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.is_dir() {
+                visit(&path, base, paths);
+            } else if path.extension().map(|e| e == "code").unwrap_or(false) {
+                if let Ok(relative) = path.strip_prefix(base) {
+                    paths.push(relative.to_string_lossy().replace('\\', "/"));
+                }
+            }
+        }
+    }
+
+    let mut paths = vec![];
+    if let Ok(home) = env::var("ZOKRATES_HOME") {
+        let home = PathBuf::from(home);
+        visit(&home, &home, &mut paths);
+    }
+    paths.sort();
+    paths
+}
+
+fn uri_to_path(uri: &str) -> PathBuf {
+    let path = match uri.starts_with("file://") {
+        true => &uri["file://".len()..],
+        false => uri,
+    };
+
+    // decode the percent-encoded bytes of the path
+    let mut bytes = vec![];
+    let mut chars = path.bytes();
+    while let Some(b) = chars.next() {
+        match b {
+            b'%' => {
+                let hex: Vec<u8> = chars.by_ref().take(2).collect();
+                match std::str::from_utf8(&hex)
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(decoded) => bytes.push(decoded),
+                    None => {
+                        bytes.push(b);
+                        bytes.extend(hex);
+                    }
+                }
+            }
+            b => bytes.push(b),
+        }
+    }
+
+    PathBuf::from(String::from_utf8_lossy(&bytes).to_string())
+}
+
+fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let mut uri = String::from("file://");
+    for c in path.chars() {
+        match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '/' | '-' | '_' | '.' | '~' => uri.push(c),
+            c => {
+                let mut buffer = [0; 4];
+                for b in c.encode_utf8(&mut buffer).bytes() {
+                    uri.push_str(&format!("%{:02X}", b));
+                }
+            }
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(content: &str) -> String {
+        format!("Content-Length: {}\r\n\r\n{}", content.len(), content)
+    }
+
+    #[test]
+    fn read_messages() {
+        let input = format!(
+            "{}{}",
+            message(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#),
+            message(r#"{"jsonrpc":"2.0","method":"exit"}"#)
+        );
+        let mut reader = input.as_bytes();
+
+        let first = read_message(&mut reader).unwrap().unwrap();
+        assert_eq!(first["method"], "initialize");
+        let second = read_message(&mut reader).unwrap().unwrap();
+        assert_eq!(second["method"], "exit");
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn uris() {
+        let path = PathBuf::from("/home/user/my project/main.code");
+        let uri = path_to_uri(&path);
+        assert_eq!(uri, "file:///home/user/my%20project/main.code");
+        assert_eq!(uri_to_path(&uri), path);
+    }
+
+    #[test]
+    fn session() {
+        let dir = tempdir::TempDir::new("lsp").unwrap();
+        let uri = path_to_uri(&dir.path().join("main.code"));
+        let mut server = Server::default();
+
+        let responses = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": {
+                    "uri": uri,
+                    "text": "def main(field a) -> (field):\n\tfield b = a\n\treturn c\n"
+                }
+            }
+        }));
+        let diagnostics = &responses[0]["params"]["diagnostics"];
+        assert_eq!(diagnostics[0]["code"], "E0100");
+        assert_eq!(diagnostics[0]["range"]["start"]["line"], 2);
+
+        let responses = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "textDocument/hover",
+            "params": {
+                "textDocument": { "uri": uri },
+                "position": { "line": 1, "character": 11 }
+            }
+        }));
+        assert_eq!(
+            responses[0]["result"]["contents"]["value"],
+            "```zokrates\nfield a\n```"
+        );
+
+        let responses = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "textDocument/definition",
+            "params": {
+                "textDocument": { "uri": uri },
+                "position": { "line": 1, "character": 11 }
+            }
+        }));
+        assert_eq!(responses[0]["result"]["range"]["start"]["line"], 0);
+
+        let responses = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "textDocument/completion",
+            "params": {
+                "textDocument": { "uri": uri },
+                "position": { "line": 2, "character": 8 }
+            }
+        }));
+        let labels: Vec<_> = responses[0]["result"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["label"].as_str().unwrap())
+            .collect();
+        assert!(labels.contains(&"a"));
+        assert!(labels.contains(&"b"));
+        assert!(labels.contains(&"main"));
+    }
+}
@@ -62,6 +62,25 @@ mod integration {
             .join(program_name)
             .join("verifier")
             .with_extension("sol");
+        let proof_path = tmp_base
+            .join(program_name)
+            .join("proof")
+            .with_extension("json");
+        let witnesses_path = tmp_base.join(program_name).join("witnesses");
+        let proofs_path = tmp_base.join(program_name).join("proofs");
+        let snarkjs_path = tmp_base.join(program_name).join("snarkjs");
+        let imported_verification_key_path = tmp_base
+            .join(program_name)
+            .join("imported_verification")
+            .with_extension("key");
+        let imported_proof_path = tmp_base
+            .join(program_name)
+            .join("imported_proof")
+            .with_extension("json");
+        let rerandomized_proof_path = tmp_base
+            .join(program_name)
+            .join("rerandomized_proof")
+            .with_extension("json");
 
         // create a tmp folder to store artifacts
         fs::create_dir(test_case_path).unwrap();
@@ -160,6 +179,11 @@ mod integration {
 
         assert_eq!(inline_witness, witness);
 
+        // prepare a batch of witnesses
+        fs::create_dir(&witnesses_path).unwrap();
+        fs::copy(&witness_path, witnesses_path.join("witness_0")).unwrap();
+        fs::copy(&inline_witness_path, witnesses_path.join("witness_1")).unwrap();
+
         for line in expected_witness.as_str().split("\n") {
             assert!(
                 witness.contains(line),
@@ -169,6 +193,26 @@ mod integration {
             );
         }
 
+        // EXPORT-R1CS
+        let r1cs_base_path = tmp_base.join(program_name).join("circuit");
+
+        assert_cli::Assert::command(&[
+            "../target/release/zokrates",
+            "export-r1cs",
+            "-i",
+            flattened_path.to_str().unwrap(),
+            "-o",
+            r1cs_base_path.to_str().unwrap(),
+            "-w",
+            witness_path.to_str().unwrap(),
+        ])
+        .succeeds()
+        .unwrap();
+
+        assert!(r1cs_base_path.with_extension("r1cs").exists());
+        assert!(r1cs_base_path.with_extension("json").exists());
+        assert!(r1cs_base_path.with_extension("wtns").exists());
+
         #[cfg(feature = "libsnark")]
         let schemes = ["pghr13", "gm17", "g16"];
         #[cfg(not(feature = "libsnark"))]
@@ -205,6 +249,9 @@ mod integration {
             .succeeds()
             .unwrap();
 
+            assert!(verification_contract_path.with_extension("json").exists());
+            assert!(verification_contract_path.with_extension("rs").exists());
+
             let mut verifier_file = File::open(&verification_contract_path).unwrap();
             let mut verifier_string = String::new();
             verifier_file.read_to_string(&mut verifier_string).unwrap();
@@ -231,11 +278,180 @@ mod integration {
                 witness_path.to_str().unwrap(),
                 "-p",
                 proving_key_path.to_str().unwrap(),
+                "-j",
+                proof_path.to_str().unwrap(),
+                "--proving-scheme",
+                scheme,
+            ])
+            .succeeds()
+            .unwrap();
+
+            // VERIFY
+            if *scheme == "g16" {
+                assert_cli::Assert::command(&[
+                    "../target/release/zokrates",
+                    "verify",
+                    "-v",
+                    verification_key_path.to_str().unwrap(),
+                    "-j",
+                    proof_path.to_str().unwrap(),
+                ])
+                .succeeds()
+                .stdout()
+                .contains("PASSED")
+                .unwrap();
+
+                // EXPORT-SNARKJS
+                assert_cli::Assert::command(&[
+                    "../target/release/zokrates",
+                    "export-snarkjs",
+                    "-v",
+                    verification_key_path.to_str().unwrap(),
+                    "-j",
+                    proof_path.to_str().unwrap(),
+                    "-o",
+                    snarkjs_path.to_str().unwrap(),
+                ])
+                .succeeds()
+                .unwrap();
+
+                // IMPORT-SNARKJS
+                assert_cli::Assert::command(&[
+                    "../target/release/zokrates",
+                    "import-snarkjs",
+                    "-i",
+                    snarkjs_path.to_str().unwrap(),
+                    "-v",
+                    imported_verification_key_path.to_str().unwrap(),
+                    "-j",
+                    imported_proof_path.to_str().unwrap(),
+                ])
+                .succeeds()
+                .unwrap();
+
+                // the imported files verify just like the originals
+                assert_cli::Assert::command(&[
+                    "../target/release/zokrates",
+                    "verify",
+                    "-v",
+                    imported_verification_key_path.to_str().unwrap(),
+                    "-j",
+                    imported_proof_path.to_str().unwrap(),
+                ])
+                .succeeds()
+                .stdout()
+                .contains("PASSED")
+                .unwrap();
+
+                // RERANDOMIZE
+                assert_cli::Assert::command(&[
+                    "../target/release/zokrates",
+                    "rerandomize",
+                    "-v",
+                    verification_key_path.to_str().unwrap(),
+                    "-j",
+                    proof_path.to_str().unwrap(),
+                    "-o",
+                    rerandomized_proof_path.to_str().unwrap(),
+                ])
+                .succeeds()
+                .unwrap();
+
+                assert_cli::Assert::command(&[
+                    "../target/release/zokrates",
+                    "verify",
+                    "-v",
+                    verification_key_path.to_str().unwrap(),
+                    "-j",
+                    rerandomized_proof_path.to_str().unwrap(),
+                    "--rerandomization-of",
+                    proof_path.to_str().unwrap(),
+                ])
+                .succeeds()
+                .stdout()
+                .contains("re-randomizations of each other")
+                .unwrap();
+
+                // SEEDED SETUP
+                let seeded_key_paths: Vec<_> = (0..2)
+                    .map(|i| {
+                        let dir = tmp_base.join(program_name);
+                        (
+                            dir.join(format!("seeded_proving_{}", i))
+                                .with_extension("key"),
+                            dir.join(format!("seeded_verification_{}", i))
+                                .with_extension("key"),
+                        )
+                    })
+                    .collect();
+
+                for (pk_path, vk_path) in &seeded_key_paths {
+                    assert_cli::Assert::command(&[
+                        "../target/release/zokrates",
+                        "setup",
+                        "-i",
+                        flattened_path.to_str().unwrap(),
+                        "-p",
+                        pk_path.to_str().unwrap(),
+                        "-v",
+                        vk_path.to_str().unwrap(),
+                        "--insecure-seed",
+                        "42",
+                    ])
+                    .succeeds()
+                    .unwrap();
+                }
+
+                // the same seed gives byte-identical keys
+                assert_eq!(
+                    fs::read(&seeded_key_paths[0].0).unwrap(),
+                    fs::read(&seeded_key_paths[1].0).unwrap()
+                );
+                assert_eq!(
+                    fs::read(&seeded_key_paths[0].1).unwrap(),
+                    fs::read(&seeded_key_paths[1].1).unwrap()
+                );
+            } else {
+                // only G16 proofs can be re-randomized
+                assert_cli::Assert::command(&[
+                    "../target/release/zokrates",
+                    "verify",
+                    "-v",
+                    verification_key_path.to_str().unwrap(),
+                    "-j",
+                    proof_path.to_str().unwrap(),
+                    "--rerandomization-of",
+                    proof_path.to_str().unwrap(),
+                ])
+                .fails()
+                .and()
+                .stdout()
+                .contains("Re-randomization is only supported for G16")
+                .unwrap();
+            }
+
+            // GENERATE-PROOFS
+            assert_cli::Assert::command(&[
+                "../target/release/zokrates",
+                "generate-proofs",
+                "-i",
+                flattened_path.to_str().unwrap(),
+                "-w",
+                witnesses_path.to_str().unwrap(),
+                "-p",
+                proving_key_path.to_str().unwrap(),
+                "-o",
+                proofs_path.to_str().unwrap(),
+                "--threads",
+                "2",
                 "--proving-scheme",
                 scheme,
             ])
             .succeeds()
             .unwrap();
+
+            assert!(proofs_path.join("witness_0.json").exists());
+            assert!(proofs_path.join("witness_1.json").exists());
         }
     }
 }
@@ -1,6 +1,6 @@
 use absy;
+use absy::types::{ArraySize, UnresolvedType};
 use imports;
-use types::Type;
 use zokrates_field::field::Field;
 use zokrates_pest_ast as pest;
 
@@ -17,7 +17,13 @@ impl<'ast, T: Field> From<pest::File<'ast>> for absy::Prog<'ast, T> {
                 .into_iter()
                 .map(|i| absy::ImportNode::from(i))
                 .collect(),
+            constants: prog
+                .constants
+                .into_iter()
+                .map(|c| absy::ConstantNode::from(c))
+                .collect(),
             imported_functions: vec![],
+            imported_constants: vec![],
         }
     }
 }
@@ -28,17 +34,49 @@ impl<'ast> From<pest::ImportDirective<'ast>> for absy::ImportNode {
 
         imports::Import::new(import.source.value)
             .alias(import.alias.map(|a| a.value))
+            .symbols(
+                import
+                    .symbols
+                    .into_iter()
+                    .map(|s| {
+                        (
+                            s.id.value.clone(),
+                            s.alias.map(|a| a.value).unwrap_or(s.id.value),
+                        )
+                    })
+                    .collect(),
+            )
             .span(import.span)
     }
 }
 
+impl<'ast, T: Field> From<pest::ConstantDefinition<'ast>> for absy::ConstantNode<'ast, T> {
+    fn from(constant: pest::ConstantDefinition<'ast>) -> absy::ConstantNode<'ast, T> {
+        use absy::NodeValue;
+
+        absy::Constant {
+            id: constant.id.span.as_str(),
+            ty: UnresolvedType::from(constant.ty),
+            expression: absy::ExpressionNode::from(constant.expression),
+        }
+        .span(constant.span)
+    }
+}
+
 impl<'ast, T: Field> From<pest::Function<'ast>> for absy::FunctionNode<'ast, T> {
     fn from(function: pest::Function<'ast>) -> absy::FunctionNode<T> {
         use absy::NodeValue;
 
         let span = function.span;
 
-        let signature = absy::Signature::new()
+        let signature = absy::UnresolvedSignature::new()
+            .generics(
+                function
+                    .generics
+                    .iter()
+                    .map(|g| g.id.span.as_str())
+                    .collect(),
+            )
             .inputs(
                 function
                     .parameters
@@ -52,7 +90,7 @@ impl<'ast, T: Field> From<pest::Function<'ast>> for absy::FunctionNode<'ast, T>
                     .returns
                     .clone()
                     .into_iter()
-                    .map(|r| Type::from(r))
+                    .map(|r| UnresolvedType::from(r))
                     .collect(),
             );
 
@@ -86,8 +124,8 @@ impl<'ast> From<pest::Parameter<'ast>> for absy::ParameterNode<'ast> {
             })
             .unwrap_or(false);
 
-        let variable =
-            absy::Variable::new(param.id.span.as_str(), Type::from(param.ty)).span(param.id.span);
+        let variable = absy::Variable::new(param.id.span.as_str(), UnresolvedType::from(param.ty))
+            .span(param.id.span);
 
         absy::Parameter::new(variable, private).span(param.span)
     }
@@ -99,10 +137,12 @@ fn statements_from_statement<'ast, T: Field>(
     match statement {
         pest::Statement::Definition(s) => statements_from_definition(s),
         pest::Statement::Iteration(s) => vec![absy::StatementNode::from(s)],
+        pest::Statement::IfElse(s) => vec![absy::StatementNode::from(s)],
         pest::Statement::Assertion(s) => vec![absy::StatementNode::from(s)],
         pest::Statement::Assignment(s) => vec![absy::StatementNode::from(s)],
         pest::Statement::Return(s) => vec![absy::StatementNode::from(s)],
         pest::Statement::MultiAssignment(s) => statements_from_multi_assignment(s),
+        pest::Statement::Log(s) => vec![absy::StatementNode::from(s)],
     }
 }
 
@@ -118,7 +158,8 @@ fn statements_from_multi_assignment<'ast, T: Field>(
         .filter(|i| i.ty.is_some())
         .map(|i| {
             absy::Statement::Declaration(
-                absy::Variable::new(i.id.span.as_str(), Type::from(i.ty.unwrap())).span(i.id.span),
+                absy::Variable::new(i.id.span.as_str(), UnresolvedType::from(i.ty.unwrap()))
+                    .span(i.id.span),
             )
             .span(i.span)
         });
@@ -153,8 +194,11 @@ fn statements_from_definition<'ast, T: Field>(
 
     vec![
         absy::Statement::Declaration(
-            absy::Variable::new(definition.id.span.as_str(), Type::from(definition.ty))
-                .span(definition.id.span.clone()),
+            absy::Variable::new(
+                definition.id.span.as_str(),
+                UnresolvedType::from(definition.ty),
+            )
+            .span(definition.id.span.clone()),
         )
         .span(definition.span.clone()),
         absy::Statement::Definition(
@@ -188,54 +232,79 @@ impl<'ast, T: Field> From<pest::AssertionStatement<'ast>> for absy::StatementNod
         use absy::NodeValue;
 
         match statement.expression {
-            pest::Expression::Binary(e) => match e.op {
-                pest::BinaryOperator::Eq => absy::Statement::Condition(
-                    absy::ExpressionNode::from(*e.left),
-                    absy::ExpressionNode::from(*e.right),
-                ),
-                _ => unimplemented!(
-                    "Assertion statements should be an equality check, found {}",
-                    statement.span.as_str()
-                ),
-            },
-            _ => unimplemented!(
-                "Assertion statements should be an equality check, found {}",
-                statement.span.as_str()
+            pest::Expression::Binary(pest::BinaryExpression {
+                op: pest::BinaryOperator::Eq,
+                left,
+                right,
+                ..
+            }) => absy::Statement::Condition(
+                absy::ExpressionNode::from(*left),
+                absy::ExpressionNode::from(*right),
             ),
+            e => absy::Statement::UnsupportedAssertion(absy::ExpressionNode::from(e)),
         }
         .span(statement.span)
     }
 }
 
+impl<'ast, T: Field> From<pest::LogStatement<'ast>> for absy::StatementNode<'ast, T> {
+    fn from(statement: pest::LogStatement<'ast>) -> absy::StatementNode<T> {
+        use absy::NodeValue;
+
+        absy::Statement::Log(
+            statement.format.value,
+            statement
+                .arguments
+                .into_iter()
+                .map(|e| absy::ExpressionNode::from(e))
+                .collect(),
+        )
+        .span(statement.span)
+    }
+}
+
 impl<'ast, T: Field> From<pest::IterationStatement<'ast>> for absy::StatementNode<'ast, T> {
     fn from(statement: pest::IterationStatement<'ast>) -> absy::StatementNode<T> {
         use absy::NodeValue;
         let from = absy::ExpressionNode::from(statement.from);
         let to = absy::ExpressionNode::from(statement.to);
         let index = statement.index.span.as_str();
-        let ty = Type::from(statement.ty);
+        let ty = UnresolvedType::from(statement.ty);
         let statements: Vec<absy::StatementNode<T>> = statement
             .statements
             .into_iter()
             .flat_map(|s| statements_from_statement(s))
             .collect();
 
-        let from = match from.value {
-            absy::Expression::Number(n) => n,
-            e => unimplemented!("For loop bounds should be constants, found {}", e),
-        };
-
-        let to = match to.value {
-            absy::Expression::Number(n) => n,
-            e => unimplemented!("For loop bounds should be constants, found {}", e),
-        };
-
         let var = absy::Variable::new(index, ty).span(statement.index.span);
 
         absy::Statement::For(var, from, to, statements).span(statement.span)
     }
 }
 
+impl<'ast, T: Field> From<pest::IfElseStatement<'ast>> for absy::StatementNode<'ast, T> {
+    fn from(statement: pest::IfElseStatement<'ast>) -> absy::StatementNode<T> {
+        use absy::NodeValue;
+        let condition = absy::ExpressionNode::from(statement.condition);
+        let consequence: Vec<absy::StatementNode<T>> = statement
+            .consequence
+            .into_iter()
+            .flat_map(|s| statements_from_statement(s))
+            .collect();
+        let alternative: Vec<absy::StatementNode<T>> = statement
+            .alternative
+            .map(|a| {
+                a.statements
+                    .into_iter()
+                    .flat_map(|s| statements_from_statement(s))
+                    .collect()
+            })
+            .unwrap_or(vec![]);
+
+        absy::Statement::IfElse(condition, consequence, alternative).span(statement.span)
+    }
+}
+
 impl<'ast, T: Field> From<pest::AssignmentStatement<'ast>> for absy::StatementNode<'ast, T> {
     fn from(statement: pest::AssignmentStatement<'ast>) -> absy::StatementNode<T> {
         use absy::NodeValue;
@@ -283,10 +352,24 @@ impl<'ast, T: Field> From<pest::BinaryExpression<'ast>> for absy::ExpressionNode
                 box absy::ExpressionNode::from(*expression.left),
                 box absy::ExpressionNode::from(*expression.right),
             ),
+            pest::BinaryOperator::IntegerDiv(bitwidth) => absy::Expression::IntegerDiv(
+                box absy::ExpressionNode::from(*expression.left),
+                box absy::ExpressionNode::from(*expression.right),
+                bitwidth,
+            ),
+            pest::BinaryOperator::Rem(bitwidth) => absy::Expression::Rem(
+                box absy::ExpressionNode::from(*expression.left),
+                box absy::ExpressionNode::from(*expression.right),
+                bitwidth,
+            ),
             pest::BinaryOperator::Eq => absy::Expression::Eq(
                 box absy::ExpressionNode::from(*expression.left),
                 box absy::ExpressionNode::from(*expression.right),
             ),
+            pest::BinaryOperator::NotEq => absy::Expression::NotEq(
+                box absy::ExpressionNode::from(*expression.left),
+                box absy::ExpressionNode::from(*expression.right),
+            ),
             pest::BinaryOperator::Lt => absy::Expression::Lt(
                 box absy::ExpressionNode::from(*expression.left),
                 box absy::ExpressionNode::from(*expression.right),
@@ -311,11 +394,14 @@ impl<'ast, T: Field> From<pest::BinaryExpression<'ast>> for absy::ExpressionNode
                 box absy::ExpressionNode::from(*expression.left),
                 box absy::ExpressionNode::from(*expression.right),
             ),
+            pest::BinaryOperator::Xor => absy::Expression::Xor(
+                box absy::ExpressionNode::from(*expression.left),
+                box absy::ExpressionNode::from(*expression.right),
+            ),
             pest::BinaryOperator::Pow => absy::Expression::Pow(
                 box absy::ExpressionNode::from(*expression.left),
                 box absy::ExpressionNode::from(*expression.right),
             ),
-            o => unimplemented!("Operator {:?} not implemented", o),
         }
         .span(expression.span)
     }
@@ -343,23 +429,13 @@ impl<'ast, T: Field> From<pest::Spread<'ast>> for absy::SpreadNode<'ast, T> {
     }
 }
 
-impl<'ast, T: Field> From<pest::Range<'ast>> for absy::RangeNode<T> {
-    fn from(range: pest::Range<'ast>) -> absy::RangeNode<T> {
+impl<'ast, T: Field> From<pest::Range<'ast>> for absy::RangeNode<'ast, T> {
+    fn from(range: pest::Range<'ast>) -> absy::RangeNode<'ast, T> {
         use absy::NodeValue;
 
-        let from = range
-            .from
-            .map(|e| match absy::ExpressionNode::from(e.0).value {
-                absy::Expression::Number(n) => n,
-                e => unimplemented!("Range bounds should be constants, found {}", e),
-            });
+        let from = range.from.map(|e| absy::ExpressionNode::from(e.0));
 
-        let to = range
-            .to
-            .map(|e| match absy::ExpressionNode::from(e.0).value {
-                absy::Expression::Number(n) => n,
-                e => unimplemented!("Range bounds should be constants, found {}", e),
-            });
+        let to = range.to.map(|e| absy::ExpressionNode::from(e.0));
 
         absy::Range { from, to }.span(range.span)
     }
@@ -443,9 +519,10 @@ impl<'ast, T: Field> From<pest::PostfixExpression<'ast>> for absy::ExpressionNod
     fn from(expression: pest::PostfixExpression<'ast>) -> absy::ExpressionNode<'ast, T> {
         use absy::NodeValue;
 
-        assert!(expression.access.len() == 1); // we only allow a single access: function call or array access
+        let mut access = expression.access.into_iter();
 
-        match expression.access[0].clone() {
+        // the first access can be a function call or an array access
+        let first = match access.next().unwrap() {
             pest::Access::Call(a) => absy::Expression::FunctionCall(
                 expression.id.value,
                 a.expressions
@@ -458,7 +535,16 @@ impl<'ast, T: Field> From<pest::PostfixExpression<'ast>> for absy::ExpressionNod
                 box absy::RangeOrExpression::from(a.expression),
             ),
         }
-        .span(expression.span)
+        .span(expression.span.clone());
+
+        // the following ones can only be array accesses, as enforced by the grammar
+        access.fold(first, |acc, a| match a {
+            pest::Access::Select(a) => {
+                absy::Expression::Select(box acc, box absy::RangeOrExpression::from(a.expression))
+                    .span(expression.span.clone())
+            }
+            pest::Access::Call(..) => unreachable!(),
+        })
     }
 }
 
@@ -490,38 +576,34 @@ impl<'ast, T: Field> From<pest::Assignee<'ast>> for absy::AssigneeNode<'ast, T>
         use absy::NodeValue;
 
         let a = absy::AssigneeNode::from(assignee.id);
-        match assignee.indices.len() {
-            0 => a,
-            1 => absy::Assignee::ArrayElement(
-                box a,
-                box absy::RangeOrExpression::from(assignee.indices[0].clone()),
-            )
-            .span(assignee.span),
-            n => unimplemented!("Array should have one dimension, found {} in {}", n, a),
-        }
+        let span = assignee.span;
+
+        // multi-dimensional accesses are rejected during semantic checking
+        assignee.indices.into_iter().fold(a, |acc, index| {
+            absy::Assignee::ArrayElement(box acc, box absy::RangeOrExpression::from(index))
+                .span(span.clone())
+        })
     }
 }
 
-impl<'ast> From<pest::Type<'ast>> for Type {
-    fn from(t: pest::Type<'ast>) -> Type {
+impl<'ast> From<pest::Type<'ast>> for UnresolvedType<'ast> {
+    fn from(t: pest::Type<'ast>) -> UnresolvedType<'ast> {
         match t {
             pest::Type::Basic(t) => match t {
-                pest::BasicType::Field(_) => Type::FieldElement,
-                pest::BasicType::Boolean(_) => Type::Boolean,
+                pest::BasicType::Field(_) => UnresolvedType::FieldElement,
+                pest::BasicType::Boolean(_) => UnresolvedType::Boolean,
             },
             pest::Type::Array(t) => {
                 let size = match t.size {
-                    pest::Expression::Constant(c) => str::parse::<usize>(&c.value).unwrap(),
-                    e => {
-                        unimplemented!("Array size should be constant, found {}", e.span().as_str())
-                    }
+                    pest::Expression::Constant(c) => str::parse::<usize>(&c.value)
+                        .map(ArraySize::Constant)
+                        .unwrap_or(ArraySize::Unsupported(c.span.as_str())),
+                    pest::Expression::Identifier(i) => ArraySize::Identifier(i.span.as_str()),
+                    e => ArraySize::Unsupported(e.span().as_str()),
                 };
                 match t.ty {
-                    pest::BasicType::Field(_) => Type::FieldElementArray(size),
-                    _ => unimplemented!(
-                        "Array elements should be field elements, found {}",
-                        t.span.as_str()
-                    ),
+                    pest::BasicType::Field(_) => UnresolvedType::FieldElementArray(size),
+                    pest::BasicType::Boolean(_) => UnresolvedType::BooleanArray(size),
                 }
             }
         }
@@ -549,13 +631,15 @@ mod tests {
                     .into(),
                 )
                 .into()],
-                signature: absy::Signature::new()
+                signature: absy::UnresolvedSignature::new()
                     .inputs(vec![])
-                    .outputs(vec![Type::FieldElement]),
+                    .outputs(vec![UnresolvedType::FieldElement]),
             }
             .into()],
             imports: vec![],
             imported_functions: vec![],
+            imported_constants: vec![],
+            constants: vec![],
         };
         assert_eq!(absy::Prog::<FieldPrime>::from(ast), expected);
     }
@@ -581,13 +665,124 @@ mod tests {
                     .into(),
                 )
                 .into()],
-                signature: absy::Signature::new()
-                    .inputs(vec![Type::FieldElement, Type::Boolean])
-                    .outputs(vec![Type::FieldElement]),
+                signature: absy::UnresolvedSignature::new()
+                    .inputs(vec![UnresolvedType::FieldElement, UnresolvedType::Boolean])
+                    .outputs(vec![UnresolvedType::FieldElement]),
+            }
+            .into()],
+            imports: vec![],
+            imported_functions: vec![],
+            imported_constants: vec![],
+            constants: vec![],
+        };
+
+        assert_eq!(absy::Prog::<FieldPrime>::from(ast), expected);
+    }
+
+    #[test]
+    fn generics() {
+        let source = "def main<N>(field[N] a) -> (field[N]): return a
+        ";
+        let ast = pest::generate_ast(&source).unwrap();
+
+        let expected: absy::Prog<FieldPrime> = absy::Prog {
+            functions: vec![absy::Function {
+                id: "main",
+                arguments: vec![absy::Parameter::public(
+                    absy::Variable::new(
+                        "a",
+                        UnresolvedType::FieldElementArray(ArraySize::Identifier("N")),
+                    )
+                    .into(),
+                )
+                .into()],
+                statements: vec![absy::Statement::Return(
+                    absy::ExpressionList {
+                        expressions: vec![absy::Expression::Identifier("a").into()],
+                    }
+                    .into(),
+                )
+                .into()],
+                signature: absy::UnresolvedSignature::new()
+                    .generics(vec!["N"])
+                    .inputs(vec![UnresolvedType::FieldElementArray(
+                        ArraySize::Identifier("N"),
+                    )])
+                    .outputs(vec![UnresolvedType::FieldElementArray(
+                        ArraySize::Identifier("N"),
+                    )]),
+            }
+            .into()],
+            imports: vec![],
+            imported_functions: vec![],
+            imported_constants: vec![],
+            constants: vec![],
+        };
+
+        assert_eq!(absy::Prog::<FieldPrime>::from(ast), expected);
+    }
+
+    #[test]
+    fn if_else_statement() {
+        let source = "def main(field a) -> (field):
+            field b = 1
+            if a == 1 then
+                b = 2
+            else
+                b = 3
+            endif
+            return b
+        ";
+        let ast = pest::generate_ast(&source).unwrap();
+
+        let expected: absy::Prog<FieldPrime> = absy::Prog {
+            functions: vec![absy::Function {
+                id: "main",
+                arguments: vec![
+                    absy::Parameter::public(absy::Variable::field_element("a").into()).into(),
+                ],
+                statements: vec![
+                    absy::Statement::Declaration(absy::Variable::field_element("b").into()).into(),
+                    absy::Statement::Definition(
+                        absy::Assignee::Identifier("b").into(),
+                        absy::Expression::Number(FieldPrime::from(1)).into(),
+                    )
+                    .into(),
+                    absy::Statement::IfElse(
+                        absy::Expression::Eq(
+                            box absy::Expression::Identifier("a").into(),
+                            box absy::Expression::Number(FieldPrime::from(1)).into(),
+                        )
+                        .into(),
+                        vec![absy::Statement::Definition(
+                            absy::Assignee::Identifier("b").into(),
+                            absy::Expression::Number(FieldPrime::from(2)).into(),
+                        )
+                        .into()],
+                        vec![absy::Statement::Definition(
+                            absy::Assignee::Identifier("b").into(),
+                            absy::Expression::Number(FieldPrime::from(3)).into(),
+                        )
+                        .into()],
+                    )
+                    .into(),
+                    absy::Statement::Return(
+                        absy::ExpressionList {
+                            expressions: vec![absy::Expression::Identifier("b").into()],
+                        }
+                        .into(),
+                    )
+                    .into(),
+                ],
+                signature: absy::UnresolvedSignature::new()
+                    .inputs(vec![UnresolvedType::FieldElement])
+                    .outputs(vec![UnresolvedType::FieldElement]),
             }
             .into()],
             imports: vec![],
             imported_functions: vec![],
+            imported_constants: vec![],
+            constants: vec![],
         };
 
         assert_eq!(absy::Prog::<FieldPrime>::from(ast), expected);
@@ -8,15 +8,17 @@
 mod from_ast;
 mod node;
 pub mod parameter;
+pub mod types;
 pub mod variable;
 
 pub use crate::absy::node::{Node, NodeValue};
 pub use crate::absy::parameter::{Parameter, ParameterNode};
+pub use crate::absy::types::{ArraySize, UnresolvedSignature, UnresolvedType};
 pub use crate::absy::variable::{Variable, VariableNode};
-use crate::types::Signature;
 
 use crate::flat_absy::*;
 use crate::imports::ImportNode;
+use crate::typed_absy::ConstantValue;
 use std::fmt;
 use zokrates_field::field::Field;
 
@@ -26,8 +28,12 @@ pub type Identifier<'ast> = &'ast str;
 pub struct Prog<'ast, T: Field> {
     /// Functions of the program
     pub functions: Vec<FunctionNode<'ast, T>>,
+    /// Global constants of the program
+    pub constants: Vec<ConstantNode<'ast, T>>,
     pub imports: Vec<ImportNode>,
     pub imported_functions: Vec<FlatFunction<T>>,
+    /// Constants imported by name from other programs, under the name they are imported as
+    pub imported_constants: Vec<(String, ConstantValue<T>)>,
 }
 
 impl<'ast, T: Field> fmt::Display for Prog<'ast, T> {
@@ -45,6 +51,12 @@ impl<'ast, T: Field> fmt::Display for Prog<'ast, T> {
                 .map(|x| format!("{}", x))
                 .collect::<Vec<_>>(),
         );
+        res.extend(
+            self.constants
+                .iter()
+                .map(|x| format!("{}", x))
+                .collect::<Vec<_>>(),
+        );
         res.extend(
             self.functions
                 .iter()
@@ -79,6 +91,31 @@ impl<'ast, T: Field> fmt::Debug for Prog<'ast, T> {
     }
 }
 
+#[derive(Clone, PartialEq)]
+pub struct Constant<'ast, T: Field> {
+    pub id: Identifier<'ast>,
+    pub ty: UnresolvedType<'ast>,
+    pub expression: ExpressionNode<'ast, T>,
+}
+
+pub type ConstantNode<'ast, T> = Node<Constant<'ast, T>>;
+
+impl<'ast, T: Field> fmt::Display for Constant<'ast, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "const {} {} = {}", self.ty, self.id, self.expression)
+    }
+}
+
+impl<'ast, T: Field> fmt::Debug for Constant<'ast, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Constant(id: {:?}, type: {:?}, expression: {:?})",
+            self.id, self.ty, self.expression
+        )
+    }
+}
+
 #[derive(Clone, PartialEq)]
 pub struct Function<'ast, T: Field> {
     /// Name of the program
@@ -88,7 +125,7 @@ pub struct Function<'ast, T: Field> {
     /// Vector of statements that are executed when running the function
     pub statements: Vec<StatementNode<'ast, T>>,
     /// function signature
-    pub signature: Signature,
+    pub signature: UnresolvedSignature<'ast>,
 }
 
 pub type FunctionNode<'ast, T> = Node<Function<'ast, T>>;
@@ -97,8 +134,12 @@ impl<'ast, T: Field> fmt::Display for Function<'ast, T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "def {}({}):\n{}",
+            "def {}{}({}):\n{}",
             self.id,
+            match self.signature.generics.len() {
+                0 => String::new(),
+                _ => format!("<{}>", self.signature.generics.join(", ")),
+            },
             self.arguments
                 .iter()
                 .map(|x| format!("{}", x))
@@ -158,8 +199,22 @@ pub enum Statement<'ast, T: Field> {
     Declaration(VariableNode<'ast>),
     Definition(AssigneeNode<'ast, T>, ExpressionNode<'ast, T>),
     Condition(ExpressionNode<'ast, T>, ExpressionNode<'ast, T>),
-    For(VariableNode<'ast>, T, T, Vec<StatementNode<'ast, T>>),
+    /// An assertion which is not an equality check, rejected during semantic checking
+    UnsupportedAssertion(ExpressionNode<'ast, T>),
+    For(
+        VariableNode<'ast>,
+        ExpressionNode<'ast, T>,
+        ExpressionNode<'ast, T>,
+        Vec<StatementNode<'ast, T>>,
+    ),
+    IfElse(
+        ExpressionNode<'ast, T>,
+        Vec<StatementNode<'ast, T>>,
+        Vec<StatementNode<'ast, T>>,
+    ),
     MultipleDefinition(Vec<AssigneeNode<'ast, T>>, ExpressionNode<'ast, T>),
+    /// A message printed when computing a witness, where each `{}` in the format is replaced by an argument
+    Log(String, Vec<ExpressionNode<'ast, T>>),
 }
 
 pub type StatementNode<'ast, T> = Node<Statement<'ast, T>>;
@@ -171,6 +226,7 @@ impl<'ast, T: Field> fmt::Display for Statement<'ast, T> {
             Statement::Declaration(ref var) => write!(f, "{}", var),
             Statement::Definition(ref lhs, ref rhs) => write!(f, "{} = {}", lhs, rhs),
             Statement::Condition(ref lhs, ref rhs) => write!(f, "{} == {}", lhs, rhs),
+            Statement::UnsupportedAssertion(ref e) => write!(f, "{}", e),
             Statement::For(ref var, ref start, ref stop, ref list) => {
                 r#try!(write!(f, "for {} in {}..{} do\n", var, start, stop));
                 for l in list {
@@ -178,6 +234,17 @@ impl<'ast, T: Field> fmt::Display for Statement<'ast, T> {
                 }
                 write!(f, "\tendfor")
             }
+            Statement::IfElse(ref condition, ref consequence, ref alternative) => {
+                r#try!(write!(f, "if {} then\n", condition));
+                for l in consequence {
+                    r#try!(write!(f, "\t\t{}\n", l));
+                }
+                r#try!(write!(f, "\telse\n"));
+                for l in alternative {
+                    r#try!(write!(f, "\t\t{}\n", l));
+                }
+                write!(f, "\tendif")
+            }
             Statement::MultipleDefinition(ref ids, ref rhs) => {
                 for (i, id) in ids.iter().enumerate() {
                     r#try!(write!(f, "{}", id));
@@ -187,6 +254,13 @@ impl<'ast, T: Field> fmt::Display for Statement<'ast, T> {
                 }
                 write!(f, " = {}", rhs)
             }
+            Statement::Log(ref format, ref arguments) => {
+                r#try!(write!(f, "log(\"{}\"", format));
+                for a in arguments {
+                    r#try!(write!(f, ", {}", a));
+                }
+                write!(f, ")")
+            }
         }
     }
 }
@@ -200,6 +274,7 @@ impl<'ast, T: Field> fmt::Debug for Statement<'ast, T> {
                 write!(f, "Definition({:?}, {:?})", lhs, rhs)
             }
             Statement::Condition(ref lhs, ref rhs) => write!(f, "Condition({:?}, {:?})", lhs, rhs),
+            Statement::UnsupportedAssertion(ref e) => write!(f, "UnsupportedAssertion({:?})", e),
             Statement::For(ref var, ref start, ref stop, ref list) => {
                 r#try!(write!(f, "for {:?} in {:?}..{:?} do\n", var, start, stop));
                 for l in list {
@@ -207,9 +282,19 @@ impl<'ast, T: Field> fmt::Debug for Statement<'ast, T> {
                 }
                 write!(f, "\tendfor")
             }
+            Statement::IfElse(ref condition, ref consequence, ref alternative) => {
+                write!(
+                    f,
+                    "IfElse({:?}, {:?}, {:?})",
+                    condition, consequence, alternative
+                )
+            }
             Statement::MultipleDefinition(ref lhs, ref rhs) => {
                 write!(f, "MultipleDefinition({:?}, {:?})", lhs, rhs)
             }
+            Statement::Log(ref format, ref arguments) => {
+                write!(f, "Log({:?}, {:?})", format, arguments)
+            }
         }
     }
 }
@@ -240,7 +325,7 @@ impl<'ast, T: Field> fmt::Debug for SpreadOrExpression<'ast, T> {
 
 #[derive(Clone, PartialEq)]
 pub enum RangeOrExpression<'ast, T: Field> {
-    Range(RangeNode<T>),
+    Range(RangeNode<'ast, T>),
     Expression(ExpressionNode<'ast, T>),
 }
 
@@ -282,14 +367,14 @@ pub struct Spread<'ast, T: Field> {
 }
 
 #[derive(Clone, PartialEq)]
-pub struct Range<T: Field> {
-    pub from: Option<T>,
-    pub to: Option<T>,
+pub struct Range<'ast, T: Field> {
+    pub from: Option<ExpressionNode<'ast, T>>,
+    pub to: Option<ExpressionNode<'ast, T>>,
 }
 
-pub type RangeNode<T> = Node<Range<T>>;
+pub type RangeNode<'ast, T> = Node<Range<'ast, T>>;
 
-impl<'ast, T: Field> fmt::Display for Range<T> {
+impl<'ast, T: Field> fmt::Display for Range<'ast, T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
@@ -306,7 +391,7 @@ impl<'ast, T: Field> fmt::Display for Range<T> {
     }
 }
 
-impl<'ast, T: Field> fmt::Debug for Range<T> {
+impl<'ast, T: Field> fmt::Debug for Range<'ast, T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Range({:?}, {:?})", self.from, self.to)
     }
@@ -320,6 +405,16 @@ pub enum Expression<'ast, T: Field> {
     Sub(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
     Mult(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
     Div(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
+    IntegerDiv(
+        Box<ExpressionNode<'ast, T>>,
+        Box<ExpressionNode<'ast, T>>,
+        usize,
+    ),
+    Rem(
+        Box<ExpressionNode<'ast, T>>,
+        Box<ExpressionNode<'ast, T>>,
+        usize,
+    ),
     Pow(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
     IfElse(
         Box<ExpressionNode<'ast, T>>,
@@ -330,6 +425,7 @@ pub enum Expression<'ast, T: Field> {
     Lt(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
     Le(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
     Eq(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
+    NotEq(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
     Ge(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
     Gt(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
     And(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
@@ -340,6 +436,7 @@ pub enum Expression<'ast, T: Field> {
         Box<RangeOrExpression<'ast, T>>,
     ),
     Or(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
+    Xor(Box<ExpressionNode<'ast, T>>, Box<ExpressionNode<'ast, T>>),
 }
 
 pub type ExpressionNode<'ast, T> = Node<Expression<'ast, T>>;
@@ -353,6 +450,12 @@ impl<'ast, T: Field> fmt::Display for Expression<'ast, T> {
             Expression::Sub(ref lhs, ref rhs) => write!(f, "({} - {})", lhs, rhs),
             Expression::Mult(ref lhs, ref rhs) => write!(f, "({} * {})", lhs, rhs),
             Expression::Div(ref lhs, ref rhs) => write!(f, "({} / {})", lhs, rhs),
+            Expression::IntegerDiv(ref lhs, ref rhs, ref bitwidth) => {
+                write!(f, "({} \\<{}> {})", lhs, bitwidth, rhs)
+            }
+            Expression::Rem(ref lhs, ref rhs, ref bitwidth) => {
+                write!(f, "({} %<{}> {})", lhs, bitwidth, rhs)
+            }
             Expression::Pow(ref lhs, ref rhs) => write!(f, "{}**{}", lhs, rhs),
             Expression::IfElse(ref condition, ref consequent, ref alternative) => write!(
                 f,
@@ -372,6 +475,7 @@ impl<'ast, T: Field> fmt::Display for Expression<'ast, T> {
             Expression::Lt(ref lhs, ref rhs) => write!(f, "{} < {}", lhs, rhs),
             Expression::Le(ref lhs, ref rhs) => write!(f, "{} <= {}", lhs, rhs),
             Expression::Eq(ref lhs, ref rhs) => write!(f, "{} == {}", lhs, rhs),
+            Expression::NotEq(ref lhs, ref rhs) => write!(f, "{} != {}", lhs, rhs),
             Expression::Ge(ref lhs, ref rhs) => write!(f, "{} >= {}", lhs, rhs),
             Expression::Gt(ref lhs, ref rhs) => write!(f, "{} > {}", lhs, rhs),
             Expression::And(ref lhs, ref rhs) => write!(f, "{} && {}", lhs, rhs),
@@ -388,6 +492,7 @@ impl<'ast, T: Field> fmt::Display for Expression<'ast, T> {
             }
             Expression::Select(ref array, ref index) => write!(f, "{}[{}]", array, index),
             Expression::Or(ref lhs, ref rhs) => write!(f, "{} || {}", lhs, rhs),
+            Expression::Xor(ref lhs, ref rhs) => write!(f, "{} ^ {}", lhs, rhs),
         }
     }
 }
@@ -401,6 +506,12 @@ impl<'ast, T: Field> fmt::Debug for Expression<'ast, T> {
             Expression::Sub(ref lhs, ref rhs) => write!(f, "Sub({:?}, {:?})", lhs, rhs),
             Expression::Mult(ref lhs, ref rhs) => write!(f, "Mult({:?}, {:?})", lhs, rhs),
             Expression::Div(ref lhs, ref rhs) => write!(f, "Div({:?}, {:?})", lhs, rhs),
+            Expression::IntegerDiv(ref lhs, ref rhs, ref bitwidth) => {
+                write!(f, "IntegerDiv({:?}, {:?}, {})", lhs, rhs, bitwidth)
+            }
+            Expression::Rem(ref lhs, ref rhs, ref bitwidth) => {
+                write!(f, "Rem({:?}, {:?}, {})", lhs, rhs, bitwidth)
+            }
             Expression::Pow(ref lhs, ref rhs) => write!(f, "Pow({:?}, {:?})", lhs, rhs),
             Expression::IfElse(ref condition, ref consequent, ref alternative) => write!(
                 f,
@@ -415,6 +526,7 @@ impl<'ast, T: Field> fmt::Debug for Expression<'ast, T> {
             Expression::Lt(ref lhs, ref rhs) => write!(f, "{} < {}", lhs, rhs),
             Expression::Le(ref lhs, ref rhs) => write!(f, "{} <= {}", lhs, rhs),
             Expression::Eq(ref lhs, ref rhs) => write!(f, "{} == {}", lhs, rhs),
+            Expression::NotEq(ref lhs, ref rhs) => write!(f, "{} != {}", lhs, rhs),
             Expression::Ge(ref lhs, ref rhs) => write!(f, "{} >= {}", lhs, rhs),
             Expression::Gt(ref lhs, ref rhs) => write!(f, "{} > {}", lhs, rhs),
             Expression::And(ref lhs, ref rhs) => write!(f, "{} && {}", lhs, rhs),
@@ -426,6 +538,7 @@ impl<'ast, T: Field> fmt::Debug for Expression<'ast, T> {
             }
             Expression::Select(ref array, ref index) => write!(f, "{}[{}]", array, index),
             Expression::Or(ref lhs, ref rhs) => write!(f, "{} || {}", lhs, rhs),
+            Expression::Xor(ref lhs, ref rhs) => write!(f, "{} ^ {}", lhs, rhs),
         }
     }
 }
@@ -0,0 +1,154 @@
+use crate::absy::Identifier;
+use crate::types::Type;
+use std::fmt;
+
+/// The size of an array type, which can refer to a generic parameter
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum ArraySize<'ast> {
+    Constant(usize),
+    Identifier(Identifier<'ast>),
+    /// Any other expression, which is rejected during semantic checking
+    Unsupported(&'ast str),
+}
+
+impl<'ast> fmt::Display for ArraySize<'ast> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ArraySize::Constant(size) => write!(f, "{}", size),
+            ArraySize::Identifier(id) => write!(f, "{}", id),
+            ArraySize::Unsupported(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// A type as written in the source, before array sizes are resolved during semantic checking
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum UnresolvedType<'ast> {
+    FieldElement,
+    Boolean,
+    FieldElementArray(ArraySize<'ast>),
+    /// Arrays of booleans are not supported, they are rejected during semantic checking
+    BooleanArray(ArraySize<'ast>),
+}
+
+impl<'ast> fmt::Display for UnresolvedType<'ast> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UnresolvedType::FieldElement => write!(f, "field"),
+            UnresolvedType::Boolean => write!(f, "bool"),
+            UnresolvedType::FieldElementArray(ref size) => {
+                write!(f, "{}[{}]", UnresolvedType::FieldElement, size)
+            }
+            UnresolvedType::BooleanArray(ref size) => {
+                write!(f, "{}[{}]", UnresolvedType::Boolean, size)
+            }
+        }
+    }
+}
+
+impl<'ast> fmt::Debug for UnresolvedType<'ast> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl<'ast> From<Type> for UnresolvedType<'ast> {
+    fn from(t: Type) -> UnresolvedType<'ast> {
+        match t {
+            Type::FieldElement => UnresolvedType::FieldElement,
+            Type::Boolean => UnresolvedType::Boolean,
+            Type::FieldElementArray(size) => {
+                UnresolvedType::FieldElementArray(ArraySize::Constant(size))
+            }
+        }
+    }
+}
+
+/// A function signature as written in the source, which can be generic over array sizes
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct UnresolvedSignature<'ast> {
+    pub generics: Vec<Identifier<'ast>>,
+    pub inputs: Vec<UnresolvedType<'ast>>,
+    pub outputs: Vec<UnresolvedType<'ast>>,
+}
+
+impl<'ast> fmt::Debug for UnresolvedSignature<'ast> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "UnresolvedSignature(generics: {:?}, inputs: {:?}, outputs: {:?})",
+            self.generics, self.inputs, self.outputs
+        )
+    }
+}
+
+impl<'ast> fmt::Display for UnresolvedSignature<'ast> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.generics.len() > 0 {
+            r#try!(write!(f, "<{}>", self.generics.join(", ")));
+        }
+        r#try!(write!(f, "("));
+        for (i, t) in self.inputs.iter().enumerate() {
+            r#try!(write!(f, "{}", t));
+            if i < self.inputs.len() - 1 {
+                r#try!(write!(f, ", "));
+            }
+        }
+        r#try!(write!(f, ") -> ("));
+        for (i, t) in self.outputs.iter().enumerate() {
+            r#try!(write!(f, "{}", t));
+            if i < self.outputs.len() - 1 {
+                r#try!(write!(f, ", "));
+            }
+        }
+        write!(f, ")")
+    }
+}
+
+impl<'ast> UnresolvedSignature<'ast> {
+    pub fn new() -> UnresolvedSignature<'ast> {
+        UnresolvedSignature {
+            generics: vec![],
+            inputs: vec![],
+            outputs: vec![],
+        }
+    }
+
+    pub fn generics(mut self, generics: Vec<Identifier<'ast>>) -> Self {
+        self.generics = generics;
+        self
+    }
+
+    pub fn inputs(mut self, inputs: Vec<UnresolvedType<'ast>>) -> Self {
+        self.inputs = inputs;
+        self
+    }
+
+    pub fn outputs(mut self, outputs: Vec<UnresolvedType<'ast>>) -> Self {
+        self.outputs = outputs;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature() {
+        let s = UnresolvedSignature::new()
+            .generics(vec!["N"])
+            .inputs(vec![
+                UnresolvedType::FieldElementArray(ArraySize::Identifier("N")),
+                UnresolvedType::Boolean,
+            ])
+            .outputs(vec![UnresolvedType::FieldElementArray(
+                ArraySize::Constant(2),
+            )]);
+
+        assert_eq!(
+            s.to_string(),
+            String::from("<N>(field[N], bool) -> (field[2])")
+        );
+    }
+}
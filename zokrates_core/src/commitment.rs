@@ -0,0 +1,334 @@
+//! Module containing the commitment to the public inputs of a program.
+//!
+//! When compiling with a commitment, the public arguments and the return values of `main` are
+//! turned into private values, and the program returns a single value instead: the sha256 hash of
+//! their 32 byte big-endian encodings, shifted right by 3 bits to fit in a field element.
+//! Verifiers compute the same hash from the original values, so that the proof has a single
+//! public input however many values the program exposes.
+
+use crate::flat_absy::{
+    FlatExpression, FlatExpressionList, FlatFunction, FlatParameter, FlatProg, FlatStatement,
+    FlatVariable,
+};
+use crate::helpers::{DirectiveStatement, Helper};
+use crate::standard::sha_round;
+use crate::types::Type;
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use zokrates_field::field::Field;
+
+/// The number of bits of the hash which are kept in the commitment
+const COMMITMENT_BITS: usize = 253;
+
+/// The initial hash value of sha256
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Returns the commitment to `values`, as computed by programs compiled with a commitment
+pub fn commitment<T: Field>(values: &[T]) -> T {
+    let mut hasher = Sha256::new();
+    for v in values {
+        let mut bytes = v.into_byte_vector();
+        bytes.resize(32, 0);
+        bytes.reverse();
+        hasher.input(&bytes);
+    }
+
+    let hash = BigUint::from_bytes_be(&hasher.result()) >> (256 - COMMITMENT_BITS);
+    T::from_byte_vector(hash.to_bytes_le())
+}
+
+/// Makes `main` commit to its public inputs, returning the transformed program and the
+/// variables holding the committed values, in order: the public arguments, then the return values
+pub fn commit_public_inputs<T: Field>(prog: FlatProg<T>) -> (FlatProg<T>, Vec<FlatVariable>) {
+    let mut committed = vec![];
+
+    let functions = prog
+        .functions
+        .into_iter()
+        .map(|f| match f.id.as_ref() {
+            "main" => {
+                let (main, c) = Committer::new(&f).commit(f);
+                committed = c;
+                main
+            }
+            _ => f,
+        })
+        .collect();
+
+    (FlatProg { functions, ..prog }, committed)
+}
+
+struct Committer {
+    next_var_idx: usize,
+}
+
+impl Committer {
+    fn new<T: Field>(main: &FlatFunction<T>) -> Committer {
+        let max = main
+            .arguments
+            .iter()
+            .map(|p| p.id)
+            .chain(main.statements.iter().flat_map(|s| statement_variables(s)))
+            .filter(|v| *v > FlatVariable::one())
+            .map(|v| v.id())
+            .max();
+
+        Committer {
+            next_var_idx: max.map(|m| m + 1).unwrap_or(0),
+        }
+    }
+
+    fn commit<T: Field>(&mut self, main: FlatFunction<T>) -> (FlatFunction<T>, Vec<FlatVariable>) {
+        let (returns, mut statements): (Vec<_>, Vec<_>) =
+            main.statements.into_iter().partition(|s| match s {
+                FlatStatement::Return(..) => true,
+                _ => false,
+            });
+
+        let return_expressions = match returns.into_iter().next() {
+            Some(FlatStatement::Return(list)) => list.expressions,
+            _ => unreachable!(),
+        };
+
+        // the public arguments are kept as arguments, so their values can be read from the witness
+        let mut committed: Vec<_> = main
+            .arguments
+            .iter()
+            .filter(|p| !p.private)
+            .map(|p| p.id)
+            .collect();
+
+        // the return values are copied by a directive, so that they are not optimized away
+        for e in return_expressions {
+            let value = self.use_sym();
+            let copy = self.use_sym();
+            statements.push(FlatStatement::Definition(value, e));
+            statements.push(FlatStatement::Directive(DirectiveStatement::new(
+                vec![copy],
+                Helper::identity(),
+                vec![value],
+            )));
+            statements.push(FlatStatement::Condition(copy.into(), value.into()));
+            committed.push(copy);
+        }
+
+        if committed.is_empty() {
+            statements.push(FlatStatement::Return(FlatExpressionList {
+                expressions: vec![],
+            }));
+            return (FlatFunction { statements, ..main }, committed);
+        }
+
+        // encode each value on 256 bits
+        let mut message: Vec<FlatExpression<T>> = vec![];
+        for v in committed.iter() {
+            message.push(FlatExpression::Number(T::zero()));
+            message.push(FlatExpression::Number(T::zero()));
+            message.extend(
+                self.decompose(*v, &mut statements)
+                    .into_iter()
+                    .map(|b| FlatExpression::Identifier(b)),
+            );
+        }
+
+        let hash = self.sha256(message, &mut statements);
+
+        // keep the first bits of the hash
+        let commitment = hash.into_iter().take(COMMITMENT_BITS).enumerate().fold(
+            FlatExpression::Number(T::zero()),
+            |acc, (i, bit)| {
+                FlatExpression::Add(
+                    box acc,
+                    box FlatExpression::Mult(
+                        box bit,
+                        box FlatExpression::Number(T::from(2).pow(COMMITMENT_BITS - i - 1)),
+                    ),
+                )
+            },
+        );
+
+        statements.push(FlatStatement::Return(FlatExpressionList {
+            expressions: vec![commitment],
+        }));
+
+        let arguments = main
+            .arguments
+            .into_iter()
+            .map(|p| FlatParameter { private: true, ..p })
+            .collect();
+
+        let signature = main.signature.outputs(vec![Type::FieldElement]);
+
+        (
+            FlatFunction {
+                id: main.id,
+                arguments,
+                statements,
+                signature,
+            },
+            committed,
+        )
+    }
+
+    // decompose `v` into its bits, most significant first, constraining the decomposition
+    fn decompose<T: Field>(
+        &mut self,
+        v: FlatVariable,
+        statements: &mut Vec<FlatStatement<T>>,
+    ) -> Vec<FlatVariable> {
+        let bitwidth = T::get_required_bits();
+        let bits: Vec<_> = (0..bitwidth).map(|_| self.use_sym()).collect();
+
+        statements.push(FlatStatement::Directive(DirectiveStatement::new(
+            bits.clone(),
+            Helper::bits(),
+            vec![v],
+        )));
+
+        let mut sum = FlatExpression::Number(T::zero());
+
+        for (i, b) in bits.iter().enumerate() {
+            statements.push(FlatStatement::Condition(
+                FlatExpression::Identifier(*b),
+                FlatExpression::Mult(
+                    box FlatExpression::Identifier(*b),
+                    box FlatExpression::Identifier(*b),
+                ),
+            ));
+            sum = FlatExpression::Add(
+                box sum,
+                box FlatExpression::Mult(
+                    box FlatExpression::Identifier(*b),
+                    box FlatExpression::Number(T::from(2).pow(bitwidth - i - 1)),
+                ),
+            );
+        }
+
+        statements.push(FlatStatement::Condition(FlatExpression::Identifier(v), sum));
+
+        bits
+    }
+
+    // hash `message`, padding it and applying one sha256 round per 512 bit block
+    fn sha256<T: Field>(
+        &mut self,
+        mut message: Vec<FlatExpression<T>>,
+        statements: &mut Vec<FlatStatement<T>>,
+    ) -> Vec<FlatExpression<T>> {
+        let length = message.len();
+
+        // append a one, zeros and the length of the message on 64 bits
+        message.push(FlatExpression::Number(T::one()));
+        while (message.len() + 64) % 512 != 0 {
+            message.push(FlatExpression::Number(T::zero()));
+        }
+        message.extend(
+            (0..64)
+                .rev()
+                .map(|i| FlatExpression::Number(T::from((length >> i) & 1))),
+        );
+
+        let round = sha_round::<T>();
+
+        let mut hash: Vec<_> = IV
+            .iter()
+            .flat_map(|word| (0..32).rev().map(move |i| (word >> i) & 1))
+            .map(|bit| FlatExpression::Number(T::from(bit)))
+            .collect();
+
+        for block in message.chunks(512) {
+            let inputs = block.iter().cloned().chain(hash).collect();
+            hash = self.inline(round.clone(), inputs, statements);
+        }
+
+        hash
+    }
+
+    // inline a call to `function`, renaming all of its variables
+    fn inline<T: Field>(
+        &mut self,
+        function: FlatFunction<T>,
+        inputs: Vec<FlatExpression<T>>,
+        statements: &mut Vec<FlatStatement<T>>,
+    ) -> Vec<FlatExpression<T>> {
+        let mut substitution = HashMap::new();
+
+        for (argument, input) in function.arguments.iter().zip(inputs) {
+            let v = self.use_sym();
+            statements.push(FlatStatement::Definition(v, input));
+            substitution.insert(argument.id, v);
+        }
+
+        let mut returned = vec![];
+
+        for s in function.statements {
+            match s {
+                FlatStatement::Return(list) => {
+                    returned = list.apply_substitution(&substitution).expressions;
+                }
+                FlatStatement::Directive(d) => {
+                    for o in d.outputs.iter() {
+                        let v = self.use_sym();
+                        substitution.insert(*o, v);
+                    }
+                    statements.push(FlatStatement::Directive(d).apply_substitution(&substitution));
+                }
+                FlatStatement::Definition(v, e) => {
+                    let new_v = self.use_sym();
+                    substitution.insert(v, new_v);
+                    statements
+                        .push(FlatStatement::Definition(v, e).apply_substitution(&substitution));
+                }
+                s => statements.push(s.apply_substitution(&substitution)),
+            }
+        }
+
+        returned
+    }
+
+    fn use_sym(&mut self) -> FlatVariable {
+        let v = FlatVariable::new(self.next_var_idx);
+        self.next_var_idx += 1;
+        v
+    }
+}
+
+fn statement_variables<T: Field>(s: &FlatStatement<T>) -> Vec<FlatVariable> {
+    match s {
+        FlatStatement::Return(list) => list
+            .expressions
+            .iter()
+            .flat_map(|e| expression_variables(e))
+            .collect(),
+        FlatStatement::Condition(lhs, rhs) => expression_variables(lhs)
+            .into_iter()
+            .chain(expression_variables(rhs))
+            .collect(),
+        FlatStatement::Definition(v, e) => {
+            std::iter::once(*v).chain(expression_variables(e)).collect()
+        }
+        FlatStatement::Directive(d) => d
+            .outputs
+            .iter()
+            .cloned()
+            .chain(d.inputs.iter().flat_map(|e| expression_variables(e)))
+            .collect(),
+    }
+}
+
+fn expression_variables<T: Field>(e: &FlatExpression<T>) -> Vec<FlatVariable> {
+    match e {
+        FlatExpression::Number(_) => vec![],
+        FlatExpression::Identifier(v) => vec![*v],
+        FlatExpression::Add(e1, e2)
+        | FlatExpression::Sub(e1, e2)
+        | FlatExpression::Mult(e1, e2) => {
+            let mut vars = expression_variables(e1);
+            vars.extend(expression_variables(e2));
+            vars
+        }
+    }
+}
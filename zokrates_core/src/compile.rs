@@ -4,16 +4,22 @@
 //! @author Thibaut Schaeffer <thibaut@schaeff.fr>
 //! @date 2018
 use absy::Prog;
+use commitment::commit_public_inputs;
+use diagnostics::{Code, Diagnostic};
 use flat_absy::FlatProg;
 use flatten::Flattener;
 use imports::{self, Importer};
 use ir;
 use optimizer::Optimize;
+use parser::Position;
 use semantics::{self, Checker};
-use static_analysis::Analyse;
+use static_analysis::{Analyse, BoundsChecker, LocationStripper, LogStripper};
 use std::fmt;
 use std::io;
 use std::io::BufRead;
+use std::path::{Component, Path};
+use symbols::SymbolIndex;
+use typed_absy::{ConstantValue, TypedProg};
 use zokrates_field::field::Field;
 use zokrates_pest_ast as pest;
 
@@ -64,12 +70,28 @@ pub struct CompileError {
 }
 
 impl CompileErrors {
-    pub fn with_context(self, context: Option<String>) -> Self {
+    /// Gathers the errors of several compilation steps
+    pub fn concat(errors: Vec<CompileErrors>) -> Self {
+        CompileErrors(errors.into_iter().flat_map(|e| e.0).collect())
+    }
+
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.0.iter().map(|e| e.diagnostic()).collect()
+    }
+
+    /// Attributes the errors of a module compiled from `location` and imported as `source` to
+    /// the path of their file relative to the importing module
+    pub fn imported_as(self, source: &String, location: &Option<String>) -> Self {
         CompileErrors(
             self.0
                 .into_iter()
                 .map(|e| CompileError {
-                    context: context.clone(),
+                    context: match e.context {
+                        Some(ref context) if Some(context) != location.as_ref() => {
+                            Some(imported_path(source, context))
+                        }
+                        _ => Some(source.clone()),
+                    },
                     ..e
                 })
                 .collect(),
@@ -77,6 +99,44 @@ impl CompileErrors {
     }
 }
 
+// the path of the module imported as `source` by the module imported as `parent`. Imports starting
+// with `./` or `../` are relative to the importing module, the others are kept as they are
+fn imported_path(parent: &String, source: &String) -> String {
+    match Path::new(source).components().next() {
+        Some(Component::CurDir) | Some(Component::ParentDir) => Path::new(parent)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(source.trim_start_matches("./"))
+            .display()
+            .to_string(),
+        _ => source.clone(),
+    }
+}
+
+impl CompileError {
+    pub fn diagnostic(&self) -> Diagnostic {
+        let diagnostic = match self.value {
+            CompileErrorInner::ParserError(ref e) => {
+                let ((start_line, start_col), (end_line, end_col)) = e.line_col();
+                Diagnostic::new(Code::SyntaxError, e.message()).with_pos(Some((
+                    Position {
+                        line: start_line,
+                        col: start_col,
+                    },
+                    Position {
+                        line: end_line,
+                        col: end_col,
+                    },
+                )))
+            }
+            CompileErrorInner::ImportError(ref e) => Diagnostic::from(e),
+            CompileErrorInner::SemanticError(ref e) => Diagnostic::from(e),
+            CompileErrorInner::ReadError(ref e) => Diagnostic::new(Code::ReadError, e.to_string()),
+        };
+        diagnostic.with_file(self.context.clone())
+    }
+}
+
 impl fmt::Display for CompileError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let context = match self.context {
@@ -123,13 +183,62 @@ impl fmt::Display for CompileErrorInner {
     }
 }
 
+/// Options changing the program produced by the compiler
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CompileConfig {
+    /// Replace the public inputs and outputs of `main` by a single commitment to them
+    pub commit_public_inputs: bool,
+    /// Remove the log statements, which are only useful when debugging a program
+    pub strip_logs: bool,
+    /// Keep the locations in the source of the statements of the main module, as used by the
+    /// debugger
+    pub debug: bool,
+}
+
 pub fn compile<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
     reader: &mut R,
     location: Option<String>,
     resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
 ) -> Result<ir::Prog<T>, CompileErrors> {
-    let compiled = compile_aux(reader, location, resolve_option)?;
-    Ok(ir::Prog::from(compiled).optimize())
+    compile_with_config(reader, location, resolve_option, CompileConfig::default())
+}
+
+pub fn compile_with_config<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
+    reader: &mut R,
+    location: Option<String>,
+    resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
+    config: CompileConfig,
+) -> Result<ir::Prog<T>, CompileErrors> {
+    compile_with_warnings(reader, location, resolve_option, config).map(|(program, _)| program)
+}
+
+/// Compiles a program, returning it along with the warnings found in its source
+pub fn compile_with_warnings<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
+    reader: &mut R,
+    location: Option<String>,
+    resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
+    config: CompileConfig,
+) -> Result<(ir::Prog<T>, Vec<Diagnostic>), CompileErrors> {
+    let (compiled, _, warnings) = compile_module(reader, location, resolve_option, &config)?;
+
+    let (compiled, committed) = match config.commit_public_inputs {
+        true => commit_public_inputs(compiled),
+        false => (compiled, vec![]),
+    };
+
+    let program = ir::Prog {
+        committed,
+        ..ir::Prog::from(compiled)
+    };
+
+    // the logs of the main module are stripped before flattening, but those of the imported
+    // functions are only found in the flattened program
+    let program = match config.strip_logs {
+        true => program.strip_logs(),
+        false => program,
+    };
+
+    Ok((program.optimize(), warnings))
 }
 
 pub fn compile_aux<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
@@ -137,38 +246,235 @@ pub fn compile_aux<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
     location: Option<String>,
     resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
 ) -> Result<FlatProg<T>, CompileErrors> {
+    compile_module(reader, location, resolve_option, &CompileConfig::default())
+        .map(|(program, _, _)| program)
+}
+
+/// Compiles a program, returning it along with the values of its global constants and the warnings
+/// found in its source. The warnings of the imported programs are not reported. Only the `debug`
+/// and `strip_logs` options of `config` are applied.
+pub fn compile_module<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
+    reader: &mut R,
+    location: Option<String>,
+    resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
+    config: &CompileConfig,
+) -> Result<
+    (
+        FlatProg<T>,
+        Vec<(String, ConstantValue<T>)>,
+        Vec<Diagnostic>,
+    ),
+    CompileErrors,
+> {
     let mut source = String::new();
     reader.read_to_string(&mut source).unwrap();
     let ast = pest::generate_ast(&source)
         .map_err(|e| CompileErrors::from(CompileErrorInner::from(e).with_context(&location)))?;
     let program_ast_without_imports: Prog<T> = Prog::from(ast);
 
-    let program_ast = Importer::new().apply_imports(
+    compile_program(
+        program_ast_without_imports,
+        location,
+        resolve_option,
+        config,
+    )
+}
+
+/// Compiles a program which was already parsed, in the same way as `compile_module`
+pub fn compile_program<'ast, T: Field, S: BufRead, E: Into<imports::Error>>(
+    program_ast_without_imports: Prog<'ast, T>,
+    location: Option<String>,
+    resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
+    config: &CompileConfig,
+) -> Result<
+    (
+        FlatProg<T>,
+        Vec<(String, ConstantValue<T>)>,
+        Vec<Diagnostic>,
+    ),
+    CompileErrors,
+> {
+    let (typed_ast, warnings) = check_program(
         program_ast_without_imports,
         location.clone(),
         resolve_option,
     )?;
 
-    // check semantics
-    let typed_ast = Checker::check(program_ast).map_err(|errors| {
+    // logs are removed before flattening, so that their arguments are not flattened either
+    let typed_ast = match config.strip_logs {
+        true => LogStripper::strip(typed_ast),
+        false => typed_ast,
+    };
+
+    let constants = typed_ast.constants.clone();
+
+    Ok((
+        flatten_program(typed_ast, &location, config.debug)?,
+        constants,
+        warnings,
+    ))
+}
+
+/// Flattens a checked program found at `location`, keeping the locations of its statements in the
+/// source if `locations` is set
+pub(crate) fn flatten_program<'ast, T: Field>(
+    typed_ast: TypedProg<'ast, T>,
+    location: &Option<String>,
+    locations: bool,
+) -> Result<FlatProg<T>, CompileErrors> {
+    // analyse (unroll and constant propagation)
+    let typed_ast = typed_ast.analyse();
+
+    // out of bounds indices are only known once loops are unrolled and constants propagated
+    let typed_ast = BoundsChecker::check(typed_ast).map_err(|errors| {
         CompileErrors(
             errors
                 .into_iter()
-                .map(|e| CompileErrorInner::from(e).with_context(&location))
+                .map(|e| CompileErrorInner::from(e).with_context(location))
                 .collect(),
         )
     })?;
 
-    // analyse (unroll and constant propagation)
-    let typed_ast = typed_ast.analyse();
+    let typed_ast = match locations {
+        true => typed_ast,
+        false => LocationStripper::strip(typed_ast),
+    };
 
     // flatten input program
     let program_flattened = Flattener::flatten(typed_ast);
 
     // analyse (constant propagation after call resolution)
-    let program_flattened = program_flattened.analyse();
+    Ok(program_flattened.analyse())
+}
 
-    Ok(program_flattened)
+/// Resolves the imports of a program and checks its semantics, returning the typed program before
+/// any static analysis. Each statement is preceded by its location in the source, which is used to
+/// report the errors found by the static analysis.
+pub(crate) fn check_program<'ast, T: Field, S: BufRead, E: Into<imports::Error>>(
+    program_ast_without_imports: Prog<'ast, T>,
+    location: Option<String>,
+    resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
+) -> Result<(TypedProg<'ast, T>, Vec<Diagnostic>), CompileErrors> {
+    check(program_ast_without_imports, location, resolve_option, false)
+}
+
+/// Compiles a module imported by another program, returning it along with the values of its global
+/// constants. Unlike programs, modules do not need a `main` function, as their constants can be
+/// imported by name.
+pub(crate) fn compile_imported_module<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
+    reader: &mut R,
+    location: Option<String>,
+    resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
+) -> Result<(FlatProg<T>, Vec<(String, ConstantValue<T>)>), CompileErrors> {
+    let mut source = String::new();
+    reader.read_to_string(&mut source).unwrap();
+    let ast = pest::generate_ast(&source)
+        .map_err(|e| CompileErrors::from(CompileErrorInner::from(e).with_context(&location)))?;
+    let program_ast_without_imports: Prog<T> = Prog::from(ast);
+
+    let (typed_ast, _) = check(
+        program_ast_without_imports,
+        location.clone(),
+        resolve_option,
+        true,
+    )?;
+
+    let constants = typed_ast.constants.clone();
+
+    Ok((flatten_program(typed_ast, &location, false)?, constants))
+}
+
+// checks a program, or a module imported by another program if `module` is set
+fn check<'ast, T: Field, S: BufRead, E: Into<imports::Error>>(
+    program_ast_without_imports: Prog<'ast, T>,
+    location: Option<String>,
+    resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
+    module: bool,
+) -> Result<(TypedProg<'ast, T>, Vec<Diagnostic>), CompileErrors> {
+    let program_ast = Importer::new().apply_imports(
+        program_ast_without_imports,
+        location.clone(),
+        resolve_option,
+    )?;
+
+    // check semantics
+    let checked = match module {
+        true => Checker::check_module_with_locations(program_ast),
+        false => Checker::check_with_locations(program_ast),
+    };
+    let (typed_ast, warnings) = checked.map_err(|errors| {
+        CompileErrors(
+            errors
+                .into_iter()
+                .map(|e| CompileErrorInner::from(e).with_context(&location))
+                .collect(),
+        )
+    })?;
+
+    let warnings = warnings
+        .into_iter()
+        .map(|w| w.with_file(location.clone()))
+        .collect();
+
+    Ok((typed_ast, warnings))
+}
+
+/// The result of checking a program without compiling it, as used by editors
+#[derive(Debug, Default)]
+pub struct Analysis {
+    /// The errors and warnings found in the program and in the programs it imports
+    pub diagnostics: Vec<Diagnostic>,
+    /// The symbols of the program, which are found even if it has semantic errors
+    pub symbols: SymbolIndex,
+}
+
+/// Checks a program without flattening it, reporting the diagnostics and symbols found in its source
+pub fn analyse<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
+    reader: &mut R,
+    location: Option<String>,
+    resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
+) -> Analysis {
+    let failed = |e: CompileErrors| Analysis {
+        diagnostics: e.diagnostics(),
+        symbols: SymbolIndex::default(),
+    };
+
+    let mut source = String::new();
+    if let Err(e) = reader.read_to_string(&mut source) {
+        return failed(CompileErrorInner::from(e).with_context(&location).into());
+    }
+
+    let ast = match pest::generate_ast(&source) {
+        Ok(ast) => ast,
+        Err(e) => return failed(CompileErrorInner::from(e).with_context(&location).into()),
+    };
+    let program_ast_without_imports: Prog<T> = Prog::from(ast);
+
+    let program_ast = match Importer::new().apply_imports(
+        program_ast_without_imports,
+        location.clone(),
+        resolve_option,
+    ) {
+        Ok(program_ast) => program_ast,
+        Err(e) => return failed(e),
+    };
+
+    let (res, warnings, symbols) = Checker::analyse(program_ast);
+
+    let errors = match res {
+        Ok(_) => vec![],
+        Err(errors) => errors,
+    };
+
+    Analysis {
+        diagnostics: errors
+            .iter()
+            .map(Diagnostic::from)
+            .chain(warnings)
+            .map(|d| d.with_file(location.clone()))
+            .collect(),
+        symbols,
+    }
 }
 
 #[cfg(test)]
@@ -225,4 +531,536 @@ mod test {
         );
         assert!(res.is_ok());
     }
+
+    #[test]
+    fn with_commitment() {
+        let mut r = BufReader::new(
+            r#"
+			def main(field a, private field b) -> (field):
+			   return a * b
+		"#
+            .as_bytes(),
+        );
+        let prog: ir::Prog<FieldPrime> = compile_with_config(
+            &mut r,
+            Some(String::from("./path/to/file")),
+            None::<
+                fn(
+                    &Option<String>,
+                    &String,
+                ) -> Result<(BufReader<Empty>, String, String), io::Error>,
+            >,
+            CompileConfig {
+                commit_public_inputs: true,
+                ..CompileConfig::default()
+            },
+        )
+        .unwrap();
+
+        // all arguments are private and the program returns the commitment only
+        assert_eq!(prog.public_arguments_count(), 0);
+        assert_eq!(prog.main.returns.len(), 1);
+        assert_eq!(prog.committed.len(), 2);
+
+        let inputs = vec![FieldPrime::from(3), FieldPrime::from(7)];
+        let witness = prog.execute(&inputs).unwrap();
+
+        assert_eq!(
+            prog.committed_values(&witness),
+            vec![FieldPrime::from(3), FieldPrime::from(21)]
+        );
+        assert_eq!(
+            witness.return_values(),
+            vec![crate::commitment::commitment(&[
+                FieldPrime::from(3),
+                FieldPrime::from(21)
+            ])]
+        );
+    }
+
+    #[test]
+    fn logs() {
+        let source = r#"
+			def main(field a, bool b) -> (field):
+			   field[2] c = [a, 2 * a + 1]
+			   log("a = {}, b = {}", a, b)
+			   if a == 1 then
+			      log("a is one")
+			   else
+			      log("a is {}", a)
+			      if a == 3 then
+			         log("c = {}, c[1] = {}", c, c[1])
+			      endif
+			   endif
+			   return a * a
+		"#;
+
+        let compile = |strip_logs| -> ir::Prog<FieldPrime> {
+            compile_with_config(
+                &mut source.as_bytes(),
+                None,
+                None::<
+                    fn(
+                        &Option<String>,
+                        &String,
+                    )
+                        -> Result<(BufReader<Empty>, String, String), io::Error>,
+                >,
+                CompileConfig {
+                    strip_logs,
+                    ..CompileConfig::default()
+                },
+            )
+            .unwrap()
+        };
+
+        let prog = compile(false);
+        let stripped = compile(true);
+
+        // logs do not add constraints
+        assert_eq!(prog.constraint_count(), stripped.constraint_count());
+
+        let inputs = vec![FieldPrime::from(3), FieldPrime::from(1)];
+
+        let mut messages = vec![];
+        let witness = prog
+            .execute_with_log(&inputs, |m| messages.push(m))
+            .unwrap();
+        assert_eq!(witness.return_values(), vec![FieldPrime::from(9)]);
+        assert_eq!(
+            messages,
+            vec!["a = 3, b = true", "a is 3", "c = [3, 7], c[1] = 7"]
+        );
+
+        let mut messages = vec![];
+        stripped
+            .execute_with_log(&inputs, |m| messages.push(m))
+            .unwrap();
+        assert!(messages.is_empty());
+    }
+
+    #[test]
+    fn log_non_linear_argument() {
+        // logging these arguments would require constraints to compute them
+        for argument in vec!["a * a", "a / 2", "a < 3", "a == 3", "[a, a * a]"] {
+            let source = format!(
+                r#"
+			def main(field a) -> (field):
+			   log("{{}}", {})
+			   return a
+		"#,
+                argument
+            );
+
+            let res: Result<ir::Prog<FieldPrime>, CompileErrors> = compile(
+                &mut source.as_bytes(),
+                None,
+                None::<
+                    fn(
+                        &Option<String>,
+                        &String,
+                    )
+                        -> Result<(BufReader<Empty>, String, String), io::Error>,
+                >,
+            );
+
+            let diagnostics = res.unwrap_err().diagnostics();
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].code, Code::InvalidLog);
+            assert_eq!(diagnostics[0].pos.unwrap().0.line, 3);
+        }
+    }
+
+    #[test]
+    fn source_names() {
+        let source = r#"
+			def foo(field x) -> (field):
+			   field y = x * x
+			   return y
+
+			def main(field a, private field[2] b) -> (field):
+			   a = a + b[1]
+			   return foo(a)
+		"#
+        .to_string();
+
+        let prog: ir::Prog<FieldPrime> = compile(
+            &mut source.as_bytes(),
+            None,
+            None::<
+                fn(
+                    &Option<String>,
+                    &String,
+                ) -> Result<(BufReader<Empty>, String, String), io::Error>,
+            >,
+        )
+        .unwrap();
+
+        let names = prog
+            .main
+            .arguments
+            .iter()
+            .map(|a| prog.names.get(a).unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["main::a", "main::b[0]", "main::b[1]"]);
+
+        let witness = prog
+            .execute(&vec![
+                FieldPrime::from(1),
+                FieldPrime::from(2),
+                FieldPrime::from(3),
+            ])
+            .unwrap();
+        assert_eq!(witness.return_values(), vec![FieldPrime::from(16)]);
+        assert!(witness
+            .format_with_names(&prog.names)
+            .contains(&format!("{} 3 main::b[1]", prog.main.arguments[2])));
+    }
+
+    #[test]
+    fn log_format_mismatch() {
+        let mut r = BufReader::new(
+            r#"
+			def main(field a) -> (field):
+			   log("a = {}, b = {}", a)
+			   return a
+		"#
+            .as_bytes(),
+        );
+        let res: Result<ir::Prog<FieldPrime>, CompileErrors> = compile(
+            &mut r,
+            None,
+            None::<
+                fn(
+                    &Option<String>,
+                    &String,
+                ) -> Result<(BufReader<Empty>, String, String), io::Error>,
+            >,
+        );
+
+        let diagnostics = res.unwrap_err().diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, Code::InvalidLog);
+        assert_eq!(diagnostics[0].pos.unwrap().0.line, 3);
+    }
+
+    #[test]
+    fn diagnostics() {
+        let mut r = BufReader::new(
+            r#"
+			def main() -> (field):
+			   field a = 1
+			   field a = 2
+			   return b
+		"#
+            .as_bytes(),
+        );
+        let res: Result<ir::Prog<FieldPrime>, CompileErrors> = compile(
+            &mut r,
+            Some(String::from("./path/to/file")),
+            None::<
+                fn(
+                    &Option<String>,
+                    &String,
+                ) -> Result<(BufReader<Empty>, String, String), io::Error>,
+            >,
+        );
+
+        // all errors are reported
+        let diagnostics = res.unwrap_err().diagnostics();
+        assert_eq!(diagnostics.len(), 2);
+
+        assert_eq!(diagnostics[0].code, Code::DuplicateDefinition);
+        assert_eq!(diagnostics[0].pos.unwrap().0.line, 4);
+        assert_eq!(diagnostics[0].notes.len(), 1);
+        assert_eq!(diagnostics[0].notes[0].pos.unwrap().0.line, 3);
+
+        assert_eq!(diagnostics[1].code, Code::UndefinedIdentifier);
+        assert_eq!(diagnostics[1].pos.unwrap().0.line, 5);
+        assert_eq!(diagnostics[1].file, Some(String::from("./path/to/file")));
+    }
+
+    #[test]
+    fn syntax_error_diagnostic() {
+        let mut r = BufReader::new(
+            r#"
+			def main() -> (field):
+			   return 1 +
+		"#
+            .as_bytes(),
+        );
+        let res: Result<ir::Prog<FieldPrime>, CompileErrors> = compile(
+            &mut r,
+            Some(String::from("./path/to/file")),
+            None::<
+                fn(
+                    &Option<String>,
+                    &String,
+                ) -> Result<(BufReader<Empty>, String, String), io::Error>,
+            >,
+        );
+
+        let diagnostics = res.unwrap_err().diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, Code::SyntaxError);
+        assert!(diagnostics[0].pos.is_some());
+    }
+
+    fn resolve(
+        _: &Option<String>,
+        source: &String,
+    ) -> Result<(BufReader<&'static [u8]>, String, String), io::Error> {
+        let code = match source.as_str() {
+            "./foo" => "def main() -> (field):\n\treturn 1\n",
+            "./bar" => "const field M = 3\ndef main() -> (field):\n\treturn M\n",
+            "./baz" => "const field M = 4\nconst field[2] P = [1, M]\n",
+            "./lib/qux" => "import \"./quux\"\ndef main() -> (field):\n\treturn quux()\n",
+            "./quux" => "def main() -> (field):\n\treturn a\n",
+            _ => return Err(io::Error::new(io::ErrorKind::NotFound, "not found")),
+        };
+        Ok((
+            BufReader::new(code.as_bytes()),
+            source.clone(),
+            source[2..].to_string(),
+        ))
+    }
+
+    #[test]
+    fn warnings() {
+        let mut r = BufReader::new(
+            r#"
+			import "./foo"
+			from "./bar" import M
+			const field N = 2
+			def main(field a, field c) -> (field):
+			   field b = 1
+			   field N = a
+			   return N + M
+		"#
+            .as_bytes(),
+        );
+        let (_, warnings): (ir::Prog<FieldPrime>, _) = compile_with_warnings(
+            &mut r,
+            Some(String::from("./path/to/file")),
+            Some(resolve),
+            CompileConfig::default(),
+        )
+        .unwrap();
+
+        // parameters are not reported, and the constant `M` imported from `bar` is used
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|w| (w.code, w.message.as_str(), w.pos.unwrap().0.line))
+                .collect::<Vec<_>>(),
+            vec![
+                (Code::UnusedImport, "Unused import foo", 2),
+                (Code::UnusedVariable, "Unused variable b", 6),
+                (
+                    Code::ShadowedName,
+                    "Variable N shadows the global constant of the same name",
+                    7
+                ),
+            ]
+        );
+        assert!(warnings
+            .iter()
+            .all(|w| w.file == Some(String::from("./path/to/file"))));
+    }
+
+    #[test]
+    fn imported_errors() {
+        let file = |source: &str| {
+            let res: Result<ir::Prog<FieldPrime>, CompileErrors> = compile(
+                &mut BufReader::new(source.as_bytes()),
+                Some(String::from("./path/to/file")),
+                Some(resolve),
+            );
+            res.unwrap_err().diagnostics()[0].file.clone()
+        };
+
+        // errors are attributed to the file they are found in, relative to the main program
+        assert_eq!(
+            file("import \"./quux\"\ndef main() -> (field):\n\treturn quux()\n"),
+            Some(String::from("./quux"))
+        );
+        assert_eq!(
+            file("import \"./lib/qux\" as qux\ndef main() -> (field):\n\treturn qux()\n"),
+            Some(String::from("./lib/quux"))
+        );
+    }
+
+    #[test]
+    fn import_constants() {
+        let compile_source = |source: &str| -> Result<ir::Prog<FieldPrime>, CompileErrors> {
+            compile(
+                &mut source.as_bytes(),
+                Some(String::from("./path/to/file")),
+                Some(resolve),
+            )
+        };
+
+        // constants are imported by name from modules without a main function, possibly renamed
+        let prog = compile_source(
+            r#"
+			from "./baz" import M, P as Q
+			def main() -> (field):
+			   return M + Q[1]
+		"#,
+        )
+        .unwrap();
+        let res = prog.execute::<FieldPrime>(&vec![]).unwrap();
+        assert_eq!(res.return_values(), vec![FieldPrime::from(8)]);
+
+        // importing the main function does not import the constants
+        let diagnostics = compile_source(
+            r#"
+			import "./bar" as bar
+			def main() -> (field):
+			   return bar() + M
+		"#,
+        )
+        .unwrap_err()
+        .diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, Code::UndefinedIdentifier);
+
+        // constants of the same name imported from two modules are reported at the second import
+        let diagnostics = compile_source(
+            r#"
+			from "./bar" import M
+			from "./baz" import M
+			def main() -> (field):
+			   return M
+		"#,
+        )
+        .unwrap_err()
+        .diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, Code::DuplicateDefinition);
+        assert_eq!(
+            diagnostics[0].message,
+            "Duplicate definition for constant M"
+        );
+        assert_eq!(diagnostics[0].pos.unwrap().0.line, 3);
+
+        // constants which are not declared in the module and main functions which do not exist are errors
+        let diagnostics = compile_source(
+            r#"
+			from "./baz" import N
+			import "./baz" as baz
+			def main() -> (field):
+			   return 1
+		"#,
+        )
+        .unwrap_err()
+        .diagnostics();
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| (d.message.as_str(), d.pos.unwrap().0.line))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Constant N not found in ./baz", 2),
+                (
+                    "Imported program has no main function, its constants can be imported by name",
+                    3
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn generic_functions() {
+        let no_resolver = None::<
+            fn(&Option<String>, &String) -> Result<(BufReader<Empty>, String, String), io::Error>,
+        >;
+
+        // the body of `foo` refers to an undefined variable, which is only found when it is called
+        let mut r = BufReader::new(
+            r#"
+			def foo<N>(field[N] a) -> (field):
+			   return a[0] + b
+			def main(field a) -> (field):
+			   return a
+		"#
+            .as_bytes(),
+        );
+        let (_, warnings): (ir::Prog<FieldPrime>, _) =
+            compile_with_warnings(&mut r, None, no_resolver, CompileConfig::default()).unwrap();
+
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|w| (w.code, w.message.as_str(), w.pos.unwrap().0.line))
+                .collect::<Vec<_>>(),
+            vec![(
+                Code::UnusedFunction,
+                "Generic function foo is never called, so its body is not checked",
+                2
+            )]
+        );
+
+        // the signature is checked whether the function is called or not
+        let res: Result<ir::Prog<FieldPrime>, CompileErrors> = compile(
+            &mut r#"
+			def foo<N>(field[M] a) -> (field):
+			   return 1
+			def main(field a) -> (field):
+			   return a
+		"#
+            .as_bytes(),
+            None,
+            no_resolver,
+        );
+
+        let diagnostics = res.unwrap_err().diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, Code::UndefinedIdentifier);
+        assert_eq!(diagnostics[0].message, "Undefined array size M");
+        assert_eq!(diagnostics[0].pos.unwrap().0.line, 2);
+    }
+
+    #[test]
+    fn analysis() {
+        let mut r = BufReader::new(
+            r#"
+			import "./foo"
+			def main(field a) -> (field):
+			   field b = foo() + a
+			   return c
+		"#
+            .as_bytes(),
+        );
+        let analysis = analyse::<FieldPrime, _, _, _>(
+            &mut r,
+            Some(String::from("./path/to/file")),
+            Some(resolve),
+        );
+
+        // the symbols are found even though `c` is undefined
+        assert_eq!(analysis.diagnostics.len(), 1);
+        assert_eq!(analysis.diagnostics[0].code, Code::UndefinedIdentifier);
+
+        let symbol = |line, col| {
+            analysis
+                .symbols
+                .symbol_at(Position { line, col })
+                .map(|s| s.detail.clone())
+        };
+
+        assert_eq!(symbol(4, 13), Some(String::from("field b")));
+        assert_eq!(symbol(4, 18), Some(String::from("def foo() -> (field)")));
+        assert_eq!(symbol(4, 25), Some(String::from("field a")));
+        assert_eq!(symbol(5, 14), None);
+
+        let foo = analysis
+            .symbols
+            .symbols
+            .iter()
+            .find(|s| s.id == "foo")
+            .unwrap();
+        assert_eq!(foo.import, Some(String::from("./foo")));
+        assert_eq!(foo.pos.unwrap().0.line, 2);
+    }
 }
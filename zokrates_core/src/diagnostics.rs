@@ -0,0 +1,271 @@
+//! Module containing the diagnostics reported by the compiler.
+//!
+//! Diagnostics carry a stable code, the span they point to and related notes. They can be rendered
+//! for humans, quoting the offending source line, or serialized for editors and other tools.
+
+use crate::parser::Position;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// The kind of a diagnostic. Codes are stable: a kind keeps its code across releases,
+/// and the code of a removed kind is not reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Code {
+    SyntaxError,
+    ImportError,
+    ReadError,
+    UndefinedIdentifier,
+    UndefinedFunction,
+    DuplicateDefinition,
+    TypeMismatch,
+    ExpectedConstant,
+    OutOfBounds,
+    InvalidMain,
+    GenericInstantiation,
+    Unsupported,
+    InvalidLog,
+    UnusedVariable,
+    UnusedImport,
+    ShadowedName,
+    UnusedFunction,
+}
+
+impl Code {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Code::SyntaxError => "E0001",
+            Code::ImportError => "E0002",
+            Code::ReadError => "E0003",
+            Code::UndefinedIdentifier => "E0100",
+            Code::UndefinedFunction => "E0101",
+            Code::DuplicateDefinition => "E0102",
+            Code::TypeMismatch => "E0103",
+            Code::ExpectedConstant => "E0104",
+            Code::OutOfBounds => "E0105",
+            Code::InvalidMain => "E0106",
+            Code::GenericInstantiation => "E0107",
+            Code::Unsupported => "E0108",
+            Code::InvalidLog => "E0109",
+            Code::UnusedVariable => "W0001",
+            Code::UnusedImport => "W0002",
+            Code::ShadowedName => "W0003",
+            Code::UnusedFunction => "W0004",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            Code::UnusedVariable
+            | Code::UnusedImport
+            | Code::ShadowedName
+            | Code::UnusedFunction => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Serialize for Code {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// A message pointing at a location related to a diagnostic, such as a previous declaration
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Note {
+    pub pos: Option<(Position, Position)>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Code,
+    /// The module the diagnostic was reported in, as passed to the compiler
+    pub file: Option<String>,
+    pub pos: Option<(Position, Position)>,
+    pub message: String,
+    pub notes: Vec<Note>,
+}
+
+impl Diagnostic {
+    pub fn new<S: Into<String>>(code: Code, message: S) -> Diagnostic {
+        Diagnostic {
+            severity: code.severity(),
+            code,
+            file: None,
+            pos: None,
+            message: message.into(),
+            notes: vec![],
+        }
+    }
+
+    pub fn with_pos(self, pos: Option<(Position, Position)>) -> Diagnostic {
+        Diagnostic { pos, ..self }
+    }
+
+    pub fn with_file(self, file: Option<String>) -> Diagnostic {
+        Diagnostic { file, ..self }
+    }
+
+    pub fn with_notes(self, notes: Vec<Note>) -> Diagnostic {
+        Diagnostic { notes, ..self }
+    }
+
+    /// Renders the diagnostic, quoting the lines of `source` it points to if the source is available
+    pub fn render(&self, source: Option<&str>) -> String {
+        let mut res = format!("{}[{}]: {}", self.severity, self.code, self.message);
+        res.push_str(&snippet(&self.file, self.pos, source));
+        for note in &self.notes {
+            res.push_str(&format!("\nnote: {}", note.message));
+            res.push_str(&snippet(&self.file, note.pos, source));
+        }
+        res
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(None))
+    }
+}
+
+// the location of `pos` in `file`, followed by the source line with the span underlined
+fn snippet(
+    file: &Option<String>,
+    pos: Option<(Position, Position)>,
+    source: Option<&str>,
+) -> String {
+    let (start, end) = match pos {
+        Some(pos) => pos,
+        None => {
+            return match file {
+                Some(file) => format!("\n --> {}", file),
+                None => String::new(),
+            };
+        }
+    };
+
+    let location = match file {
+        Some(file) => format!("{}:{}", file, start),
+        None => format!("{}", start),
+    };
+
+    let line = match source.and_then(|s| s.lines().nth(start.line - 1)) {
+        Some(line) => line,
+        None => return format!("\n --> {}", location),
+    };
+
+    let number = start.line.to_string();
+    let gutter = " ".repeat(number.len());
+
+    // spans covering several lines are underlined until the end of their first line
+    let length = line.chars().count() + 1;
+    let end = match end.line == start.line {
+        true => std::cmp::min(end.col, length),
+        false => length,
+    };
+
+    // keep tabs so that the underline is aligned with the quoted line
+    let indent: String = line
+        .chars()
+        .take(start.col - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let underline = "^".repeat(std::cmp::max(end.saturating_sub(start.col), 1));
+
+    format!(
+        "\n{gutter}--> {location}\n{gutter} |\n{number} | {line}\n{gutter} | {indent}{underline}",
+        gutter = gutter,
+        location = location,
+        number = number,
+        line = line,
+        indent = indent,
+        underline = underline
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(line: usize, start: usize, end: usize) -> Option<(Position, Position)> {
+        Some((Position { line, col: start }, Position { line, col: end }))
+    }
+
+    #[test]
+    fn render_with_source() {
+        let source = "def main() -> (field):\n\tfield a = b\n\treturn 1";
+        let d = Diagnostic::new(Code::UndefinedIdentifier, "Identifier \"b\" is undefined")
+            .with_file(Some("./main.code".to_string()))
+            .with_pos(pos(2, 12, 13));
+
+        assert_eq!(
+            d.render(Some(source)),
+            "error[E0100]: Identifier \"b\" is undefined\n --> ./main.code:2:12\n  |\n2 | \tfield a = b\n  | \t          ^"
+        );
+    }
+
+    #[test]
+    fn render_notes() {
+        let source = "field a = 1\nfield a = 2";
+        let d = Diagnostic::new(
+            Code::DuplicateDefinition,
+            "Duplicate declaration for variable named a",
+        )
+        .with_pos(pos(2, 1, 8))
+        .with_notes(vec![Note {
+            pos: pos(1, 1, 8),
+            message: "previous declaration here".to_string(),
+        }]);
+
+        assert_eq!(
+            d.render(Some(source)),
+            "error[E0102]: Duplicate declaration for variable named a\n --> 2:1\n  |\n2 | field a = 2\n  | ^^^^^^^\nnote: previous declaration here\n --> 1:1\n  |\n1 | field a = 1\n  | ^^^^^^^"
+        );
+    }
+
+    #[test]
+    fn render_without_source() {
+        let d = Diagnostic::new(Code::UnusedVariable, "Unused variable a")
+            .with_file(Some("./main.code".to_string()))
+            .with_pos(pos(2, 1, 8));
+
+        assert_eq!(
+            d.to_string(),
+            "warning[W0001]: Unused variable a\n --> ./main.code:2:1"
+        );
+    }
+
+    #[test]
+    fn serialize() {
+        let d = Diagnostic::new(Code::UnusedImport, "Unused import foo").with_pos(pos(1, 1, 4));
+
+        assert_eq!(
+            serde_json::to_string(&d).unwrap(),
+            r#"{"severity":"warning","code":"W0002","file":null,"pos":[{"line":1,"col":1},{"line":1,"col":4}],"message":"Unused import foo","notes":[]}"#
+        );
+    }
+}
@@ -0,0 +1,368 @@
+//! Module containing the differential testing of the compiler.
+//!
+//! A program is run both by the interpreter of typed programs and as the constraint system it
+//! compiles to, on the same random inputs. As both should return the same outputs and fail on the
+//! same inputs, any disagreement points to a miscompilation in flattening, propagation or the
+//! optimizers rather than to a bug in the program.
+
+use absy::Prog;
+use compile::{check_program, flatten_program, CompileErrorInner, CompileErrors};
+use imports;
+use ir;
+use optimizer::Optimize;
+use rand::{ChaChaRng, Rng, SeedableRng};
+use std::fmt;
+use std::io::BufRead;
+use typed_absy::TypedProg;
+use types::Type;
+use zokrates_field::field::Field;
+use zokrates_pest_ast as pest;
+
+/// The outcome of running a program on some inputs, the error being described as a string
+pub type Outcome<T> = Result<Vec<T>, String>;
+
+/// Inputs on which the compiled program disagrees with the interpreter
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch<T> {
+    pub inputs: Vec<T>,
+    /// The outcome of the interpreter
+    pub expected: Outcome<T>,
+    /// The outcome of the compiled program
+    pub found: Outcome<T>,
+}
+
+impl<T: Field> fmt::Display for Mismatch<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "inputs {}\n\texpected: {}\n\tfound: {}",
+            format_values(&self.inputs),
+            format_outcome(&self.expected),
+            format_outcome(&self.found)
+        )
+    }
+}
+
+fn format_values<T: Field>(values: &Vec<T>) -> String {
+    format!(
+        "[{}]",
+        values
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn format_outcome<T: Field>(outcome: &Outcome<T>) -> String {
+    match *outcome {
+        Ok(ref values) => format!("returns {}", format_values(values)),
+        Err(ref e) if e.is_empty() => String::from("fails"),
+        Err(ref e) => format!("fails ({})", e),
+    }
+}
+
+/// A program, both as checked and as compiled, ready to be run on inputs
+pub struct Differential<'ast, T: Field> {
+    typed: TypedProg<'ast, T>,
+    compiled: ir::Prog<T>,
+    input_types: Vec<Type>,
+}
+
+impl<'ast, T: Field> Differential<'ast, T> {
+    pub fn new<S: BufRead, E: Into<imports::Error>>(
+        program: Prog<'ast, T>,
+        location: Option<String>,
+        resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
+    ) -> Result<Self, CompileErrors> {
+        let (typed, _) = check_program(program, location.clone(), resolve_option)?;
+
+        let input_types = typed
+            .functions
+            .iter()
+            .find(|f| f.id == "main")
+            .unwrap()
+            .arguments
+            .iter()
+            .map(|p| p.id.get_type())
+            .collect();
+
+        let compiled = ir::Prog::from(flatten_program(typed.clone(), &location, false)?).optimize();
+
+        Ok(Differential {
+            typed,
+            compiled,
+            input_types,
+        })
+    }
+
+    /// Returns the number of field elements the program takes as inputs
+    pub fn input_count(&self) -> usize {
+        self.input_types
+            .iter()
+            .map(|t| t.get_primitive_count())
+            .sum()
+    }
+
+    /// Runs both versions of the program on `inputs`, returning how they disagree if they do.
+    /// Errors are not compared, only whether both versions fail.
+    pub fn check(&self, inputs: &Vec<T>) -> Option<Mismatch<T>> {
+        let (expected, found) = self.run(inputs);
+        compare(inputs, expected, found)
+    }
+
+    /// Returns the outcomes of the interpreter and of the compiled program on `inputs`
+    fn run(&self, inputs: &Vec<T>) -> (Outcome<T>, Outcome<T>) {
+        let expected = self.typed.execute(inputs).map_err(|e| e.to_string());
+        let found = self
+            .compiled
+            .execute(inputs)
+            .map(|witness| witness.return_values())
+            .map_err(|e| e.to_string());
+
+        (expected, found)
+    }
+
+    /// Generates random inputs for the program. Booleans are mostly valid, and field elements are
+    /// biased towards small values and the bounds of comparisons and integer operations.
+    pub fn random_inputs<R: Rng>(&self, rng: &mut R) -> Vec<T> {
+        self.input_types
+            .iter()
+            .flat_map(|t| match *t {
+                Type::Boolean => vec![match rng.gen_weighted_bool(16) {
+                    true => random_field_element(rng),
+                    false => T::from(rng.gen_range(0u32, 2)),
+                }],
+                ref t => (0..t.get_primitive_count())
+                    .map(|_| random_field_element(rng))
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+fn compare<T: Field>(
+    inputs: &Vec<T>,
+    expected: Outcome<T>,
+    found: Outcome<T>,
+) -> Option<Mismatch<T>> {
+    let agree = match (&expected, &found) {
+        (&Ok(ref expected), &Ok(ref found)) => expected == found,
+        (&Err(_), &Err(_)) => true,
+        _ => false,
+    };
+
+    match agree {
+        true => None,
+        false => Some(Mismatch {
+            inputs: inputs.clone(),
+            expected,
+            found,
+        }),
+    }
+}
+
+fn random_field_element<T: Field, R: Rng>(rng: &mut R) -> T {
+    let bitwidth = T::get_required_bits();
+
+    match rng.gen_range(0, 6) {
+        // small values, which are the most likely to satisfy the assertions of a program
+        0 | 1 => T::from(rng.gen_range(0u32, 16)),
+        // small negative values, just below the order of the field
+        2 => T::zero() - T::from(rng.gen_range(1u32, 16)),
+        // values around a power of two, at the bounds of comparisons and integer operations
+        3 | 4 => {
+            let power = T::from(2).pow(rng.gen_range(0, bitwidth + 1));
+            match rng.gen_range(0, 3) {
+                0 => power - T::one(),
+                1 => power,
+                _ => power + T::one(),
+            }
+        }
+        // values of any size
+        _ => (0..rng.gen_range(0, bitwidth + 1)).fold(T::zero(), |acc, _| {
+            acc * T::from(2) + T::from(rng.gen_range(0u32, 2))
+        }),
+    }
+}
+
+/// The result of the differential testing of a program
+#[derive(Debug, Clone, PartialEq)]
+pub struct DifferentialReport<T> {
+    pub runs: usize,
+    /// The number of inputs on which both versions of the program failed
+    pub failures: usize,
+    pub mismatches: Vec<Mismatch<T>>,
+}
+
+/// Compiles a program and runs it `runs` times on random inputs, both with the interpreter and as
+/// compiled. The inputs only depend on `seed`, so that a mismatch can be reproduced.
+pub fn run_differential<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
+    reader: &mut R,
+    location: Option<String>,
+    resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
+    runs: usize,
+    seed: u64,
+) -> Result<DifferentialReport<T>, CompileErrors> {
+    let mut source = String::new();
+    reader
+        .read_to_string(&mut source)
+        .map_err(|e| CompileErrors::from(CompileErrorInner::from(e).with_context(&location)))?;
+    let ast = pest::generate_ast(&source)
+        .map_err(|e| CompileErrors::from(CompileErrorInner::from(e).with_context(&location)))?;
+    let program: Prog<T> = Prog::from(ast);
+
+    let differential = Differential::new(program, location, resolve_option)?;
+
+    let mut rng = ChaChaRng::from_seed(&[seed as u32, (seed >> 32) as u32]);
+
+    let mut report = DifferentialReport {
+        runs,
+        failures: 0,
+        mismatches: vec![],
+    };
+
+    for _ in 0..runs {
+        let inputs = differential.random_inputs(&mut rng);
+        let (expected, found) = differential.run(&inputs);
+
+        if expected.is_err() && found.is_err() {
+            report.failures += 1;
+        }
+
+        report.mismatches.extend(compare(&inputs, expected, found));
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glob::glob;
+    use std::fs::File;
+    use std::io;
+    use std::io::{BufReader, Empty, Read};
+    use zokrates_field::field::{FieldPrime, Pow};
+
+    fn differential(source: &str) -> Differential<FieldPrime> {
+        let ast = pest::generate_ast(source).unwrap();
+        Differential::new(
+            Prog::from(ast),
+            None,
+            None::<
+                fn(
+                    &Option<String>,
+                    &String,
+                ) -> Result<(BufReader<Empty>, String, String), io::Error>,
+            >,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn agreement() {
+        let d = differential(
+            r#"
+			def main(field a, field b) -> (field):
+			   field c = if a < b then a / b else b fi
+			   c * c == c * c
+			   return c
+		"#,
+        );
+
+        assert_eq!(d.input_count(), 2);
+        assert_eq!(
+            d.check(&vec![FieldPrime::from(2), FieldPrime::from(4)]),
+            None
+        );
+        // division by zero
+        assert_eq!(
+            d.check(&vec![FieldPrime::from(0), FieldPrime::from(0)]),
+            None
+        );
+        // out of the range of comparisons
+        assert_eq!(
+            d.check(&vec![FieldPrime::from(-1), FieldPrime::from(0)]),
+            None
+        );
+    }
+
+    #[test]
+    fn large_comparison() {
+        // comparisons used to be wrong for operands further apart than about 2**251
+        let d = differential(
+            r#"
+			def main(field a, field b) -> (field):
+			   return if a < b then 1 else 0 fi
+		"#,
+        );
+
+        let max = FieldPrime::from(2).pow(252) - FieldPrime::from(1);
+
+        assert_eq!(d.check(&vec![FieldPrime::from(0), max.clone()]), None);
+        assert_eq!(
+            d.compiled
+                .execute(&vec![FieldPrime::from(0), max])
+                .unwrap()
+                .return_values(),
+            vec![FieldPrime::from(1)]
+        );
+    }
+
+    #[test]
+    fn random_inputs() {
+        let d = differential(
+            r#"
+			def main(bool a, field[3] b) -> (field):
+			   return b[0]
+		"#,
+        );
+
+        let mut rng = ChaChaRng::from_seed(&[42]);
+        assert_eq!(d.random_inputs(&mut rng).len(), 4);
+    }
+
+    #[test]
+    fn examples() {
+        // the examples are run on random inputs, which is deterministic as the seed is fixed
+        for p in glob("../zokrates_cli/examples/**/*.code").expect("Failed to read glob pattern") {
+            let path = p.unwrap();
+
+            if path.to_str().unwrap().contains("error") {
+                continue;
+            }
+
+            // imports are resolved by the CLI, and are not needed to test the compiler itself
+            let mut source = String::new();
+            BufReader::new(File::open(&path).unwrap())
+                .read_to_string(&mut source)
+                .unwrap();
+            if source.contains("import ") {
+                continue;
+            }
+
+            let report = run_differential::<FieldPrime, _, _, _>(
+                &mut source.as_bytes(),
+                None,
+                None::<
+                    fn(
+                        &Option<String>,
+                        &String,
+                    )
+                        -> Result<(BufReader<Empty>, String, String), io::Error>,
+                >,
+                20,
+                42,
+            )
+            .unwrap();
+
+            assert!(
+                report.mismatches.is_empty(),
+                "{}: {}",
+                path.display(),
+                report.mismatches[0]
+            );
+        }
+    }
+}
@@ -7,9 +7,11 @@
 
 pub mod flat_parameter;
 pub mod flat_variable;
+pub mod source_names;
 
 pub use self::flat_parameter::FlatParameter;
 pub use self::flat_variable::FlatVariable;
+pub use self::source_names::{SourceName, SourceNames};
 
 use crate::helpers::{DirectiveStatement, Executable};
 use crate::types::Signature;
@@ -21,6 +23,8 @@ use zokrates_field::field::Field;
 pub struct FlatProg<T: Field> {
     /// FlatFunctions of the program
     pub functions: Vec<FlatFunction<T>>,
+    /// Names in the source of the variables of the main function
+    pub names: SourceNames,
 }
 
 impl<T: Field> FlatProg<T> {
@@ -0,0 +1,68 @@
+use crate::flat_absy::FlatVariable;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The name in the source of a variable of a flattened program
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SourceName {
+    /// The function the variable is declared in, preceded by the calls it was inlined through,
+    /// such as `main::foo#2` for the second call to `foo`
+    pub function: String,
+    pub variable: String,
+    /// The index of the element, if the variable is an array
+    pub index: Option<usize>,
+    /// The number of times the variable was defined before
+    pub version: usize,
+}
+
+impl fmt::Display for SourceName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        r#try!(write!(f, "{}::{}", self.function, self.variable));
+        if self.version > 0 {
+            r#try!(write!(f, "@{}", self.version));
+        }
+        match self.index {
+            Some(index) => write!(f, "[{}]", index),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The names in the source of the variables of a flattened program. The variables introduced by
+/// the compiler have none.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SourceNames(pub BTreeMap<FlatVariable, SourceName>);
+
+impl SourceNames {
+    pub fn get(&self, variable: &FlatVariable) -> Option<&SourceName> {
+        self.0.get(variable)
+    }
+
+    pub fn insert(&mut self, variable: FlatVariable, name: SourceName) {
+        self.0.insert(variable, name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let name = SourceName {
+            function: String::from("main::foo#2"),
+            variable: String::from("a"),
+            index: None,
+            version: 0,
+        };
+        assert_eq!(name.to_string(), "main::foo#2::a");
+
+        let name = SourceName {
+            function: String::from("main"),
+            variable: String::from("a"),
+            index: Some(3),
+            version: 1,
+        };
+        assert_eq!(name.to_string(), "main::a@1[3]");
+    }
+}
@@ -21,6 +21,10 @@ pub struct Flattener<'ast> {
     next_var_idx: usize,
     ///
     layout: HashMap<Identifier<'ast>, Vec<FlatVariable>>,
+    /// Function being flattened
+    function: FunctionIdentifier<'ast>,
+    /// Names in the source of the variables of the function being flattened
+    names: SourceNames,
 }
 impl<'ast> Flattener<'ast> {
     pub fn flatten<T: Field>(p: TypedProg<T>) -> FlatProg<T> {
@@ -37,6 +41,8 @@ impl<'ast> Flattener<'ast> {
         Flattener {
             next_var_idx: 0,
             layout: HashMap::new(),
+            function: "",
+            names: SourceNames::default(),
         }
     }
 
@@ -234,16 +240,15 @@ impl<'ast> Flattener<'ast> {
                     ));
                 }
 
-                // sym := (lhs * 2) - (rhs * 2)
-                let subtraction_result = FlatExpression::Sub(
-                    box FlatExpression::Mult(
-                        box FlatExpression::Number(T::from(2)),
+                // sym := lhs - rhs + 2**(bitwidth - 2)
+                // as both operands are smaller than 2**(bitwidth - 2), sym is between 0 and 2**(bitwidth - 1)
+                // and lhs < rhs iff sym < 2**(bitwidth - 2)
+                let subtraction_result = FlatExpression::Add(
+                    box FlatExpression::Sub(
                         box FlatExpression::Identifier(lhs_id),
-                    ),
-                    box FlatExpression::Mult(
-                        box FlatExpression::Number(T::from(2)),
                         box FlatExpression::Identifier(rhs_id),
                     ),
+                    box FlatExpression::Number(T::from(2).pow(bitwidth - 2)),
                 );
 
                 // define variables for the bits
@@ -256,13 +261,16 @@ impl<'ast> Flattener<'ast> {
                     vec![subtraction_result.clone()],
                 )));
 
+                // we only use the last `bitwidth - 1` bits so that the decomposition is unique
+                let sub_bits = &sub_bits[1..];
+
                 // bitness checks
-                for i in 0..bitwidth {
+                for bit in sub_bits {
                     statements_flattened.push(FlatStatement::Condition(
-                        FlatExpression::Identifier(sub_bits[i]),
+                        FlatExpression::Identifier(*bit),
                         FlatExpression::Mult(
-                            box FlatExpression::Identifier(sub_bits[i]),
-                            box FlatExpression::Identifier(sub_bits[i]),
+                            box FlatExpression::Identifier(*bit),
+                            box FlatExpression::Identifier(*bit),
                         ),
                     ));
                 }
@@ -270,61 +278,75 @@ impl<'ast> Flattener<'ast> {
                 // sum(sym_b{i} * 2**i)
                 let mut expr = FlatExpression::Number(T::from(0));
 
-                for i in 0..bitwidth {
+                for (i, bit) in sub_bits.iter().enumerate() {
                     expr = FlatExpression::Add(
                         box expr,
                         box FlatExpression::Mult(
-                            box FlatExpression::Identifier(sub_bits[i]),
-                            box FlatExpression::Number(T::from(2).pow(bitwidth - i - 1)),
+                            box FlatExpression::Identifier(*bit),
+                            box FlatExpression::Number(T::from(2).pow(sub_bits.len() - i - 1)),
                         ),
                     );
                 }
 
                 statements_flattened.push(FlatStatement::Condition(subtraction_result, expr));
 
-                FlatExpression::Identifier(sub_bits[0])
+                // the most significant bit of sym is set iff lhs >= rhs
+                FlatExpression::Sub(
+                    box FlatExpression::Number(T::one()),
+                    box FlatExpression::Identifier(sub_bits[0]),
+                )
             }
             BooleanExpression::Eq(box lhs, box rhs) => {
                 // We know from semantic checking that lhs and rhs have the same type
                 // What the expression will flatten to depends on that type
 
-                // Wanted: (Y = (X != 0) ? 1 : 0)
-                // X = a - b
-                // # Y = if X == 0 then 0 else 1 fi
-                // # M = if X == 0 then 1 else 1/X fi
-                // Y == X * M
-                // 0 == (1-Y) * X
-
-                let name_y = self.use_sym();
-                let name_m = self.use_sym();
-
+                // lhs == rhs iff lhs - rhs == 0
                 let x = self.flatten_field_expression(
                     functions_flattened,
                     statements_flattened,
                     FieldElementExpression::Sub(box lhs, box rhs),
                 );
 
-                statements_flattened.push(FlatStatement::Directive(DirectiveStatement::new(
-                    vec![name_y, name_m],
-                    Helper::Rust(RustHelper::ConditionEq),
-                    vec![x.clone()],
-                )));
-                statements_flattened.push(FlatStatement::Condition(
-                    FlatExpression::Identifier(name_y),
-                    FlatExpression::Mult(box x.clone(), box FlatExpression::Identifier(name_m)),
-                ));
-
-                let res = FlatExpression::Sub(
-                    box FlatExpression::Number(T::one()),
-                    box FlatExpression::Identifier(name_y),
+                self.flatten_is_zero(statements_flattened, x)
+            }
+            BooleanExpression::BoolEq(box lhs, box rhs) => {
+                // lhs == rhs is !(lhs ^ rhs)
+                self.flatten_boolean_expression(
+                    functions_flattened,
+                    statements_flattened,
+                    BooleanExpression::Not(box BooleanExpression::Xor(box lhs, box rhs)),
+                )
+            }
+            BooleanExpression::FieldElementArrayEq(box lhs, box rhs) => {
+                let lhs_flattened = self.flatten_field_array_expression(
+                    functions_flattened,
+                    statements_flattened,
+                    lhs,
+                );
+                let rhs_flattened = self.flatten_field_array_expression(
+                    functions_flattened,
+                    statements_flattened,
+                    rhs,
                 );
 
-                statements_flattened.push(FlatStatement::Condition(
-                    FlatExpression::Number(T::zero()),
-                    FlatExpression::Mult(box res.clone(), box x),
-                ));
+                let size = lhs_flattened.len();
+
+                // count the elements which are equal
+                let mut equal_count = FlatExpression::Number(T::zero());
+
+                for (l, r) in lhs_flattened.into_iter().zip(rhs_flattened.into_iter()) {
+                    let l = self.define_if_not_linear(statements_flattened, l);
+                    let r = self.define_if_not_linear(statements_flattened, r);
+                    let eq = self
+                        .flatten_is_zero(statements_flattened, FlatExpression::Sub(box l, box r));
+                    equal_count = FlatExpression::Add(box equal_count, box eq);
+                }
 
-                res
+                // the arrays are equal iff all elements are equal. The count cannot overflow as it is at most `size`
+                self.flatten_is_zero(
+                    statements_flattened,
+                    FlatExpression::Sub(box FlatExpression::Number(T::from(size)), box equal_count),
+                )
             }
             BooleanExpression::Le(box lhs, box rhs) => {
                 let lt = self.flatten_boolean_expression(
@@ -386,6 +408,28 @@ impl<'ast> Flattener<'ast> {
 
                 FlatExpression::Identifier(name_x_and_y)
             }
+            BooleanExpression::Xor(box lhs, box rhs) => {
+                let x =
+                    self.flatten_boolean_expression(functions_flattened, statements_flattened, lhs);
+                let y =
+                    self.flatten_boolean_expression(functions_flattened, statements_flattened, rhs);
+
+                let name_x_and_y = self.use_sym();
+                assert!(x.is_linear() && y.is_linear());
+                statements_flattened.push(FlatStatement::Definition(
+                    name_x_and_y,
+                    FlatExpression::Mult(box x.clone(), box y.clone()),
+                ));
+
+                // x ^ y == x + y - 2 * (x && y)
+                FlatExpression::Sub(
+                    box FlatExpression::Add(box x, box y),
+                    box FlatExpression::Mult(
+                        box FlatExpression::Number(T::from(2)),
+                        box FlatExpression::Identifier(name_x_and_y),
+                    ),
+                )
+            }
             BooleanExpression::Not(box exp) => {
                 let x =
                     self.flatten_boolean_expression(functions_flattened, statements_flattened, exp);
@@ -398,6 +442,40 @@ impl<'ast> Flattener<'ast> {
         }
     }
 
+    /// Flattens the condition of a log, which is a conjunction of the conditions of the branches
+    /// it is found in, to a linear expression equal to one if and only if it holds. As all
+    /// conditions are booleans, `a && b` holds if `a + b - 1` is one.
+    fn flatten_log_condition<T: Field>(
+        &mut self,
+        condition: BooleanExpression<'ast, T>,
+    ) -> FlatExpression<T> {
+        match condition {
+            BooleanExpression::Value(b) => FlatExpression::Number(match b {
+                true => T::one(),
+                false => T::zero(),
+            }),
+            BooleanExpression::Identifier(x) => {
+                FlatExpression::Identifier(self.layout.get(&x).unwrap().clone()[0])
+            }
+            // negations only apply to the condition of a single branch
+            BooleanExpression::Not(box condition) => FlatExpression::Sub(
+                box FlatExpression::Number(T::one()),
+                box self.flatten_log_condition(condition),
+            ),
+            BooleanExpression::And(box lhs, box rhs) => FlatExpression::Sub(
+                box FlatExpression::Add(
+                    box self.flatten_log_condition(lhs),
+                    box self.flatten_log_condition(rhs),
+                ),
+                box FlatExpression::Number(T::one()),
+            ),
+            condition => unreachable!(
+                "log conditions are conjunctions of branch conditions, found {}",
+                condition
+            ),
+        }
+    }
+
     fn flatten_function_call<T: Field>(
         &mut self,
         functions_flattened: &Vec<FlatFunction<T>>,
@@ -644,55 +722,199 @@ impl<'ast> Flattener<'ast> {
 
                 inverse.into()
             }
+            FieldElementExpression::IntegerDiv(box left, box right, bitwidth) => {
+                let (quotient, _) = self.flatten_integer_division(
+                    functions_flattened,
+                    statements_flattened,
+                    left,
+                    right,
+                    bitwidth,
+                );
+                quotient.into()
+            }
+            FieldElementExpression::Rem(box left, box right, bitwidth) => {
+                let (_, remainder) = self.flatten_integer_division(
+                    functions_flattened,
+                    statements_flattened,
+                    left,
+                    right,
+                    bitwidth,
+                );
+                remainder.into()
+            }
             FieldElementExpression::Pow(box base, box exponent) => {
+                // flatten the base expression
+                let base_flattened =
+                    self.flatten_field_expression(functions_flattened, statements_flattened, base);
+
+                // the base can be any expression, so we define an intermediate variable if it is not linear
+                let base_flattened = if base_flattened.is_linear() {
+                    base_flattened
+                } else {
+                    let id = self.use_sym();
+                    statements_flattened.push(FlatStatement::Definition(id, base_flattened));
+                    FlatExpression::Identifier(id)
+                };
+
                 match exponent {
-                    FieldElementExpression::Number(ref e) => {
-                        // flatten the base expression
-                        let base_flattened = self.flatten_field_expression(
+                    FieldElementExpression::Number(e) => {
+                        match e {
+                            e if e == T::zero() => FlatExpression::Number(T::one()),
+                            // flatten(base ** 1) == flatten(base)
+                            e if e == T::one() => base_flattened,
+                            // square and multiply over the bits of the exponent, from the most significant one
+                            e => {
+                                let bits: Vec<bool> = e
+                                    .into_byte_vector()
+                                    .into_iter()
+                                    .rev()
+                                    .flat_map(|byte| {
+                                        (0..8).rev().map(move |i| (byte >> i) & 1 == 1)
+                                    })
+                                    .skip_while(|bit| !bit)
+                                    .collect();
+
+                                let mut res = base_flattened.clone();
+
+                                for bit in &bits[1..] {
+                                    // flatten(base ** 2k) == flatten(base ** k) * flatten(base ** k)
+                                    // no need to define an intermediate variable if base ** k is linear,
+                                    // which is the case for k == 1
+                                    let square = if res.is_linear() {
+                                        res
+                                    } else {
+                                        let id = self.use_sym();
+                                        statements_flattened
+                                            .push(FlatStatement::Definition(id, res));
+                                        FlatExpression::Identifier(id)
+                                    };
+                                    res = FlatExpression::Mult(box square.clone(), box square);
+
+                                    // flatten(base ** (2k + 1)) == flatten(base ** 2k) * flatten(base)
+                                    if *bit {
+                                        let id = self.use_sym();
+                                        statements_flattened
+                                            .push(FlatStatement::Definition(id, res));
+                                        res = FlatExpression::Mult(
+                                            box FlatExpression::Identifier(id),
+                                            box base_flattened.clone(),
+                                        );
+                                    }
+                                }
+
+                                res
+                            }
+                        }
+                    }
+                    exponent => {
+                        // square and multiply over the bits of the exponent, from the most significant one
+                        // base ** e == prod((b_i * (base - 1) + 1) ** (2 ** i))
+                        let bitwidth = T::get_required_bits();
+
+                        let exponent_flattened = self.flatten_field_expression(
                             functions_flattened,
                             statements_flattened,
-                            base.clone(),
+                            exponent,
                         );
 
-                        // we require from the base to be linear
-                        // TODO change that
-                        assert!(base_flattened.is_linear());
+                        let exponent_id = self.use_sym();
+                        statements_flattened
+                            .push(FlatStatement::Definition(exponent_id, exponent_flattened));
+
+                        // define variables for the bits
+                        let exponent_bits: Vec<FlatVariable> =
+                            (0..bitwidth).map(|_| self.use_sym()).collect();
+
+                        // add a directive to get the bits
+                        statements_flattened.push(FlatStatement::Directive(
+                            DirectiveStatement::new(
+                                exponent_bits.clone(),
+                                Helper::bits(),
+                                vec![exponent_id],
+                            ),
+                        ));
 
-                        match e {
-                            e if *e == T::zero() => FlatExpression::Number(T::one()),
-                            // flatten(base ** 1) == flatten(base)
-                            e if *e == T::one() => base_flattened,
-                            // flatten(base ** 2) == flatten(base) * flatten(base)
-                            // in this case, no need to define an intermediate variable
-                            // as if a is linear, a ** 2 quadratic
-                            e if *e == T::from(2) => {
-                                FlatExpression::Mult(box base_flattened.clone(), box base_flattened)
-                            }
-                            // flatten(base ** n) = flatten(base) * flatten(base ** (n-1))
-                            e => {
-                                // flatten(base ** (n-1))
-                                let tmp_expression = self.flatten_field_expression(
-                                    functions_flattened,
-                                    statements_flattened,
-                                    FieldElementExpression::Pow(
-                                        box base,
-                                        box FieldElementExpression::Number(e.clone() - T::one()),
+                        // as for comparisons, we only use the last `bitwidth - 2` bits so that the decomposition is unique
+                        let exponent_bits = &exponent_bits[2..];
+
+                        // bitness checks
+                        for bit in exponent_bits {
+                            statements_flattened.push(FlatStatement::Condition(
+                                FlatExpression::Identifier(*bit),
+                                FlatExpression::Mult(
+                                    box FlatExpression::Identifier(*bit),
+                                    box FlatExpression::Identifier(*bit),
+                                ),
+                            ));
+                        }
+
+                        // bit decomposition check
+                        let mut exponent_sum = FlatExpression::Number(T::from(0));
+
+                        for (i, bit) in exponent_bits.iter().enumerate() {
+                            exponent_sum = FlatExpression::Add(
+                                box exponent_sum,
+                                box FlatExpression::Mult(
+                                    box FlatExpression::Identifier(*bit),
+                                    box FlatExpression::Number(
+                                        T::from(2).pow(exponent_bits.len() - i - 1),
                                     ),
-                                );
+                                ),
+                            );
+                        }
 
-                                let id = self.use_sym();
+                        statements_flattened.push(FlatStatement::Condition(
+                            FlatExpression::Identifier(exponent_id),
+                            exponent_sum,
+                        ));
 
-                                statements_flattened
-                                    .push(FlatStatement::Definition(id, tmp_expression));
+                        let mut res: Option<FlatExpression<T>> = None;
 
+                        for bit in exponent_bits {
+                            // factor = bit ? base : 1
+                            let factor_id = self.use_sym();
+                            statements_flattened.push(FlatStatement::Definition(
+                                factor_id,
                                 FlatExpression::Mult(
-                                    box FlatExpression::Identifier(id),
-                                    box base_flattened,
-                                )
-                            }
+                                    box FlatExpression::Identifier(*bit),
+                                    box FlatExpression::Sub(
+                                        box base_flattened.clone(),
+                                        box FlatExpression::Number(T::one()),
+                                    ),
+                                ),
+                            ));
+                            let factor = FlatExpression::Add(
+                                box FlatExpression::Identifier(factor_id),
+                                box FlatExpression::Number(T::one()),
+                            );
+
+                            res = Some(match res {
+                                // the first factor does not need to be multiplied
+                                None => factor,
+                                Some(res) => {
+                                    // square = res * res
+                                    let square_id = self.use_sym();
+                                    statements_flattened.push(FlatStatement::Definition(
+                                        square_id,
+                                        FlatExpression::Mult(box res.clone(), box res),
+                                    ));
+
+                                    // res = square * factor
+                                    let id = self.use_sym();
+                                    statements_flattened.push(FlatStatement::Definition(
+                                        id,
+                                        FlatExpression::Mult(
+                                            box FlatExpression::Identifier(square_id),
+                                            box factor,
+                                        ),
+                                    ));
+                                    FlatExpression::Identifier(id)
+                                }
+                            });
                         }
+
+                        res.unwrap()
                     }
-                    _ => panic!("Expected number as pow exponent"),
                 }
             }
             FieldElementExpression::IfElse(box condition, box consequent, box alternative) => self
@@ -734,9 +956,9 @@ impl<'ast> Flattener<'ast> {
                                 expressions[n.to_dec_string().parse::<usize>().unwrap()].clone(),
                             )
                         }
-                        FieldElementArrayExpression::FunctionCall(..) => {
-                            unimplemented!("please use intermediate variables for now")
-                        }
+                        FieldElementArrayExpression::FunctionCall(..) => unreachable!(
+                            "selecting in a function call result should have been caught at semantic stage"
+                        ),
                         FieldElementArrayExpression::IfElse(
                             condition,
                             consequence,
@@ -813,8 +1035,8 @@ impl<'ast> Flattener<'ast> {
                                             expressions[i].clone()
                                         }
                                         FieldElementArrayExpression::FunctionCall(..) => {
-                                            unimplemented!(
-                                                "please use intermediate variables for now"
+                                            unreachable!(
+                                                "selecting in a function call result should have been caught at semantic stage"
                                             )
                                         }
                                         FieldElementArrayExpression::IfElse(
@@ -969,10 +1191,10 @@ impl<'ast> Flattener<'ast> {
                                     FieldElementExpression::Number(n) => match array {
                                         TypedAssignee::Identifier(id) => {
                                             let var = self.issue_new_variables(1);
+                                            let index = n.to_dec_string().parse::<usize>().unwrap();
+                                            self.name_variable(&id.id, Some(index), var[0]);
                                             let variables = self.layout.get_mut(&id.id).unwrap();
-                                            variables
-                                                [n.to_dec_string().parse::<usize>().unwrap()] =
-                                                var[0];
+                                            variables[index] = var[0];
                                             statements_flattened.push(FlatStatement::Definition(
                                                 var[0],
                                                 rhs[0].clone(),
@@ -1089,14 +1311,7 @@ impl<'ast> Flattener<'ast> {
                             ),
                         );
 
-                        if lhs.is_linear() {
-                            statements_flattened.push(FlatStatement::Condition(lhs, rhs));
-                        } else if rhs.is_linear() {
-                            // swap so that left side is linear
-                            statements_flattened.push(FlatStatement::Condition(rhs, lhs));
-                        } else {
-                            unimplemented!()
-                        }
+                        self.flatten_condition(statements_flattened, lhs, rhs);
                     }
                     (TypedExpression::Boolean(e1), TypedExpression::Boolean(e2)) => {
                         let (lhs, rhs) = (
@@ -1112,14 +1327,7 @@ impl<'ast> Flattener<'ast> {
                             ),
                         );
 
-                        if lhs.is_linear() {
-                            statements_flattened.push(FlatStatement::Condition(lhs, rhs));
-                        } else if rhs.is_linear() {
-                            // swap so that left side is linear
-                            statements_flattened.push(FlatStatement::Condition(rhs, lhs));
-                        } else {
-                            unimplemented!()
-                        }
+                        self.flatten_condition(statements_flattened, lhs, rhs);
                     }
                     (
                         TypedExpression::FieldElementArray(e1),
@@ -1141,14 +1349,7 @@ impl<'ast> Flattener<'ast> {
                         assert_eq!(lhs.len(), rhs.len());
 
                         for (l, r) in lhs.into_iter().zip(rhs.into_iter()) {
-                            if l.is_linear() {
-                                statements_flattened.push(FlatStatement::Condition(l, r));
-                            } else if r.is_linear() {
-                                // swap so that left side is linear
-                                statements_flattened.push(FlatStatement::Condition(r, l));
-                            } else {
-                                unimplemented!()
-                            }
+                            self.flatten_condition(statements_flattened, l, r);
                         }
                     }
                     _ => panic!(
@@ -1156,6 +1357,33 @@ impl<'ast> Flattener<'ast> {
                     ),
                 }
             }
+            TypedStatement::Log(condition, format, arguments) => {
+                // the condition and the arguments are passed to a directive without outputs, which
+                // adds no constraint. The arguments are checked to flatten to linear expressions.
+                let types = arguments.iter().map(|a| a.get_type()).collect();
+
+                let mut inputs = vec![self.flatten_log_condition(condition)];
+                for e in arguments {
+                    inputs.extend(self.flatten_expression(
+                        functions_flattened,
+                        statements_flattened,
+                        e,
+                    ));
+                }
+
+                statements_flattened.push(FlatStatement::Directive(DirectiveStatement::new(
+                    vec![],
+                    Helper::Rust(RustHelper::Log(format, types)),
+                    inputs,
+                )));
+            }
+            TypedStatement::Location(position) => {
+                statements_flattened.push(FlatStatement::Directive(DirectiveStatement::new(
+                    vec![],
+                    Helper::Rust(RustHelper::Location(position.0)),
+                    Vec::<FlatExpression<T>>::new(),
+                )));
+            }
             TypedStatement::For(var, start, end, statements) => {
                 let mut current = start;
                 while current < end {
@@ -1169,6 +1397,9 @@ impl<'ast> Flattener<'ast> {
                     current = T::one() + &current;
                 }
             }
+            TypedStatement::IfElse(..) => {
+                panic!("if-else statements should have been lowered during static analysis")
+            }
             TypedStatement::MultipleDefinition(vars, rhs) => {
                 // flatten the right side to p = sum(var_i.type.primitive_count) expressions
                 // define p new variables to the right side expressions
@@ -1209,6 +1440,8 @@ impl<'ast> Flattener<'ast> {
         funct: TypedFunction<'ast, T>,
     ) -> FlatFunction<T> {
         self.layout = HashMap::new();
+        self.function = funct.id;
+        self.names = SourceNames::default();
 
         self.next_var_idx = 0;
         let mut statements_flattened: Vec<FlatStatement<T>> = Vec::new();
@@ -1247,13 +1480,19 @@ impl<'ast> Flattener<'ast> {
             functions_flattened.push(func);
         }
 
+        let mut names = SourceNames::default();
+
         for func in prog.functions {
             let flattened_func = self.flatten_function(&mut functions_flattened, func);
+            if flattened_func.id == "main" {
+                names = std::mem::replace(&mut self.names, SourceNames::default());
+            }
             functions_flattened.push(flattened_func);
         }
 
         FlatProg {
             functions: functions_flattened,
+            names,
         }
     }
 
@@ -1268,10 +1507,52 @@ impl<'ast> Flattener<'ast> {
             Type::FieldElementArray(size) => self.issue_new_variables(size),
         };
 
+        match variable.get_type() {
+            Type::FieldElementArray(_) => {
+                for (index, v) in vars.iter().enumerate() {
+                    self.name_variable(&variable.id, Some(index), *v);
+                }
+            }
+            _ => self.name_variable(&variable.id, None, vars[0]),
+        };
+
         self.layout.insert(variable.id.clone(), vars.clone());
         vars
     }
 
+    /// Records the name in the source of a flat variable
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the identifier of the variable in the source
+    /// * `index` - the index of the element if the variable is an array
+    /// * `variable` - the flat variable
+    fn name_variable(
+        &mut self,
+        id: &Identifier<'ast>,
+        index: Option<usize>,
+        variable: FlatVariable,
+    ) {
+        let function = std::iter::once(self.function.to_string())
+            .chain(
+                id.stack
+                    .iter()
+                    .map(|(function, _, count)| format!("{}#{}", function, count)),
+            )
+            .collect::<Vec<_>>()
+            .join("::");
+
+        self.names.insert(
+            variable,
+            SourceName {
+                function,
+                variable: id.id.to_string(),
+                index,
+                version: id.version,
+            },
+        );
+    }
+
     fn use_parameter<T: Field>(
         &mut self,
         parameter: &Parameter<'ast>,
@@ -1302,6 +1583,193 @@ impl<'ast> Flattener<'ast> {
             .collect()
     }
 
+    /// Returns a boolean flat expression which is `1` if `x` is zero and `0` otherwise
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - a linear flat expression
+    fn flatten_is_zero<T: Field>(
+        &mut self,
+        statements_flattened: &mut Vec<FlatStatement<T>>,
+        x: FlatExpression<T>,
+    ) -> FlatExpression<T> {
+        // Wanted: (Y = (X != 0) ? 1 : 0)
+        // # Y = if X == 0 then 0 else 1 fi
+        // # M = if X == 0 then 1 else 1/X fi
+        // Y == X * M
+        // 0 == (1-Y) * X
+
+        let name_y = self.use_sym();
+        let name_m = self.use_sym();
+
+        statements_flattened.push(FlatStatement::Directive(DirectiveStatement::new(
+            vec![name_y, name_m],
+            Helper::Rust(RustHelper::ConditionEq),
+            vec![x.clone()],
+        )));
+        statements_flattened.push(FlatStatement::Condition(
+            FlatExpression::Identifier(name_y),
+            FlatExpression::Mult(box x.clone(), box FlatExpression::Identifier(name_m)),
+        ));
+
+        let res = FlatExpression::Sub(
+            box FlatExpression::Number(T::one()),
+            box FlatExpression::Identifier(name_y),
+        );
+
+        statements_flattened.push(FlatStatement::Condition(
+            FlatExpression::Number(T::zero()),
+            FlatExpression::Mult(box res.clone(), box x),
+        ));
+
+        res
+    }
+
+    /// Adds a condition `lhs == rhs`, where at least one side must be linear
+    fn flatten_condition<T: Field>(
+        &mut self,
+        statements_flattened: &mut Vec<FlatStatement<T>>,
+        lhs: FlatExpression<T>,
+        rhs: FlatExpression<T>,
+    ) {
+        if lhs.is_linear() {
+            statements_flattened.push(FlatStatement::Condition(lhs, rhs));
+        } else if rhs.is_linear() {
+            // swap so that left side is linear
+            statements_flattened.push(FlatStatement::Condition(rhs, lhs));
+        } else {
+            // both sides are quadratic, define an intermediate variable for the left side
+            let lhs = self.define_if_not_linear(statements_flattened, lhs);
+            statements_flattened.push(FlatStatement::Condition(lhs, rhs));
+        }
+    }
+
+    /// Defines an intermediate variable for `e` if it is not linear
+    fn define_if_not_linear<T: Field>(
+        &mut self,
+        statements_flattened: &mut Vec<FlatStatement<T>>,
+        e: FlatExpression<T>,
+    ) -> FlatExpression<T> {
+        if e.is_linear() {
+            e
+        } else {
+            let id = self.use_sym();
+            statements_flattened.push(FlatStatement::Definition(id, e));
+            FlatExpression::Identifier(id)
+        }
+    }
+
+    /// Flattens the euclidean division of `left` by `right`, both interpreted as `bitwidth`-bit unsigned integers
+    ///
+    /// # Returns
+    ///
+    /// The variables holding the quotient and the remainder
+    fn flatten_integer_division<T: Field>(
+        &mut self,
+        functions_flattened: &Vec<FlatFunction<T>>,
+        statements_flattened: &mut Vec<FlatStatement<T>>,
+        left: FieldElementExpression<'ast, T>,
+        right: FieldElementExpression<'ast, T>,
+        bitwidth: usize,
+    ) -> (FlatVariable, FlatVariable) {
+        let left_flattened =
+            self.flatten_field_expression(functions_flattened, statements_flattened, left);
+        let right_flattened =
+            self.flatten_field_expression(functions_flattened, statements_flattened, right);
+
+        let left_id = self.use_sym();
+        statements_flattened.push(FlatStatement::Definition(left_id, left_flattened));
+        let right_id = self.use_sym();
+        statements_flattened.push(FlatStatement::Definition(right_id, right_flattened));
+
+        let quotient = self.use_sym();
+        let remainder = self.use_sym();
+
+        // # q, r = a \ b, a % b
+        statements_flattened.push(FlatStatement::Directive(DirectiveStatement::new(
+            vec![quotient, remainder],
+            Helper::Rust(RustHelper::IntegerDiv),
+            vec![left_id, right_id],
+        )));
+
+        // all operands fit in `bitwidth` bits
+        for id in vec![left_id, right_id, quotient, remainder] {
+            self.range_check(statements_flattened, id.into(), bitwidth);
+        }
+
+        // r < b, which also ensures that b != 0
+        self.range_check(
+            statements_flattened,
+            FlatExpression::Sub(
+                box FlatExpression::Sub(
+                    box FlatExpression::Identifier(right_id),
+                    box FlatExpression::Number(T::one()),
+                ),
+                box FlatExpression::Identifier(remainder),
+            ),
+            bitwidth,
+        );
+
+        // assert(a == q * b + r)
+        // this cannot overflow as the bitwidth is at most (T::get_required_bits() - 3) / 2
+        let product = self.use_sym();
+        statements_flattened.push(FlatStatement::Definition(
+            product,
+            FlatExpression::Mult(
+                box FlatExpression::Identifier(quotient),
+                box FlatExpression::Identifier(right_id),
+            ),
+        ));
+        statements_flattened.push(FlatStatement::Condition(
+            FlatExpression::Identifier(left_id),
+            FlatExpression::Add(
+                box FlatExpression::Identifier(product),
+                box FlatExpression::Identifier(remainder),
+            ),
+        ));
+
+        (quotient, remainder)
+    }
+
+    /// Adds constraints ensuring that `e` is smaller than `2 ** bitwidth`
+    fn range_check<T: Field>(
+        &mut self,
+        statements_flattened: &mut Vec<FlatStatement<T>>,
+        e: FlatExpression<T>,
+        bitwidth: usize,
+    ) {
+        // `e` can be decomposed and checked directly if it is linear
+        let e = self.define_if_not_linear(statements_flattened, e);
+
+        // define variables for the bits
+        let bits: Vec<FlatVariable> = (0..bitwidth).map(|_| self.use_sym()).collect();
+
+        // add a directive to get the bits
+        statements_flattened.push(FlatStatement::Directive(DirectiveStatement::new(
+            bits.clone(),
+            Helper::Rust(RustHelper::LeastSignificantBits(bitwidth)),
+            vec![e.clone()],
+        )));
+
+        // bitness checks
+        statements_flattened.extend(Self::boolean_constraint(&bits));
+
+        // bit decomposition check
+        let mut sum = FlatExpression::Number(T::from(0));
+
+        for (i, bit) in bits.iter().enumerate() {
+            sum = FlatExpression::Add(
+                box sum,
+                box FlatExpression::Mult(
+                    box FlatExpression::Identifier(*bit),
+                    box FlatExpression::Number(T::from(2).pow(bitwidth - i - 1)),
+                ),
+            );
+        }
+
+        statements_flattened.push(FlatStatement::Condition(e, sum));
+    }
+
     fn boolean_constraint<T: Field>(variables: &Vec<FlatVariable>) -> Vec<FlatStatement<T>> {
         variables
             .iter()
@@ -1669,9 +2137,8 @@ mod tests {
         // def main():
         //     _0 = 7
         //     _1 = (_0 * _0)
-        //     _2 = (_1 * _0)
-        //     _3 = (_2 * _0)
-        //     return _3
+        //     _2 = (_1 * _1)
+        //     return _2
 
         let function = TypedFunction {
             id: "main",
@@ -1718,18 +2185,11 @@ mod tests {
                     FlatVariable::new(2),
                     FlatExpression::Mult(
                         box FlatExpression::Identifier(FlatVariable::new(1)),
-                        box FlatExpression::Identifier(FlatVariable::new(0)),
-                    ),
-                ),
-                FlatStatement::Definition(
-                    FlatVariable::new(3),
-                    FlatExpression::Mult(
-                        box FlatExpression::Identifier(FlatVariable::new(2)),
-                        box FlatExpression::Identifier(FlatVariable::new(0)),
+                        box FlatExpression::Identifier(FlatVariable::new(1)),
                     ),
                 ),
                 FlatStatement::Return(FlatExpressionList {
-                    expressions: vec![FlatExpression::Identifier(FlatVariable::new(3))],
+                    expressions: vec![FlatExpression::Identifier(FlatVariable::new(2))],
                 }),
             ],
             signature: Signature::new().outputs(vec![Type::FieldElement]),
@@ -1817,6 +2277,7 @@ mod tests {
             functions: functions,
             imported_functions: vec![],
             imports: vec![],
+            constants: vec![],
         });
 
         // shouldn't panic
@@ -1881,6 +2342,70 @@ mod tests {
         flattener.flatten_field_expression(&functions_flattened, &mut vec![], expression);
     }
 
+    #[test]
+    fn bool_xor_and_array_eq() {
+        // def main(bool x, bool y, field[2] a, field[2] b) -> (bool, bool, bool, bool):
+        //    return x ^ y, x == y, a == b, a != b
+        let x = || box BooleanExpression::Identifier("x".into());
+        let y = || box BooleanExpression::Identifier("y".into());
+        let a = || box FieldElementArrayExpression::Identifier(2, "a".into());
+        let b = || box FieldElementArrayExpression::Identifier(2, "b".into());
+
+        let main = TypedFunction {
+            id: "main",
+            arguments: vec![
+                Parameter::private(Variable::boolean("x".into())),
+                Parameter::private(Variable::boolean("y".into())),
+                Parameter::private(Variable::field_array("a".into(), 2)),
+                Parameter::private(Variable::field_array("b".into(), 2)),
+            ],
+            statements: vec![TypedStatement::Return(vec![
+                BooleanExpression::Xor(x(), y()).into(),
+                BooleanExpression::BoolEq(x(), y()).into(),
+                BooleanExpression::FieldElementArrayEq(a(), b()).into(),
+                BooleanExpression::Not(box BooleanExpression::FieldElementArrayEq(a(), b())).into(),
+            ])],
+            signature: Signature::new()
+                .inputs(vec![
+                    Type::Boolean,
+                    Type::Boolean,
+                    Type::FieldElementArray(2),
+                    Type::FieldElementArray(2),
+                ])
+                .outputs(vec![Type::Boolean; 4]),
+        };
+
+        let program: crate::ir::Prog<FieldPrime> = Flattener::flatten(TypedProg {
+            functions: vec![main],
+            imports: vec![],
+            imported_functions: vec![],
+            constants: vec![],
+        })
+        .into();
+
+        // x, y, a, b and the expected x ^ y, x == y, a == b, a != b
+        for (x, y, a, b, expected) in vec![
+            (0, 0, [1, 2], [1, 2], [0, 1, 1, 0]),
+            (0, 1, [1, 2], [1, 3], [1, 0, 0, 1]),
+            (1, 0, [1, 2], [2, 1], [1, 0, 0, 1]),
+            (1, 1, [0, 0], [0, 0], [0, 1, 1, 0]),
+            (1, 1, [5, 0], [0, 0], [0, 1, 0, 1]),
+        ] {
+            let inputs: Vec<FieldPrime> = vec![x, y, a[0], a[1], b[0], b[1]]
+                .into_iter()
+                .map(FieldPrime::from)
+                .collect();
+
+            assert_eq!(
+                program.execute(&inputs).unwrap().return_values(),
+                expected
+                    .iter()
+                    .map(|v| FieldPrime::from(*v))
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
     #[test]
     fn div() {
         // a = 5 / b / b
@@ -2288,4 +2813,35 @@ mod tests {
             Some(&vec![FlatVariable::new(2)])
         );
     }
+
+    #[test]
+    fn source_names() {
+        let mut flattener = Flattener::new();
+        flattener.function = "main";
+
+        let a = Identifier {
+            id: "a",
+            version: 1,
+            stack: vec![("foo", Signature::new(), 2)],
+        };
+        flattener.use_variable(&Variable::field_array(a, 2));
+
+        assert_eq!(
+            flattener.names.get(&FlatVariable::new(1)),
+            Some(&SourceName {
+                function: String::from("main::foo#2"),
+                variable: String::from("a"),
+                index: Some(1),
+                version: 1
+            })
+        );
+        assert_eq!(
+            flattener
+                .names
+                .get(&FlatVariable::new(0))
+                .unwrap()
+                .to_string(),
+            "main::foo#2::a@1[0]"
+        );
+    }
 }
@@ -0,0 +1,796 @@
+//! Module containing the formatter, which prints programs in a canonical layout.
+//!
+//! Programs are printed from their syntax tree, so that their layout does not depend on how they
+//! were written. Comments are kept next to the code they were written next to.
+
+use compile::{CompileErrorInner, CompileErrors};
+use zokrates_pest_ast as pest;
+use zokrates_pest_ast::{
+    Access, BasicType, BinaryOperator, Expression, File, Function, ImportDirective,
+    RangeOrExpression, Span, SpreadOrExpression, Statement, Type, UnaryOperator, Visibility,
+};
+
+// statements wider than this are broken after their operators
+const MAX_WIDTH: usize = 100;
+// the width of a tab when measuring lines
+const TAB_WIDTH: usize = 4;
+
+/// Formats a program, failing if it cannot be parsed
+pub fn format(source: &str) -> Result<String, CompileErrors> {
+    let file = pest::generate_ast(source)
+        .map_err(|e| CompileErrors::from(CompileErrorInner::from(e).with_context(&None)))?;
+
+    Ok(Formatter::new(source, &file).file(&file))
+}
+
+#[derive(Clone)]
+struct Comment {
+    start: usize,
+    end: usize,
+    line: usize,
+    text: String,
+    // whether the comment follows some code on its line
+    trailing: bool,
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    // the comments of the program, the ones before `next` having been printed
+    comments: Vec<Comment>,
+    next: usize,
+    lines: Vec<String>,
+    indent: usize,
+    // whether nothing was printed since the start of the current block
+    block_start: bool,
+    // the line of the source where the last code printed ends
+    last_line: usize,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(source: &'a str, file: &File) -> Self {
+        let comments = file
+            .comments
+            .iter()
+            .map(|c| Comment {
+                start: c.span.start(),
+                end: c.span.end(),
+                line: c.span.start_pos().line_col().0,
+                text: c.value.trim_end().to_string(),
+                trailing: !line_before(source, c.span.start()).trim().is_empty(),
+            })
+            .collect();
+
+        Formatter {
+            source,
+            comments,
+            next: 0,
+            lines: vec![],
+            indent: 0,
+            block_start: true,
+            last_line: 0,
+        }
+    }
+
+    fn file(mut self, file: &File) -> String {
+        self.imports(&file.imports);
+
+        for (index, constant) in file.constants.iter().enumerate() {
+            if index == 0 {
+                self.blank();
+            }
+            let prefix = format!("const {} {} = ", ty(&constant.ty), constant.id.value);
+            let lines = self.wrap(&prefix, &constant.expression);
+            self.item(
+                constant.span.start(),
+                constant.expression.span().end(),
+                lines,
+            );
+        }
+
+        for (index, function) in file.functions.iter().enumerate() {
+            let next = file
+                .functions
+                .get(index + 1)
+                .map(|f| f.span.start())
+                .unwrap_or(self.source.len());
+            self.blank();
+            self.function(function, next);
+        }
+
+        self.comments_before(self.source.len());
+
+        let mut res = self.lines.join("\n");
+        res.push('\n');
+        res
+    }
+
+    // imports are sorted by source, along with the comments written before and after each of them
+    fn imports(&mut self, imports: &[ImportDirective]) {
+        let mut groups = vec![];
+
+        for (index, import) in imports.iter().enumerate() {
+            // the comments at the top of the file stay there
+            if index == 0 {
+                self.comments_before(import.span.start());
+            }
+
+            let leading = self.take_comments(import.span.start());
+
+            let end = match (import.symbols.last(), &import.alias) {
+                (Some(symbol), _) => symbol.span.end(),
+                (None, Some(alias)) => alias.span.end(),
+                (None, None) => import.source.span.end() + 1,
+            };
+            let trailing = match self.comments.get(self.next).cloned() {
+                Some(c) if c.trailing && c.line == line_of(self.source, end) => {
+                    self.next += 1;
+                    Some(c)
+                }
+                _ => None,
+            };
+
+            let line = match (import.symbols.len(), &import.alias) {
+                (0, Some(alias)) => {
+                    format!("import \"{}\" as {}", import.source.value, alias.value)
+                }
+                (0, None) => format!("import \"{}\"", import.source.value),
+                _ => format!(
+                    "from \"{}\" import {}",
+                    import.source.value,
+                    import
+                        .symbols
+                        .iter()
+                        .map(|symbol| match &symbol.alias {
+                            Some(alias) => format!("{} as {}", symbol.id.value, alias.value),
+                            None => symbol.id.value.clone(),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            };
+
+            groups.push((import.source.value.clone(), leading, line, trailing));
+        }
+
+        if !groups.is_empty()
+            && !self.block_start
+            && self.follows_blank_line(imports[0].span.start())
+        {
+            self.blank();
+        }
+
+        groups.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (_, leading, line, trailing) in groups {
+            for comment in leading {
+                self.push_comment(&comment);
+            }
+            self.push(line);
+            if let Some(comment) = trailing {
+                self.append(&comment.text);
+            }
+            self.block_start = false;
+        }
+    }
+
+    fn function(&mut self, function: &Function, next: usize) {
+        let header = format!(
+            "def {}{}({}) -> ({}):",
+            function.id.value,
+            match function.generics.len() {
+                0 => String::new(),
+                _ => format!(
+                    "<{}>",
+                    function
+                        .generics
+                        .iter()
+                        .map(|g| g.id.value.clone())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            },
+            function
+                .parameters
+                .iter()
+                .map(|p| format!(
+                    "{}{} {}",
+                    match p.visibility {
+                        Some(Visibility::Public(_)) => "public ",
+                        Some(Visibility::Private(_)) => "private ",
+                        None => "",
+                    },
+                    ty(&p.ty),
+                    p.id.value
+                ))
+                .collect::<Vec<_>>()
+                .join(", "),
+            function
+                .returns
+                .iter()
+                .map(ty)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        let start = function.span.start();
+        self.open(start, start, header);
+        self.statements(&function.statements);
+
+        // the indented comments following the last statement are part of the body
+        while let Some(comment) = self.comments.get(self.next).cloned() {
+            let indented = !comment.trailing && !line_before(self.source, comment.start).is_empty();
+            let on_last_line = comment.trailing && comment.line == self.last_line;
+            if comment.start >= next || !(indented || on_last_line) {
+                break;
+            }
+            self.next += 1;
+            self.print_comment(&comment);
+        }
+
+        self.indent -= 1;
+        self.block_start = false;
+    }
+
+    fn statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Return(s) => {
+                let lines = match s.expressions.len() {
+                    1 => self.wrap("return ", &s.expressions[0]),
+                    _ => vec![format!("return {}", list(&s.expressions))
+                        .trim_end()
+                        .to_string()],
+                };
+                self.item(s.span.start(), s.span.end(), lines);
+            }
+            Statement::Definition(s) => {
+                let prefix = format!("{} {} = ", ty(&s.ty), s.id.value);
+                let lines = self.wrap(&prefix, &s.expression);
+                self.item(s.span.start(), s.span.end(), lines);
+            }
+            Statement::Assertion(s) => {
+                let lines = self.wrap("", &s.expression);
+                self.item(s.span.start(), s.span.end(), lines);
+            }
+            Statement::Assignment(s) => {
+                let prefix = format!(
+                    "{}{} = ",
+                    s.assignee.id.value,
+                    s.assignee
+                        .indices
+                        .iter()
+                        .map(|i| format!("[{}]", range_or_expression(i)))
+                        .collect::<String>()
+                );
+                let lines = self.wrap(&prefix, &s.expression);
+                self.item(s.span.start(), s.span.end(), lines);
+            }
+            Statement::MultiAssignment(s) => {
+                let line = format!(
+                    "{} = {}({})",
+                    s.lhs
+                        .iter()
+                        .map(|i| match &i.ty {
+                            Some(t) => format!("{} {}", ty(t), i.id.value),
+                            None => i.id.value.clone(),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    s.function_id.value,
+                    list(&s.arguments)
+                );
+                self.item(s.span.start(), s.span.end(), vec![line]);
+            }
+            Statement::Log(s) => {
+                let line = format!(
+                    "log(\"{}\"{})",
+                    s.format.value,
+                    s.arguments
+                        .iter()
+                        .map(|a| format!(", {}", expression(a)))
+                        .collect::<String>()
+                );
+                self.item(s.span.start(), s.span.end(), vec![line]);
+            }
+            Statement::Iteration(s) => {
+                let header = format!(
+                    "for {} {} in {}..{} do",
+                    ty(&s.ty),
+                    s.index.value,
+                    expression(&s.from),
+                    expression(&s.to)
+                );
+                self.open(s.span.start(), s.to.span().end(), header);
+                self.statements(&s.statements);
+                self.close(s.span.end(), "endfor");
+            }
+            Statement::IfElse(s) => {
+                let header = format!("if {} then", expression(&s.condition));
+                self.open(s.span.start(), s.condition.span().end(), header);
+                self.statements(&s.consequence);
+
+                if let Some(alternative) = &s.alternative {
+                    let from = s
+                        .consequence
+                        .last()
+                        .map(|s| statement_span(s).end())
+                        .unwrap_or(s.condition.span().end());
+                    let offset = self.keyword("else", from, alternative.span.start());
+                    self.close(offset, "else");
+                    self.indent += 1;
+                    self.block_start = true;
+                    self.statements(&alternative.statements);
+                }
+
+                self.close(s.span.end(), "endif");
+            }
+        }
+    }
+
+    // prints an item found between `start` and `end` in the source
+    fn item(&mut self, start: usize, end: usize, lines: Vec<String>) {
+        self.comments_before(start);
+        if !self.block_start && self.follows_blank_line(start) {
+            self.blank();
+        }
+        for line in lines {
+            self.push(line);
+        }
+        self.block_start = false;
+        self.last_line = line_of(self.source, end);
+    }
+
+    // prints the header of a block, whose content is indented
+    fn open(&mut self, start: usize, end: usize, header: String) {
+        self.item(start, end, vec![header]);
+        self.indent += 1;
+        self.block_start = true;
+    }
+
+    // closes a block with `keyword`, found right before `end` in the source
+    fn close(&mut self, end: usize, keyword: &str) {
+        self.comments_before(end);
+        self.indent -= 1;
+        self.push(keyword.to_string());
+        self.block_start = false;
+        self.last_line = line_of(self.source, end);
+    }
+
+    // the offset of the first `keyword` between `from` and `to` which is not in a comment
+    fn keyword(&self, keyword: &str, from: usize, to: usize) -> usize {
+        let mut offset = from;
+        while let Some(index) = self.source[offset..to].find(keyword) {
+            let candidate = offset + index;
+            match self
+                .comments
+                .iter()
+                .find(|c| c.start <= candidate && candidate < c.end)
+            {
+                Some(comment) => offset = comment.end,
+                None => return candidate,
+            }
+        }
+        to
+    }
+
+    fn take_comments(&mut self, before: usize) -> Vec<Comment> {
+        let mut res = vec![];
+        while let Some(comment) = self.comments.get(self.next) {
+            if comment.start >= before {
+                break;
+            }
+            res.push(comment.clone());
+            self.next += 1;
+        }
+        res
+    }
+
+    fn comments_before(&mut self, before: usize) {
+        for comment in self.take_comments(before) {
+            self.print_comment(&comment);
+        }
+    }
+
+    // comments following code are kept on the line of that code
+    fn print_comment(&mut self, comment: &Comment) {
+        match comment.trailing && comment.line == self.last_line && !self.lines.is_empty() {
+            true => self.append(&comment.text),
+            false => {
+                if !self.block_start && self.follows_blank_line(comment.start) {
+                    self.blank();
+                }
+                self.push_comment(comment);
+                self.block_start = false;
+                self.last_line = comment.line + comment.text.matches('\n').count();
+            }
+        }
+    }
+
+    // block comments spanning several lines are printed as they are written
+    fn push_comment(&mut self, comment: &Comment) {
+        let mut lines = comment.text.lines();
+        if let Some(first) = lines.next() {
+            self.push(first.to_string());
+        }
+        for line in lines {
+            self.lines.push(line.trim_end().to_string());
+        }
+    }
+
+    fn push(&mut self, line: String) {
+        self.lines
+            .push(format!("{}{}", "\t".repeat(self.indent), line));
+    }
+
+    fn append(&mut self, text: &str) {
+        if let Some(line) = self.lines.last_mut() {
+            line.push(' ');
+            line.push_str(text);
+        }
+    }
+
+    // prints a single blank line, unless the output is empty
+    fn blank(&mut self) {
+        if self.lines.last().map(|l| !l.is_empty()).unwrap_or(false) {
+            self.lines.push(String::new());
+        }
+    }
+
+    fn follows_blank_line(&self, offset: usize) -> bool {
+        let mut lines = self.source[..offset].rsplit('\n');
+        lines.next();
+        lines.next().map(|l| l.trim().is_empty()).unwrap_or(false)
+    }
+
+    fn width(&self, line: &str) -> usize {
+        (self.indent + line.matches('\t').count()) * TAB_WIDTH + line.replace('\t', "").len()
+    }
+
+    // prints `e` after `prefix`, breaking it after its top-level operators if it is too wide
+    fn wrap(&self, prefix: &str, e: &Expression) -> Vec<String> {
+        let line = format!("{}{}", prefix, expression(e));
+
+        let e = match e {
+            Expression::Binary(e) if self.width(&line) > MAX_WIDTH => e,
+            _ => return vec![line],
+        };
+
+        // the operands chained by operators of the same precedence, such as `a`, `b` and `c` in `a + b - c`
+        let level = precedence(&e.op);
+        let mut operands = vec![(Some(&e.op), &*e.right)];
+        let mut left = &*e.left;
+        while let Expression::Binary(l) = left {
+            if precedence(&l.op) != level {
+                break;
+            }
+            operands.push((Some(&l.op), &*l.right));
+            left = &*l.left;
+        }
+        operands.push((None, left));
+        operands.reverse();
+
+        let mut lines = vec![];
+        let mut current = format!("{}{}", prefix, operand(operands[0].1, level, true));
+        for (op, e) in operands.into_iter().skip(1) {
+            let op = operator(op.unwrap());
+            let next = operand(e, level, false);
+            let candidate = format!("{} {} {}", current, op, next);
+            // keep room for the operator and the continuation which may follow
+            match self.width(&candidate) + op.len() + 3 > MAX_WIDTH {
+                true => {
+                    lines.push(format!("{} {} \\", current, op));
+                    current = format!("\t{}", next);
+                }
+                false => current = candidate,
+            }
+        }
+        lines.push(current);
+        lines
+    }
+}
+
+fn statement_span<'a, 'ast>(statement: &'a Statement<'ast>) -> &'a Span<'ast> {
+    match statement {
+        Statement::Return(s) => &s.span,
+        Statement::Definition(s) => &s.span,
+        Statement::Assertion(s) => &s.span,
+        Statement::Iteration(s) => &s.span,
+        Statement::IfElse(s) => &s.span,
+        Statement::Assignment(s) => &s.span,
+        Statement::MultiAssignment(s) => &s.span,
+        Statement::Log(s) => &s.span,
+    }
+}
+
+// the part of the line of `offset` which is before it
+fn line_before(source: &str, offset: usize) -> &str {
+    source[..offset].rsplit('\n').next().unwrap_or("")
+}
+
+fn line_of(source: &str, offset: usize) -> usize {
+    source[..offset].matches('\n').count() + 1
+}
+
+fn ty(t: &Type) -> String {
+    match t {
+        Type::Basic(t) => basic_type(t).to_string(),
+        Type::Array(t) => format!("{}[{}]", basic_type(&t.ty), expression(&t.size)),
+    }
+}
+
+fn basic_type(t: &BasicType) -> &'static str {
+    match t {
+        BasicType::Field(_) => "field",
+        BasicType::Boolean(_) => "bool",
+    }
+}
+
+fn list(expressions: &[Expression]) -> String {
+    expressions
+        .iter()
+        .map(expression)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// operators of higher precedence bind tighter, as in the parser
+fn precedence(op: &BinaryOperator) -> usize {
+    match op {
+        BinaryOperator::Or => 1,
+        BinaryOperator::Xor => 2,
+        BinaryOperator::And => 3,
+        BinaryOperator::Eq | BinaryOperator::NotEq => 4,
+        BinaryOperator::Lt | BinaryOperator::Lte | BinaryOperator::Gt | BinaryOperator::Gte => 5,
+        BinaryOperator::Add | BinaryOperator::Sub => 6,
+        BinaryOperator::Mul
+        | BinaryOperator::Div
+        | BinaryOperator::IntegerDiv(_)
+        | BinaryOperator::Rem(_) => 7,
+        BinaryOperator::Pow => 8,
+    }
+}
+
+fn operator(op: &BinaryOperator) -> String {
+    match op {
+        BinaryOperator::Or => "||".to_string(),
+        BinaryOperator::Xor => "^".to_string(),
+        BinaryOperator::And => "&&".to_string(),
+        BinaryOperator::Eq => "==".to_string(),
+        BinaryOperator::NotEq => "!=".to_string(),
+        BinaryOperator::Lt => "<".to_string(),
+        BinaryOperator::Lte => "<=".to_string(),
+        BinaryOperator::Gt => ">".to_string(),
+        BinaryOperator::Gte => ">=".to_string(),
+        BinaryOperator::Add => "+".to_string(),
+        BinaryOperator::Sub => "-".to_string(),
+        BinaryOperator::Mul => "*".to_string(),
+        BinaryOperator::Div => "/".to_string(),
+        BinaryOperator::IntegerDiv(bits) => format!("\\<{}>", bits),
+        BinaryOperator::Rem(bits) => format!("%<{}>", bits),
+        BinaryOperator::Pow => "**".to_string(),
+    }
+}
+
+// all operators are left associative, so a right operand of the same precedence needs parentheses
+fn operand(e: &Expression, level: usize, left: bool) -> String {
+    match e {
+        Expression::Binary(b)
+            if precedence(&b.op) < level || (!left && precedence(&b.op) == level) =>
+        {
+            format!("({})", expression(e))
+        }
+        _ => expression(e),
+    }
+}
+
+fn expression(e: &Expression) -> String {
+    match e {
+        Expression::Binary(e) => {
+            let level = precedence(&e.op);
+            format!(
+                "{} {} {}",
+                operand(&e.left, level, true),
+                operator(&e.op),
+                operand(&e.right, level, false)
+            )
+        }
+        Expression::Ternary(e) => format!(
+            "if {} then {} else {} fi",
+            expression(&e.first),
+            expression(&e.second),
+            expression(&e.third)
+        ),
+        Expression::Postfix(e) => format!(
+            "{}{}",
+            e.id.value,
+            e.access
+                .iter()
+                .map(|a| match a {
+                    Access::Call(c) => format!("({})", list(&c.expressions)),
+                    Access::Select(s) => format!("[{}]", range_or_expression(&s.expression)),
+                })
+                .collect::<String>()
+        ),
+        Expression::Identifier(e) => e.value.clone(),
+        Expression::Constant(e) => e.value.clone(),
+        Expression::InlineArray(e) => format!(
+            "[{}]",
+            e.expressions
+                .iter()
+                .map(|e| match e {
+                    SpreadOrExpression::Spread(s) => format!("...{}", expression(&s.expression)),
+                    SpreadOrExpression::Expression(e) => expression(e),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Expression::ArrayInitializer(e) => {
+            format!("[{}; {}]", expression(&e.value), e.count.value)
+        }
+        Expression::Unary(e) => {
+            let op = match e.op {
+                UnaryOperator::Not(_) => "!",
+            };
+            match *e.expression {
+                Expression::Binary(_) => format!("{}({})", op, expression(&e.expression)),
+                _ => format!("{}{}", op, expression(&e.expression)),
+            }
+        }
+    }
+}
+
+fn range_or_expression(e: &RangeOrExpression) -> String {
+    match e {
+        RangeOrExpression::Range(r) => format!(
+            "{}..{}",
+            r.from
+                .as_ref()
+                .map(|e| expression(&e.0))
+                .unwrap_or_default(),
+            r.to.as_ref().map(|e| expression(&e.0)).unwrap_or_default()
+        ),
+        RangeOrExpression::Expression(e) => expression(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use absy::Prog;
+    use zokrates_field::field::FieldPrime;
+
+    #[test]
+    fn layout() {
+        let source = r#"import "./foo.code"   as   foo
+import "./bar.code"
+def main(private field a,field[2] b)->(field):
+  field c=a+b[0]*(b[1]-1)
+    for field i in 0..2 do
+  c = c+b[i]
+      endfor
+  return if c==0 then 1 else foo() fi
+"#;
+
+        assert_eq!(
+            format(source).unwrap(),
+            r#"import "./bar.code"
+import "./foo.code" as foo
+
+def main(private field a, field[2] b) -> (field):
+	field c = a + b[0] * (b[1] - 1)
+	for field i in 0..2 do
+		c = c + b[i]
+	endfor
+	return if c == 0 then 1 else foo() fi
+"#
+        );
+    }
+
+    #[test]
+    fn comments() {
+        let source = r#"// a program
+import "./foo.code" // the import
+
+def main() -> (field): // the main function
+	// the result
+	field a = 1 /* one */
+
+	// a check
+	a == 1
+	return a
+	// done
+"#;
+
+        assert_eq!(format(source).unwrap(), source);
+    }
+
+    #[test]
+    fn from_imports() {
+        let source = r#"from "./foo.code"   import  A,B   as  C // constants
+import "./bar.code"
+def main() -> (field):
+  return A + C
+"#;
+
+        assert_eq!(
+            format(source).unwrap(),
+            r#"import "./bar.code"
+from "./foo.code" import A, B as C // constants
+
+def main() -> (field):
+	return A + C
+"#
+        );
+    }
+
+    #[test]
+    fn parentheses() {
+        let source = "def main(field a, field b) -> (field):\n\treturn (a - (b - 1)) * (a + b) / ((a * b) * 2)\n";
+
+        assert_eq!(
+            format(source).unwrap(),
+            "def main(field a, field b) -> (field):\n\treturn (a - (b - 1)) * (a + b) / (a * b * 2)\n"
+        );
+    }
+
+    #[test]
+    fn line_continuation() {
+        let operands: Vec<_> = (0..60).map(|i| format!("a{}", i)).collect();
+        let source = format!(
+            "def main() -> (field):\n\treturn {}\n",
+            operands.join(" + ")
+        );
+
+        let formatted = format(&source).unwrap();
+        let lines: Vec<_> = formatted.lines().collect();
+
+        assert!(lines.len() > 3);
+        assert!(lines[1].ends_with(" + \\"));
+        assert!(lines[2].starts_with("\t\t"));
+        assert!(lines
+            .iter()
+            .all(|l| l.replace('\t', "    ").len() <= MAX_WIDTH));
+    }
+
+    #[test]
+    fn examples() {
+        use glob::glob;
+        use std::fs;
+
+        // imports are sorted, so they are compared regardless of their order
+        fn program(source: &str) -> (Vec<String>, String) {
+            let prog: Prog<FieldPrime> = Prog::from(pest::generate_ast(source).unwrap());
+            let mut imports: Vec<_> = prog.imports.iter().map(|i| i.to_string()).collect();
+            imports.sort();
+            (
+                imports,
+                Prog {
+                    imports: vec![],
+                    ..prog
+                }
+                .to_string(),
+            )
+        }
+
+        for entry in
+            glob("../zokrates_cli/examples/**/*.code").expect("Failed to read glob pattern")
+        {
+            let path = entry.unwrap();
+            if path.to_str().unwrap().contains("error") {
+                continue;
+            }
+
+            let source = fs::read_to_string(&path).unwrap();
+            let formatted = format(&source).unwrap();
+
+            // formatting keeps the meaning of the program and is stable
+            assert_eq!(program(&formatted), program(&source), "{}", path.display());
+            assert_eq!(format(&formatted).unwrap(), formatted, "{}", path.display());
+        }
+    }
+}
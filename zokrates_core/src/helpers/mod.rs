@@ -6,6 +6,7 @@ pub use self::rust::RustHelper;
 #[cfg(feature = "wasm")]
 pub use self::wasm::WasmHelper;
 use crate::flat_absy::{FlatExpression, FlatVariable};
+use crate::parser::Position;
 use std::fmt;
 use zokrates_field::field::Field;
 
@@ -82,6 +83,26 @@ impl Helper {
     }
 }
 
+impl Helper {
+    /// Returns the message of a log given its inputs, or `None` if it is not printed or if the
+    /// helper is not a log
+    pub fn log_message<T: Field>(&self, inputs: &Vec<T>) -> Option<String> {
+        match self {
+            Helper::Rust(helper) => helper.log_message(inputs),
+            #[cfg(feature = "wasm")]
+            Helper::Wasm(_) => None,
+        }
+    }
+
+    /// Returns the location in the source marked by the helper, if it is a location
+    pub fn location(&self) -> Option<Position> {
+        match self {
+            Helper::Rust(RustHelper::Location(position)) => Some(*position),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for Helper {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -1,4 +1,8 @@
 use crate::helpers::{Executable, Signed};
+use crate::parser::Position;
+use crate::types::Type;
+use num::Zero;
+use num_bigint::BigUint;
 use std::fmt;
 use zokrates_embed::generate_sha256_round_witness;
 use zokrates_field::field::Field;
@@ -9,7 +13,16 @@ pub enum RustHelper {
     ConditionEq,
     Bits,
     Div,
+    IntegerDiv,
     Sha256Round,
+    /// A message printed when computing a witness. The inputs are the condition for it to be
+    /// printed followed by the arguments, of the given types, which replace each `{}` in the format
+    Log(String, Vec<Type>),
+    /// Marks the beginning of the statement found at this location in the source, for the
+    /// debugger. It computes nothing.
+    Location(Position),
+    /// The given number of least significant bits of the input, from the most significant one
+    LeastSignificantBits(usize),
 }
 
 impl fmt::Display for RustHelper {
@@ -25,7 +38,50 @@ impl Signed for RustHelper {
             RustHelper::ConditionEq => (1, 2),
             RustHelper::Bits => (1, 254),
             RustHelper::Div => (2, 1),
+            RustHelper::IntegerDiv => (2, 2),
             RustHelper::Sha256Round => (768, 26935),
+            RustHelper::Log(_, types) => (
+                1 + types.iter().map(|t| t.get_primitive_count()).sum::<usize>(),
+                0,
+            ),
+            RustHelper::Location(_) => (0, 0),
+            RustHelper::LeastSignificantBits(bitwidth) => (1, *bitwidth),
+        }
+    }
+}
+
+impl RustHelper {
+    /// Returns the message of a log given its inputs, or `None` if it is not printed
+    pub fn log_message<T: Field>(&self, inputs: &Vec<T>) -> Option<String> {
+        match self {
+            RustHelper::Log(format, types) if inputs[0] == T::one() => {
+                let mut values = inputs[1..].iter();
+                let arguments: Vec<_> = types
+                    .iter()
+                    .map(|t| match t {
+                        Type::FieldElement => values.next().unwrap().to_string(),
+                        Type::Boolean => (*values.next().unwrap() == T::one()).to_string(),
+                        Type::FieldElementArray(size) => format!(
+                            "[{}]",
+                            values
+                                .by_ref()
+                                .take(*size)
+                                .map(|v| v.to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    })
+                    .collect();
+
+                let mut pieces = format.split("{}");
+                let mut message = pieces.next().unwrap().to_string();
+                for (argument, piece) in arguments.iter().zip(pieces) {
+                    message.push_str(argument);
+                    message.push_str(piece);
+                }
+                Some(message)
+            }
+            _ => None,
         }
     }
 }
@@ -54,6 +110,18 @@ impl<T: Field> Executable<T> for RustHelper {
                 Ok(res)
             }
             RustHelper::Div => Ok(vec![inputs[0].clone() / inputs[1].clone()]),
+            RustHelper::IntegerDiv => {
+                // the inputs are interpreted as integers in [0, p - 1]
+                let a = BigUint::from_bytes_le(&inputs[0].into_byte_vector());
+                let b = BigUint::from_bytes_le(&inputs[1].into_byte_vector());
+                if b.is_zero() {
+                    return Err(String::from("Integer division by zero"));
+                }
+                Ok(vec![
+                    T::from_byte_vector((&a / &b).to_bytes_le()),
+                    T::from_byte_vector((&a % &b).to_bytes_le()),
+                ])
+            }
             RustHelper::Sha256Round => {
                 let i = &inputs[0..512];
                 let h = &inputs[512..];
@@ -65,6 +133,21 @@ impl<T: Field> Executable<T> for RustHelper {
                     .map(|x| T::from_bellman(x))
                     .collect())
             }
+            // the message is printed by the interpreter, as it is not part of the witness
+            RustHelper::Log(..) => Ok(vec![]),
+            RustHelper::Location(_) => Ok(vec![]),
+            RustHelper::LeastSignificantBits(bitwidth) => {
+                let bytes = inputs[0].into_byte_vector();
+                Ok((0..*bitwidth)
+                    .rev()
+                    .map(
+                        |i| match bytes.get(i / 8).map_or(0, |byte| (byte >> (i % 8)) & 1) {
+                            0 => T::zero(),
+                            _ => T::one(),
+                        },
+                    )
+                    .collect())
+            }
         }
     }
 }
@@ -84,6 +167,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn log_message() {
+        let helper = RustHelper::Log(
+            String::from("a = {}, b = {}, c = {}"),
+            vec![
+                Type::FieldElement,
+                Type::Boolean,
+                Type::FieldElementArray(2),
+            ],
+        );
+        let inputs = |condition| {
+            vec![condition, 42, 1, 2, 3]
+                .into_iter()
+                .map(|i| FieldPrime::from(i))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(helper.get_signature(), (5, 0));
+        assert_eq!(helper.execute(&inputs(1)).unwrap(), vec![]);
+        assert_eq!(
+            helper.log_message(&inputs(1)),
+            Some(String::from("a = 42, b = true, c = [2, 3]"))
+        );
+        assert_eq!(helper.log_message(&inputs(0)), None);
+    }
+
+    #[test]
+    fn integer_div() {
+        let inputs = vec![FieldPrime::from(42), FieldPrime::from(5)];
+        let res = RustHelper::IntegerDiv.execute(&inputs).unwrap();
+        assert_eq!(res, vec![FieldPrime::from(8), FieldPrime::from(2)]);
+    }
+
+    #[test]
+    fn integer_div_by_zero() {
+        let inputs = vec![FieldPrime::from(42), FieldPrime::from(0)];
+        assert!(RustHelper::IntegerDiv.execute(&inputs).is_err());
+    }
+
     #[test]
     fn bits_of_42() {
         let inputs = vec![FieldPrime::from(42)];
@@ -96,4 +218,28 @@ mod tests {
         assert_eq!(res[248], FieldPrime::from(1));
         assert_eq!(res[247], FieldPrime::from(0));
     }
+
+    #[test]
+    fn least_significant_bits() {
+        let helper = RustHelper::LeastSignificantBits(4);
+        assert_eq!(
+            helper.execute(&vec![FieldPrime::from(10)]).unwrap(),
+            vec![1, 0, 1, 0]
+                .into_iter()
+                .map(FieldPrime::from)
+                .collect::<Vec<_>>()
+        );
+        // the bits of values which do not fit are truncated, so that the decomposition check fails
+        assert_eq!(
+            helper.execute(&vec![FieldPrime::from(42)]).unwrap(),
+            vec![1, 0, 1, 0]
+                .into_iter()
+                .map(FieldPrime::from)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            helper.execute(&vec![FieldPrime::from(-1)]).unwrap().len(),
+            4
+        );
+    }
 }
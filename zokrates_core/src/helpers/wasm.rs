@@ -2,6 +2,7 @@ use helpers::{Executable, Signed};
 use std::fmt;
 
 use rustc_hex::FromHex;
+use serde::de::Error as DeError;
 use serde::{Deserialize, Deserializer};
 use std::rc::Rc;
 use wasmi::{ImportsBuilder, ModuleInstance, ModuleRef, NopExternals};
@@ -42,16 +43,53 @@ impl WasmHelper {
             .expect(format!("invalid bytecode: {}", code_hex).as_str());
         WasmHelper::from(code)
     }
-}
 
-impl<U: Into<Vec<u8>>> From<U> for WasmHelper {
-    fn from(code: U) -> Self {
+    pub fn try_from_bytes<U: Into<Vec<u8>>>(code: U) -> Result<Self, String> {
         let code_vec = code.into();
-        let module = wasmi::Module::from_buffer(code_vec.clone()).expect("Error decoding buffer");
+        let module = wasmi::Module::from_buffer(code_vec.clone())
+            .map_err(|e| format!("Error decoding buffer: {:?}", e))?;
+        // a start function would be run when loading the module, so such modules are rejected
+        let has_start = parity_wasm::deserialize_buffer::<parity_wasm::elements::Module>(&code_vec)
+            .map_err(|e| format!("Error decoding buffer: {:?}", e))?
+            .start_section()
+            .is_some();
+        if has_start {
+            return Err(String::from("Module has a start function"));
+        }
         let modinst = ModuleInstance::new(&module, &ImportsBuilder::default())
-            .expect("Failed to instantiate module")
+            .map_err(|e| format!("Failed to instantiate module: {:?}", e))?
             .assert_no_start();
-        WasmHelper(Rc::new(modinst), code_vec)
+        Ok(WasmHelper(Rc::new(modinst), code_vec))
+    }
+
+    /// Checks that the module has the exports used to get its signature and to execute it
+    pub fn validate(&self) -> Result<(), String> {
+        for global in &["min_inputs", "min_outputs", "field_size"] {
+            get_export::<i32>(global, self.0.as_ref())?;
+        }
+
+        for function in &["get_inputs_off", "solve"] {
+            match self.0.export_by_name(function) {
+                Some(ref export) if export.as_func().is_some() => {}
+                _ => {
+                    return Err(format!(
+                        "Could not find exported function `{}` in module",
+                        function
+                    ))
+                }
+            }
+        }
+
+        match self.0.export_by_name("memory") {
+            Some(ref export) if export.as_memory().is_some() => Ok(()),
+            _ => Err(String::from("Module didn't export its memory section")),
+        }
+    }
+}
+
+impl<U: Into<Vec<u8>>> From<U> for WasmHelper {
+    fn from(code: U) -> Self {
+        WasmHelper::try_from_bytes(code).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
@@ -61,7 +99,7 @@ impl<'de> Deserialize<'de> for WasmHelper {
         D: Deserializer<'de>,
     {
         let hex: Vec<u8> = serde_bytes::deserialize(deserializer)?;
-        Ok(WasmHelper::from(hex))
+        WasmHelper::try_from_bytes(hex).map_err(D::Error::custom)
     }
 }
 
@@ -206,7 +244,7 @@ impl<T: Field> Executable<T> for WasmHelper {
 mod tests {
     use super::*;
     use parity_wasm::builder::*;
-    use parity_wasm::elements::{Instruction, Instructions, ValueType};
+    use parity_wasm::elements::{Instruction, Instructions, Section, ValueType};
     use std::panic;
     use zokrates_field::field::FieldPrime;
 
@@ -406,6 +444,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn validate_module() {
+        assert_eq!(
+            WasmHelper::from_hex(WasmHelper::IDENTITY_WASM).validate(),
+            Ok(())
+        );
+
+        let code: Vec<u8> = FromHex::from_hex(&WasmHelper::IDENTITY_WASM[..20]).unwrap();
+        assert_eq!(
+            WasmHelper::try_from_bytes(code),
+            Err(String::from(
+                "Error decoding buffer: Validation(\"I/O Error: UnexpectedEof\")"
+            ))
+        );
+
+        // the start function would run when loading the module
+        let code = replace_function(
+            WasmHelper::IDENTITY_WASM,
+            "start",
+            vec![],
+            None,
+            vec![Instruction::End],
+        );
+        let mut module: parity_wasm::elements::Module =
+            parity_wasm::deserialize_buffer(&code[..]).unwrap();
+        let index = module
+            .sections()
+            .iter()
+            .position(|section| match section {
+                Section::Element(..) | Section::Code(..) | Section::Data(..) => true,
+                _ => false,
+            })
+            .unwrap();
+        module.sections_mut().insert(index, Section::Start(2));
+        assert_eq!(
+            WasmHelper::try_from_bytes(parity_wasm::serialize(module).unwrap()),
+            Err(String::from("Module has a start function"))
+        );
+
+        for (export, message) in vec![
+            (
+                "min_inputs",
+                "Could not find exported symbol `min_inputs` in module",
+            ),
+            (
+                "min_outputs",
+                "Could not find exported symbol `min_outputs` in module",
+            ),
+            (
+                "field_size",
+                "Could not find exported symbol `field_size` in module",
+            ),
+            (
+                "get_inputs_off",
+                "Could not find exported function `get_inputs_off` in module",
+            ),
+            (
+                "solve",
+                "Could not find exported function `solve` in module",
+            ),
+            ("memory", "Module didn't export its memory section"),
+        ] {
+            let helper = WasmHelper::from(remove_export(WasmHelper::IDENTITY_WASM, export));
+            assert_eq!(helper.validate(), Err(String::from(message)));
+        }
+    }
+
     #[test]
     fn check_invalid_function_type() {
         /* Test identity, with a different function return type */
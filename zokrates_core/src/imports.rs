@@ -5,29 +5,42 @@
 //! @date 2018
 
 use crate::absy::*;
-use crate::compile::compile_aux;
+use crate::compile::compile_imported_module;
 use crate::compile::{CompileErrorInner, CompileErrors};
+use crate::diagnostics::{Code, Diagnostic};
 use crate::flat_absy::*;
 use crate::parser::Position;
+use crate::typed_absy::ConstantValue;
 use std::fmt;
 use std::io;
 use std::io::BufRead;
 use zokrates_field::field::Field;
 
 pub struct CompiledImport<T: Field> {
-    pub flat_func: FlatFunction<T>,
+    pub flat_func: Option<FlatFunction<T>>,
+    pub constants: Vec<(String, ConstantValue<T>)>,
 }
 
 impl<T: Field> CompiledImport<T> {
-    fn new(prog: FlatProg<T>, alias: String) -> CompiledImport<T> {
+    fn new(prog: FlatProg<T>, alias: String) -> Result<CompiledImport<T>, Error> {
         match prog.functions.iter().find(|fun| fun.id == "main") {
-            Some(fun) => CompiledImport {
-                flat_func: FlatFunction {
+            Some(fun) => Ok(CompiledImport {
+                flat_func: Some(FlatFunction {
                     id: alias,
                     ..fun.clone()
-                },
-            },
-            None => panic!("no main"),
+                }),
+                constants: vec![],
+            }),
+            None => Err(Error::new(
+                "Imported program has no main function, its constants can be imported by name",
+            )),
+        }
+    }
+
+    fn constants(constants: Vec<(String, ConstantValue<T>)>) -> CompiledImport<T> {
+        CompiledImport {
+            flat_func: None,
+            constants,
         }
     }
 }
@@ -61,6 +74,12 @@ impl fmt::Display for Error {
     }
 }
 
+impl<'a> From<&'a Error> for Diagnostic {
+    fn from(error: &'a Error) -> Diagnostic {
+        Diagnostic::new(Code::ImportError, error.message.clone()).with_pos(error.pos)
+    }
+}
+
 impl From<io::Error> for Error {
     fn from(error: io::Error) -> Self {
         Error {
@@ -74,6 +93,8 @@ impl From<io::Error> for Error {
 pub struct Import {
     source: String,
     alias: Option<String>,
+    // the constants imported by name, along with the name they are imported as
+    symbols: Vec<(String, String)>,
 }
 
 pub type ImportNode = Node<Import>;
@@ -83,6 +104,7 @@ impl Import {
         Import {
             source: source,
             alias: None,
+            symbols: vec![],
         }
     }
 
@@ -94,6 +116,7 @@ impl Import {
         Import {
             source: source,
             alias: Some(alias.clone()),
+            symbols: vec![],
         }
     }
 
@@ -102,14 +125,43 @@ impl Import {
         self
     }
 
+    /// Imports the constants `symbols` by name instead of the main function, each under the name
+    /// it is paired with
+    pub fn symbols(mut self, symbols: Vec<(String, String)>) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
     pub fn get_source(&self) -> &String {
         &self.source
     }
+
+    pub fn get_symbols(&self) -> &Vec<(String, String)> {
+        &self.symbols
+    }
+}
+
+// `A` or `A as B`
+fn format_symbols(symbols: &Vec<(String, String)>) -> String {
+    symbols
+        .iter()
+        .map(|(id, alias)| match id == alias {
+            true => id.clone(),
+            false => format!("{} as {}", id, alias),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 impl fmt::Display for Import {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.alias {
+            _ if self.symbols.len() > 0 => write!(
+                f,
+                "from {} import {}",
+                self.source,
+                format_symbols(&self.symbols)
+            ),
             Some(ref alias) => write!(f, "import {} as {}", self.source, alias),
             None => write!(f, "import {}", self.source),
         }
@@ -119,6 +171,12 @@ impl fmt::Display for Import {
 impl fmt::Debug for Import {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.alias {
+            _ if self.symbols.len() > 0 => write!(
+                f,
+                "import(source: {}, symbols: {})",
+                self.source,
+                format_symbols(&self.symbols)
+            ),
             Some(ref alias) => write!(f, "import(source: {}, alias: {})", self.source, alias),
             None => write!(f, "import(source: {})", self.source),
         }
@@ -143,96 +201,246 @@ impl Importer {
     where
         'before: 'after,
     {
-        let mut origins: Vec<CompiledImport<T>> = vec![];
+        let mut errors = vec![];
+        let mut imports = vec![];
+        let mut imported_functions = vec![];
+        let mut imported_constants = vec![];
 
+        // all imports are resolved so that all their errors are reported
         for import in destination.imports.iter() {
-            let pos = import.pos();
-            let import = &import.value;
-            // handle the case of special bellman and packing imports
-            if import.source.starts_with("BELLMAN") {
-                match import.source.as_ref() {
-                    "BELLMAN/sha256round" => {
-                        use crate::standard::sha_round;
-
-                        let compiled = FlatProg {
-                            functions: vec![sha_round()],
-                        };
-
-                        let alias = match import.alias {
-                            Some(ref alias) => alias.clone(),
-                            None => String::from("sha256round"),
-                        };
-
-                        origins.push(CompiledImport::new(compiled, alias));
-                    }
-                    s => {
-                        return Err(CompileErrorInner::ImportError(
-                            Error::new(format!("Gadget {} not found", s)).with_pos(Some(pos)),
-                        )
-                        .with_context(&location)
-                        .into());
-                    }
+            match self.apply_import(import, &location, resolve_option) {
+                Ok(origin) => {
+                    let alias = origin.flat_func.as_ref().map(|f| f.id.clone());
+                    // the checker refers to imports by their alias
+                    imports.push(ImportNode::new(
+                        import.start,
+                        import.end,
+                        import.value.clone().alias(alias),
+                    ));
+                    imported_functions.extend(origin.flat_func);
+                    imported_constants.extend(origin.constants);
                 }
-            } else if import.source.starts_with("PACKING") {
-                use crate::types::conversions::split;
-
-                match import.source.as_ref() {
-                    "PACKING/split" => {
-                        let compiled = split();
-                        let alias = match import.alias {
-                            Some(ref alias) => alias.clone(),
-                            None => String::from("split"),
-                        };
-
-                        origins.push(CompiledImport::new(compiled, alias));
-                    }
-                    s => {
-                        return Err(CompileErrorInner::ImportError(
-                            Error::new(format!("Packing helper {} not found", s))
-                                .with_pos(Some(pos)),
-                        )
-                        .with_context(&location)
-                        .into());
-                    }
+                Err(e) => errors.push(e),
+            }
+        }
+
+        if errors.len() > 0 {
+            return Err(CompileErrors::concat(errors));
+        }
+
+        Ok(Prog {
+            imports,
+            functions: destination.clone().functions,
+            constants: destination.clone().constants,
+            imported_functions,
+            imported_constants,
+        })
+    }
+
+    fn apply_import<T: Field, S: BufRead, E: Into<Error>>(
+        &self,
+        import: &ImportNode,
+        location: &Option<String>,
+        resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
+    ) -> Result<CompiledImport<T>, CompileErrors> {
+        let pos = import.pos();
+        let import = &import.value;
+        let import_error = |e: Error| -> CompileErrors {
+            CompileErrorInner::ImportError(e.with_pos(Some(pos)))
+                .with_context(location)
+                .into()
+        };
+        // handle the case of special bellman and packing imports
+        if import.source.starts_with("BELLMAN") {
+            match import.source.as_ref() {
+                "BELLMAN/sha256round" => {
+                    use crate::standard::sha_round;
+
+                    let compiled = FlatProg {
+                        functions: vec![sha_round()],
+                        names: SourceNames::default(),
+                    };
+
+                    let alias = match import.alias {
+                        Some(ref alias) => alias.clone(),
+                        None => String::from("sha256round"),
+                    };
+
+                    CompiledImport::new(compiled, alias).map_err(import_error)
                 }
-            } else {
-                // to resolve imports, we need a resolver
-                match resolve_option {
-                    Some(resolve) => match resolve(&location, &import.source) {
-                        Ok((mut reader, location, auto_alias)) => {
-                            let compiled = compile_aux(&mut reader, Some(location), resolve_option)
-                                .map_err(|e| e.with_context(Some(import.source.clone())))?;
-                            let alias = match import.alias {
-                                Some(ref alias) => alias.clone(),
-                                None => auto_alias,
-                            };
-                            origins.push(CompiledImport::new(compiled, alias));
-                        }
-                        Err(err) => {
-                            return Err(CompileErrorInner::ImportError(
-                                err.into().with_pos(Some(pos)),
-                            )
-                            .with_context(&location)
-                            .into());
+                s => {
+                    return Err(CompileErrorInner::ImportError(
+                        Error::new(format!("Gadget {} not found", s)).with_pos(Some(pos)),
+                    )
+                    .with_context(&location)
+                    .into());
+                }
+            }
+        } else if import.source.starts_with("PACKING") {
+            use crate::types::conversions::split;
+
+            match import.source.as_ref() {
+                "PACKING/split" => {
+                    let compiled = split();
+                    let alias = match import.alias {
+                        Some(ref alias) => alias.clone(),
+                        None => String::from("split"),
+                    };
+
+                    CompiledImport::new(compiled, alias).map_err(import_error)
+                }
+                s => {
+                    return Err(CompileErrorInner::ImportError(
+                        Error::new(format!("Packing helper {} not found", s)).with_pos(Some(pos)),
+                    )
+                    .with_context(&location)
+                    .into());
+                }
+            }
+        } else if import.source.starts_with("R1CS/") {
+            // the constraint system is resolved like any other import, and comes with a witness generator
+            let path = import.source["R1CS/".len()..].to_string();
+
+            let resolve = match resolve_option {
+                Some(resolve) => resolve,
+                None => {
+                    return Err(CompileErrorInner::from(Error::new(
+                        "Can't resolve import without a resolver",
+                    ))
+                    .with_context(&location)
+                    .into());
+                }
+            };
+
+            let (compiled, auto_alias) =
+                import_r1cs(resolve, &location, &path).map_err(import_error)?;
+
+            let alias = match import.alias {
+                Some(ref alias) => alias.clone(),
+                None => auto_alias,
+            };
+
+            CompiledImport::new(compiled, alias).map_err(import_error)
+        } else {
+            // to resolve imports, we need a resolver
+            match resolve_option {
+                Some(resolve) => match resolve(&location, &import.source) {
+                    Ok((mut reader, import_location, auto_alias)) => {
+                        let import_location = Some(import_location);
+                        let (compiled, constants) = compile_imported_module(
+                            &mut reader,
+                            import_location.clone(),
+                            resolve_option,
+                        )
+                        .map_err(|e| e.imported_as(&import.source, &import_location))?;
+
+                        match import.symbols.len() {
+                            0 => {
+                                let alias = match import.alias {
+                                    Some(ref alias) => alias.clone(),
+                                    None => auto_alias,
+                                };
+                                CompiledImport::new(compiled, alias).map_err(import_error)
+                            }
+                            // constants imported by name are stored under the name they are imported as
+                            _ => import
+                                .symbols
+                                .iter()
+                                .map(|(id, local)| {
+                                    constants
+                                        .iter()
+                                        .find(|(c, _)| c == id)
+                                        .map(|(_, value)| (local.clone(), value.clone()))
+                                        .ok_or_else(|| {
+                                            import_error(Error::new(format!(
+                                                "Constant {} not found in {}",
+                                                id, import.source
+                                            )))
+                                        })
+                                })
+                                .collect::<Result<Vec<_>, _>>()
+                                .map(CompiledImport::constants),
                         }
-                    },
-                    None => {
-                        return Err(CompileErrorInner::from(Error::new(
-                            "Can't resolve import without a resolver",
-                        ))
-                        .with_context(&location)
-                        .into());
                     }
+                    Err(err) => {
+                        return Err(
+                            CompileErrorInner::ImportError(err.into().with_pos(Some(pos)))
+                                .with_context(&location)
+                                .into(),
+                        );
+                    }
+                },
+                None => {
+                    return Err(CompileErrorInner::from(Error::new(
+                        "Can't resolve import without a resolver",
+                    ))
+                    .with_context(&location)
+                    .into());
                 }
             }
         }
+    }
+}
 
-        Ok(Prog {
-            imports: vec![],
-            functions: destination.clone().functions,
-            imported_functions: origins.into_iter().map(|o| o.flat_func).collect(),
-        })
+// read the `.r1cs` file at `path` and its witness generator, the `.wasm` file next to it
+#[cfg(feature = "wasm")]
+fn import_r1cs<T: Field, S: BufRead, E: Into<Error>>(
+    resolve: fn(&Option<String>, &String) -> Result<(S, String, String), E>,
+    location: &Option<String>,
+    path: &String,
+) -> Result<(FlatProg<T>, String), Error> {
+    use crate::helpers::{Helper, Signed, WasmHelper};
+    use crate::ir::R1cs;
+    use crate::standard::r1cs_gadget;
+    use std::io::Read;
+    use std::path::Path;
+
+    let (reader, _, alias) = resolve(location, path).map_err(|e| -> Error { e.into() })?;
+    let r1cs = R1cs::read_binary(reader)
+        .map_err(|why| Error::new(format!("Invalid constraint system {}: {}", path, why)))?;
+
+    let wasm_path = Path::new(path)
+        .with_extension("wasm")
+        .to_string_lossy()
+        .to_string();
+    let (mut reader, _, _) = resolve(location, &wasm_path).map_err(|e| -> Error { e.into() })?;
+    let mut code = vec![];
+    reader.read_to_end(&mut code)?;
+
+    let helper = WasmHelper::try_from_bytes(code)
+        .and_then(|helper| helper.validate().map(|_| helper))
+        .map_err(|why| Error::new(format!("Invalid witness generator {}: {}", wasm_path, why)))?;
+
+    let expected = (r1cs.public_inputs + r1cs.private_inputs, r1cs.wires.len());
+    if helper.get_signature() != expected {
+        return Err(Error::new(format!(
+            "Witness generator {} should take {} inputs and return {} values, found {} and {}",
+            wasm_path,
+            expected.0,
+            expected.1,
+            helper.get_signature().0,
+            helper.get_signature().1
+        )));
     }
+
+    let compiled = FlatProg {
+        functions: vec![r1cs_gadget(r1cs, Helper::Wasm(helper))],
+        names: SourceNames::default(),
+    };
+
+    Ok((compiled, alias))
+}
+
+#[cfg(not(feature = "wasm"))]
+fn import_r1cs<T: Field, S: BufRead, E: Into<Error>>(
+    _: fn(&Option<String>, &String) -> Result<(S, String, String), E>,
+    _: &Option<String>,
+    path: &String,
+) -> Result<(FlatProg<T>, String), Error> {
+    Err(Error::new(format!(
+        "Cannot import {}: importing R1CS circuits requires the `wasm` feature",
+        path
+    )))
 }
 
 #[cfg(test)]
@@ -247,6 +455,7 @@ mod tests {
             Import {
                 source: String::from("./foo/bar/baz.code"),
                 alias: None,
+                symbols: vec![],
             }
         );
     }
@@ -258,6 +467,7 @@ mod tests {
             Import {
                 source: String::from("./foo/bar/baz.code"),
                 alias: Some("myalias".to_string()),
+                symbols: vec![],
             }
         );
     }
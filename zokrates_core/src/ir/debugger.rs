@@ -0,0 +1,179 @@
+use crate::flat_absy::{FlatVariable, SourceName};
+use crate::ir::{Error, Prog, Statement, Witness};
+use crate::parser::Position;
+use std::collections::{BTreeMap, BTreeSet};
+use zokrates_field::field::Field;
+
+/// What happened when the debugger executed a statement
+#[derive(Debug, PartialEq)]
+pub enum Step<'a, T: Field> {
+    /// The execution reached the statement found at this location in the source
+    Location(Position),
+    /// The statement was executed
+    Executed(&'a Statement<T>),
+    /// The statement could not be executed, which ends the execution
+    Failed(&'a Statement<T>, Error),
+}
+
+/// Executes a program one statement at a time, keeping track of the location in the source of the
+/// statement being executed. Locations are only found in programs compiled with `debug` set.
+pub struct Debugger<'a, T: Field> {
+    program: &'a Prog<T>,
+    witness: BTreeMap<FlatVariable, T>,
+    // the index of the next statement to execute
+    next: usize,
+    location: Option<Position>,
+}
+
+impl<'a, T: Field> Debugger<'a, T> {
+    pub fn new<U: Into<T> + Clone>(program: &'a Prog<T>, inputs: &Vec<U>) -> Result<Self, Error> {
+        Ok(Debugger {
+            program,
+            witness: program.initial_witness(inputs)?,
+            next: 0,
+            location: None,
+        })
+    }
+
+    /// Returns the location in the source of the statement being executed
+    pub fn location(&self) -> Option<Position> {
+        self.location
+    }
+
+    /// Executes the next statement, passing the message of a log statement to `log`, or returns
+    /// `None` if the execution is over
+    pub fn step<L: FnMut(String)>(&mut self, mut log: L) -> Option<Step<'a, T>> {
+        let program = self.program;
+        let statement = program.main.statements.get(self.next)?;
+        self.next += 1;
+
+        if let Statement::Directive(ref d) = statement {
+            if let Some(position) = d.helper.location() {
+                self.location = Some(position);
+                return Some(Step::Location(position));
+            }
+        }
+
+        match statement.execute(&mut self.witness, &mut log) {
+            Ok(()) => Some(Step::Executed(statement)),
+            Err(e) => {
+                self.next = program.main.statements.len();
+                Some(Step::Failed(statement, e))
+            }
+        }
+    }
+
+    /// Returns the variables of `statement` which have a name in the source, along with their value
+    /// if it is known yet
+    pub fn named_values(&self, statement: &Statement<T>) -> Vec<(&'a SourceName, Option<T>)> {
+        let variables: BTreeSet<_> = match statement {
+            Statement::Constraint(quad, lin) => quad
+                .left
+                .0
+                .iter()
+                .chain(quad.right.0.iter())
+                .chain(lin.0.iter())
+                .map(|(v, _)| *v)
+                .collect(),
+            Statement::Directive(d) => d
+                .inputs
+                .iter()
+                .flat_map(|i| i.0.iter().map(|(v, _)| *v))
+                .chain(d.outputs.iter().cloned())
+                .collect(),
+        };
+
+        variables
+            .into_iter()
+            .filter_map(|v| {
+                self.program
+                    .names
+                    .get(&v)
+                    .map(|name| (name, self.witness.get(&v).cloned()))
+            })
+            .collect()
+    }
+
+    /// Returns the values computed so far
+    pub fn witness(&self) -> Witness<T> {
+        Witness(self.witness.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::{compile_with_config, CompileConfig};
+    use std::io::{self, BufReader, Empty};
+    use zokrates_field::field::FieldPrime;
+
+    fn compile(source: &str) -> Prog<FieldPrime> {
+        compile_with_config(
+            &mut source.as_bytes(),
+            None,
+            None::<
+                fn(
+                    &Option<String>,
+                    &String,
+                ) -> Result<(BufReader<Empty>, String, String), io::Error>,
+            >,
+            CompileConfig {
+                debug: true,
+                ..CompileConfig::default()
+            },
+        )
+        .unwrap()
+    }
+
+    const SOURCE: &str = r#"
+def main(field a) -> (field):
+    field b = a * a
+    b == 4
+    return b
+"#;
+
+    #[test]
+    fn run() {
+        let program = compile(SOURCE);
+        let inputs = vec![FieldPrime::from(2)];
+
+        let mut debugger = Debugger::new(&program, &inputs).unwrap();
+        let mut lines = vec![];
+        while let Some(step) = debugger.step(|_| {}) {
+            match step {
+                Step::Location(position) => lines.push(position.line),
+                Step::Executed(_) => {}
+                Step::Failed(..) => panic!("the execution should succeed"),
+            }
+        }
+
+        assert_eq!(lines, vec![3, 4, 5]);
+        assert_eq!(debugger.witness(), program.execute(&inputs).unwrap());
+    }
+
+    #[test]
+    fn stop_at_failing_constraint() {
+        let program = compile(SOURCE);
+
+        let mut debugger = Debugger::new(&program, &vec![FieldPrime::from(3)]).unwrap();
+        let failure = loop {
+            if let Step::Failed(statement, error) = debugger.step(|_| {}).unwrap() {
+                break (statement, error);
+            }
+        };
+
+        assert_eq!(debugger.location().map(|p| p.line), Some(4));
+        match failure.1 {
+            Error::UnsatisfiedConstraint { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        };
+        assert!(debugger
+            .named_values(failure.0)
+            .iter()
+            .any(|(name, value)| name.to_string() == "main::b"
+                && *value == Some(FieldPrime::from(9))));
+
+        // the execution ends at the failing constraint
+        assert_eq!(debugger.step(|_| {}), None);
+    }
+}
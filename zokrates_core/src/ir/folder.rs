@@ -42,6 +42,8 @@ pub fn fold_program<T: Field, F: Folder<T>>(f: &mut F, p: Prog<T>) -> Prog<T> {
     Prog {
         main: f.fold_function(p.main),
         private: p.private,
+        committed: p.committed,
+        names: p.names,
     }
 }
 
@@ -75,7 +75,12 @@ impl<T: Field> From<FlatProg<T>> for Prog<T> {
 
         let main = main.into();
 
-        Prog { private, main }
+        Prog {
+            private,
+            main,
+            committed: vec![],
+            names: flat_prog.names,
+        }
     }
 }
 
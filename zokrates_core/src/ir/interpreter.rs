@@ -9,52 +9,36 @@ pub type ExecutionResult<T> = Result<Witness<T>, Error>;
 
 impl<T: Field> Prog<T> {
     pub fn execute<U: Into<T> + Clone>(&self, inputs: &Vec<U>) -> ExecutionResult<T> {
-        let main = &self.main;
+        self.execute_with_log(inputs, |_| {})
+    }
+
+    /// Executes the program, passing the message of each log statement printed to `log`
+    pub fn execute_with_log<U: Into<T> + Clone, L: FnMut(String)>(
+        &self,
+        inputs: &Vec<U>,
+        mut log: L,
+    ) -> ExecutionResult<T> {
+        let mut witness = self.initial_witness(inputs)?;
+
+        for statement in &self.main.statements {
+            statement.execute(&mut witness, &mut log)?;
+        }
+
+        Ok(Witness(witness))
+    }
+
+    /// Returns the witness before any statement is executed, which holds the inputs of `main`
+    pub(crate) fn initial_witness<U: Into<T> + Clone>(
+        &self,
+        inputs: &Vec<U>,
+    ) -> Result<BTreeMap<FlatVariable, T>, Error> {
         self.check_inputs(&inputs)?;
         let mut witness = BTreeMap::new();
         witness.insert(FlatVariable::one(), T::one());
-        for (arg, value) in main.arguments.iter().zip(inputs.iter()) {
+        for (arg, value) in self.main.arguments.iter().zip(inputs.iter()) {
             witness.insert(arg.clone(), value.clone().into());
         }
-
-        for statement in &main.statements {
-            match statement {
-                Statement::Constraint(quad, lin) => match lin.is_assignee(&witness) {
-                    true => {
-                        let val = quad.evaluate(&witness).unwrap();
-                        witness.insert(lin.0.iter().next().unwrap().0.clone(), val);
-                    }
-                    false => {
-                        let lhs_value = quad.evaluate(&witness).unwrap();
-                        let rhs_value = lin.evaluate(&witness).unwrap();
-                        if lhs_value != rhs_value {
-                            return Err(Error::UnsatisfiedConstraint {
-                                left: lhs_value.to_dec_string(),
-                                right: rhs_value.to_dec_string(),
-                            });
-                        }
-                    }
-                },
-                Statement::Directive(ref d) => {
-                    let input_values: Vec<T> = d
-                        .inputs
-                        .iter()
-                        .map(|i| i.evaluate(&witness).unwrap())
-                        .collect();
-                    match d.helper.execute(&input_values) {
-                        Ok(res) => {
-                            for (i, o) in d.outputs.iter().enumerate() {
-                                witness.insert(o.clone(), res[i].clone());
-                            }
-                            continue;
-                        }
-                        Err(_) => return Err(Error::Solver),
-                    };
-                }
-            }
-        }
-
-        Ok(Witness(witness))
+        Ok(witness)
     }
 
     fn check_inputs<U>(&self, inputs: &Vec<U>) -> Result<(), Error> {
@@ -69,6 +53,55 @@ impl<T: Field> Prog<T> {
     }
 }
 
+impl<T: Field> Statement<T> {
+    /// Executes the statement, adding the values it defines to `witness`
+    pub(crate) fn execute<L: FnMut(String)>(
+        &self,
+        witness: &mut BTreeMap<FlatVariable, T>,
+        log: &mut L,
+    ) -> Result<(), Error> {
+        match self {
+            Statement::Constraint(quad, lin) => match lin.is_assignee(&witness) {
+                true => {
+                    let val = quad.evaluate(&witness).unwrap();
+                    witness.insert(lin.0.iter().next().unwrap().0.clone(), val);
+                    Ok(())
+                }
+                false => {
+                    let lhs_value = quad.evaluate(&witness).unwrap();
+                    let rhs_value = lin.evaluate(&witness).unwrap();
+                    if lhs_value != rhs_value {
+                        return Err(Error::UnsatisfiedConstraint {
+                            left: lhs_value.to_dec_string(),
+                            right: rhs_value.to_dec_string(),
+                        });
+                    }
+                    Ok(())
+                }
+            },
+            Statement::Directive(ref d) => {
+                let input_values: Vec<T> = d
+                    .inputs
+                    .iter()
+                    .map(|i| i.evaluate(&witness).unwrap())
+                    .collect();
+                if let Some(message) = d.helper.log_message(&input_values) {
+                    log(message);
+                }
+                match d.helper.execute(&input_values) {
+                    Ok(res) => {
+                        for (i, o) in d.outputs.iter().enumerate() {
+                            witness.insert(o.clone(), res[i].clone());
+                        }
+                        Ok(())
+                    }
+                    Err(_) => Err(Error::Solver),
+                }
+            }
+        }
+    }
+}
+
 impl<T: Field> LinComb<T> {
     fn evaluate(&self, witness: &BTreeMap<FlatVariable, T>) -> Result<T, ()> {
         self.0
@@ -1,19 +1,24 @@
 use crate::flat_absy::flat_parameter::FlatParameter;
-use crate::flat_absy::FlatVariable;
-use crate::helpers::Helper;
+use crate::flat_absy::{FlatVariable, SourceNames};
+use crate::helpers::{Helper, RustHelper};
 use std::fmt;
+use std::io::Read;
 use zokrates_field::field::Field;
 
+mod debugger;
 mod expression;
 pub mod folder;
 mod from_flat;
 mod interpreter;
+mod r1cs;
 mod witness;
 
 use self::expression::QuadComb;
 pub use self::expression::{CanonicalLinComb, LinComb};
 
+pub use self::debugger::{Debugger, Step};
 pub use self::interpreter::{Error, ExecutionResult};
+pub use self::r1cs::{r1cs_program, R1cs};
 pub use self::witness::Witness;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -106,9 +111,60 @@ impl<T: Field> fmt::Display for Function<T> {
 pub struct Prog<T: Field> {
     pub main: Function<T>,
     pub private: Vec<bool>,
+    /// The variables whose values are committed to by the single output of `main`, if the
+    /// program was compiled with a commitment to its public inputs
+    #[serde(default)]
+    pub committed: Vec<FlatVariable>,
+    /// Names in the source of the variables of `main`
+    #[serde(default)]
+    pub names: SourceNames,
+}
+
+// the layout of the programs compiled before the source names were recorded
+#[derive(Deserialize)]
+struct UnnamedProg<T: Field> {
+    main: Function<T>,
+    private: Vec<bool>,
+    committed: Vec<FlatVariable>,
+}
+
+// the layout of the programs compiled before the commitment and the source names were recorded
+#[derive(Deserialize)]
+struct LegacyProg<T: Field> {
+    main: Function<T>,
+    private: Vec<bool>,
 }
 
 impl<T: Field> Prog<T> {
+    /// Reads a program written by `compile`. Programs compiled before the commitment to the
+    /// public inputs and the source names were recorded are read without them.
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, String> {
+        let mut buffer = vec![];
+        reader
+            .read_to_end(&mut buffer)
+            .map_err(|why| why.to_string())?;
+
+        // bincode does not record field names, so `serde(default)` cannot fill in missing fields
+        bincode::deserialize(&buffer).or_else(|why| {
+            bincode::deserialize::<UnnamedProg<T>>(&buffer)
+                .map(|unnamed| Prog {
+                    main: unnamed.main,
+                    private: unnamed.private,
+                    committed: unnamed.committed,
+                    names: SourceNames::default(),
+                })
+                .or_else(|_| {
+                    bincode::deserialize::<LegacyProg<T>>(&buffer).map(|legacy| Prog {
+                        main: legacy.main,
+                        private: legacy.private,
+                        committed: vec![],
+                        names: SourceNames::default(),
+                    })
+                })
+                .map_err(|_| why.to_string())
+        })
+    }
+
     pub fn constraint_count(&self) -> usize {
         self.main
             .statements
@@ -128,6 +184,38 @@ impl<T: Field> Prog<T> {
         self.private.iter().filter(|b| **b).count()
     }
 
+    /// Returns the values committed to by the program, which are empty unless it was compiled with a commitment
+    pub fn committed_values(&self, witness: &Witness<T>) -> Vec<T> {
+        self.committed
+            .iter()
+            .map(|v| witness.0.get(v).unwrap().clone())
+            .collect()
+    }
+
+    /// Removes the log statements of the program, which do not take part in its constraints
+    pub fn strip_logs(self) -> Self {
+        let statements = self
+            .main
+            .statements
+            .into_iter()
+            .filter(|s| match s {
+                Statement::Directive(Directive {
+                    helper: Helper::Rust(RustHelper::Log(..)),
+                    ..
+                }) => false,
+                _ => true,
+            })
+            .collect();
+
+        Prog {
+            main: Function {
+                statements,
+                ..self.main
+            },
+            ..self
+        }
+    }
+
     pub fn parameters(&self) -> Vec<FlatParameter> {
         self.main
             .arguments
@@ -167,4 +255,57 @@ mod tests {
             assert_eq!(format!("{}", c), "(1 * _42) * (1 * _42) == 1 * _42")
         }
     }
+
+    mod prog {
+        use super::*;
+        use bincode::Infinite;
+
+        #[test]
+        fn read_legacy() {
+            let main = Function {
+                id: String::from("main"),
+                statements: vec![Statement::definition(
+                    FlatVariable::public(0),
+                    FlatVariable::new(0),
+                )],
+                arguments: vec![FlatVariable::new(0)],
+                returns: vec![FlatVariable::public(0)],
+            };
+            let private = vec![true];
+
+            // programs compiled before `committed` and `names` were added only have these fields
+            let legacy = bincode::serialize(&(main.clone(), private.clone()), Infinite).unwrap();
+            let prog = Prog::<FieldPrime>::read(&legacy[..]).unwrap();
+            assert_eq!(prog.main, main);
+            assert_eq!(prog.private, private);
+            assert!(prog.committed.is_empty());
+
+            let current = bincode::serialize(
+                &Prog {
+                    committed: vec![FlatVariable::new(0)],
+                    ..prog
+                },
+                Infinite,
+            )
+            .unwrap();
+            let prog = Prog::<FieldPrime>::read(&current[..]).unwrap();
+            assert_eq!(prog.committed, vec![FlatVariable::new(0)]);
+
+            // programs compiled before `names` was added
+            let unnamed = bincode::serialize(
+                &(
+                    prog.main.clone(),
+                    prog.private.clone(),
+                    prog.committed.clone(),
+                ),
+                Infinite,
+            )
+            .unwrap();
+            let prog = Prog::<FieldPrime>::read(&unnamed[..]).unwrap();
+            assert_eq!(prog.committed, vec![FlatVariable::new(0)]);
+            assert_eq!(prog.names, SourceNames::default());
+
+            assert!(Prog::<FieldPrime>::read(&legacy[..4]).is_err());
+        }
+    }
 }
@@ -0,0 +1,594 @@
+//! Rank-1 constraint system representation of programs.
+//!
+//! Besides the raw matrices used by the libsnark backends, programs can be exported in the
+//! binary `.r1cs` and `.wtns` formats of the circom ecosystem, and as JSON.
+
+use crate::flat_absy::FlatVariable;
+use crate::ir::{Prog, Statement, Witness};
+use num_bigint::BigUint;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Read, Write};
+use zokrates_field::field::Field;
+
+/// Returns the index of `var` in `variables`, adding `var` with incremented index if it not yet exists.
+///
+/// # Arguments
+///
+/// * `variables` - A mutual map that maps all existing variables to their index.
+/// * `var` - Variable to be searched for.
+pub fn provide_variable_idx(
+    variables: &mut HashMap<FlatVariable, usize>,
+    var: &FlatVariable,
+) -> usize {
+    let index = variables.len();
+    *variables.entry(*var).or_insert(index)
+}
+
+/// Calculates one R1CS row representation of a program and returns (V, A, B, C) so that:
+/// * `V` contains all used variables and the index in the vector represents the used number in `A`, `B`, `C`
+/// * `<A,x>*<B,x> = <C,x>` for a witness `x`
+///
+/// # Arguments
+///
+/// * `prog` - The program the representation is calculated for.
+pub fn r1cs_program<T: Field>(
+    prog: Prog<T>,
+) -> (
+    Vec<FlatVariable>,
+    usize,
+    Vec<Vec<(usize, T)>>,
+    Vec<Vec<(usize, T)>>,
+    Vec<Vec<(usize, T)>>,
+) {
+    let mut variables: HashMap<FlatVariable, usize> = HashMap::new();
+    provide_variable_idx(&mut variables, &FlatVariable::one());
+
+    for x in prog
+        .main
+        .arguments
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !prog.private[*index])
+    {
+        provide_variable_idx(&mut variables, &x.1);
+    }
+
+    //Only the main function is relevant in this step, since all calls to other functions were resolved during flattening
+    let main = prog.main;
+
+    //~out are added after main's arguments as we want variables (columns)
+    //in the r1cs to be aligned like "public inputs | private inputs"
+    let main_return_count = main.returns.len();
+
+    for i in 0..main_return_count {
+        provide_variable_idx(&mut variables, &FlatVariable::public(i));
+    }
+
+    // position where private part of witness starts
+    let private_inputs_offset = variables.len();
+
+    // first pass through statements to populate `variables`
+    for (quad, lin) in main.statements.iter().filter_map(|s| match s {
+        Statement::Constraint(quad, lin) => Some((quad, lin)),
+        Statement::Directive(..) => None,
+    }) {
+        for (k, _) in &quad.left.0 {
+            provide_variable_idx(&mut variables, &k);
+        }
+        for (k, _) in &quad.right.0 {
+            provide_variable_idx(&mut variables, &k);
+        }
+        for (k, _) in &lin.0 {
+            provide_variable_idx(&mut variables, &k);
+        }
+    }
+
+    let mut a = vec![];
+    let mut b = vec![];
+    let mut c = vec![];
+
+    // second pass to convert program to raw sparse vectors
+    for (quad, lin) in main.statements.into_iter().filter_map(|s| match s {
+        Statement::Constraint(quad, lin) => Some((quad, lin)),
+        Statement::Directive(..) => None,
+    }) {
+        a.push(
+            quad.left
+                .0
+                .into_iter()
+                .map(|(k, v)| (variables.get(&k).unwrap().clone(), v))
+                .collect(),
+        );
+        b.push(
+            quad.right
+                .0
+                .into_iter()
+                .map(|(k, v)| (variables.get(&k).unwrap().clone(), v))
+                .collect(),
+        );
+        c.push(
+            lin.0
+                .into_iter()
+                .map(|(k, v)| (variables.get(&k).unwrap().clone(), v))
+                .collect(),
+        );
+    }
+
+    // Convert map back into list ordered by index
+    let mut variables_list = vec![FlatVariable::new(0); variables.len()];
+    for (k, v) in variables.drain() {
+        assert_eq!(variables_list[v], FlatVariable::new(0));
+        std::mem::replace(&mut variables_list[v], k);
+    }
+    (variables_list, private_inputs_offset, a, b, c)
+}
+
+type LinearCombination<T> = Vec<(usize, T)>;
+
+/// A constraint system with wires laid out as `~one | outputs | public inputs | private inputs | internal`,
+/// which is the layout expected by circom-based tools.
+#[derive(Debug, Clone, PartialEq)]
+pub struct R1cs<T: Field> {
+    pub wires: Vec<FlatVariable>,
+    pub public_outputs: usize,
+    pub public_inputs: usize,
+    pub private_inputs: usize,
+    pub constraints: Vec<(
+        LinearCombination<T>,
+        LinearCombination<T>,
+        LinearCombination<T>,
+    )>,
+}
+
+impl<T: Field> From<Prog<T>> for R1cs<T> {
+    fn from(prog: Prog<T>) -> R1cs<T> {
+        let outputs: Vec<_> = (0..prog.main.returns.len())
+            .map(FlatVariable::public)
+            .collect();
+        let (public_arguments, private_arguments): (Vec<_>, Vec<_>) = prog
+            .main
+            .arguments
+            .iter()
+            .zip(prog.private.iter())
+            .partition(|(_, private)| !**private);
+
+        let mut wires = vec![FlatVariable::one()];
+        wires.extend(outputs.iter().cloned());
+        wires.extend(public_arguments.iter().map(|(v, _)| **v));
+        wires.extend(private_arguments.iter().map(|(v, _)| **v));
+
+        let (variables, _, a, b, c) = r1cs_program(prog);
+
+        // the remaining variables are internal and keep their relative order
+        let mut indices: HashMap<FlatVariable, usize> = HashMap::new();
+        for v in wires.iter() {
+            provide_variable_idx(&mut indices, v);
+        }
+        for v in variables.iter() {
+            if !indices.contains_key(v) {
+                wires.push(*v);
+                provide_variable_idx(&mut indices, v);
+            }
+        }
+
+        let remap = |lc: Vec<(usize, T)>| -> LinearCombination<T> {
+            lc.into_iter()
+                .map(|(i, value)| (indices[&variables[i]], value))
+                .collect()
+        };
+
+        let constraints = a
+            .into_iter()
+            .zip(b.into_iter())
+            .zip(c.into_iter())
+            .map(|((a, b), c)| (remap(a), remap(b), remap(c)))
+            .collect();
+
+        R1cs {
+            wires,
+            public_outputs: outputs.len(),
+            public_inputs: public_arguments.len(),
+            private_inputs: private_arguments.len(),
+            constraints,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct R1csJson {
+    n8: usize,
+    prime: String,
+    #[serde(rename = "nVars")]
+    n_vars: usize,
+    #[serde(rename = "nOutputs")]
+    n_outputs: usize,
+    #[serde(rename = "nPubInputs")]
+    n_pub_inputs: usize,
+    #[serde(rename = "nPrvInputs")]
+    n_prv_inputs: usize,
+    #[serde(rename = "nLabels")]
+    n_labels: usize,
+    #[serde(rename = "nConstraints")]
+    n_constraints: usize,
+    constraints: Vec<[BTreeMap<usize, String>; 3]>,
+    map: Vec<usize>,
+    labels: Vec<String>,
+}
+
+const R1CS_MAGIC: &[u8; 4] = b"r1cs";
+const R1CS_VERSION: u32 = 1;
+const WTNS_MAGIC: &[u8; 4] = b"wtns";
+const WTNS_VERSION: u32 = 2;
+
+// size in bytes of a field element, rounded up to 64 bit words as required by the formats
+fn field_size<T: Field>() -> usize {
+    (T::get_required_bits() + 63) / 64 * 8
+}
+
+fn field_to_bytes<T: Field>(value: &T) -> Vec<u8> {
+    let mut bytes = value.into_byte_vector();
+    bytes.resize(field_size::<T>(), 0);
+    bytes
+}
+
+// the modulus is one more than the maximum value, which is even, so there is no carry
+fn prime_bytes<T: Field>() -> Vec<u8> {
+    let mut bytes = field_to_bytes(&T::max_value());
+    bytes[0] += 1;
+    bytes
+}
+
+fn write_section<W: Write>(writer: &mut W, section_type: u32, content: &[u8]) -> io::Result<()> {
+    writer.write_all(&section_type.to_le_bytes())?;
+    writer.write_all(&(content.len() as u64).to_le_bytes())?;
+    writer.write_all(content)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn invalid_data<E: Into<String>>(message: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+fn read_linear_combination<T: Field, R: Read>(
+    reader: &mut R,
+    n8: usize,
+) -> io::Result<LinearCombination<T>> {
+    let count = read_u32(reader)?;
+    (0..count)
+        .map(|_| {
+            let wire = read_u32(reader)? as usize;
+            let mut value = vec![0u8; n8];
+            reader.read_exact(&mut value)?;
+            Ok((wire, T::from_byte_vector(value)))
+        })
+        .collect()
+}
+
+impl<T: Field> R1cs<T> {
+    /// Reads a constraint system in the binary `.r1cs` format. Wire `i` is represented by `FlatVariable::new(i)`.
+    pub fn read_binary<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != R1CS_MAGIC {
+            return Err(invalid_data("Not an r1cs file"));
+        }
+
+        let version = read_u32(&mut reader)?;
+        if version != R1CS_VERSION {
+            return Err(invalid_data(format!(
+                "Unsupported r1cs version {}",
+                version
+            )));
+        }
+
+        // sections can appear in any order, but constraints can only be decoded once the header is known
+        let section_count = read_u32(&mut reader)?;
+        let mut sections = HashMap::new();
+        for _ in 0..section_count {
+            let section_type = read_u32(&mut reader)?;
+            let size = read_u64(&mut reader)?;
+            // the size is not trusted to allocate the section, which is only as large as its content
+            let mut content = vec![];
+            reader.by_ref().take(size).read_to_end(&mut content)?;
+            if content.len() as u64 != size {
+                return Err(invalid_data(format!(
+                    "Section of type {} is truncated",
+                    section_type
+                )));
+            }
+            sections.insert(section_type, content);
+        }
+
+        let header = sections
+            .get(&1)
+            .ok_or_else(|| invalid_data("Missing header section"))?;
+        let mut header = &header[..];
+
+        let n8 = read_u32(&mut header)? as usize;
+        let mut prime = vec![0u8; n8];
+        header.read_exact(&mut prime)?;
+        if n8 != field_size::<T>() || prime != prime_bytes::<T>() {
+            return Err(invalid_data(
+                "The constraint system is defined over a different field",
+            ));
+        }
+
+        let wire_count = read_u32(&mut header)? as usize;
+        let public_outputs = read_u32(&mut header)? as usize;
+        let public_inputs = read_u32(&mut header)? as usize;
+        let private_inputs = read_u32(&mut header)? as usize;
+        let _label_count = read_u64(&mut header)?;
+        let constraint_count = read_u32(&mut header)?;
+
+        if 1 + public_outputs + public_inputs + private_inputs > wire_count {
+            return Err(invalid_data("More inputs and outputs than wires"));
+        }
+
+        let constraints = sections
+            .get(&2)
+            .ok_or_else(|| invalid_data("Missing constraints section"))?;
+        let mut constraints = &constraints[..];
+
+        let constraints = (0..constraint_count)
+            .map(|_| {
+                Ok((
+                    read_linear_combination(&mut constraints, n8)?,
+                    read_linear_combination(&mut constraints, n8)?,
+                    read_linear_combination(&mut constraints, n8)?,
+                ))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        if constraints
+            .iter()
+            .flat_map(|(a, b, c)| a.iter().chain(b.iter()).chain(c.iter()))
+            .any(|(wire, _)| *wire >= wire_count)
+        {
+            return Err(invalid_data("Constraint refers to an unknown wire"));
+        }
+
+        Ok(R1cs {
+            wires: (0..wire_count).map(FlatVariable::new).collect(),
+            public_outputs,
+            public_inputs,
+            private_inputs,
+            constraints,
+        })
+    }
+
+    /// Writes the constraint system in the binary `.r1cs` format
+    pub fn write_binary<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let n8 = field_size::<T>();
+
+        let mut header = vec![];
+        header.extend_from_slice(&(n8 as u32).to_le_bytes());
+        header.extend_from_slice(&prime_bytes::<T>());
+        header.extend_from_slice(&(self.wires.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(self.public_outputs as u32).to_le_bytes());
+        header.extend_from_slice(&(self.public_inputs as u32).to_le_bytes());
+        header.extend_from_slice(&(self.private_inputs as u32).to_le_bytes());
+        header.extend_from_slice(&(self.wires.len() as u64).to_le_bytes());
+        header.extend_from_slice(&(self.constraints.len() as u32).to_le_bytes());
+
+        let mut constraints = vec![];
+        for (a, b, c) in &self.constraints {
+            for lc in &[a, b, c] {
+                constraints.extend_from_slice(&(lc.len() as u32).to_le_bytes());
+                for (wire, value) in lc.iter() {
+                    constraints.extend_from_slice(&(*wire as u32).to_le_bytes());
+                    constraints.extend_from_slice(&field_to_bytes(value));
+                }
+            }
+        }
+
+        // each wire is its own label
+        let mut map = vec![];
+        for wire in 0..self.wires.len() {
+            map.extend_from_slice(&(wire as u64).to_le_bytes());
+        }
+
+        writer.write_all(R1CS_MAGIC)?;
+        writer.write_all(&R1CS_VERSION.to_le_bytes())?;
+        writer.write_all(&3u32.to_le_bytes())?;
+        write_section(&mut writer, 1, &header)?;
+        write_section(&mut writer, 2, &constraints)?;
+        write_section(&mut writer, 3, &map)
+    }
+
+    /// Writes the constraint system as JSON, in the layout of `snarkjs r1cs export json`,
+    /// with the name of each wire in `labels`
+    pub fn write_json<W: Write>(&self, writer: W) -> io::Result<()> {
+        let lc_json = |lc: &LinearCombination<T>| -> BTreeMap<usize, String> {
+            lc.iter().map(|(w, v)| (*w, v.to_dec_string())).collect()
+        };
+
+        let json = R1csJson {
+            n8: field_size::<T>(),
+            prime: BigUint::from_bytes_le(&prime_bytes::<T>()).to_str_radix(10),
+            n_vars: self.wires.len(),
+            n_outputs: self.public_outputs,
+            n_pub_inputs: self.public_inputs,
+            n_prv_inputs: self.private_inputs,
+            n_labels: self.wires.len(),
+            n_constraints: self.constraints.len(),
+            constraints: self
+                .constraints
+                .iter()
+                .map(|(a, b, c)| [lc_json(a), lc_json(b), lc_json(c)])
+                .collect(),
+            map: (0..self.wires.len()).collect(),
+            labels: self.wires.iter().map(|w| w.to_string()).collect(),
+        };
+
+        serde_json::to_writer_pretty(writer, &json)
+            .map_err(|why| io::Error::new(io::ErrorKind::Other, why))
+    }
+
+    /// Writes the values of `witness` for each wire in the binary `.wtns` format
+    pub fn write_witness<W: Write>(&self, witness: &Witness<T>, mut writer: W) -> io::Result<()> {
+        let n8 = field_size::<T>();
+
+        let mut header = vec![];
+        header.extend_from_slice(&(n8 as u32).to_le_bytes());
+        header.extend_from_slice(&prime_bytes::<T>());
+        header.extend_from_slice(&(self.wires.len() as u32).to_le_bytes());
+
+        let mut values = vec![];
+        for wire in &self.wires {
+            let value = witness.0.get(wire).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("Witness has no value for {}", wire),
+                )
+            })?;
+            values.extend_from_slice(&field_to_bytes(value));
+        }
+
+        writer.write_all(WTNS_MAGIC)?;
+        writer.write_all(&WTNS_VERSION.to_le_bytes())?;
+        writer.write_all(&2u32.to_le_bytes())?;
+        write_section(&mut writer, 1, &header)?;
+        write_section(&mut writer, 2, &values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flat_absy::SourceNames;
+    use crate::ir::{Function, QuadComb};
+    use zokrates_field::field::FieldPrime;
+
+    // main(private a, b) -> a * b
+    fn program() -> Prog<FieldPrime> {
+        Prog {
+            main: Function {
+                id: String::from("main"),
+                arguments: vec![FlatVariable::new(0), FlatVariable::new(1)],
+                returns: vec![FlatVariable::public(0)],
+                statements: vec![Statement::constraint(
+                    QuadComb::from_linear_combinations(
+                        FlatVariable::new(0).into(),
+                        FlatVariable::new(1).into(),
+                    ),
+                    FlatVariable::public(0),
+                )],
+            },
+            private: vec![true, false],
+            committed: vec![],
+            names: SourceNames::default(),
+        }
+    }
+
+    #[test]
+    fn layout() {
+        let r1cs = R1cs::from(program());
+
+        assert_eq!(
+            r1cs.wires,
+            vec![
+                FlatVariable::one(),
+                FlatVariable::public(0),
+                FlatVariable::new(1),
+                FlatVariable::new(0)
+            ]
+        );
+        assert_eq!(r1cs.public_outputs, 1);
+        assert_eq!(r1cs.public_inputs, 1);
+        assert_eq!(r1cs.private_inputs, 1);
+        assert_eq!(
+            r1cs.constraints,
+            vec![(
+                vec![(3, FieldPrime::from(1))],
+                vec![(2, FieldPrime::from(1))],
+                vec![(1, FieldPrime::from(1))]
+            )]
+        );
+    }
+
+    #[test]
+    fn binary() {
+        let r1cs = R1cs::from(program());
+
+        let mut buffer = vec![];
+        r1cs.write_binary(&mut buffer).unwrap();
+
+        assert_eq!(&buffer[0..4], b"r1cs");
+        assert_eq!(&buffer[4..8], &1u32.to_le_bytes());
+        assert_eq!(&buffer[8..12], &3u32.to_le_bytes());
+        // header section of 32 + 32 bytes
+        assert_eq!(&buffer[12..16], &1u32.to_le_bytes());
+        assert_eq!(&buffer[16..24], &64u64.to_le_bytes());
+        // the modulus is written right after the field size
+        assert_eq!(
+            BigUint::from_bytes_le(&buffer[28..60]).to_str_radix(10),
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617"
+        );
+    }
+
+    #[test]
+    fn binary_roundtrip() {
+        let r1cs = R1cs::from(program());
+
+        let mut buffer = vec![];
+        r1cs.write_binary(&mut buffer).unwrap();
+
+        let read = R1cs::<FieldPrime>::read_binary(&buffer[..]).unwrap();
+
+        assert_eq!(read.wires.len(), 4);
+        assert_eq!(read.public_outputs, r1cs.public_outputs);
+        assert_eq!(read.public_inputs, r1cs.public_inputs);
+        assert_eq!(read.private_inputs, r1cs.private_inputs);
+        assert_eq!(read.constraints, r1cs.constraints);
+    }
+
+    #[test]
+    fn read_invalid() {
+        assert!(R1cs::<FieldPrime>::read_binary(&b"wtns"[..]).is_err());
+
+        // a section claiming more bytes than the file contains is not allocated
+        let mut buffer = vec![];
+        buffer.extend_from_slice(b"r1cs");
+        buffer.extend_from_slice(&1u32.to_le_bytes());
+        buffer.extend_from_slice(&1u32.to_le_bytes());
+        buffer.extend_from_slice(&1u32.to_le_bytes());
+        buffer.extend_from_slice(&std::u64::MAX.to_le_bytes());
+        buffer.extend_from_slice(&[0u8; 8]);
+
+        let error = R1cs::<FieldPrime>::read_binary(&buffer[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "Section of type 1 is truncated");
+    }
+
+    #[test]
+    fn witness() {
+        let prog = program();
+        let witness = prog
+            .clone()
+            .execute(&vec![FieldPrime::from(3), FieldPrime::from(4)])
+            .unwrap();
+        let r1cs = R1cs::from(prog);
+
+        let mut buffer = vec![];
+        r1cs.write_witness(&witness, &mut buffer).unwrap();
+
+        // magic, version, section count, two section headers, field size, modulus and wire count
+        let values = &buffer[4 + 4 + 4 + 12 + 4 + 32 + 4 + 12..];
+        assert_eq!(values.len(), 4 * 32);
+        assert_eq!(values[0], 1);
+        assert_eq!(values[32], 12);
+        assert_eq!(values[64], 4);
+        assert_eq!(values[96], 3);
+    }
+}
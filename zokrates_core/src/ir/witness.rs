@@ -1,4 +1,4 @@
-use crate::flat_absy::FlatVariable;
+use crate::flat_absy::{FlatVariable, SourceNames};
 use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::io;
@@ -33,6 +33,19 @@ impl<T: Field> Witness<T> {
             .join("\n")
     }
 
+    /// Returns every variable of the witness with its value, followed by its name in the source
+    /// if it has one
+    pub fn format_with_names(&self, names: &SourceNames) -> String {
+        self.0
+            .iter()
+            .map(|(variable, value)| match names.get(variable) {
+                Some(name) => format!("{} {} {}", variable, value, name),
+                None => format!("{} {}", variable, value),
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
     pub fn empty() -> Self {
         Witness(BTreeMap::new())
     }
@@ -87,6 +100,34 @@ impl<T: Field> Witness<T> {
 
         Ok(Witness(map))
     }
+
+    /// Reads a witness from a JSON object mapping variables to decimal values,
+    /// for example `{"~one": "1", "_0": "42", "~out_0": "42"}`
+    pub fn read_json<R: Read>(reader: R) -> io::Result<Self> {
+        let values: BTreeMap<String, String> = serde_json::from_reader(reader)
+            .map_err(|why| io::Error::new(io::ErrorKind::Other, format!("{}", why)))?;
+
+        let map = values
+            .into_iter()
+            .map(|(variable, value)| {
+                let variable = FlatVariable::try_from_human_readable(&variable).map_err(|why| {
+                    io::Error::new(
+                        io::ErrorKind::Other,
+                        format!("Invalid variable in witness: {}", why),
+                    )
+                })?;
+                let value = T::try_from_dec_str(&value).map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::Other,
+                        format!("Invalid value in witness: {}", value),
+                    )
+                })?;
+                Ok((variable, value))
+            })
+            .collect::<io::Result<BTreeMap<FlatVariable, T>>>()?;
+
+        Ok(Witness(map))
+    }
 }
 
 impl<T: Field> fmt::Display for Witness<T> {
@@ -154,6 +195,33 @@ mod tests {
             assert!(Witness::<FieldPrime>::read(buff).is_err());
         }
 
+        #[test]
+        fn read_json() {
+            let buff = Cursor::new(r#"{"~one": "1", "_42": "42", "~out_8": "8"}"#);
+
+            let w = Witness::read_json(buff).unwrap();
+
+            assert_eq!(
+                w,
+                Witness(
+                    vec![
+                        (FlatVariable::new(42), FieldPrime::from(42)),
+                        (FlatVariable::public(8), FieldPrime::from(8)),
+                        (FlatVariable::one(), FieldPrime::from(1)),
+                    ]
+                    .into_iter()
+                    .collect()
+                )
+            );
+        }
+
+        #[test]
+        fn read_json_wrong_variable() {
+            let buff = Cursor::new(r#"{"_1bug": "1"}"#);
+
+            assert!(Witness::<FieldPrime>::read_json(buff).is_err());
+        }
+
         #[test]
         fn not_csv() {
             let mut buff = Cursor::new(vec![]);
@@ -163,4 +231,36 @@ mod tests {
             assert!(Witness::<FieldPrime>::read(buff).is_err());
         }
     }
+
+    #[test]
+    fn format_with_names() {
+        use crate::flat_absy::SourceName;
+
+        let w = Witness(
+            vec![
+                (FlatVariable::new(0), FieldPrime::from(3)),
+                (FlatVariable::new(1), FieldPrime::from(9)),
+                (FlatVariable::public(0), FieldPrime::from(9)),
+                (FlatVariable::one(), FieldPrime::from(1)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let mut names = SourceNames::default();
+        names.insert(
+            FlatVariable::new(0),
+            SourceName {
+                function: String::from("main"),
+                variable: String::from("a"),
+                index: None,
+                version: 0,
+            },
+        );
+
+        assert_eq!(
+            w.format_with_names(&names),
+            "~out_0 9\n~one 1\n_0 3 main::a\n_1 9"
+        );
+    }
 }
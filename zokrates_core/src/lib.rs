@@ -9,16 +9,23 @@ extern crate serde_json;
 extern crate serde_derive;
 extern crate bellman;
 extern crate bincode;
+#[cfg(test)]
+extern crate glob;
 extern crate ff;
 extern crate lazy_static;
 extern crate pairing;
 #[cfg(feature = "wasm")]
 extern crate parity_wasm;
+extern crate rand;
 extern crate regex;
 #[cfg(feature = "wasm")]
 extern crate rustc_hex;
 #[cfg(feature = "wasm")]
 extern crate serde_bytes;
+extern crate sha2;
+#[cfg(test)]
+extern crate tempdir;
+extern crate tiny_keccak;
 #[cfg(feature = "wasm")]
 extern crate wasmi;
 extern crate zokrates_embed;
@@ -37,7 +44,13 @@ mod typed_absy;
 mod types;
 
 pub mod absy;
+pub mod commitment;
 pub mod compile;
+pub mod diagnostics;
+pub mod differential;
 pub mod flat_absy;
+pub mod format;
 pub mod ir;
 pub mod proof_system;
+pub mod symbols;
+pub mod testing;
@@ -0,0 +1,486 @@
+//! The Ethereum ABI of the `verifyTx` function of exported verifiers, and generation of
+//! off-chain code encoding proofs as calls to it.
+
+use tiny_keccak::keccak256;
+
+/// A parameter of a contract function, as found in a JSON ABI
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AbiParameter {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<AbiParameter>,
+}
+
+impl AbiParameter {
+    pub fn new<N: Into<String>, T: Into<String>>(name: N, ty: T) -> Self {
+        AbiParameter {
+            name: name.into(),
+            ty: ty.into(),
+            components: vec![],
+        }
+    }
+
+    pub fn tuple<S: Into<String>>(name: S, components: Vec<AbiParameter>) -> Self {
+        AbiParameter {
+            name: name.into(),
+            ty: String::from("tuple"),
+            components,
+        }
+    }
+
+    // the type as it appears in function signatures
+    fn canonical_type(&self) -> String {
+        match self.ty.as_ref() {
+            "tuple" => format!(
+                "({})",
+                self.components
+                    .iter()
+                    .map(|c| c.canonical_type())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            ty => ty.to_string(),
+        }
+    }
+
+    // the number of 32 byte words in the encoding of this parameter, which only holds for static types
+    fn word_count(&self) -> usize {
+        match self.ty.as_ref() {
+            "tuple" => self.components.iter().map(|c| c.word_count()).sum(),
+            ty => ty
+                .split('[')
+                .skip(1)
+                .map(|size| size.trim_end_matches(']').parse::<usize>().unwrap())
+                .product(),
+        }
+    }
+}
+
+/// The `verifyTx` function of an exported verifier
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifierInterface {
+    pub parameters: Vec<AbiParameter>,
+    pub view: bool,
+    pub verified_event: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AbiEntry {
+    #[serde(rename = "type")]
+    ty: &'static str,
+    name: &'static str,
+    inputs: Vec<AbiParameter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    outputs: Option<Vec<AbiParameter>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state_mutability: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    anonymous: Option<bool>,
+}
+
+impl VerifierInterface {
+    pub fn new(parameters: Vec<AbiParameter>) -> Self {
+        VerifierInterface {
+            parameters,
+            view: false,
+            verified_event: false,
+        }
+    }
+
+    pub fn view(self) -> Self {
+        VerifierInterface { view: true, ..self }
+    }
+
+    pub fn with_verified_event(self) -> Self {
+        VerifierInterface {
+            verified_event: true,
+            ..self
+        }
+    }
+
+    pub fn signature(&self) -> String {
+        format!(
+            "verifyTx({})",
+            self.parameters
+                .iter()
+                .map(|p| p.canonical_type())
+                .collect::<Vec<_>>()
+                .join(",")
+        )
+    }
+
+    pub fn selector(&self) -> [u8; 4] {
+        let hash = keccak256(self.signature().as_bytes());
+        [hash[0], hash[1], hash[2], hash[3]]
+    }
+
+    /// Returns the JSON ABI of the verifier contract
+    pub fn abi(&self) -> String {
+        let mut entries = vec![AbiEntry {
+            ty: "function",
+            name: "verifyTx",
+            inputs: self.parameters.clone(),
+            outputs: Some(vec![AbiParameter::new("r", "bool")]),
+            state_mutability: Some(if self.view { "view" } else { "nonpayable" }),
+            anonymous: None,
+        }];
+
+        if self.verified_event {
+            entries.push(AbiEntry {
+                ty: "event",
+                name: "Verified",
+                inputs: vec![AbiParameter::new("s", "string")],
+                outputs: None,
+                state_mutability: None,
+                anonymous: Some(false),
+            });
+        }
+
+        serde_json::to_string_pretty(&entries).unwrap()
+    }
+
+    // the path in `proof.json` of the values of each parameter, in order
+    fn proof_paths(&self) -> Vec<Vec<String>> {
+        self.parameters
+            .iter()
+            .flat_map(|p| match (p.name.as_ref(), p.ty.as_ref()) {
+                ("input", _) => vec![vec![String::from("inputs")]],
+                ("proof", "tuple") => p
+                    .components
+                    .iter()
+                    .map(|c| vec![String::from("proof"), c.name.clone()])
+                    .collect(),
+                (name, _) => vec![vec![String::from("proof"), name.to_string()]],
+            })
+            .collect()
+    }
+
+    /// Returns a Rust module encoding the proofs written to `proof.json` as calldata for `verifyTx`
+    pub fn rust_module(&self) -> String {
+        let selector = self
+            .selector()
+            .iter()
+            .map(|b| format!("0x{:02x}", b))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let paths = self
+            .proof_paths()
+            .iter()
+            .map(|path| {
+                format!(
+                    "    &[{}],\n",
+                    path.iter()
+                        .map(|p| format!("{:?}", p))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+            .collect::<String>();
+
+        RUST_MODULE_TEMPLATE
+            .replace("<%signature%>", &self.signature())
+            .replace("<%selector%>", &selector)
+            .replace(
+                "<%words%>",
+                &self
+                    .parameters
+                    .iter()
+                    .map(|p| p.word_count())
+                    .sum::<usize>()
+                    .to_string(),
+            )
+            .replace("<%paths%>", &paths)
+    }
+}
+
+const RUST_MODULE_TEMPLATE: &str = r#"//! Encodes proofs as calls to `<%signature%>` of the verifier exported by ZoKrates.
+//!
+//! This file is generated. It depends on the `serde_json` crate.
+
+use serde_json::Value;
+
+/// The selector of `<%signature%>`
+pub const SELECTOR: [u8; 4] = [<%selector%>];
+
+/// The number of 32 byte words following the selector in the calldata
+pub const WORDS: usize = <%words%>;
+
+// the location of the values of each parameter in `proof.json`
+const PATHS: &[&[&str]] = &[
+<%paths%>];
+
+/// Encodes the content of a `proof.json` file as calldata for `verifyTx`
+pub fn encode_proof_json(proof_json: &str) -> Result<Vec<u8>, String> {
+    let proof: Value = serde_json::from_str(proof_json).map_err(|e| e.to_string())?;
+
+    let mut words = vec![];
+    for path in PATHS {
+        let value = path
+            .iter()
+            .try_fold(&proof, |value, key| lookup(value, key))?;
+        collect_words(value, &mut words)?;
+    }
+
+    encode(&words)
+}
+
+/// Encodes the arguments of `verifyTx`, given as hexadecimal or decimal numbers in order, as calldata
+pub fn encode(words: &[String]) -> Result<Vec<u8>, String> {
+    if words.len() != WORDS {
+        return Err(format!("Expected {} values, found {}", WORDS, words.len()));
+    }
+
+    let mut calldata = SELECTOR.to_vec();
+    for w in words {
+        calldata.extend_from_slice(&word(w)?);
+    }
+    Ok(calldata)
+}
+
+// proofs committing to their inputs hold the original inputs in `committed_inputs`, and proofs
+// written by libsnark use capitalized keys and `input` instead of `inputs`
+fn lookup<'a>(value: &'a Value, key: &str) -> Result<&'a Value, String> {
+    let committed = format!("committed_{}", key);
+    let capitalized = key[..1].to_uppercase() + &key[1..];
+    let alternatives = [
+        committed.as_str(),
+        key,
+        capitalized.as_str(),
+        key.trim_end_matches('s'),
+    ];
+
+    alternatives
+        .iter()
+        .filter_map(|k| value.get(k))
+        .next()
+        .ok_or_else(|| format!("Missing `{}` in proof", key))
+}
+
+fn collect_words(value: &Value, words: &mut Vec<String>) -> Result<(), String> {
+    match value {
+        Value::String(s) => {
+            words.push(s.clone());
+            Ok(())
+        }
+        Value::Array(values) => values.iter().try_for_each(|v| collect_words(v, words)),
+        v => Err(format!("Unexpected value {} in proof", v)),
+    }
+}
+
+// encode a number as a big-endian 32 byte word
+fn word(s: &str) -> Result<[u8; 32], String> {
+    let mut word = [0u8; 32];
+
+    if s.starts_with("0x") || s.starts_with("0X") {
+        let digits = &s[2..];
+        if digits.len() > 64 {
+            return Err(format!("{} does not fit in 32 bytes", s));
+        }
+        let digits = format!("{:0>64}", digits);
+        for (i, byte) in word.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&digits[2 * i..2 * i + 2], 16)
+                .map_err(|_| format!("Invalid hexadecimal number {}", s))?;
+        }
+    } else {
+        for c in s.chars() {
+            let digit = c
+                .to_digit(10)
+                .ok_or_else(|| format!("Invalid number {}", s))?;
+            // word = word * 10 + digit
+            let mut carry = digit;
+            for byte in word.iter_mut().rev() {
+                let v = *byte as u32 * 10 + carry;
+                *byte = v as u8;
+                carry = v >> 8;
+            }
+            if carry != 0 {
+                return Err(format!("{} does not fit in 32 bytes", s));
+            }
+        }
+    }
+
+    Ok(word)
+}
+"#;
+
+// the module generated for `tests::g16_interface`, compiled to check the calldata it encodes
+#[cfg(test)]
+#[path = "abi/g16_encoder.rs"]
+mod g16_encoder;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn g16_interface() -> VerifierInterface {
+        VerifierInterface::new(vec![
+            AbiParameter::new("a", "uint256[2]"),
+            AbiParameter::new("b", "uint256[2][2]"),
+            AbiParameter::new("c", "uint256[2]"),
+            AbiParameter::new("input", "uint256[3]"),
+        ])
+    }
+
+    #[test]
+    fn signature() {
+        let interface = g16_interface();
+        assert_eq!(
+            interface.signature(),
+            "verifyTx(uint256[2],uint256[2][2],uint256[2],uint256[3])"
+        );
+        assert_eq!(
+            interface
+                .parameters
+                .iter()
+                .map(|p| p.word_count())
+                .sum::<usize>(),
+            11
+        );
+    }
+
+    #[test]
+    fn tuple_signature() {
+        let g1 = || {
+            vec![
+                AbiParameter::new("X", "uint256"),
+                AbiParameter::new("Y", "uint256"),
+            ]
+        };
+        let interface = VerifierInterface::new(vec![
+            AbiParameter::tuple(
+                "proof",
+                vec![
+                    AbiParameter::tuple("a", g1()),
+                    AbiParameter::tuple(
+                        "b",
+                        vec![
+                            AbiParameter::new("X", "uint256[2]"),
+                            AbiParameter::new("Y", "uint256[2]"),
+                        ],
+                    ),
+                    AbiParameter::tuple("c", g1()),
+                ],
+            ),
+            AbiParameter::new("input", "uint256[1]"),
+        ]);
+
+        assert_eq!(
+            interface.signature(),
+            "verifyTx(((uint256,uint256),(uint256[2],uint256[2]),(uint256,uint256)),uint256[1])"
+        );
+        assert_eq!(
+            interface.proof_paths(),
+            vec![
+                vec!["proof", "a"],
+                vec!["proof", "b"],
+                vec!["proof", "c"],
+                vec!["inputs"]
+            ]
+        );
+    }
+
+    #[test]
+    fn selector() {
+        // the well-known selector of `transfer(address,uint256)`
+        let keccak = keccak256(b"transfer(address,uint256)");
+        assert_eq!(&keccak[..4], &[0xa9, 0x05, 0x9c, 0xbb]);
+
+        let interface = g16_interface();
+        assert_eq!(interface.selector(), {
+            let h = keccak256(interface.signature().as_bytes());
+            [h[0], h[1], h[2], h[3]]
+        });
+    }
+
+    #[test]
+    fn abi() {
+        let abi: serde_json::Value =
+            serde_json::from_str(&g16_interface().with_verified_event().abi()).unwrap();
+
+        assert_eq!(abi[0]["name"], "verifyTx");
+        assert_eq!(abi[0]["stateMutability"], "nonpayable");
+        assert_eq!(abi[0]["inputs"][1]["type"], "uint256[2][2]");
+        assert_eq!(abi[1]["type"], "event");
+    }
+
+    #[test]
+    fn rust_module() {
+        let module = g16_interface().rust_module();
+        assert!(module.contains("pub const WORDS: usize = 11;"));
+        assert!(module.contains(
+            "const PATHS: &[&[&str]] = &[\n    &[\"proof\", \"a\"],\n    &[\"proof\", \"b\"],\n    &[\"proof\", \"c\"],\n    &[\"inputs\"],\n];"
+        ));
+        assert!(!module.contains("<%"));
+        assert_eq!(module, include_str!("abi/g16_encoder.rs"));
+    }
+
+    #[test]
+    fn rust_module_calldata() {
+        let proof_json = r#"{
+            "proof": {
+                "a": [
+                    "0x079c042de6746772b2c753574d99d19c2507759b36af971eed2ef1c113d1e9e3",
+                    "0x0a92277c82490b3b5320dff019a9067509de6e53b861afb70639f08b7f0a674d"
+                ],
+                "b": [
+                    [
+                        "0x0f002bf2e1bda755fe1f014ef1d7e893b0c9049e85d62cf30e9ba56dd7d3a0ae",
+                        "0x10ca04c6205c5a84c812ab06c15930b68adb5a900030e56599e90c3b5ef74752"
+                    ],
+                    [
+                        "0x0935e754d629f1f033f58438d7c47d97c5e2ec79bb0e1dc57c47ba500268bfa9",
+                        "0x012004a51ee979f5558d858214dd3bf2723deaa933a0a95decd28f49f414602b"
+                    ]
+                ],
+                "c": [
+                    "0x0dcdef3a05253f307058f10128d7c5d4ba48faaa1ca724868684d6ecfa4d8a6f",
+                    "0x0ef9e5e929cbf3f63a0eb1b35a6054838e38b52609cbd6c1ebfba18f0ca109de"
+                ]
+            },
+            "inputs": [
+                "0x0000000000000000000000000000000000000000000000000000000000000001",
+                "21888242871839275222246405745257275088548364400416034343698204186575808495616",
+                "0x2a"
+            ]
+        }"#;
+
+        // the selector of `verifyTx(uint256[2],uint256[2][2],uint256[2],uint256[3])` followed by
+        // the big-endian words of a, b, c and the inputs
+        let expected = vec![
+            "415df87c",
+            "079c042de6746772b2c753574d99d19c2507759b36af971eed2ef1c113d1e9e3",
+            "0a92277c82490b3b5320dff019a9067509de6e53b861afb70639f08b7f0a674d",
+            "0f002bf2e1bda755fe1f014ef1d7e893b0c9049e85d62cf30e9ba56dd7d3a0ae",
+            "10ca04c6205c5a84c812ab06c15930b68adb5a900030e56599e90c3b5ef74752",
+            "0935e754d629f1f033f58438d7c47d97c5e2ec79bb0e1dc57c47ba500268bfa9",
+            "012004a51ee979f5558d858214dd3bf2723deaa933a0a95decd28f49f414602b",
+            "0dcdef3a05253f307058f10128d7c5d4ba48faaa1ca724868684d6ecfa4d8a6f",
+            "0ef9e5e929cbf3f63a0eb1b35a6054838e38b52609cbd6c1ebfba18f0ca109de",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000",
+            "000000000000000000000000000000000000000000000000000000000000002a",
+        ]
+        .concat();
+
+        let calldata = g16_encoder::encode_proof_json(proof_json).unwrap();
+        assert_eq!(
+            calldata
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>(),
+            expected
+        );
+        assert_eq!(&calldata[..4], &g16_interface().selector());
+
+        assert_eq!(
+            g16_encoder::encode_proof_json(
+                r#"{"proof": {"a": [], "b": [], "c": []}, "inputs": []}"#
+            ),
+            Err(String::from("Expected 11 values, found 0"))
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Encodes proofs as calls to `verifyTx(uint256[2],uint256[2][2],uint256[2],uint256[3])` of the verifier exported by ZoKrates.
+//!
+//! This file is generated. It depends on the `serde_json` crate.
+
+use serde_json::Value;
+
+/// The selector of `verifyTx(uint256[2],uint256[2][2],uint256[2],uint256[3])`
+pub const SELECTOR: [u8; 4] = [0x41, 0x5d, 0xf8, 0x7c];
+
+/// The number of 32 byte words following the selector in the calldata
+pub const WORDS: usize = 11;
+
+// the location of the values of each parameter in `proof.json`
+const PATHS: &[&[&str]] = &[
+    &["proof", "a"],
+    &["proof", "b"],
+    &["proof", "c"],
+    &["inputs"],
+];
+
+/// Encodes the content of a `proof.json` file as calldata for `verifyTx`
+pub fn encode_proof_json(proof_json: &str) -> Result<Vec<u8>, String> {
+    let proof: Value = serde_json::from_str(proof_json).map_err(|e| e.to_string())?;
+
+    let mut words = vec![];
+    for path in PATHS {
+        let value = path
+            .iter()
+            .try_fold(&proof, |value, key| lookup(value, key))?;
+        collect_words(value, &mut words)?;
+    }
+
+    encode(&words)
+}
+
+/// Encodes the arguments of `verifyTx`, given as hexadecimal or decimal numbers in order, as calldata
+pub fn encode(words: &[String]) -> Result<Vec<u8>, String> {
+    if words.len() != WORDS {
+        return Err(format!("Expected {} values, found {}", WORDS, words.len()));
+    }
+
+    let mut calldata = SELECTOR.to_vec();
+    for w in words {
+        calldata.extend_from_slice(&word(w)?);
+    }
+    Ok(calldata)
+}
+
+// proofs committing to their inputs hold the original inputs in `committed_inputs`, and proofs
+// written by libsnark use capitalized keys and `input` instead of `inputs`
+fn lookup<'a>(value: &'a Value, key: &str) -> Result<&'a Value, String> {
+    let committed = format!("committed_{}", key);
+    let capitalized = key[..1].to_uppercase() + &key[1..];
+    let alternatives = [
+        committed.as_str(),
+        key,
+        capitalized.as_str(),
+        key.trim_end_matches('s'),
+    ];
+
+    alternatives
+        .iter()
+        .filter_map(|k| value.get(k))
+        .next()
+        .ok_or_else(|| format!("Missing `{}` in proof", key))
+}
+
+fn collect_words(value: &Value, words: &mut Vec<String>) -> Result<(), String> {
+    match value {
+        Value::String(s) => {
+            words.push(s.clone());
+            Ok(())
+        }
+        Value::Array(values) => values.iter().try_for_each(|v| collect_words(v, words)),
+        v => Err(format!("Unexpected value {} in proof", v)),
+    }
+}
+
+// encode a number as a big-endian 32 byte word
+fn word(s: &str) -> Result<[u8; 32], String> {
+    let mut word = [0u8; 32];
+
+    if s.starts_with("0x") || s.starts_with("0X") {
+        let digits = &s[2..];
+        if digits.len() > 64 {
+            return Err(format!("{} does not fit in 32 bytes", s));
+        }
+        let digits = format!("{:0>64}", digits);
+        for (i, byte) in word.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&digits[2 * i..2 * i + 2], 16)
+                .map_err(|_| format!("Invalid hexadecimal number {}", s))?;
+        }
+    } else {
+        for c in s.chars() {
+            let digit = c
+                .to_digit(10)
+                .ok_or_else(|| format!("Invalid number {}", s))?;
+            // word = word * 10 + digit
+            let mut carry = digit;
+            for byte in word.iter_mut().rev() {
+                let v = *byte as u32 * 10 + carry;
+                *byte = v as u8;
+                carry = v >> 8;
+            }
+            if carry != 0 {
+                return Err(format!("{} does not fit in 32 bytes", s));
+            }
+        }
+    }
+
+    Ok(word)
+}
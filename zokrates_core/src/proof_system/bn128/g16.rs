@@ -1,29 +1,248 @@
+use crate::commitment::commitment;
 use crate::ir;
-use crate::proof_system::bn128::utils::bellman::Computation;
+use crate::proof_system::bn128::utils::bellman::{
+    fr_from_hex, g1_from_point, g2_from_point, parse_g1_point, parse_g2_point, rerandomize,
+    seeded_rng, Computation,
+};
+use crate::proof_system::bn128::utils::json::{G1Point, G2Point};
+use crate::proof_system::bn128::utils::snarkjs;
 use crate::proof_system::bn128::utils::solidity::{SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB};
-use crate::proof_system::ProofSystem;
-use bellman::groth16::Parameters;
+use crate::proof_system::{
+    proofs_result, AbiParameter, ProofSystem, Seed, SolidityTarget, VerifierInterface,
+};
+use bellman::groth16::{prepare_verifying_key, verify_proof, Parameters};
+use bellman::pairing::CurveAffine;
+use bincode::Infinite;
+use pairing::bn256::{Bn256, G1Affine};
 use regex::Regex;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufReader, Read, Write};
 use std::path::PathBuf;
-use zokrates_field::field::FieldPrime;
+use std::sync::Arc;
+use std::thread;
+use zokrates_field::field::{Field, FieldPrime};
 
 const G16_WARNING: &str = "WARNING: You are using the G16 scheme which is subject to malleability. See zokrates.github.io/reference/proving_schemes.html#g16-malleability for implications.";
 
 pub struct G16 {}
+
+impl G16 {
+    /// Converts a verification key to the snarkjs layout
+    pub fn export_snarkjs_vk<R: Read>(&self, vk_reader: R) -> Result<String, String> {
+        let vk = read_vk(vk_reader)?;
+
+        let vk = snarkjs::VerificationKey {
+            protocol: String::from(snarkjs::PROTOCOL),
+            curve: vk.curve,
+            n_public: vk.inputs,
+            vk_alpha_1: snarkjs::g1_to_snarkjs(&vk.alpha)?,
+            vk_beta_2: snarkjs::g2_to_snarkjs(&vk.beta)?,
+            vk_gamma_2: snarkjs::g2_to_snarkjs(&vk.gamma)?,
+            vk_delta_2: snarkjs::g2_to_snarkjs(&vk.delta)?,
+            ic: vk
+                .gamma_abc
+                .iter()
+                .map(snarkjs::g1_to_snarkjs)
+                .collect::<Result<_, _>>()?,
+        };
+
+        Ok(serde_json::to_string_pretty(&vk).unwrap())
+    }
+
+    /// Converts a proof to the snarkjs layout, returning the proof and its public inputs
+    pub fn export_snarkjs_proof<R: Read>(
+        &self,
+        proof_reader: R,
+    ) -> Result<(String, String), String> {
+        let proof = read_proof(proof_reader)?;
+
+        let points = snarkjs::Proof {
+            pi_a: snarkjs::g1_to_snarkjs(&proof.proof.a)?,
+            pi_b: snarkjs::g2_to_snarkjs(&proof.proof.b)?,
+            pi_c: snarkjs::g1_to_snarkjs(&proof.proof.c)?,
+            protocol: String::from(snarkjs::PROTOCOL),
+        };
+
+        let inputs = proof
+            .inputs
+            .iter()
+            .map(|i| snarkjs::hex_to_decimal(i))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((
+            serde_json::to_string_pretty(&points).unwrap(),
+            serde_json::to_string_pretty(&inputs).unwrap(),
+        ))
+    }
+
+    /// Converts a snarkjs verification key to a G16 verification key
+    pub fn import_snarkjs_vk<R: Read>(&self, vk_reader: R) -> Result<String, String> {
+        let vk: snarkjs::VerificationKey = serde_json::from_reader(vk_reader)
+            .map_err(|why| format!("Invalid snarkjs verification key: {}", why))?;
+
+        if vk.protocol != snarkjs::PROTOCOL || vk.curve != snarkjs::CURVE {
+            return Err(format!(
+                "Expected a {} verification key on {}, found {} on {}",
+                snarkjs::PROTOCOL,
+                snarkjs::CURVE,
+                vk.protocol,
+                vk.curve
+            ));
+        }
+
+        if vk.ic.len() != vk.n_public + 1 {
+            return Err(format!(
+                "Expected {} points in IC for {} public inputs, found {}",
+                vk.n_public + 1,
+                vk.n_public,
+                vk.ic.len()
+            ));
+        }
+
+        let vk = VerificationKey {
+            scheme: String::from("g16"),
+            curve: vk.curve,
+            inputs: vk.n_public,
+            committed_inputs: None,
+            alpha: snarkjs::g1_from_snarkjs(&vk.vk_alpha_1)?,
+            beta: snarkjs::g2_from_snarkjs(&vk.vk_beta_2)?,
+            gamma: snarkjs::g2_from_snarkjs(&vk.vk_gamma_2)?,
+            delta: snarkjs::g2_from_snarkjs(&vk.vk_delta_2)?,
+            gamma_abc: vk
+                .ic
+                .iter()
+                .map(|p| snarkjs::g1_from_snarkjs(p))
+                .collect::<Result<_, _>>()?,
+        };
+
+        // make sure all points are on the curve before writing the key
+        vk.clone().into_bellman()?;
+
+        Ok(serde_json::to_string_pretty(&vk).unwrap())
+    }
+
+    /// Converts a snarkjs proof and its public inputs to a G16 proof
+    pub fn import_snarkjs_proof<R: Read, S: Read>(
+        &self,
+        proof_reader: R,
+        public_reader: S,
+    ) -> Result<String, String> {
+        let proof: snarkjs::Proof = serde_json::from_reader(proof_reader)
+            .map_err(|why| format!("Invalid snarkjs proof: {}", why))?;
+        let inputs: Vec<String> = serde_json::from_reader(public_reader)
+            .map_err(|why| format!("Invalid snarkjs public inputs: {}", why))?;
+
+        if proof.protocol != snarkjs::PROTOCOL {
+            return Err(format!(
+                "Expected a {} proof, found {}",
+                snarkjs::PROTOCOL,
+                proof.protocol
+            ));
+        }
+
+        let proof = Proof {
+            proof: ProofPoints {
+                a: snarkjs::g1_from_snarkjs(&proof.pi_a)?,
+                b: snarkjs::g2_from_snarkjs(&proof.pi_b)?,
+                c: snarkjs::g1_from_snarkjs(&proof.pi_c)?,
+            },
+            inputs: inputs
+                .iter()
+                .map(|i| snarkjs::decimal_to_hex(i))
+                .collect::<Result<_, _>>()?,
+            committed_inputs: vec![],
+        };
+
+        // make sure all points are on the curve before writing the proof
+        proof.proof.clone().into_bellman()?;
+
+        Ok(serde_json::to_string_pretty(&proof).unwrap())
+    }
+
+    /// Re-randomizes a proof, returning a fresh proof for the same public inputs which cannot be
+    /// linked to the original one
+    pub fn rerandomize<R: Read, S: Read>(
+        &self,
+        vk_reader: R,
+        proof_reader: S,
+    ) -> Result<String, String> {
+        let vk = read_vk(vk_reader)?;
+        let proof = read_proof(proof_reader)?;
+
+        if !check_proof(&vk, &proof)? {
+            return Err(String::from(
+                "Cannot re-randomize a proof which does not verify",
+            ));
+        }
+
+        let points = rerandomize(&proof.proof.clone().into_bellman()?, &vk.into_bellman()?);
+
+        let proof = Proof {
+            proof: ProofPoints {
+                a: parse_g1_point(&points.a),
+                b: parse_g2_point(&points.b),
+                c: parse_g1_point(&points.c),
+            },
+            ..proof
+        };
+
+        Ok(serde_json::to_string_pretty(&proof).unwrap())
+    }
+
+    /// Checks whether two proofs could be re-randomizations of each other, that is whether they
+    /// are different valid proofs of the same public inputs.
+    ///
+    /// Re-randomized proofs are unlinkable by design, so this cannot tell a re-randomization apart
+    /// from another proof of the same statement, which is equivalent to it for any verifier.
+    pub fn is_rerandomization<R: Read, S: Read, T: Read>(
+        &self,
+        vk_reader: R,
+        proof_reader: S,
+        other_reader: T,
+    ) -> Result<bool, String> {
+        let vk = read_vk(vk_reader)?;
+        let proof = read_proof(proof_reader)?;
+        let other = read_proof(other_reader)?;
+
+        let same_inputs = |a: &Vec<String>, b: &Vec<String>| -> Result<bool, String> {
+            let a = a
+                .iter()
+                .map(|i| fr_from_hex(i))
+                .collect::<Result<Vec<_>, _>>()?;
+            let b = b
+                .iter()
+                .map(|i| fr_from_hex(i))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(a == b)
+        };
+
+        Ok(proof.proof != other.proof
+            && same_inputs(&proof.inputs, &other.inputs)?
+            && same_inputs(&proof.committed_inputs, &other.committed_inputs)?
+            && check_proof(&vk, &proof)?
+            && check_proof(&vk, &other)?)
+    }
+}
 impl ProofSystem for G16 {
-    fn setup(&self, program: ir::Prog<FieldPrime>, pk_path: &str, vk_path: &str) {
+    fn setup(
+        &self,
+        program: ir::Prog<FieldPrime>,
+        pk_path: &str,
+        vk_path: &str,
+        seed: Option<Seed>,
+    ) {
         std::env::set_var("BELLMAN_VERBOSE", "0");
 
         println!("{}", G16_WARNING);
 
-        let parameters = Computation::without_witness(program).setup();
+        let committed = program.committed.len();
+        let parameters =
+            Computation::without_witness(program).setup_with_rng(&mut seeded_rng(seed, 0));
         let parameters_file = File::create(PathBuf::from(pk_path)).unwrap();
         parameters.write(parameters_file).unwrap();
         let mut vk_file = File::create(PathBuf::from(vk_path)).unwrap();
         vk_file
-            .write(serialize::serialize_vk(parameters.vk).as_ref())
+            .write(serialize::serialize_vk(parameters.vk, committed).as_ref())
             .unwrap();
     }
 
@@ -33,140 +252,554 @@ impl ProofSystem for G16 {
         witness: ir::Witness<FieldPrime>,
         pk_path: &str,
         proof_path: &str,
+        seed: Option<Seed>,
     ) -> bool {
         std::env::set_var("BELLMAN_VERBOSE", "0");
 
         println!("{}", G16_WARNING);
 
-        let computation = Computation::with_witness(program, witness);
         let parameters_file = File::open(PathBuf::from(pk_path)).unwrap();
 
         let params = Parameters::read(parameters_file, true).unwrap();
 
-        let proof = computation.clone().prove(&params);
+        prove_to_file(program, witness, &params, proof_path, seed, 0)
+            .unwrap_or_else(|e| panic!("{}", e));
+        true
+    }
+
+    fn generate_proofs(
+        &self,
+        program: ir::Prog<FieldPrime>,
+        jobs: Vec<(ir::Witness<FieldPrime>, String, String)>,
+        pk_path: &str,
+        threads: usize,
+        seed: Option<Seed>,
+    ) -> Result<(), String> {
+        std::env::set_var("BELLMAN_VERBOSE", "0");
 
-        let mut proof_file = File::create(PathBuf::from(proof_path)).unwrap();
-        write!(
-            proof_file,
-            "{}",
-            serialize::serialize_proof(&proof, &computation.public_inputs_values())
-        )
-        .unwrap();
+        println!("{}", G16_WARNING);
+
+        // the proving key is read and checked once for the whole batch
+        let parameters_file = File::open(PathBuf::from(pk_path))
+            .map_err(|why| format!("couldn't open {}: {}", pk_path, why))?;
+        let params = Arc::new(
+            Parameters::read(parameters_file, true)
+                .map_err(|why| format!("Invalid proving key {}: {}", pk_path, why))?,
+        );
+
+        // directives can hold helpers which cannot be shared across threads,
+        // so each worker deserializes its own copy of the program
+        let program = Arc::new(bincode::serialize(&program, Infinite).unwrap());
+
+        // distribute the jobs evenly over the workers
+        let threads = std::cmp::max(threads, 1);
+        let mut chunks: Vec<Vec<_>> = (0..threads).map(|_| vec![]).collect();
+        // jobs keep their index, so that each proof of a seeded batch gets its own randomness
+        for (index, job) in jobs.into_iter().enumerate() {
+            chunks[index % threads].push((index, job));
+        }
+
+        let workers: Vec<_> = chunks
+            .into_iter()
+            .filter(|chunk| !chunk.is_empty())
+            .map(|chunk| {
+                let params = params.clone();
+                let program = program.clone();
+                // the names of the witnesses, to report them if the worker panics
+                let names: Vec<_> = chunk.iter().map(|(_, (_, name, _))| name.clone()).collect();
+                let worker = thread::spawn(move || -> Vec<String> {
+                    let program: ir::Prog<FieldPrime> = match bincode::deserialize(&program) {
+                        Ok(program) => program,
+                        Err(why) => return vec![format!("Could not load the program: {}", why)],
+                    };
+                    chunk
+                        .into_iter()
+                        .filter_map(|(index, (witness, name, proof_path))| {
+                            prove_to_file(
+                                program.clone(),
+                                witness,
+                                &params,
+                                &proof_path,
+                                seed,
+                                index,
+                            )
+                            .err()
+                            .map(|why| format!("Could not prove {}: {}", name, why))
+                        })
+                        .collect()
+                });
+                (worker, names)
+            })
+            .collect();
+
+        // every worker is joined, so that no proof is still being written when this returns
+        let errors = workers
+            .into_iter()
+            .flat_map(|(worker, names)| match worker.join() {
+                Ok(errors) => errors,
+                Err(_) => vec![format!(
+                    "Proving panicked while handling one of {}",
+                    names.join(", ")
+                )],
+            })
+            .collect();
+
+        proofs_result(errors)
+    }
+
+    fn supports_seed(&self) -> bool {
         true
     }
 
-    fn export_solidity_verifier(&self, reader: BufReader<File>) -> String {
-        let mut lines = reader.lines();
-
-        let mut template_text = String::from(CONTRACT_TEMPLATE);
-        let gamma_abc_template = String::from("vk.gammaABC[index] = Pairing.G1Point(points);"); //copy this for each entry
-
-        //replace things in template
-        let vk_regex = Regex::new(r#"(<%vk_[^i%]*%>)"#).unwrap();
-        let vk_gamma_abc_len_regex = Regex::new(r#"(<%vk_gammaABC_length%>)"#).unwrap();
-        let vk_gamma_abc_index_regex = Regex::new(r#"index"#).unwrap();
-        let vk_gamma_abc_points_regex = Regex::new(r#"points"#).unwrap();
-        let vk_gamma_abc_repeat_regex = Regex::new(r#"(<%vk_gammaABC_pts%>)"#).unwrap();
-        let vk_input_len_regex = Regex::new(r#"(<%vk_input_length%>)"#).unwrap();
-
-        for _ in 0..4 {
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-            template_text = vk_regex
-                .replace(template_text.as_str(), current_line_split[1].trim())
-                .into_owned();
+    fn export_solidity_verifier(&self, reader: BufReader<File>) -> Result<String, String> {
+        self.export_solidity_verifier_for(reader, SolidityTarget::Default)
+    }
+
+    fn export_solidity_verifier_for(
+        &self,
+        reader: BufReader<File>,
+        target: SolidityTarget,
+    ) -> Result<String, String> {
+        let vk = read_vk(reader)?;
+
+        match (target, vk.committed_inputs) {
+            (SolidityTarget::Optimized, Some(_)) | (SolidityTarget::Library, Some(_)) => {
+                return Err(format!(
+                    "The {} target does not support commitments to the public inputs",
+                    target
+                ))
+            }
+            _ => {}
         }
 
-        let current_line: String = lines
-            .next()
-            .expect("Unexpected end of file in verification key!")
-            .unwrap();
-        let current_line_split: Vec<&str> = current_line.split("=").collect();
-        assert_eq!(current_line_split.len(), 2);
-        let gamma_abc_count: i32 = current_line_split[1].trim().parse().unwrap();
-
-        template_text = vk_gamma_abc_len_regex
-            .replace(
-                template_text.as_str(),
-                format!("{}", gamma_abc_count).as_str(),
-            )
-            .into_owned();
-        template_text = vk_input_len_regex
-            .replace(
-                template_text.as_str(),
-                format!("{}", gamma_abc_count - 1).as_str(),
-            )
-            .into_owned();
-
-        let mut gamma_abc_repeat_text = String::new();
-        for x in 0..gamma_abc_count {
-            let mut curr_template = gamma_abc_template.clone();
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-            curr_template = vk_gamma_abc_index_regex
-                .replace(curr_template.as_str(), format!("{}", x).as_str())
-                .into_owned();
-            curr_template = vk_gamma_abc_points_regex
-                .replace(curr_template.as_str(), current_line_split[1].trim())
-                .into_owned();
-            gamma_abc_repeat_text.push_str(curr_template.as_str());
-            if x < gamma_abc_count - 1 {
-                gamma_abc_repeat_text.push_str("\n        ");
+        Ok(match target {
+            SolidityTarget::Default => format!(
+                "{}{}{}",
+                SOLIDITY_G2_ADDITION_LIB,
+                SOLIDITY_PAIRING_LIB,
+                render_contract(&vk, CONTRACT_TEMPLATE)
+            ),
+            SolidityTarget::AbiV2 => format!(
+                "pragma experimental ABIEncoderV2;\n\n{}{}{}",
+                SOLIDITY_G2_ADDITION_LIB,
+                SOLIDITY_PAIRING_LIB,
+                render_contract(&vk, CONTRACT_TEMPLATE_V2)
+            ),
+            SolidityTarget::Optimized => render_optimized_contract(&vk),
+            SolidityTarget::Library => {
+                format!("{}{}", LIBRARY_TEMPLATE, render_library_contract(&vk))
+            }
+        })
+    }
+
+    fn verifier_interface(
+        &self,
+        inputs: usize,
+        target: SolidityTarget,
+    ) -> Result<VerifierInterface, String> {
+        let input = AbiParameter::new("input", format!("uint256[{}]", inputs));
+
+        Ok(match target {
+            SolidityTarget::AbiV2 => {
+                let g1 = || {
+                    vec![
+                        AbiParameter::new("X", "uint256"),
+                        AbiParameter::new("Y", "uint256"),
+                    ]
+                };
+                let g2 = vec![
+                    AbiParameter::new("X", "uint256[2]"),
+                    AbiParameter::new("Y", "uint256[2]"),
+                ];
+
+                VerifierInterface::new(vec![
+                    AbiParameter::tuple(
+                        "proof",
+                        vec![
+                            AbiParameter::tuple("a", g1()),
+                            AbiParameter::tuple("b", g2),
+                            AbiParameter::tuple("c", g1()),
+                        ],
+                    ),
+                    input,
+                ])
+            }
+            target => {
+                let interface = VerifierInterface::new(vec![
+                    AbiParameter::new("a", "uint256[2]"),
+                    AbiParameter::new("b", "uint256[2][2]"),
+                    AbiParameter::new("c", "uint256[2]"),
+                    input,
+                ]);
+
+                match target {
+                    SolidityTarget::Default => interface.with_verified_event(),
+                    _ => interface.view(),
+                }
             }
+        })
+    }
+
+    fn verify(
+        &self,
+        vk_reader: BufReader<File>,
+        proof_reader: BufReader<File>,
+    ) -> Result<bool, String> {
+        let vk = read_vk(vk_reader)?;
+        let proof = read_proof(proof_reader)?;
+
+        check_proof(&vk, &proof)
+    }
+}
+
+/// A G16 verification key as written to the verification key file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct VerificationKey {
+    scheme: String,
+    curve: String,
+    inputs: usize,
+    /// The number of values committed to by the single public input, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    committed_inputs: Option<usize>,
+    alpha: G1Point,
+    beta: G2Point,
+    gamma: G2Point,
+    delta: G2Point,
+    gamma_abc: Vec<G1Point>,
+}
+
+impl VerificationKey {
+    fn from_bellman(vk: &bellman::groth16::VerifyingKey<Bn256>, committed: usize) -> Self {
+        VerificationKey {
+            scheme: String::from("g16"),
+            curve: String::from("bn128"),
+            inputs: vk.ic.len() - 1,
+            committed_inputs: match committed {
+                0 => None,
+                n => Some(n),
+            },
+            alpha: parse_g1_point(&vk.alpha_g1),
+            beta: parse_g2_point(&vk.beta_g2),
+            gamma: parse_g2_point(&vk.gamma_g2),
+            delta: parse_g2_point(&vk.delta_g2),
+            gamma_abc: vk.ic.iter().map(parse_g1_point).collect(),
         }
-        template_text = vk_gamma_abc_repeat_regex
-            .replace(template_text.as_str(), gamma_abc_repeat_text.as_str())
-            .into_owned();
+    }
 
-        let re = Regex::new(r"(?P<v>0[xX][0-9a-fA-F]{64})").unwrap();
-        template_text = re.replace_all(&template_text, "uint256($v)").to_string();
+    fn into_bellman(self) -> Result<bellman::groth16::VerifyingKey<Bn256>, String> {
+        Ok(bellman::groth16::VerifyingKey {
+            alpha_g1: g1_from_point(&self.alpha)?,
+            // `beta_g1` and `delta_g1` are not needed to verify proofs, so they are not exported
+            beta_g1: G1Affine::zero(),
+            beta_g2: g2_from_point(&self.beta)?,
+            gamma_g2: g2_from_point(&self.gamma)?,
+            delta_g1: G1Affine::zero(),
+            delta_g2: g2_from_point(&self.delta)?,
+            ic: self
+                .gamma_abc
+                .iter()
+                .map(g1_from_point)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
 
-        format!(
-            "{}{}{}",
-            SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB, template_text
+// fill in a template building the verification key in memory with the `Pairing` library
+fn render_contract(vk: &VerificationKey, template: &str) -> String {
+    let gamma_abc_repeat_text = vk
+        .gamma_abc
+        .iter()
+        .enumerate()
+        .map(|(index, point)| format!("vk.gammaABC[{}] = Pairing.G1Point({});", index, point))
+        .collect::<Vec<_>>()
+        .join("\n        ");
+
+    let template_text = template
+        .replace("<%vk_a%>", &vk.alpha.to_string())
+        .replace("<%vk_b%>", &vk.beta.to_string())
+        .replace("<%vk_gamma%>", &vk.gamma.to_string())
+        .replace("<%vk_delta%>", &vk.delta.to_string())
+        .replace("<%vk_gammaABC_length%>", &vk.gamma_abc.len().to_string())
+        .replace("<%vk_gammaABC_pts%>", &gamma_abc_repeat_text)
+        .replace("<%input_values%>", &input_values(vk))
+        .replace(
+            "<%vk_input_length%>",
+            &vk.committed_inputs.unwrap_or(vk.inputs).to_string(),
+        );
+
+    let re = Regex::new(r"(?P<v>0[xX][0-9a-fA-F]{64})").unwrap();
+    re.replace_all(&template_text, "uint256($v)").to_string()
+}
+
+// copy the inputs of `verifyTx` to `inputValues`, or replace them by their commitment
+fn input_values(vk: &VerificationKey) -> String {
+    match vk.committed_inputs {
+        Some(_) => String::from(COMMITMENT_INPUT_VALUES),
+        None => String::from(INPUT_VALUES),
+    }
+}
+
+fn g1_constants(name: &str, p: &G1Point) -> String {
+    format!(
+        "uint256 constant {name}_X = {};\n    uint256 constant {name}_Y = {};",
+        p.0,
+        p.1,
+        name = name
+    )
+}
+
+fn g2_constants(name: &str, p: &G2Point) -> String {
+    format!(
+        "uint256 constant {name}_X1 = {};\n    uint256 constant {name}_X0 = {};\n    uint256 constant {name}_Y1 = {};\n    uint256 constant {name}_Y0 = {};",
+        (p.0).0,
+        (p.0).1,
+        (p.1).0,
+        (p.1).1,
+        name = name
+    )
+}
+
+// write the coordinates of the G2 constant `name` to the pairing input, starting at `offset`
+fn g2_pairing_input(name: &str, offset: usize) -> String {
+    ["X1", "X0", "Y1", "Y0"]
+        .iter()
+        .enumerate()
+        .map(|(i, c)| format!("p[{}] = {}_{};", offset + i, name, c))
+        .collect::<Vec<_>>()
+        .join("\n        ")
+}
+
+fn render_optimized_contract(vk: &VerificationKey) -> String {
+    let constants = std::iter::once(g1_constants("ALPHA", &vk.alpha))
+        .chain(std::iter::once(g2_constants("BETA", &vk.beta)))
+        .chain(std::iter::once(g2_constants("GAMMA", &vk.gamma)))
+        .chain(std::iter::once(g2_constants("DELTA", &vk.delta)))
+        .chain(
+            vk.gamma_abc
+                .iter()
+                .enumerate()
+                .map(|(i, p)| g1_constants(&format!("GAMMA_ABC_{}", i), p)),
         )
+        .collect::<Vec<_>>()
+        .join("\n    ");
+
+    // the linear combination of the inputs is unrolled to use the constants directly
+    let vk_x = (0..vk.inputs)
+        .map(|i| {
+            OPTIMIZED_INPUT_TEMPLATE
+                .replace("<%input_index%>", &i.to_string())
+                .replace("<%point_index%>", &(i + 1).to_string())
+        })
+        .collect::<Vec<_>>()
+        .join("");
+
+    OPTIMIZED_CONTRACT_TEMPLATE
+        .replace("<%vk_constants%>", &constants)
+        .replace("<%vk_x%>", &vk_x)
+        .replace("<%vk_gamma%>", &g2_pairing_input("GAMMA", 8))
+        .replace("<%vk_delta%>", &g2_pairing_input("DELTA", 14))
+        .replace("<%vk_beta%>", &g2_pairing_input("BETA", 20))
+        .replace("<%vk_input_length%>", &vk.inputs.to_string())
+}
+
+fn render_library_contract(vk: &VerificationKey) -> String {
+    let vk_values: Vec<&String> = vec![
+        &vk.alpha.0,
+        &vk.alpha.1,
+        &(vk.beta.0).0,
+        &(vk.beta.0).1,
+        &(vk.beta.1).0,
+        &(vk.beta.1).1,
+        &(vk.gamma.0).0,
+        &(vk.gamma.0).1,
+        &(vk.gamma.1).0,
+        &(vk.gamma.1).1,
+        &(vk.delta.0).0,
+        &(vk.delta.0).1,
+        &(vk.delta.1).0,
+        &(vk.delta.1).1,
+    ];
+
+    let vk_text = vk_values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| format!("vk[{}] = {};", i, v))
+        .collect::<Vec<_>>()
+        .join("\n        ");
+
+    let gamma_abc_text = vk
+        .gamma_abc
+        .iter()
+        .flat_map(|p| vec![&p.0, &p.1])
+        .enumerate()
+        .map(|(i, v)| format!("gammaABC[{}] = {};", i, v))
+        .collect::<Vec<_>>()
+        .join("\n        ");
+
+    LIBRARY_CONTRACT_TEMPLATE
+        .replace("<%vk%>", &vk_text)
+        .replace(
+            "<%vk_gammaABC_length%>",
+            &(2 * vk.gamma_abc.len()).to_string(),
+        )
+        .replace("<%vk_gammaABC%>", &gamma_abc_text)
+        .replace("<%vk_input_length%>", &vk.inputs.to_string())
+}
+
+// check `proof` against `vk`, including its commitment to its inputs if `vk` expects one
+fn check_proof(vk: &VerificationKey, proof: &Proof) -> Result<bool, String> {
+    if proof.inputs.len() != vk.inputs {
+        return Err(format!(
+            "Verification key expects {} public inputs but the proof has {}",
+            vk.inputs,
+            proof.inputs.len()
+        ));
+    }
+
+    if let Some(count) = vk.committed_inputs {
+        check_commitment(proof, count)?;
+    }
+
+    let vk = vk.clone().into_bellman()?;
+    let inputs = proof
+        .inputs
+        .iter()
+        .map(|i| fr_from_hex(i))
+        .collect::<Result<Vec<_>, _>>()?;
+    let proof = proof.proof.clone().into_bellman()?;
+
+    verify_proof(&prepare_verifying_key(&vk), &proof, &inputs).map_err(|e| format!("{:?}", e))
+}
+
+// check that the single input of `proof` is the commitment to its `count` committed inputs
+fn check_commitment(proof: &Proof, count: usize) -> Result<(), String> {
+    if proof.committed_inputs.len() != count {
+        return Err(format!(
+            "Verification key expects {} committed inputs but the proof has {}",
+            count,
+            proof.committed_inputs.len()
+        ));
+    }
+
+    let committed = proof
+        .committed_inputs
+        .iter()
+        .map(|i| fr_from_hex(i).map(FieldPrime::from_bellman))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match commitment(&committed).into_bellman() == fr_from_hex(&proof.inputs[0])? {
+        true => Ok(()),
+        false => Err(String::from(
+            "The committed inputs of the proof do not match its public input",
+        )),
     }
 }
 
+fn read_vk<R: Read>(vk_reader: R) -> Result<VerificationKey, String> {
+    let vk: VerificationKey = serde_json::from_reader(vk_reader)
+        .map_err(|why| format!("Invalid verification key: {}", why))?;
+
+    if vk.scheme != "g16" {
+        return Err(format!(
+            "Expected a g16 verification key, found {}",
+            vk.scheme
+        ));
+    }
+
+    Ok(vk)
+}
+
+fn read_proof<R: Read>(proof_reader: R) -> Result<Proof, String> {
+    serde_json::from_reader(proof_reader).map_err(|why| format!("Invalid proof: {}", why))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct ProofPoints {
+    a: G1Point,
+    b: G2Point,
+    c: G1Point,
+}
+
+impl ProofPoints {
+    fn into_bellman(self) -> Result<bellman::groth16::Proof<Bn256>, String> {
+        Ok(bellman::groth16::Proof {
+            a: g1_from_point(&self.a)?,
+            b: g2_from_point(&self.b)?,
+            c: g1_from_point(&self.c)?,
+        })
+    }
+}
+
+/// A G16 proof as written to the JSON proof file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Proof {
+    proof: ProofPoints,
+    inputs: Vec<String>,
+    /// The values committed to by the single input, if the program commits to its public inputs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    committed_inputs: Vec<String>,
+}
+
+// prove with the randomness of `stream` if a seed is given
+fn prove_to_file(
+    program: ir::Prog<FieldPrime>,
+    witness: ir::Witness<FieldPrime>,
+    params: &Parameters<Bn256>,
+    proof_path: &str,
+    seed: Option<Seed>,
+    stream: usize,
+) -> Result<(), String> {
+    let committed = program
+        .committed
+        .iter()
+        .map(|v| {
+            witness
+                .0
+                .get(v)
+                .map(|value| value.clone().into_bellman())
+                .ok_or_else(|| format!("The witness has no value for {}", v))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let computation = Computation::with_witness(program, witness);
+
+    let proof = computation
+        .clone()
+        .try_prove_with_rng(params, &mut seeded_rng(seed, stream))?;
+
+    let mut proof_file = File::create(PathBuf::from(proof_path))
+        .map_err(|why| format!("couldn't create {}: {}", proof_path, why))?;
+    write!(
+        proof_file,
+        "{}",
+        serialize::serialize_proof(&proof, &computation.public_inputs_values(), &committed)
+    )
+    .map_err(|why| format!("couldn't write {}: {}", proof_path, why))
+}
+
 mod serialize {
 
-    use crate::proof_system::bn128::utils::bellman::{
-        parse_fr_json, parse_g1_hex, parse_g1_json, parse_g2_hex, parse_g2_json,
-    };
+    use crate::proof_system::bn128::utils::bellman::{parse_fr_json, parse_g1_json, parse_g2_json};
     use bellman::groth16::{Proof, VerifyingKey};
     use pairing::bn256::{Bn256, Fr};
 
-    pub fn serialize_vk(vk: VerifyingKey<Bn256>) -> String {
-        format!(
-            "vk.alpha = {}
-    vk.beta = {}
-    vk.gamma = {}
-    vk.delta = {}
-    vk.gammaABC.len() = {}
-    {}",
-            parse_g1_hex(&vk.alpha_g1),
-            parse_g2_hex(&vk.beta_g2),
-            parse_g2_hex(&vk.gamma_g2),
-            parse_g2_hex(&vk.delta_g2),
-            vk.ic.len(),
-            vk.ic
-                .iter()
-                .enumerate()
-                .map(|(i, x)| format!("vk.gammaABC[{}] = {}", i, parse_g1_hex(x)))
-                .collect::<Vec<_>>()
-                .join("\n")
-        )
+    pub fn serialize_vk(vk: VerifyingKey<Bn256>, committed: usize) -> String {
+        serde_json::to_string_pretty(&super::VerificationKey::from_bellman(&vk, committed)).unwrap()
     }
 
-    pub fn serialize_proof(p: &Proof<Bn256>, inputs: &Vec<Fr>) -> String {
+    pub fn serialize_proof(p: &Proof<Bn256>, inputs: &Vec<Fr>, committed: &Vec<Fr>) -> String {
+        let committed = match committed.len() {
+            0 => String::new(),
+            _ => format!(
+                ",\n        \"committed_inputs\": [{}]",
+                committed
+                    .iter()
+                    .map(parse_fr_json)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+
         format!(
             "{{
         \"proof\": {{
@@ -174,7 +807,7 @@ mod serialize {
             \"b\": {},
             \"c\": {}
         }},
-        \"inputs\": [{}]
+        \"inputs\": [{}]{}
     }}",
             parse_g1_json(&p.a),
             parse_g2_json(&p.b),
@@ -184,10 +817,23 @@ mod serialize {
                 .map(parse_fr_json)
                 .collect::<Vec<_>>()
                 .join(", "),
+            committed
         )
     }
 }
 
+const INPUT_VALUES: &str = r#"uint[] memory inputValues = new uint[](input.length);
+        for(uint i = 0; i < input.length; i++){
+            inputValues[i] = input[i];
+        }"#;
+
+const COMMITMENT_INPUT_VALUES: &str = r#"// the proof has a single public input, the commitment to the inputs
+        uint[] memory inputValues = new uint[](1);
+        for(uint i = 0; i < input.length; i++){
+            require(input[i] < 21888242871839275222246405745257275088548364400416034343698204186575808495617);
+        }
+        inputValues[0] = uint256(sha256(abi.encodePacked(input))) >> 3;"#;
+
 const CONTRACT_TEMPLATE: &str = r#"
 contract Verifier {
     using Pairing for *;
@@ -237,10 +883,7 @@ contract Verifier {
         proof.A = Pairing.G1Point(a[0], a[1]);
         proof.B = Pairing.G2Point([b[0][0], b[0][1]], [b[1][0], b[1][1]]);
         proof.C = Pairing.G1Point(c[0], c[1]);
-        uint[] memory inputValues = new uint[](input.length);
-        for(uint i = 0; i < input.length; i++){
-            inputValues[i] = input[i];
-        }
+        <%input_values%>
         if (verify(inputValues, proof) == 0) {
             emit Verified("Transaction successfully verified.");
             return true;
@@ -251,6 +894,211 @@ contract Verifier {
 }
 "#;
 
+const CONTRACT_TEMPLATE_V2: &str = r#"
+contract Verifier {
+    using Pairing for *;
+    struct VerifyingKey {
+        Pairing.G1Point a;
+        Pairing.G2Point b;
+        Pairing.G2Point gamma;
+        Pairing.G2Point delta;
+        Pairing.G1Point[] gammaABC;
+    }
+    struct Proof {
+        Pairing.G1Point a;
+        Pairing.G2Point b;
+        Pairing.G1Point c;
+    }
+    function verifyingKey() pure internal returns (VerifyingKey memory vk) {
+        vk.a = Pairing.G1Point(<%vk_a%>);
+        vk.b = Pairing.G2Point(<%vk_b%>);
+        vk.gamma = Pairing.G2Point(<%vk_gamma%>);
+        vk.delta = Pairing.G2Point(<%vk_delta%>);
+        vk.gammaABC = new Pairing.G1Point[](<%vk_gammaABC_length%>);
+        <%vk_gammaABC_pts%>
+    }
+    function verify(uint[] memory input, Proof memory proof) internal returns (uint) {
+        VerifyingKey memory vk = verifyingKey();
+        require(input.length + 1 == vk.gammaABC.length);
+        // Compute the linear combination vk_x
+        Pairing.G1Point memory vk_x = Pairing.G1Point(0, 0);
+        for (uint i = 0; i < input.length; i++)
+            vk_x = Pairing.addition(vk_x, Pairing.scalar_mul(vk.gammaABC[i + 1], input[i]));
+        vk_x = Pairing.addition(vk_x, vk.gammaABC[0]);
+        if(!Pairing.pairingProd4(
+             proof.a, proof.b,
+             Pairing.negate(vk_x), vk.gamma,
+             Pairing.negate(proof.c), vk.delta,
+             Pairing.negate(vk.a), vk.b)) return 1;
+        return 0;
+    }
+    function verifyTx(
+            Proof memory proof,
+            uint[<%vk_input_length%>] memory input
+        ) public returns (bool r) {
+        <%input_values%>
+        return verify(inputValues, proof) == 0;
+    }
+}
+"#;
+
+const OPTIMIZED_CONTRACT_TEMPLATE: &str = r#"pragma solidity ^0.5.0;
+
+contract Verifier {
+    // The order of the scalar field, which public inputs must be lower than
+    uint256 constant SNARK_SCALAR_FIELD = 21888242871839275222246405745257275088548364400416034343698204186575808495617;
+    // The prime of the base field, used to negate points
+    uint256 constant PRIME_Q = 21888242871839275222246405745257275088696311157297823662689037894645226208583;
+
+    <%vk_constants%>
+
+    function verifyTx(
+            uint[2] calldata a,
+            uint[2][2] calldata b,
+            uint[2] calldata c,
+            uint[<%vk_input_length%>] calldata input
+        ) external view returns (bool r) {
+        bool success = true;
+
+        // Compute the linear combination vk_x in `acc`, using `term` as input to the multiplication
+        uint256[4] memory acc;
+        uint256[3] memory term;
+        acc[0] = GAMMA_ABC_0_X;
+        acc[1] = GAMMA_ABC_0_Y;
+        <%vk_x%>
+        require(success, "Invalid public input");
+
+        // e(A, B) * e(-vk_x, gamma) * e(-C, delta) * e(-alpha, beta) == 1
+        uint256[24] memory p;
+        p[0] = a[0];
+        p[1] = a[1];
+        p[2] = b[0][0];
+        p[3] = b[0][1];
+        p[4] = b[1][0];
+        p[5] = b[1][1];
+        p[6] = acc[0];
+        p[7] = (PRIME_Q - (acc[1] % PRIME_Q)) % PRIME_Q;
+        <%vk_gamma%>
+        p[12] = c[0];
+        p[13] = (PRIME_Q - (c[1] % PRIME_Q)) % PRIME_Q;
+        <%vk_delta%>
+        p[18] = ALPHA_X;
+        p[19] = PRIME_Q - ALPHA_Y;
+        <%vk_beta%>
+
+        uint256[1] memory out;
+        assembly {
+            success := staticcall(gas, 8, p, 0x300, out, 0x20)
+        }
+        require(success, "Invalid proof");
+        return out[0] == 1;
+    }
+}
+"#;
+
+const OPTIMIZED_INPUT_TEMPLATE: &str = r#"
+        require(input[<%input_index%>] < SNARK_SCALAR_FIELD, "Public input out of range");
+        term[0] = GAMMA_ABC_<%point_index%>_X;
+        term[1] = GAMMA_ABC_<%point_index%>_Y;
+        term[2] = input[<%input_index%>];
+        assembly {
+            success := and(success, staticcall(gas, 7, term, 0x60, add(acc, 0x40), 0x40))
+            success := and(success, staticcall(gas, 6, acc, 0x80, acc, 0x40))
+        }
+"#;
+
+const LIBRARY_TEMPLATE: &str = r#"pragma solidity ^0.5.0;
+
+// Deploy this library once, and link it to the verifier of each circuit
+library Groth16Verifier {
+    // The order of the scalar field, which public inputs must be lower than
+    uint256 constant SNARK_SCALAR_FIELD = 21888242871839275222246405745257275088548364400416034343698204186575808495617;
+    // The prime of the base field, used to negate points
+    uint256 constant PRIME_Q = 21888242871839275222246405745257275088696311157297823662689037894645226208583;
+
+    /// @param vk alpha, beta, gamma and delta, with the coordinates of G2 points ordered as x1, x0, y1, y0
+    /// @param gammaABC the points of the linear combination of the inputs, as x, y pairs
+    /// @return whether e(A, B) * e(-vk_x, gamma) * e(-C, delta) * e(-alpha, beta) == 1
+    function verify(
+            uint256[14] memory vk,
+            uint256[] memory gammaABC,
+            uint256[2] memory a,
+            uint256[2][2] memory b,
+            uint256[2] memory c,
+            uint256[] memory input
+        ) public view returns (bool) {
+        require(gammaABC.length == 2 * (input.length + 1), "Invalid input length");
+        bool success = true;
+
+        // Compute the linear combination vk_x in `acc`, using `term` as input to the multiplication
+        uint256[4] memory acc;
+        uint256[3] memory term;
+        acc[0] = gammaABC[0];
+        acc[1] = gammaABC[1];
+        for (uint256 i = 0; i < input.length; i++) {
+            require(input[i] < SNARK_SCALAR_FIELD, "Public input out of range");
+            term[0] = gammaABC[2 * i + 2];
+            term[1] = gammaABC[2 * i + 3];
+            term[2] = input[i];
+            assembly {
+                success := and(success, staticcall(gas, 7, term, 0x60, add(acc, 0x40), 0x40))
+                success := and(success, staticcall(gas, 6, acc, 0x80, acc, 0x40))
+            }
+        }
+        require(success, "Invalid public input");
+
+        uint256[24] memory p;
+        p[0] = a[0];
+        p[1] = a[1];
+        p[2] = b[0][0];
+        p[3] = b[0][1];
+        p[4] = b[1][0];
+        p[5] = b[1][1];
+        p[6] = acc[0];
+        p[7] = (PRIME_Q - (acc[1] % PRIME_Q)) % PRIME_Q;
+        for (uint256 i = 0; i < 4; i++) {
+            p[8 + i] = vk[6 + i];
+            p[14 + i] = vk[10 + i];
+            p[20 + i] = vk[2 + i];
+        }
+        p[12] = c[0];
+        p[13] = (PRIME_Q - (c[1] % PRIME_Q)) % PRIME_Q;
+        p[18] = vk[0];
+        p[19] = (PRIME_Q - (vk[1] % PRIME_Q)) % PRIME_Q;
+
+        uint256[1] memory out;
+        assembly {
+            success := staticcall(gas, 8, p, 0x300, out, 0x20)
+        }
+        require(success, "Invalid proof");
+        return out[0] == 1;
+    }
+}
+"#;
+
+const LIBRARY_CONTRACT_TEMPLATE: &str = r#"
+contract Verifier {
+    function verifyingKey() pure internal returns (uint256[14] memory vk, uint256[] memory gammaABC) {
+        <%vk%>
+        gammaABC = new uint256[](<%vk_gammaABC_length%>);
+        <%vk_gammaABC%>
+    }
+    function verifyTx(
+            uint[2] memory a,
+            uint[2][2] memory b,
+            uint[2] memory c,
+            uint[<%vk_input_length%>] memory input
+        ) public view returns (bool r) {
+        (uint256[14] memory vk, uint256[] memory gammaABC) = verifyingKey();
+        uint256[] memory inputValues = new uint256[](input.length);
+        for (uint i = 0; i < input.length; i++) {
+            inputValues[i] = input[i];
+        }
+        return Groth16Verifier.verify(vk, gammaABC, a, b, c, inputValues);
+    }
+}
+"#;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -259,9 +1107,8 @@ mod tests {
 
         mod proof {
             use super::*;
-            use crate::flat_absy::FlatVariable;
-            use crate::ir::*;
             use crate::proof_system::bn128::g16::serialize::serialize_proof;
+            use crate::proof_system::bn128::utils::bellman::fixtures::public_identity;
 
             #[allow(dead_code)]
             #[derive(Deserialize)]
@@ -280,18 +1127,7 @@ mod tests {
 
             #[test]
             fn serialize() {
-                let program: Prog<FieldPrime> = Prog {
-                    main: Function {
-                        id: String::from("main"),
-                        arguments: vec![FlatVariable::new(0)],
-                        returns: vec![FlatVariable::public(0)],
-                        statements: vec![Statement::Constraint(
-                            FlatVariable::new(0).into(),
-                            FlatVariable::public(0).into(),
-                        )],
-                    },
-                    private: vec![false],
-                };
+                let program = public_identity();
 
                 let witness = program
                     .clone()
@@ -304,9 +1140,291 @@ mod tests {
                 let params = computation.clone().setup();
                 let proof = computation.prove(&params);
 
-                let serialized_proof = serialize_proof(&proof, &public_inputs_values);
+                let serialized_proof = serialize_proof(&proof, &public_inputs_values, &vec![]);
                 serde_json::from_str::<G16Proof>(&serialized_proof).unwrap();
             }
         }
+
+        mod vk {
+            use super::*;
+            use crate::proof_system::bn128::g16::serialize::{serialize_proof, serialize_vk};
+            use crate::proof_system::bn128::utils::bellman::fixtures::public_identity;
+            use crate::proof_system::VerificationKeyHeader;
+            use tempdir::TempDir;
+
+            #[test]
+            fn verify() {
+                let program = public_identity();
+
+                let witness = program
+                    .clone()
+                    .execute::<FieldPrime>(&vec![FieldPrime::from(42)])
+                    .unwrap();
+                let computation = Computation::with_witness(program, witness);
+
+                let public_inputs_values = computation.public_inputs_values();
+
+                let params = computation.clone().setup();
+                let proof = computation.prove(&params);
+
+                let serialized_vk = serialize_vk(params.vk, 0);
+
+                assert_eq!(
+                    serde_json::from_str::<VerificationKeyHeader>(&serialized_vk).unwrap(),
+                    VerificationKeyHeader {
+                        scheme: String::from("g16"),
+                        curve: String::from("bn128"),
+                        inputs: 2,
+                        committed_inputs: None,
+                    }
+                );
+
+                let dir = TempDir::new("g16_verify").unwrap();
+                let vk_path = dir.path().join("verification.key");
+                let proof_path = dir.path().join("proof.json");
+
+                File::create(&vk_path)
+                    .unwrap()
+                    .write_all(serialized_vk.as_bytes())
+                    .unwrap();
+                File::create(&proof_path)
+                    .unwrap()
+                    .write_all(serialize_proof(&proof, &public_inputs_values, &vec![]).as_bytes())
+                    .unwrap();
+
+                assert!(G16 {}
+                    .verify(
+                        BufReader::new(File::open(&vk_path).unwrap()),
+                        BufReader::new(File::open(&proof_path).unwrap())
+                    )
+                    .unwrap());
+            }
+
+            #[test]
+            fn seeded() {
+                let program = public_identity();
+
+                let witness = program
+                    .clone()
+                    .execute::<FieldPrime>(&vec![FieldPrime::from(42)])
+                    .unwrap();
+
+                let dir = TempDir::new("g16_seeded").unwrap();
+                let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+                let read = |name: &str| std::fs::read(path(name)).unwrap();
+
+                // run setup and proving twice with the same seed and once with another seed
+                for (run, seed) in vec![("a", 42), ("b", 42), ("c", 43)] {
+                    let pk = path(&format!("{}.pk", run));
+                    let vk = path(&format!("{}.vk", run));
+                    let proof = path(&format!("{}.json", run));
+
+                    G16 {}.setup(program.clone(), &pk, &vk, Some(Seed(seed)));
+                    assert!(G16 {}.generate_proof(
+                        program.clone(),
+                        witness.clone(),
+                        &pk,
+                        &proof,
+                        Some(Seed(seed))
+                    ));
+                }
+
+                assert_eq!(read("a.pk"), read("b.pk"));
+                assert_eq!(read("a.vk"), read("b.vk"));
+                assert_eq!(read("a.json"), read("b.json"));
+
+                assert_ne!(read("a.vk"), read("c.vk"));
+                assert_ne!(read("a.json"), read("c.json"));
+            }
+
+            #[test]
+            fn generate_proofs_errors() {
+                let program = public_identity();
+
+                let witness = program
+                    .clone()
+                    .execute::<FieldPrime>(&vec![FieldPrime::from(42)])
+                    .unwrap();
+
+                let dir = TempDir::new("g16_batch").unwrap();
+                let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+
+                // an invalid proving key fails the batch instead of panicking
+                let error = G16 {}
+                    .generate_proofs(program.clone(), vec![], &path("missing.pk"), 1, None)
+                    .unwrap_err();
+                assert!(error.contains("missing.pk"));
+
+                G16 {}.setup(program.clone(), &path("pk"), &path("vk"), Some(Seed(42)));
+
+                // an incomplete witness is reported by name, and the other proofs are still written
+                let jobs = vec![
+                    (witness.clone(), String::from("witness a"), path("a.json")),
+                    (
+                        ir::Witness(std::collections::BTreeMap::new()),
+                        String::from("witness b"),
+                        path("b.json"),
+                    ),
+                    (witness, String::from("witness c"), path("c.json")),
+                ];
+                let error = G16 {}
+                    .generate_proofs(program, jobs, &path("pk"), 2, Some(Seed(42)))
+                    .unwrap_err();
+
+                assert!(error.starts_with("1 proof could not be generated"));
+                assert!(error.contains("Could not prove witness b"));
+                assert!(dir.path().join("a.json").exists());
+                assert!(!dir.path().join("b.json").exists());
+                assert!(dir.path().join("c.json").exists());
+            }
+
+            #[test]
+            fn export_targets() {
+                let program = public_identity();
+
+                let params = Computation::without_witness(program).setup();
+
+                let dir = TempDir::new("g16_export").unwrap();
+                let vk_path = dir.path().join("verification.key");
+                File::create(&vk_path)
+                    .unwrap()
+                    .write_all(serialize_vk(params.vk, 0).as_bytes())
+                    .unwrap();
+
+                let export = |target| {
+                    G16 {}
+                        .export_solidity_verifier_for(
+                            BufReader::new(File::open(&vk_path).unwrap()),
+                            target,
+                        )
+                        .unwrap()
+                };
+
+                assert_eq!(
+                    export(SolidityTarget::Default),
+                    G16 {}
+                        .export_solidity_verifier(BufReader::new(File::open(&vk_path).unwrap()))
+                        .unwrap()
+                );
+
+                let abiv2 = export(SolidityTarget::AbiV2);
+                assert!(abiv2.starts_with("pragma experimental ABIEncoderV2;"));
+                assert!(abiv2.contains("Proof memory proof,"));
+
+                let optimized = export(SolidityTarget::Optimized);
+                assert!(optimized.contains("uint[2] calldata input"));
+                assert!(optimized.contains("GAMMA_ABC_2_X"));
+                assert!(!optimized.contains("<%"));
+
+                let library = export(SolidityTarget::Library);
+                assert!(library.contains("library Groth16Verifier"));
+                assert!(library.contains("gammaABC = new uint256[](6);"));
+                assert!(!library.contains("<%"));
+            }
+
+            #[test]
+            fn export_invalid_key() {
+                let dir = TempDir::new("g16_export_invalid").unwrap();
+                let vk_path = dir.path().join("verification.key");
+                File::create(&vk_path)
+                    .unwrap()
+                    .write_all(b"{\"scheme\": \"g16\"}")
+                    .unwrap();
+
+                assert!(G16 {}
+                    .export_solidity_verifier(BufReader::new(File::open(&vk_path).unwrap()))
+                    .is_err());
+            }
+
+            #[test]
+            fn snarkjs_roundtrip() {
+                let program = public_identity();
+
+                let witness = program
+                    .clone()
+                    .execute::<FieldPrime>(&vec![FieldPrime::from(42)])
+                    .unwrap();
+                let computation = Computation::with_witness(program, witness);
+
+                let public_inputs_values = computation.public_inputs_values();
+
+                let params = computation.clone().setup();
+                let proof = computation.prove(&params);
+
+                let serialized_vk = serialize_vk(params.vk, 0);
+                let serialized_proof = serialize_proof(&proof, &public_inputs_values, &vec![]);
+
+                let snarkjs_vk = G16 {}.export_snarkjs_vk(serialized_vk.as_bytes()).unwrap();
+                let (snarkjs_proof, snarkjs_public) = G16 {}
+                    .export_snarkjs_proof(serialized_proof.as_bytes())
+                    .unwrap();
+
+                assert_eq!(
+                    serde_json::from_str::<Vec<String>>(&snarkjs_public).unwrap(),
+                    vec!["42", "42"]
+                );
+
+                let imported_vk = G16 {}.import_snarkjs_vk(snarkjs_vk.as_bytes()).unwrap();
+                let imported_proof = G16 {}
+                    .import_snarkjs_proof(snarkjs_proof.as_bytes(), snarkjs_public.as_bytes())
+                    .unwrap();
+
+                assert_eq!(
+                    serde_json::from_str::<VerificationKey>(&imported_vk).unwrap(),
+                    serde_json::from_str::<VerificationKey>(&serialized_vk).unwrap()
+                );
+                assert_eq!(
+                    serde_json::from_str::<Proof>(&imported_proof).unwrap(),
+                    serde_json::from_str::<Proof>(&serialized_proof).unwrap()
+                );
+            }
+
+            #[test]
+            fn rerandomize() {
+                let program = public_identity();
+
+                let witness = program
+                    .clone()
+                    .execute::<FieldPrime>(&vec![FieldPrime::from(42)])
+                    .unwrap();
+                let computation = Computation::with_witness(program, witness);
+
+                let public_inputs_values = computation.public_inputs_values();
+
+                let params = computation.clone().setup();
+                let proof = computation.prove(&params);
+
+                let serialized_vk = serialize_vk(params.vk, 0);
+                let serialized_proof = serialize_proof(&proof, &public_inputs_values, &vec![]);
+
+                let rerandomized = G16 {}
+                    .rerandomize(serialized_vk.as_bytes(), serialized_proof.as_bytes())
+                    .unwrap();
+
+                let original = serde_json::from_str::<Proof>(&serialized_proof).unwrap();
+                let fresh = serde_json::from_str::<Proof>(&rerandomized).unwrap();
+
+                assert_eq!(fresh.inputs, original.inputs);
+                assert_ne!(fresh.proof, original.proof);
+
+                let vk = read_vk(serialized_vk.as_bytes()).unwrap();
+                assert!(check_proof(&vk, &fresh).unwrap());
+
+                assert!(G16 {}
+                    .is_rerandomization(
+                        serialized_vk.as_bytes(),
+                        serialized_proof.as_bytes(),
+                        rerandomized.as_bytes()
+                    )
+                    .unwrap());
+                assert!(!G16 {}
+                    .is_rerandomization(
+                        serialized_vk.as_bytes(),
+                        serialized_proof.as_bytes(),
+                        serialized_proof.as_bytes()
+                    )
+                    .unwrap());
+            }
+        }
     }
 }
@@ -2,12 +2,16 @@ extern crate libc;
 
 use self::libc::{c_char, c_int};
 use ir;
-use proof_system::bn128::utils::libsnark::{prepare_generate_proof, prepare_setup};
+use proof_system::bn128::utils::json::{G1Point, G2Point};
+use proof_system::bn128::utils::libsnark::{
+    parse_g1_hex_text, parse_g2_hex_text, prepare_generate_proof, prepare_setup, read_text_vk,
+};
 use proof_system::bn128::utils::solidity::{SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB};
-use proof_system::ProofSystem;
+use proof_system::{AbiParameter, ProofSystem, Seed, SolidityTarget, VerifierInterface};
 use regex::Regex;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufReader, Write};
+use std::path::PathBuf;
 
 use zokrates_field::field::FieldPrime;
 
@@ -45,7 +49,13 @@ extern "C" {
 }
 
 impl ProofSystem for GM17 {
-    fn setup(&self, program: ir::Prog<FieldPrime>, pk_path: &str, vk_path: &str) {
+    fn setup(
+        &self,
+        program: ir::Prog<FieldPrime>,
+        pk_path: &str,
+        vk_path: &str,
+        _seed: Option<Seed>,
+    ) {
         let (
             a_arr,
             b_arr,
@@ -75,6 +85,12 @@ impl ProofSystem for GM17 {
                 vk_path_cstring.as_ptr(),
             );
         }
+
+        let vk = VerificationKey::from_text(vk_path);
+        let mut vk_file = File::create(PathBuf::from(vk_path)).unwrap();
+        vk_file
+            .write(serde_json::to_string_pretty(&vk).unwrap().as_ref())
+            .unwrap();
     }
 
     fn generate_proof(
@@ -83,6 +99,7 @@ impl ProofSystem for GM17 {
         witness: ir::Witness<FieldPrime>,
         pk_path: &str,
         proof_path: &str,
+        _seed: Option<Seed>,
     ) -> bool {
         let (
             pk_path_cstring,
@@ -105,81 +122,91 @@ impl ProofSystem for GM17 {
         }
     }
 
-    fn export_solidity_verifier(&self, reader: BufReader<File>) -> String {
-        let mut lines = reader.lines();
-
-        let mut template_text = String::from(CONTRACT_TEMPLATE);
-        let query_template = String::from("vk.query[index] = Pairing.G1Point(points);"); //copy this for each entry
-
-        //replace things in template
-        let vk_regex = Regex::new(r#"(<%vk_[^i%]*%>)"#).unwrap();
-        let vk_query_len_regex = Regex::new(r#"(<%vk_query_length%>)"#).unwrap();
-        let vk_query_index_regex = Regex::new(r#"index"#).unwrap();
-        let vk_query_points_regex = Regex::new(r#"points"#).unwrap();
-        let vk_query_repeat_regex = Regex::new(r#"(<%vk_query_pts%>)"#).unwrap();
-        let vk_input_len_regex = Regex::new(r#"(<%vk_input_length%>)"#).unwrap();
-
-        for _ in 0..5 {
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-            template_text = vk_regex
-                .replace(template_text.as_str(), current_line_split[1].trim())
-                .into_owned();
-        }
+    fn export_solidity_verifier(&self, reader: BufReader<File>) -> Result<String, String> {
+        let vk: VerificationKey = serde_json::from_reader(reader)
+            .map_err(|why| format!("Invalid verification key: {}", why))?;
 
-        let current_line: String = lines
-            .next()
-            .expect("Unexpected end of file in verification key!")
-            .unwrap();
-        let current_line_split: Vec<&str> = current_line.split("=").collect();
-        assert_eq!(current_line_split.len(), 2);
-        let query_count: i32 = current_line_split[1].trim().parse().unwrap();
-
-        template_text = vk_query_len_regex
-            .replace(template_text.as_str(), format!("{}", query_count).as_str())
-            .into_owned();
-        template_text = vk_input_len_regex
-            .replace(
-                template_text.as_str(),
-                format!("{}", query_count - 1).as_str(),
-            )
-            .into_owned();
-
-        let mut query_repeat_text = String::new();
-        for x in 0..query_count {
-            let mut curr_template = query_template.clone();
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-            curr_template = vk_query_index_regex
-                .replace(curr_template.as_str(), format!("{}", x).as_str())
-                .into_owned();
-            curr_template = vk_query_points_regex
-                .replace(curr_template.as_str(), current_line_split[1].trim())
-                .into_owned();
-            query_repeat_text.push_str(curr_template.as_str());
-            if x < query_count - 1 {
-                query_repeat_text.push_str("\n        ");
-            }
-        }
-        template_text = vk_query_repeat_regex
-            .replace(template_text.as_str(), query_repeat_text.as_str())
-            .into_owned();
+        let query_repeat_text = vk
+            .query
+            .iter()
+            .enumerate()
+            .map(|(index, point)| format!("vk.query[{}] = Pairing.G1Point({});", index, point))
+            .collect::<Vec<_>>()
+            .join("\n        ");
+
+        let template_text = CONTRACT_TEMPLATE
+            .replace("<%vk_h%>", &vk.h.to_string())
+            .replace("<%vk_g_alpha%>", &vk.g_alpha.to_string())
+            .replace("<%vk_h_beta%>", &vk.h_beta.to_string())
+            .replace("<%vk_g_gamma%>", &vk.g_gamma.to_string())
+            .replace("<%vk_h_gamma%>", &vk.h_gamma.to_string())
+            .replace("<%vk_query_length%>", &vk.query.len().to_string())
+            .replace("<%vk_query_pts%>", &query_repeat_text)
+            .replace("<%vk_input_length%>", &vk.inputs.to_string());
 
         let re = Regex::new(r"(?P<v>0[xX][0-9a-fA-F]{64})").unwrap();
-        template_text = re.replace_all(&template_text, "uint256($v)").to_string();
+        let template_text = re.replace_all(&template_text, "uint256($v)").to_string();
 
-        format!(
+        Ok(format!(
             "{}{}{}",
             SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB, template_text
-        )
+        ))
+    }
+
+    fn verifier_interface(
+        &self,
+        inputs: usize,
+        target: SolidityTarget,
+    ) -> Result<VerifierInterface, String> {
+        match target {
+            SolidityTarget::Default => Ok(VerifierInterface::new(vec![
+                AbiParameter::new("a", "uint256[2]"),
+                AbiParameter::new("b", "uint256[2][2]"),
+                AbiParameter::new("c", "uint256[2]"),
+                AbiParameter::new("input", format!("uint256[{}]", inputs)),
+            ])
+            .with_verified_event()),
+            target => Err(format!(
+                "The {} target is not supported for this proving scheme",
+                target
+            )),
+        }
+    }
+}
+
+/// A GM17 verification key as written to the verification key file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct VerificationKey {
+    scheme: String,
+    curve: String,
+    inputs: usize,
+    h: G2Point,
+    g_alpha: G1Point,
+    h_beta: G2Point,
+    g_gamma: G1Point,
+    h_gamma: G2Point,
+    query: Vec<G1Point>,
+}
+
+impl VerificationKey {
+    // libsnark writes the verification key as `name = value` lines, which we convert to json
+    fn from_text(vk_path: &str) -> Self {
+        let values = read_text_vk(vk_path);
+
+        // the values are followed by the length of `query` and its points
+        let query: Vec<_> = values[6..].iter().map(|v| parse_g1_hex_text(v)).collect();
+
+        VerificationKey {
+            scheme: String::from("gm17"),
+            curve: String::from("bn128"),
+            inputs: query.len() - 1,
+            h: parse_g2_hex_text(&values[0]),
+            g_alpha: parse_g1_hex_text(&values[1]),
+            h_beta: parse_g2_hex_text(&values[2]),
+            g_gamma: parse_g1_hex_text(&values[3]),
+            h_gamma: parse_g2_hex_text(&values[4]),
+            query,
+        }
     }
 }
 
@@ -11,3 +11,4 @@ pub use self::g16::G16;
 pub use self::gm17::GM17;
 #[cfg(feature = "libsnark")]
 pub use self::pghr13::PGHR13;
+pub use self::utils::bellman::field_from_hex;
@@ -2,13 +2,17 @@ extern crate libc;
 
 use self::libc::{c_char, c_int};
 use ir;
-use proof_system::bn128::utils::libsnark::{prepare_generate_proof, prepare_setup};
+use proof_system::bn128::utils::json::{G1Point, G2Point};
+use proof_system::bn128::utils::libsnark::{
+    parse_g1_hex_text, parse_g2_hex_text, prepare_generate_proof, prepare_setup, read_text_vk,
+};
 use proof_system::bn128::utils::solidity::{SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB};
-use proof_system::ProofSystem;
+use proof_system::{AbiParameter, ProofSystem, Seed, SolidityTarget, VerifierInterface};
 
 use regex::Regex;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufReader, Write};
+use std::path::PathBuf;
 
 use zokrates_field::field::FieldPrime;
 
@@ -46,7 +50,13 @@ extern "C" {
 }
 
 impl ProofSystem for PGHR13 {
-    fn setup(&self, program: ir::Prog<FieldPrime>, pk_path: &str, vk_path: &str) {
+    fn setup(
+        &self,
+        program: ir::Prog<FieldPrime>,
+        pk_path: &str,
+        vk_path: &str,
+        _seed: Option<Seed>,
+    ) {
         let (
             a_arr,
             b_arr,
@@ -76,6 +86,12 @@ impl ProofSystem for PGHR13 {
                 vk_path_cstring.as_ptr(),
             );
         }
+
+        let vk = VerificationKey::from_text(vk_path);
+        let mut vk_file = File::create(PathBuf::from(vk_path)).unwrap();
+        vk_file
+            .write(serde_json::to_string_pretty(&vk).unwrap().as_ref())
+            .unwrap();
     }
 
     fn generate_proof(
@@ -84,6 +100,7 @@ impl ProofSystem for PGHR13 {
         witness: ir::Witness<FieldPrime>,
         pk_path: &str,
         proof_path: &str,
+        _seed: Option<Seed>,
     ) -> bool {
         let (
             pk_path_cstring,
@@ -111,78 +128,102 @@ impl ProofSystem for PGHR13 {
         }
     }
 
-    fn export_solidity_verifier(&self, reader: BufReader<File>) -> String {
-        let mut lines = reader.lines();
+    fn export_solidity_verifier(&self, reader: BufReader<File>) -> Result<String, String> {
+        let vk: VerificationKey = serde_json::from_reader(reader)
+            .map_err(|why| format!("Invalid verification key: {}", why))?;
 
-        let mut template_text = String::from(CONTRACT_TEMPLATE);
-        let ic_template = String::from("vk.IC[index] = Pairing.G1Point(points);"); //copy this for each entry
+        let ic_repeat_text = vk
+            .ic
+            .iter()
+            .enumerate()
+            .map(|(index, point)| format!("vk.IC[{}] = Pairing.G1Point({});", index, point))
+            .collect::<Vec<_>>()
+            .join("\n        ");
 
-        //replace things in template
-        let vk_regex = Regex::new(r#"(<%vk_[^i%]*%>)"#).unwrap();
-        let vk_ic_len_regex = Regex::new(r#"(<%vk_ic_length%>)"#).unwrap();
-        let vk_ic_index_regex = Regex::new(r#"index"#).unwrap();
-        let vk_ic_points_regex = Regex::new(r#"points"#).unwrap();
-        let vk_ic_repeat_regex = Regex::new(r#"(<%vk_ic_pts%>)"#).unwrap();
-        let vk_input_len_regex = Regex::new(r#"(<%vk_input_length%>)"#).unwrap();
+        let template_text = CONTRACT_TEMPLATE
+            .replace("<%vk_a%>", &vk.a.to_string())
+            .replace("<%vk_b%>", &vk.b.to_string())
+            .replace("<%vk_c%>", &vk.c.to_string())
+            .replace("<%vk_g%>", &vk.gamma.to_string())
+            .replace("<%vk_gb1%>", &vk.gamma_beta_1.to_string())
+            .replace("<%vk_gb2%>", &vk.gamma_beta_2.to_string())
+            .replace("<%vk_z%>", &vk.z.to_string())
+            .replace("<%vk_ic_length%>", &vk.ic.len().to_string())
+            .replace("<%vk_ic_pts%>", &ic_repeat_text)
+            .replace("<%vk_input_length%>", &vk.inputs.to_string());
 
-        for _ in 0..7 {
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-            template_text = vk_regex
-                .replace(template_text.as_str(), current_line_split[1].trim())
-                .into_owned();
-        }
-
-        let current_line: String = lines
-            .next()
-            .expect("Unexpected end of file in verification key!")
-            .unwrap();
-        let current_line_split: Vec<&str> = current_line.split("=").collect();
-        assert_eq!(current_line_split.len(), 2);
-        let ic_count: i32 = current_line_split[1].trim().parse().unwrap();
+        let re = Regex::new(r"(?P<v>0[xX][0-9a-fA-F]{64})").unwrap();
+        let template_text = re.replace_all(&template_text, "uint256($v)").to_string();
 
-        template_text = vk_ic_len_regex
-            .replace(template_text.as_str(), format!("{}", ic_count).as_str())
-            .into_owned();
-        template_text = vk_input_len_regex
-            .replace(template_text.as_str(), format!("{}", ic_count - 1).as_str())
-            .into_owned();
+        Ok(format!(
+            "{}{}{}",
+            SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB, template_text
+        ))
+    }
 
-        let mut ic_repeat_text = String::new();
-        for x in 0..ic_count {
-            let mut curr_template = ic_template.clone();
-            let current_line: String = lines
-                .next()
-                .expect("Unexpected end of file in verification key!")
-                .unwrap();
-            let current_line_split: Vec<&str> = current_line.split("=").collect();
-            assert_eq!(current_line_split.len(), 2);
-            curr_template = vk_ic_index_regex
-                .replace(curr_template.as_str(), format!("{}", x).as_str())
-                .into_owned();
-            curr_template = vk_ic_points_regex
-                .replace(curr_template.as_str(), current_line_split[1].trim())
-                .into_owned();
-            ic_repeat_text.push_str(curr_template.as_str());
-            if x < ic_count - 1 {
-                ic_repeat_text.push_str("\n        ");
-            }
+    fn verifier_interface(
+        &self,
+        inputs: usize,
+        target: SolidityTarget,
+    ) -> Result<VerifierInterface, String> {
+        match target {
+            SolidityTarget::Default => Ok(VerifierInterface::new(vec![
+                AbiParameter::new("a", "uint256[2]"),
+                AbiParameter::new("a_p", "uint256[2]"),
+                AbiParameter::new("b", "uint256[2][2]"),
+                AbiParameter::new("b_p", "uint256[2]"),
+                AbiParameter::new("c", "uint256[2]"),
+                AbiParameter::new("c_p", "uint256[2]"),
+                AbiParameter::new("h", "uint256[2]"),
+                AbiParameter::new("k", "uint256[2]"),
+                AbiParameter::new("input", format!("uint256[{}]", inputs)),
+            ])
+            .with_verified_event()),
+            target => Err(format!(
+                "The {} target is not supported for this proving scheme",
+                target
+            )),
         }
-        template_text = vk_ic_repeat_regex
-            .replace(template_text.as_str(), ic_repeat_text.as_str())
-            .into_owned();
+    }
+}
 
-        let re = Regex::new(r"(?P<v>0[xX][0-9a-fA-F]{64})").unwrap();
-        template_text = re.replace_all(&template_text, "uint256($v)").to_string();
+/// A PGHR13 verification key as written to the verification key file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct VerificationKey {
+    scheme: String,
+    curve: String,
+    inputs: usize,
+    a: G2Point,
+    b: G1Point,
+    c: G2Point,
+    gamma: G2Point,
+    gamma_beta_1: G1Point,
+    gamma_beta_2: G2Point,
+    z: G2Point,
+    ic: Vec<G1Point>,
+}
 
-        format!(
-            "{}{}{}",
-            SOLIDITY_G2_ADDITION_LIB, SOLIDITY_PAIRING_LIB, template_text
-        )
+impl VerificationKey {
+    // libsnark writes the verification key as `name = value` lines, which we convert to json
+    fn from_text(vk_path: &str) -> Self {
+        let values = read_text_vk(vk_path);
+
+        // the values are followed by the length of `ic` and its points
+        let ic: Vec<_> = values[8..].iter().map(|v| parse_g1_hex_text(v)).collect();
+
+        VerificationKey {
+            scheme: String::from("pghr13"),
+            curve: String::from("bn128"),
+            inputs: ic.len() - 1,
+            a: parse_g2_hex_text(&values[0]),
+            b: parse_g1_hex_text(&values[1]),
+            c: parse_g2_hex_text(&values[2]),
+            gamma: parse_g2_hex_text(&values[3]),
+            gamma_beta_1: parse_g1_hex_text(&values[4]),
+            gamma_beta_2: parse_g2_hex_text(&values[5]),
+            z: parse_g2_hex_text(&values[6]),
+            ic,
+        }
     }
 }
 
@@ -1,11 +1,14 @@
 extern crate rand;
 
 use crate::ir::{CanonicalLinComb, Prog, Statement, Witness};
+use crate::proof_system::Seed;
 use bellman::groth16::Proof;
 use bellman::groth16::{
     create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
-    Parameters,
+    Parameters, VerifyingKey,
 };
+use bellman::pairing::ff::{Field as PairingField, PrimeField};
+use bellman::pairing::{CurveAffine, CurveProjective};
 use bellman::{Circuit, ConstraintSystem, LinearCombination, SynthesisError, Variable};
 use pairing::bn256::{Bn256, Fr};
 use std::collections::BTreeMap;
@@ -43,42 +46,43 @@ fn bellman_combination<CS: ConstraintSystem<Bn256>>(
     cs: &mut CS,
     symbols: &mut BTreeMap<FlatVariable, Variable>,
     witness: &mut Witness<FieldPrime>,
-) -> LinearCombination<Bn256> {
-    l.0.into_iter()
-        .map(|(k, v)| {
-            (
-                v.into_bellman(),
-                symbols
-                    .entry(k)
-                    .or_insert_with(|| {
-                        match k.is_output() {
-                            true => cs.alloc_input(
-                                || format!("{}", k),
-                                || {
-                                    Ok(witness
-                                        .0
-                                        .remove(&k)
-                                        .ok_or(SynthesisError::AssignmentMissing)?
-                                        .into_bellman())
-                                },
-                            ),
-                            false => cs.alloc(
-                                || format!("{}", k),
-                                || {
-                                    Ok(witness
-                                        .0
-                                        .remove(&k)
-                                        .ok_or(SynthesisError::AssignmentMissing)?
-                                        .into_bellman())
-                                },
-                            ),
-                        }
-                        .unwrap()
-                    })
-                    .clone(),
-            )
-        })
-        .fold(LinearCombination::zero(), |acc, e| acc + e)
+) -> Result<LinearCombination<Bn256>, SynthesisError> {
+    let mut res = LinearCombination::zero();
+
+    for (k, v) in l.0 {
+        let variable = match symbols.get(&k).cloned() {
+            Some(variable) => variable,
+            None => {
+                let variable = match k.is_output() {
+                    true => cs.alloc_input(
+                        || format!("{}", k),
+                        || {
+                            Ok(witness
+                                .0
+                                .remove(&k)
+                                .ok_or(SynthesisError::AssignmentMissing)?
+                                .into_bellman())
+                        },
+                    ),
+                    false => cs.alloc(
+                        || format!("{}", k),
+                        || {
+                            Ok(witness
+                                .0
+                                .remove(&k)
+                                .ok_or(SynthesisError::AssignmentMissing)?
+                                .into_bellman())
+                        },
+                    ),
+                }?;
+                symbols.insert(k, variable);
+                variable
+            }
+        };
+        res = res + (v.into_bellman(), variable);
+    }
+
+    Ok(res)
 }
 
 impl Prog<FieldPrime> {
@@ -94,39 +98,31 @@ impl Prog<FieldPrime> {
 
         assert!(symbols.insert(FlatVariable::one(), CS::one()).is_none());
 
-        symbols.extend(
-            self.main
-                .arguments
-                .iter()
-                .zip(self.private)
-                .enumerate()
-                .map(|(index, (var, private))| {
-                    let wire = match private {
-                        true => cs.alloc(
-                            || format!("PRIVATE_INPUT_{}", index),
-                            || {
-                                Ok(witness
-                                    .0
-                                    .remove(&var)
-                                    .ok_or(SynthesisError::AssignmentMissing)?
-                                    .into_bellman())
-                            },
-                        ),
-                        false => cs.alloc_input(
-                            || format!("PUBLIC_INPUT_{}", index),
-                            || {
-                                Ok(witness
-                                    .0
-                                    .remove(&var)
-                                    .ok_or(SynthesisError::AssignmentMissing)?
-                                    .into_bellman())
-                            },
-                        ),
-                    }
-                    .unwrap();
-                    (var.clone(), wire)
-                }),
-        );
+        for (index, (var, private)) in self.main.arguments.iter().zip(self.private).enumerate() {
+            let wire = match private {
+                true => cs.alloc(
+                    || format!("PRIVATE_INPUT_{}", index),
+                    || {
+                        Ok(witness
+                            .0
+                            .remove(var)
+                            .ok_or(SynthesisError::AssignmentMissing)?
+                            .into_bellman())
+                    },
+                ),
+                false => cs.alloc_input(
+                    || format!("PUBLIC_INPUT_{}", index),
+                    || {
+                        Ok(witness
+                            .0
+                            .remove(var)
+                            .ok_or(SynthesisError::AssignmentMissing)?
+                            .into_bellman())
+                    },
+                ),
+            }?;
+            symbols.insert(var.clone(), wire);
+        }
 
         let main = self.main;
 
@@ -138,15 +134,15 @@ impl Prog<FieldPrime> {
                         cs,
                         &mut symbols,
                         &mut witness,
-                    );
+                    )?;
                     let b = &bellman_combination(
                         quad.right.clone().as_canonical(),
                         cs,
                         &mut symbols,
                         &mut witness,
-                    );
+                    )?;
                     let c =
-                        &bellman_combination(lin.as_canonical(), cs, &mut symbols, &mut witness);
+                        &bellman_combination(lin.as_canonical(), cs, &mut symbols, &mut witness)?;
 
                     cs.enforce(|| "Constraint", |lc| lc + a, |lc| lc + b, |lc| lc + c);
                 }
@@ -160,17 +156,36 @@ impl Prog<FieldPrime> {
 
 impl Computation<FieldPrime> {
     pub fn prove(self, params: &Parameters<Bn256>) -> Proof<Bn256> {
-        let rng = &mut thread_rng();
-        let proof = create_random_proof(self.clone(), params, rng).unwrap();
+        self.prove_with_rng(params, &mut thread_rng())
+    }
+
+    pub fn prove_with_rng<R: Rng>(self, params: &Parameters<Bn256>, rng: &mut R) -> Proof<Bn256> {
+        self.try_prove_with_rng(params, rng)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Proves the computation like `prove_with_rng`, returning an error if the witness is
+    /// incomplete or does not satisfy the constraints of the program
+    pub fn try_prove_with_rng<R: Rng>(
+        self,
+        params: &Parameters<Bn256>,
+        rng: &mut R,
+    ) -> Result<Proof<Bn256>, String> {
+        let proof = create_random_proof(self.clone(), params, rng)
+            .map_err(|e| format!("Could not create proof: {:?}", e))?;
 
         let pvk = prepare_verifying_key(&params.vk);
 
         // extract public inputs
         let public_inputs = self.public_inputs_values();
 
-        assert!(verify_proof(&pvk, &proof, &public_inputs).unwrap());
-
-        proof
+        match verify_proof(&pvk, &proof, &public_inputs) {
+            Ok(true) => Ok(proof),
+            Ok(false) => Err(String::from(
+                "The witness does not satisfy the constraints of the program",
+            )),
+            Err(e) => Err(format!("Could not verify proof: {:?}", e)),
+        }
     }
 
     pub fn public_inputs_values(&self) -> Vec<Fr> {
@@ -189,12 +204,67 @@ impl Computation<FieldPrime> {
     }
 
     pub fn setup(self) -> Parameters<Bn256> {
-        let rng = &mut thread_rng();
+        self.setup_with_rng(&mut thread_rng())
+    }
+
+    pub fn setup_with_rng<R: Rng>(self, rng: &mut R) -> Parameters<Bn256> {
         // run setup phase
         generate_random_parameters(self, rng).unwrap()
     }
 }
 
+/// Returns the source of randomness for setup and proving, which is only secure if no `seed` is
+/// given. Different `stream`s yield independent sequences for the same seed, for example one per
+/// proof of a batch.
+pub fn seeded_rng(seed: Option<Seed>, stream: usize) -> Box<dyn Rng> {
+    match seed {
+        Some(Seed(seed)) => {
+            let stream = stream as u64;
+            Box::new(ChaChaRng::from_seed(&[
+                seed as u32,
+                (seed >> 32) as u32,
+                stream as u32,
+                (stream >> 32) as u32,
+            ]))
+        }
+        None => Box::new(thread_rng()),
+    }
+}
+
+/// Returns a fresh proof for the same statement as `proof`, which cannot be linked to it.
+///
+/// With random `r1 != 0` and `r2`, the proof `(A / r1, r1 * B + r1 * r2 * delta, C + r2 * A)`
+/// satisfies the same verification equation as `(A, B, C)` and is distributed like a proof
+/// generated from scratch.
+pub fn rerandomize(proof: &Proof<Bn256>, vk: &VerifyingKey<Bn256>) -> Proof<Bn256> {
+    let rng = &mut thread_rng();
+
+    let (r1, r1_inverse) = loop {
+        let r1: Fr = rng.gen();
+        if let Some(inverse) = r1.inverse() {
+            break (r1, inverse);
+        }
+    };
+    let r2: Fr = rng.gen();
+
+    let mut r1_r2 = r1;
+    r1_r2.mul_assign(&r2);
+
+    let a = proof.a.mul(r1_inverse.into_repr());
+
+    let mut b = proof.b.mul(r1.into_repr());
+    b.add_assign(&vk.delta_g2.mul(r1_r2.into_repr()));
+
+    let mut c = proof.c.into_projective();
+    c.add_assign(&proof.a.mul(r2.into_repr()));
+
+    Proof {
+        a: a.into_affine(),
+        b: b.into_affine(),
+        c: c.into_affine(),
+    }
+}
+
 impl Circuit<Bn256> for Computation<FieldPrime> {
     fn synthesize<CS: ConstraintSystem<Bn256>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
         self.program.synthesize(cs, self.witness)
@@ -205,6 +275,10 @@ mod parse {
     use lazy_static::lazy_static;
 
     use super::*;
+    use crate::proof_system::bn128::utils::json::{G1Point, G2Point};
+    use bellman::pairing::ff::{PrimeField, PrimeFieldRepr};
+    use bellman::pairing::{CurveAffine, EncodedPoint};
+    use pairing::bn256::{FrRepr, G1Affine, G2Affine};
     use regex::Regex;
 
     lazy_static! {
@@ -277,22 +351,110 @@ mod parse {
         format!("\"{}\"", parsed)
     }
 
-    pub fn parse_g1_hex(e: &<Bn256 as bellman::pairing::Engine>::G1Affine) -> String {
+    pub fn parse_g1_point(e: &<Bn256 as bellman::pairing::Engine>::G1Affine) -> G1Point {
         let parsed = parse_g1(e);
 
-        format!("{}, {}", parsed.0, parsed.1)
+        G1Point(parsed.0, parsed.1)
     }
 
-    pub fn parse_g2_hex(e: &<Bn256 as bellman::pairing::Engine>::G2Affine) -> String {
+    pub fn parse_g2_point(e: &<Bn256 as bellman::pairing::Engine>::G2Affine) -> G2Point {
         let parsed = parse_g2(e);
 
-        format!("[{}, {}], [{}, {}]", parsed.0, parsed.1, parsed.2, parsed.3,)
+        G2Point((parsed.0, parsed.1), (parsed.2, parsed.3))
+    }
+
+    // decode a hexadecimal number to 32 big-endian bytes
+    fn hex_to_bytes(s: &str) -> Result<Vec<u8>, String> {
+        let digits = s.trim_start_matches("0x").trim_start_matches("0X");
+
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("Invalid hexadecimal number {}", s));
+        }
+
+        if digits.len() > 64 {
+            return Err(format!("{} does not fit in 32 bytes", s));
+        }
+
+        let digits = format!("{:0>64}", digits);
+
+        (0..32)
+            .map(|i| {
+                u8::from_str_radix(&digits[2 * i..2 * i + 2], 16)
+                    .map_err(|_| format!("Invalid hexadecimal number {}", s))
+            })
+            .collect()
+    }
+
+    pub fn g1_from_point(p: &G1Point) -> Result<G1Affine, String> {
+        let mut encoded = <G1Affine as CurveAffine>::Uncompressed::empty();
+        {
+            let bytes = encoded.as_mut();
+            bytes[0..32].copy_from_slice(&hex_to_bytes(&p.0)?);
+            bytes[32..64].copy_from_slice(&hex_to_bytes(&p.1)?);
+        }
+        encoded
+            .into_affine()
+            .map_err(|e| format!("Invalid G1 point {}: {}", p, e))
+    }
+
+    pub fn g2_from_point(p: &G2Point) -> Result<G2Affine, String> {
+        let mut encoded = <G2Affine as CurveAffine>::Uncompressed::empty();
+        {
+            // the encoding expects the same coefficient order as our representation: x1, x0, y1, y0
+            let bytes = encoded.as_mut();
+            bytes[0..32].copy_from_slice(&hex_to_bytes(&(p.0).0)?);
+            bytes[32..64].copy_from_slice(&hex_to_bytes(&(p.0).1)?);
+            bytes[64..96].copy_from_slice(&hex_to_bytes(&(p.1).0)?);
+            bytes[96..128].copy_from_slice(&hex_to_bytes(&(p.1).1)?);
+        }
+        encoded
+            .into_affine()
+            .map_err(|e| format!("Invalid G2 point {}: {}", p, e))
+    }
+
+    pub fn fr_from_hex(s: &str) -> Result<Fr, String> {
+        let mut repr = FrRepr::default();
+        repr.read_be(&hex_to_bytes(s)?[..])
+            .map_err(|e| format!("Invalid field element {}: {}", s, e))?;
+        Fr::from_repr(repr).map_err(|e| format!("Invalid field element {}: {}", s, e))
+    }
+
+    /// Parses a field element written as a hexadecimal number, rejecting values outside the field
+    pub fn field_from_hex(s: &str) -> Result<FieldPrime, String> {
+        fr_from_hex(s).map(FieldPrime::from_bellman)
+    }
+}
+
+#[cfg(test)]
+pub mod fixtures {
+    use super::*;
+    use crate::flat_absy::SourceNames;
+    use crate::ir::Function;
+    use zokrates_field::field::FieldPrime;
+
+    /// The program `def main(field a) -> (field): return a` with a public argument
+    pub fn public_identity() -> Prog<FieldPrime> {
+        Prog {
+            main: Function {
+                id: String::from("main"),
+                arguments: vec![FlatVariable::new(0)],
+                returns: vec![FlatVariable::public(0)],
+                statements: vec![Statement::Constraint(
+                    FlatVariable::new(0).into(),
+                    FlatVariable::public(0).into(),
+                )],
+            },
+            private: vec![false],
+            committed: vec![],
+            names: SourceNames::default(),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::flat_absy::SourceNames;
     use crate::ir::{Function, LinComb};
     use zokrates_field::field::FieldPrime;
 
@@ -309,6 +471,8 @@ mod tests {
                     statements: vec![],
                 },
                 private: vec![],
+                committed: vec![],
+                names: SourceNames::default(),
             };
 
             let witness = program.clone().execute::<FieldPrime>(&vec![]).unwrap();
@@ -331,6 +495,8 @@ mod tests {
                     )],
                 },
                 private: vec![true],
+                committed: vec![],
+                names: SourceNames::default(),
             };
 
             let witness = program
@@ -356,6 +522,8 @@ mod tests {
                     )],
                 },
                 private: vec![false],
+                committed: vec![],
+                names: SourceNames::default(),
             };
 
             let witness = program
@@ -381,6 +549,8 @@ mod tests {
                     )],
                 },
                 private: vec![],
+                committed: vec![],
+                names: SourceNames::default(),
             };
 
             let witness = program.clone().execute::<FieldPrime>(&vec![]).unwrap();
@@ -415,6 +585,8 @@ mod tests {
                     ],
                 },
                 private: vec![true, false],
+                committed: vec![],
+                names: SourceNames::default(),
             };
 
             let witness = program
@@ -440,6 +612,8 @@ mod tests {
                     )],
                 },
                 private: vec![false],
+                committed: vec![],
+                names: SourceNames::default(),
             };
 
             let witness = program
@@ -452,6 +626,28 @@ mod tests {
             let _proof = computation.prove(&params);
         }
 
+        #[test]
+        fn points_roundtrip() {
+            let program = fixtures::public_identity();
+
+            let witness = program
+                .clone()
+                .execute::<FieldPrime>(&vec![FieldPrime::from(42)])
+                .unwrap();
+            let computation = Computation::with_witness(program, witness);
+
+            let params = computation.clone().setup();
+            let public_inputs = computation.public_inputs_values();
+            let proof = computation.prove(&params);
+
+            assert_eq!(g1_from_point(&parse_g1_point(&proof.a)).unwrap(), proof.a);
+            assert_eq!(g2_from_point(&parse_g2_point(&proof.b)).unwrap(), proof.b);
+            assert_eq!(
+                fr_from_hex(parse_fr_json(&public_inputs[0]).trim_matches('"')).unwrap(),
+                public_inputs[0]
+            );
+        }
+
         #[test]
         fn with_directives() {
             let program: Prog<FieldPrime> = Prog {
@@ -467,6 +663,8 @@ mod tests {
                     )],
                 },
                 private: vec![true, false],
+                committed: vec![],
+                names: SourceNames::default(),
             };
 
             let witness = program
@@ -479,4 +677,18 @@ mod tests {
             let _proof = computation.prove(&params);
         }
     }
+
+    #[test]
+    fn field_from_hex_invalid() {
+        assert_eq!(field_from_hex("0x2a").unwrap(), FieldPrime::from(42));
+        assert_eq!(field_from_hex("0x02a").unwrap(), FieldPrime::from(42));
+        // non-ASCII digits, values outside the field and values larger than 32 bytes are rejected
+        assert!(field_from_hex("0xé1").is_err());
+        assert!(field_from_hex(
+            "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001"
+        )
+        .is_err());
+        assert!(field_from_hex(&"1".repeat(65)).is_err());
+        assert!(field_from_hex("0xzz").is_err());
+    }
 }
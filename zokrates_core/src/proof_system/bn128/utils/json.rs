@@ -0,0 +1,61 @@
+//! Typed JSON representations of elliptic curve points, used in keys and proofs.
+
+use std::fmt;
+
+/// A point on G1, as hexadecimal coordinates `[x, y]`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct G1Point(pub String, pub String);
+
+/// A point on G2, as hexadecimal coordinates `[[x1, x0], [y1, y0]]`
+///
+/// The coefficients of each coordinate are ordered as expected by the Ethereum precompiles.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct G2Point(pub (String, String), pub (String, String));
+
+impl fmt::Display for G1Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, {}", self.0, self.1)
+    }
+}
+
+impl fmt::Display for G2Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[{}, {}], [{}, {}]",
+            (self.0).0,
+            (self.0).1,
+            (self.1).0,
+            (self.1).1
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_g1() {
+        let p = G1Point(String::from("0x01"), String::from("0x02"));
+        assert_eq!(serde_json::to_string(&p).unwrap(), r#"["0x01","0x02"]"#);
+        assert_eq!(p.to_string(), "0x01, 0x02");
+    }
+
+    #[test]
+    fn serialize_g2() {
+        let p = G2Point(
+            (String::from("0x01"), String::from("0x02")),
+            (String::from("0x03"), String::from("0x04")),
+        );
+        assert_eq!(
+            serde_json::to_string(&p).unwrap(),
+            r#"[["0x01","0x02"],["0x03","0x04"]]"#
+        );
+        assert_eq!(
+            serde_json::from_str::<G2Point>(r#"[["0x01","0x02"],["0x03","0x04"]]"#).unwrap(),
+            p
+        );
+        assert_eq!(p.to_string(), "[0x01, 0x02], [0x03, 0x04]");
+    }
+}
@@ -1,8 +1,10 @@
-use flat_absy::FlatVariable;
-use ir::{self, Statement};
+use ir::{self, r1cs_program};
+use proof_system::bn128::utils::json::{G1Point, G2Point};
+use regex::Regex;
 use std::cmp::max;
-use std::collections::HashMap;
 use std::ffi::CString;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 use zokrates_field::field::Field;
 
 // utility function. Converts a Fields vector-based byte representation to fixed size array.
@@ -192,115 +194,38 @@ pub fn prepare_generate_proof<T: Field>(
     )
 }
 
-/// Returns the index of `var` in `variables`, adding `var` with incremented index if it not yet exists.
-///
-/// # Arguments
-///
-/// * `variables` - A mutual map that maps all existing variables to their index.
-/// * `var` - Variable to be searched for.
-pub fn provide_variable_idx(
-    variables: &mut HashMap<FlatVariable, usize>,
-    var: &FlatVariable,
-) -> usize {
-    let index = variables.len();
-    *variables.entry(*var).or_insert(index)
+/// Reads the values of a verification key written by libsnark as `name = value` lines, in order.
+pub fn read_text_vk(vk_path: &str) -> Vec<String> {
+    let file = File::open(vk_path).expect("Could not open verification key!");
+
+    BufReader::new(file)
+        .lines()
+        .map(|line| line.unwrap())
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let split: Vec<&str> = line.split("=").collect();
+            assert_eq!(split.len(), 2);
+            split[1].trim().to_string()
+        })
+        .collect()
 }
 
-/// Calculates one R1CS row representation of a program and returns (V, A, B, C) so that:
-/// * `V` contains all used variables and the index in the vector represents the used number in `A`, `B`, `C`
-/// * `<A,x>*<B,x> = <C,x>` for a witness `x`
-///
-/// # Arguments
-///
-/// * `prog` - The program the representation is calculated for.
-pub fn r1cs_program<T: Field>(
-    prog: ir::Prog<T>,
-) -> (
-    Vec<FlatVariable>,
-    usize,
-    Vec<Vec<(usize, T)>>,
-    Vec<Vec<(usize, T)>>,
-    Vec<Vec<(usize, T)>>,
-) {
-    let mut variables: HashMap<FlatVariable, usize> = HashMap::new();
-    provide_variable_idx(&mut variables, &FlatVariable::one());
-
-    for x in prog
-        .main
-        .arguments
-        .iter()
-        .enumerate()
-        .filter(|(index, _)| !prog.private[*index])
-    {
-        provide_variable_idx(&mut variables, &x.1);
-    }
-
-    //Only the main function is relevant in this step, since all calls to other functions were resolved during flattening
-    let main = prog.main;
-
-    //~out are added after main's arguments as we want variables (columns)
-    //in the r1cs to be aligned like "public inputs | private inputs"
-    let main_return_count = main.returns.len();
-
-    for i in 0..main_return_count {
-        provide_variable_idx(&mut variables, &FlatVariable::public(i));
-    }
-
-    // position where private part of witness starts
-    let private_inputs_offset = variables.len();
-
-    // first pass through statements to populate `variables`
-    for (quad, lin) in main.statements.iter().filter_map(|s| match s {
-        Statement::Constraint(quad, lin) => Some((quad, lin)),
-        Statement::Directive(..) => None,
-    }) {
-        for (k, _) in &quad.left.0 {
-            provide_variable_idx(&mut variables, &k);
-        }
-        for (k, _) in &quad.right.0 {
-            provide_variable_idx(&mut variables, &k);
-        }
-        for (k, _) in &lin.0 {
-            provide_variable_idx(&mut variables, &k);
-        }
-    }
-
-    let mut a = vec![];
-    let mut b = vec![];
-    let mut c = vec![];
+// extract the hexadecimal numbers of a point written as `0x.., 0x..` or `[0x.., 0x..], [0x.., 0x..]`
+fn hex_numbers(s: &str) -> Vec<String> {
+    let re = Regex::new(r"0[xX][0-9a-fA-F]+").unwrap();
+    re.find_iter(s).map(|m| m.as_str().to_string()).collect()
+}
 
-    // second pass to convert program to raw sparse vectors
-    for (quad, lin) in main.statements.into_iter().filter_map(|s| match s {
-        Statement::Constraint(quad, lin) => Some((quad, lin)),
-        Statement::Directive(..) => None,
-    }) {
-        a.push(
-            quad.left
-                .0
-                .into_iter()
-                .map(|(k, v)| (variables.get(&k).unwrap().clone(), v))
-                .collect(),
-        );
-        b.push(
-            quad.right
-                .0
-                .into_iter()
-                .map(|(k, v)| (variables.get(&k).unwrap().clone(), v))
-                .collect(),
-        );
-        c.push(
-            lin.0
-                .into_iter()
-                .map(|(k, v)| (variables.get(&k).unwrap().clone(), v))
-                .collect(),
-        );
-    }
+pub fn parse_g1_hex_text(s: &str) -> G1Point {
+    let mut n = hex_numbers(s);
+    assert_eq!(n.len(), 2, "Invalid G1 point: {}", s);
+    let y = n.pop().unwrap();
+    let x = n.pop().unwrap();
+    G1Point(x, y)
+}
 
-    // Convert map back into list ordered by index
-    let mut variables_list = vec![FlatVariable::new(0); variables.len()];
-    for (k, v) in variables.drain() {
-        assert_eq!(variables_list[v], FlatVariable::new(0));
-        std::mem::replace(&mut variables_list[v], k);
-    }
-    (variables_list, private_inputs_offset, a, b, c)
+pub fn parse_g2_hex_text(s: &str) -> G2Point {
+    let n = hex_numbers(s);
+    assert_eq!(n.len(), 4, "Invalid G2 point: {}", s);
+    G2Point((n[0].clone(), n[1].clone()), (n[2].clone(), n[3].clone()))
 }
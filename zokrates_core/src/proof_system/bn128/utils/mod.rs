@@ -1,4 +1,6 @@
 pub mod bellman;
+pub mod json;
 #[cfg(feature = "libsnark")]
 pub mod libsnark;
+pub mod snarkjs;
 pub mod solidity;
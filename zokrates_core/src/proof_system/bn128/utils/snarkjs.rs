@@ -0,0 +1,122 @@
+//! The JSON layout used by snarkjs for Groth16 verification keys and proofs.
+//!
+//! snarkjs writes field elements as decimal strings and points in projective coordinates,
+//! with the coefficients of G2 coordinates in the order `[c0, c1]`.
+
+use crate::proof_system::bn128::utils::json::{G1Point, G2Point};
+use num_bigint::BigUint;
+
+/// A Groth16 verification key as read and written by snarkjs
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VerificationKey {
+    pub protocol: String,
+    pub curve: String,
+    #[serde(rename = "nPublic")]
+    pub n_public: usize,
+    pub vk_alpha_1: Vec<String>,
+    pub vk_beta_2: Vec<Vec<String>>,
+    pub vk_gamma_2: Vec<Vec<String>>,
+    pub vk_delta_2: Vec<Vec<String>>,
+    #[serde(rename = "IC")]
+    pub ic: Vec<Vec<String>>,
+}
+
+/// A Groth16 proof as read and written by snarkjs, without its public inputs
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Proof {
+    pub pi_a: Vec<String>,
+    pub pi_b: Vec<Vec<String>>,
+    pub pi_c: Vec<String>,
+    pub protocol: String,
+}
+
+pub const PROTOCOL: &str = "groth16";
+pub const CURVE: &str = "bn128";
+
+pub fn hex_to_decimal(s: &str) -> Result<String, String> {
+    let digits = s.trim_start_matches("0x").trim_start_matches("0X");
+    BigUint::parse_bytes(digits.as_bytes(), 16)
+        .map(|n| n.to_str_radix(10))
+        .ok_or_else(|| format!("Invalid hexadecimal number {}", s))
+}
+
+pub fn decimal_to_hex(s: &str) -> Result<String, String> {
+    BigUint::parse_bytes(s.as_bytes(), 10)
+        .map(|n| format!("0x{:0>64}", n.to_str_radix(16)))
+        .ok_or_else(|| format!("Invalid decimal number {}", s))
+}
+
+pub fn g1_to_snarkjs(p: &G1Point) -> Result<Vec<String>, String> {
+    Ok(vec![
+        hex_to_decimal(&p.0)?,
+        hex_to_decimal(&p.1)?,
+        String::from("1"),
+    ])
+}
+
+pub fn g2_to_snarkjs(p: &G2Point) -> Result<Vec<Vec<String>>, String> {
+    Ok(vec![
+        vec![hex_to_decimal(&(p.0).1)?, hex_to_decimal(&(p.0).0)?],
+        vec![hex_to_decimal(&(p.1).1)?, hex_to_decimal(&(p.1).0)?],
+        vec![String::from("1"), String::from("0")],
+    ])
+}
+
+pub fn g1_from_snarkjs(p: &[String]) -> Result<G1Point, String> {
+    match p {
+        [x, y, z] if z == "1" => Ok(G1Point(decimal_to_hex(x)?, decimal_to_hex(y)?)),
+        _ => Err(format!(
+            "Expected an affine G1 point [x, y, \"1\"], found {:?}",
+            p
+        )),
+    }
+}
+
+pub fn g2_from_snarkjs(p: &[Vec<String>]) -> Result<G2Point, String> {
+    match p {
+        [x, y, z] if x.len() == 2 && y.len() == 2 && z[..] == ["1", "0"] => Ok(G2Point(
+            (decimal_to_hex(&x[1])?, decimal_to_hex(&x[0])?),
+            (decimal_to_hex(&y[1])?, decimal_to_hex(&y[0])?),
+        )),
+        _ => Err(format!(
+            "Expected an affine G2 point [[x0, x1], [y0, y1], [\"1\", \"0\"]], found {:?}",
+            p
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimal_roundtrip() {
+        assert_eq!(hex_to_decimal("0xff").unwrap(), "255");
+        assert_eq!(
+            decimal_to_hex("255").unwrap(),
+            "0x00000000000000000000000000000000000000000000000000000000000000ff"
+        );
+        assert!(decimal_to_hex("0xff").is_err());
+    }
+
+    #[test]
+    fn g2_roundtrip() {
+        let p = G2Point(
+            (decimal_to_hex("1").unwrap(), decimal_to_hex("2").unwrap()),
+            (decimal_to_hex("3").unwrap(), decimal_to_hex("4").unwrap()),
+        );
+
+        let snarkjs = g2_to_snarkjs(&p).unwrap();
+        assert_eq!(
+            snarkjs,
+            vec![vec!["2", "1"], vec!["4", "3"], vec!["1", "0"]]
+        );
+        assert_eq!(g2_from_snarkjs(&snarkjs).unwrap(), p);
+    }
+
+    #[test]
+    fn g1_projective_rejected() {
+        let p = vec![String::from("1"), String::from("2"), String::from("3")];
+        assert!(g1_from_snarkjs(&p).is_err());
+    }
+}
@@ -1,8 +1,13 @@
+mod abi;
 mod bn128;
 
+use std::fmt;
 use std::fs::File;
+use std::str::FromStr;
 use zokrates_field::field::FieldPrime;
 
+pub use self::abi::{AbiParameter, VerifierInterface};
+pub use self::bn128::field_from_hex;
 pub use self::bn128::G16;
 #[cfg(feature = "libsnark")]
 pub use self::bn128::GM17;
@@ -12,16 +17,182 @@ pub use self::bn128::PGHR13;
 use crate::ir;
 use std::io::BufReader;
 
+/// The fields shared by the JSON verification keys of all proving schemes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VerificationKeyHeader {
+    /// The proving scheme the key was generated for, for example `g16`
+    pub scheme: String,
+    /// The curve the key is defined on, for example `bn128`
+    pub curve: String,
+    /// The number of public inputs, including the outputs of the program
+    pub inputs: usize,
+    /// The number of values committed to by the single public input, if the program was
+    /// compiled with a commitment to its public inputs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub committed_inputs: Option<usize>,
+}
+
+/// The flavours of Solidity verifier which can be exported
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SolidityTarget {
+    /// A self-contained verifier taking the proof as arrays
+    Default,
+    /// A verifier taking the proof as a `Proof` struct, using `ABIEncoderV2`
+    AbiV2,
+    /// A gas-optimized verifier with a hard-coded verification key and calldata parameters
+    Optimized,
+    /// A shared verification library and a thin contract holding the verification key
+    Library,
+}
+
+impl SolidityTarget {
+    pub const VALUES: [&'static str; 4] = ["default", "abiv2", "optimized", "library"];
+}
+
+impl FromStr for SolidityTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(SolidityTarget::Default),
+            "abiv2" => Ok(SolidityTarget::AbiV2),
+            "optimized" => Ok(SolidityTarget::Optimized),
+            "library" => Ok(SolidityTarget::Library),
+            s => Err(format!("Unknown Solidity target {}", s)),
+        }
+    }
+}
+
+/// A seed making setup and proving deterministic, so that the same keys and proofs are generated
+/// on every run.
+///
+/// INSECURE: the randomness of a setup is its toxic waste, from which proofs of false statements
+/// can be forged, and the randomness of a proof reveals its witness. Only use seeds in tests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Seed(pub u64);
+
+impl FromStr for Seed {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .map(Seed)
+            .map_err(|_| format!("Invalid seed {}, expected an unsigned 64 bit integer", s))
+    }
+}
+
+impl fmt::Display for SolidityTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            SolidityTarget::Default => "default",
+            SolidityTarget::AbiV2 => "abiv2",
+            SolidityTarget::Optimized => "optimized",
+            SolidityTarget::Library => "library",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 pub trait ProofSystem {
-    fn setup(&self, program: ir::Prog<FieldPrime>, pk_path: &str, vk_path: &str);
+    /// Runs the setup for `program`. If `seed` is given, the keys are the same on every run, which
+    /// is insecure and only meant for tests.
+    fn setup(
+        &self,
+        program: ir::Prog<FieldPrime>,
+        pk_path: &str,
+        vk_path: &str,
+        seed: Option<Seed>,
+    );
 
+    /// Generates a proof. If `seed` is given, the proof is the same on every run, which is
+    /// insecure and only meant for tests.
     fn generate_proof(
         &self,
         program: ir::Prog<FieldPrime>,
         witness: ir::Witness<FieldPrime>,
         pk_path: &str,
         proof_path: &str,
+        seed: Option<Seed>,
     ) -> bool;
 
-    fn export_solidity_verifier(&self, reader: BufReader<File>) -> String;
+    /// Generates one proof per `(witness, name, proof_path)` job for the same program and proving
+    /// key, where `name` identifies the witness in errors. All jobs are run, and the failing ones
+    /// are reported together.
+    ///
+    /// Schemes which can keep the proving key in memory should override this to avoid
+    /// reading it again for every proof. `threads` is a hint and may be ignored.
+    fn generate_proofs(
+        &self,
+        program: ir::Prog<FieldPrime>,
+        jobs: Vec<(ir::Witness<FieldPrime>, String, String)>,
+        pk_path: &str,
+        _threads: usize,
+        seed: Option<Seed>,
+    ) -> Result<(), String> {
+        let errors: Vec<_> = jobs
+            .into_iter()
+            .filter_map(|(witness, name, proof_path)| {
+                match self.generate_proof(program.clone(), witness, pk_path, &proof_path, seed) {
+                    true => None,
+                    false => Some(format!("Could not prove {}", name)),
+                }
+            })
+            .collect();
+
+        proofs_result(errors)
+    }
+
+    /// Whether `setup` and `generate_proof` honour their `seed`. Schemes relying on libsnark
+    /// draw their own randomness and ignore it.
+    fn supports_seed(&self) -> bool {
+        false
+    }
+
+    fn export_solidity_verifier(&self, reader: BufReader<File>) -> Result<String, String>;
+
+    /// Exports a Solidity verifier of the given flavour. Schemes only support the default target unless they override this.
+    fn export_solidity_verifier_for(
+        &self,
+        reader: BufReader<File>,
+        target: SolidityTarget,
+    ) -> Result<String, String> {
+        match target {
+            SolidityTarget::Default => self.export_solidity_verifier(reader),
+            target => Err(format!(
+                "The {} target is not supported for this proving scheme",
+                target
+            )),
+        }
+    }
+
+    /// Describes the `verifyTx` function of the verifier exported for `target`, for a program with `inputs` public inputs
+    fn verifier_interface(
+        &self,
+        inputs: usize,
+        target: SolidityTarget,
+    ) -> Result<VerifierInterface, String>;
+
+    /// Checks the JSON proof read from `proof_reader` against the JSON verification key read from `vk_reader`
+    fn verify(
+        &self,
+        _vk_reader: BufReader<File>,
+        _proof_reader: BufReader<File>,
+    ) -> Result<bool, String> {
+        Err(String::from(
+            "Verification is not supported for this proving scheme",
+        ))
+    }
+}
+
+// the result of a batch of proofs, reporting all the jobs which failed
+pub(crate) fn proofs_result(errors: Vec<String>) -> Result<(), String> {
+    match errors.len() {
+        0 => Ok(()),
+        n => Err(format!(
+            "{} proof{} could not be generated:\n{}",
+            n,
+            if n == 1 { "" } else { "s" },
+            errors.join("\n")
+        )),
+    }
 }
@@ -6,12 +6,15 @@
 //! @author Thibaut Schaeffer <thibaut@schaeff.fr>
 //! @date 2017
 
-use crate::absy::variable::Variable;
 use crate::absy::Identifier;
 use crate::absy::*;
+use crate::diagnostics::{Code, Diagnostic, Note};
+use crate::static_analysis::Propagator;
+use crate::symbols::{Symbol, SymbolIndex, SymbolKind};
+use crate::typed_absy::Variable;
 use crate::typed_absy::*;
 use crate::types::Signature;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use zokrates_field::field::Field;
 
@@ -23,8 +26,45 @@ use std::hash::{Hash, Hasher};
 
 #[derive(PartialEq, Debug)]
 pub struct Error {
+    code: Code,
     pos: Option<(Position, Position)>,
     message: String,
+    notes: Vec<Note>,
+}
+
+impl Error {
+    pub(crate) fn new<S: Into<String>>(code: Code, message: S) -> Error {
+        Error {
+            code,
+            pos: None,
+            message: message.into(),
+            notes: vec![],
+        }
+    }
+
+    pub(crate) fn with_pos(self, pos: Option<(Position, Position)>) -> Error {
+        Error { pos, ..self }
+    }
+
+    fn with_note<S: Into<String>>(
+        mut self,
+        pos: Option<(Position, Position)>,
+        message: S,
+    ) -> Error {
+        self.notes.push(Note {
+            pos,
+            message: message.into(),
+        });
+        self
+    }
+}
+
+impl<'a> From<&'a Error> for Diagnostic {
+    fn from(error: &'a Error) -> Diagnostic {
+        Diagnostic::new(error.code, error.message.clone())
+            .with_pos(error.pos)
+            .with_notes(error.notes.clone())
+    }
 }
 
 impl fmt::Display for Error {
@@ -96,12 +136,66 @@ impl<'ast> FunctionQuery<'ast> {
             .cloned()
             .collect()
     }
+
+    /// Infers the values of the generic parameters of `signature` so that it matches the query
+    fn infer_generics(
+        &self,
+        signature: &UnresolvedSignature<'ast>,
+    ) -> Option<HashMap<Identifier<'ast>, usize>> {
+        if self.inputs.len() != signature.inputs.len()
+            || self.outputs.len() != signature.outputs.len()
+        {
+            return None;
+        }
+
+        let mut generics = HashMap::new();
+
+        let inputs = self.inputs.iter().zip(signature.inputs.iter());
+        let outputs = self
+            .outputs
+            .iter()
+            .zip(signature.outputs.iter())
+            .filter_map(|(t, u)| t.as_ref().map(|t| (t, u)));
+
+        for (t, u) in inputs.chain(outputs) {
+            let unifies = match (t, u) {
+                (Type::FieldElement, UnresolvedType::FieldElement) => true,
+                (Type::Boolean, UnresolvedType::Boolean) => true,
+                (
+                    Type::FieldElementArray(size),
+                    UnresolvedType::FieldElementArray(ArraySize::Constant(s)),
+                ) => size == s,
+                (
+                    Type::FieldElementArray(size),
+                    UnresolvedType::FieldElementArray(ArraySize::Identifier(id)),
+                ) if signature.generics.contains(id) => {
+                    *generics.entry(*id).or_insert(*size) == *size
+                }
+                _ => false,
+            };
+
+            if !unifies {
+                return None;
+            }
+        }
+
+        match signature
+            .generics
+            .iter()
+            .all(|id| generics.contains_key(id))
+        {
+            true => Some(generics),
+            false => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct ScopedVariable<'ast> {
     id: Variable<'ast>,
     level: usize,
+    // where the variable is declared, if it is declared in the source
+    pos: Option<(Position, Position)>,
 }
 
 impl<'ast> Hash for ScopedVariable<'ast> {
@@ -124,29 +218,123 @@ pub struct FunctionDeclaration {
 }
 
 // Checker, checks the semantics of a program.
-pub struct Checker<'ast> {
+pub struct Checker<'ast, T: Field> {
     scope: HashSet<ScopedVariable<'ast>>,
     functions: HashSet<FunctionDeclaration>,
     level: usize,
+    // the values of the generic parameters of the function being checked
+    generics: HashMap<Identifier<'ast>, usize>,
+    // generic functions are checked once for each combination of generic values they are called with
+    generic_functions: Vec<FunctionNode<'ast, T>>,
+    // the number of generic functions which can be called from the function being checked
+    visible_generic_functions: usize,
+    // the indices of the generic functions which are called, and whose body is therefore checked
+    instantiated_generic_functions: HashSet<usize>,
+    // the instances of generic functions created while checking the current function
+    instances: Vec<TypedFunction<'ast, T>>,
+    // the values of the global constants, including the imported ones
+    constants: HashMap<String, ConstantValue<T>>,
+    // the variables of the current scope which are read
+    used_variables: HashSet<Identifier<'ast>>,
+    // the functions which are called and the constants which are used
+    used_symbols: HashSet<String>,
+    warnings: Vec<Diagnostic>,
+    symbols: SymbolIndex,
+    // the symbol of each function, including the instances of generic functions
+    function_symbols: HashMap<FunctionDeclaration, usize>,
+    // the function being checked, which is the scope of the variables declared
+    function: Option<(Position, Position)>,
+    // whether each statement is preceded by its location in the source, for the debugger
+    locations: bool,
+    // whether the program must have a main function, which modules imported by name do not
+    require_main: bool,
+    // whether the statements being checked are inside an if-else statement
+    in_branch: bool,
 }
 
-impl<'ast> Checker<'ast> {
-    fn new() -> Checker<'ast> {
+impl<'ast, T: Field> Checker<'ast, T> {
+    fn new() -> Checker<'ast, T> {
         Checker {
             scope: HashSet::new(),
             functions: HashSet::new(),
             level: 0,
+            generics: HashMap::new(),
+            generic_functions: vec![],
+            visible_generic_functions: 0,
+            instantiated_generic_functions: HashSet::new(),
+            instances: vec![],
+            constants: HashMap::new(),
+            used_variables: HashSet::new(),
+            used_symbols: HashSet::new(),
+            warnings: vec![],
+            symbols: SymbolIndex::default(),
+            function_symbols: HashMap::new(),
+            function: None,
+            locations: false,
+            require_main: true,
+            in_branch: false,
         }
     }
 
-    pub fn check<T: Field>(prog: Prog<'ast, T>) -> Result<TypedProg<'ast, T>, Vec<Error>> {
-        Checker::new().check_program(prog)
+    /// Checks `prog`, returning the typed program along with the warnings found
+    pub fn check(prog: Prog<'ast, T>) -> Result<(TypedProg<'ast, T>, Vec<Diagnostic>), Vec<Error>> {
+        let (res, warnings, _) = Checker::analyse(prog);
+        res.map(|prog| (prog, warnings))
     }
 
-    fn check_program<T: Field>(
-        &mut self,
+    /// Checks `prog` like `check`, preceding each statement of the typed program by its location in
+    /// the source
+    pub fn check_with_locations(
+        prog: Prog<'ast, T>,
+    ) -> Result<(TypedProg<'ast, T>, Vec<Diagnostic>), Vec<Error>> {
+        let mut checker = Checker::new();
+        checker.locations = true;
+        let (res, warnings, _) = checker.run(prog);
+        res.map(|prog| (prog, warnings))
+    }
+
+    /// Checks `prog` like `check_with_locations`, without requiring a main function as `prog` is
+    /// imported by another program
+    pub fn check_module_with_locations(
+        prog: Prog<'ast, T>,
+    ) -> Result<(TypedProg<'ast, T>, Vec<Diagnostic>), Vec<Error>> {
+        let mut checker = Checker::new();
+        checker.locations = true;
+        checker.require_main = false;
+        let (res, warnings, _) = checker.run(prog);
+        res.map(|prog| (prog, warnings))
+    }
+
+    /// Checks `prog`, returning the warnings and the symbols found whether the program is correct or not
+    pub fn analyse(
+        prog: Prog<'ast, T>,
+    ) -> (
+        Result<TypedProg<'ast, T>, Vec<Error>>,
+        Vec<Diagnostic>,
+        SymbolIndex,
+    ) {
+        Checker::new().run(prog)
+    }
+
+    fn run(
+        mut self,
         prog: Prog<'ast, T>,
-    ) -> Result<TypedProg<'ast, T>, Vec<Error>> {
+    ) -> (
+        Result<TypedProg<'ast, T>, Vec<Error>>,
+        Vec<Diagnostic>,
+        SymbolIndex,
+    ) {
+        let res = self.check_program(prog);
+
+        let mut warnings = self.warnings;
+        warnings.sort_by_key(|w| w.pos.map(|(start, _)| (start.line, start.col)));
+        // generic functions are checked once per instance, which can report the same warning several times
+        warnings.dedup();
+
+        (res, warnings, self.symbols)
+    }
+
+    fn check_program(&mut self, prog: Prog<'ast, T>) -> Result<TypedProg<'ast, T>, Vec<Error>> {
         for func in &prog.imported_functions {
             self.functions.insert(FunctionDeclaration {
                 id: func.id.to_string(), // we use strings here as flat absy is still string based
@@ -154,18 +342,141 @@ impl<'ast> Checker<'ast> {
             });
         }
 
+        // the importer resolves the alias of each import, and constants are imported under their local name
+        let imports: HashMap<_, _> = prog
+            .imports
+            .iter()
+            .flat_map(|i| {
+                i.value
+                    .get_alias()
+                    .clone()
+                    .into_iter()
+                    .chain(i.value.get_symbols().iter().map(|(_, local)| local.clone()))
+                    .map(move |id| (id, (i.value.get_source().clone(), i.pos())))
+            })
+            .collect();
+
+        for func in &prog.imported_functions {
+            if let Some((source, pos)) = imports.get(&func.id) {
+                let index = self.symbols.insert(Symbol {
+                    id: func.id.clone(),
+                    kind: SymbolKind::Function,
+                    detail: format!("def {}{}", func.id, func.signature),
+                    pos: Some(*pos),
+                    import: Some(source.clone()),
+                    scope: None,
+                });
+                self.function_symbols.insert(
+                    FunctionDeclaration {
+                        id: func.id.clone(),
+                        signature: func.signature.clone(),
+                    },
+                    index,
+                );
+            }
+        }
+
         let mut errors = vec![];
+        let mut checked_constants = vec![];
         let mut checked_functions = vec![];
 
+        for (id, value) in prog.imported_constants {
+            if let Some((source, pos)) = imports.get(&id) {
+                self.symbols.insert(Symbol {
+                    id: id.clone(),
+                    kind: SymbolKind::Constant,
+                    detail: format!("const {} {} = {}", value.get_type(), id, value),
+                    pos: Some(*pos),
+                    import: Some(source.clone()),
+                    scope: None,
+                });
+            }
+            if self.constants.insert(id.clone(), value).is_some() {
+                errors.push(
+                    Error::new(
+                        Code::DuplicateDefinition,
+                        format!("Duplicate definition for constant {}", id),
+                    )
+                    .with_pos(imports.get(&id).map(|(_, pos)| *pos)),
+                );
+            }
+        }
+
+        for constant in prog.constants {
+            match self.check_constant(constant) {
+                Ok((id, value)) => {
+                    self.constants.insert(id.to_string(), value.clone());
+                    checked_constants.push((id.to_string(), value));
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+
         for func in prog.functions {
+            if func.value.signature.generics.len() > 0 {
+                match func.value.id {
+                    "main" => errors.push(
+                        Error::new(
+                            Code::InvalidMain,
+                            format!("The main function cannot be generic"),
+                        )
+                        .with_pos(Some(func.pos())),
+                    ),
+                    _ => {
+                        self.symbols.insert(Symbol {
+                            id: func.value.id.to_string(),
+                            kind: SymbolKind::Function,
+                            detail: function_detail(&func.value),
+                            pos: Some(func.pos()),
+                            import: None,
+                            scope: None,
+                        });
+
+                        // the signature is valid for any generic values, so it is checked for
+                        // arbitrary ones even if the function is never called
+                        self.generics = func
+                            .value
+                            .signature
+                            .generics
+                            .iter()
+                            .map(|id| (*id, 1))
+                            .collect();
+                        let signature = self.check_signature(&func.value.signature, func.pos());
+                        self.generics = HashMap::new();
+
+                        match signature {
+                            Ok(_) => {
+                                self.generic_functions.push(func);
+                                self.visible_generic_functions = self.generic_functions.len();
+                            }
+                            Err(e) => errors.push(e),
+                        }
+                    }
+                }
+                continue;
+            }
+
             self.enter_scope();
 
-            let dec = FunctionDeclaration {
-                id: func.value.id.to_string(),
-                signature: func.value.signature.clone(),
-            };
+            let pos = func.pos();
+            let id = func.value.id;
+            let signature = self.check_signature(&func.value.signature, pos);
+
+            let symbol = self.symbols.insert(Symbol {
+                id: id.to_string(),
+                kind: SymbolKind::Function,
+                detail: function_detail(&func.value),
+                pos: Some(pos),
+                import: None,
+                scope: None,
+            });
+
+            let res = self.check_function(func);
+
+            // instances of generic functions are called by this function so they come first
+            checked_functions.extend(self.instances.drain(..));
 
-            match self.check_function(func) {
+            match res {
                 Ok(checked_function) => {
                     checked_functions.push(checked_function);
                 }
@@ -173,7 +484,14 @@ impl<'ast> Checker<'ast> {
                     errors.extend(e);
                 }
             };
-            self.functions.insert(dec);
+            if let Ok(signature) = signature {
+                let declaration = FunctionDeclaration {
+                    id: id.to_string(),
+                    signature,
+                };
+                self.function_symbols.insert(declaration.clone(), symbol);
+                self.functions.insert(declaration);
+            }
             self.exit_scope();
         }
 
@@ -182,6 +500,42 @@ impl<'ast> Checker<'ast> {
             Err(e) => errors.push(e),
         };
 
+        // the body of a generic function is only checked for the generic values it is called with
+        for (index, func) in self.generic_functions.iter().enumerate() {
+            if !self.instantiated_generic_functions.contains(&index) {
+                self.warnings.push(
+                    Diagnostic::new(
+                        Code::UnusedFunction,
+                        format!(
+                            "Generic function {} is never called, so its body is not checked",
+                            func.value.id
+                        ),
+                    )
+                    .with_pos(Some(func.pos())),
+                );
+            }
+        }
+
+        for import in &prog.imports {
+            // the importer resolves the alias of each import
+            if let Some(alias) = import.value.get_alias() {
+                if !self.used_symbols.contains(alias) {
+                    self.warnings.push(
+                        Diagnostic::new(Code::UnusedImport, format!("Unused import {}", alias))
+                            .with_pos(Some(import.pos())),
+                    );
+                }
+            }
+            for (_, local) in import.value.get_symbols() {
+                if !self.used_symbols.contains(local) {
+                    self.warnings.push(
+                        Diagnostic::new(Code::UnusedImport, format!("Unused import {}", local))
+                            .with_pos(Some(import.pos())),
+                    );
+                }
+            }
+        }
+
         if errors.len() > 0 {
             return Err(errors);
         }
@@ -190,34 +544,225 @@ impl<'ast> Checker<'ast> {
             functions: checked_functions,
             imported_functions: prog.imported_functions,
             imports: prog.imports.into_iter().map(|i| i.value).collect(),
+            constants: checked_constants,
         })
     }
 
+    // the assertions of a called function are enforced whether the branch calling it is taken or not
+    fn check_call_outside_branch(&self, id: &str, pos: (Position, Position)) -> Result<(), Error> {
+        match self.in_branch {
+            true => Err(Error::new(
+                Code::Unsupported,
+                format!(
+                    "Function call to {} is not allowed inside if-else statements, as its assertions would be enforced even if the branch is not taken",
+                    id
+                ),
+            )
+            .with_pos(Some(pos))),
+            false => Ok(()),
+        }
+    }
+
     fn check_single_main(&mut self) -> Result<(), Error> {
         match self.functions.iter().filter(|fun| fun.id == "main").count() {
             1 => Ok(()),
-            0 => Err(Error {
-                pos: None,
-                message: format!("No main function found"),
-            }),
-            n => Err(Error {
-                pos: None,
-                message: format!("Only one main function allowed, found {}", n),
-            }),
+            0 if !self.require_main => Ok(()),
+            0 => Err(Error::new(
+                Code::InvalidMain,
+                format!("No main function found"),
+            )),
+            n => Err(Error::new(
+                Code::InvalidMain,
+                format!("Only one main function allowed, found {}", n),
+            )),
         }
     }
 
     fn check_for_var(&self, var: &VariableNode) -> Result<(), Error> {
         match var.value.get_type() {
-            Type::FieldElement => Ok(()),
-            t => Err(Error {
-                pos: Some(var.pos()),
-                message: format!("Variable in for loop cannot have type {}", t),
-            }),
+            UnresolvedType::FieldElement => Ok(()),
+            t => Err(Error::new(
+                Code::TypeMismatch,
+                format!("Variable in for loop cannot have type {}", t),
+            )
+            .with_pos(Some(var.pos()))),
         }
     }
 
-    fn check_function<T: Field>(
+    fn check_array_size(
+        &mut self,
+        size: ArraySize<'ast>,
+        pos: (Position, Position),
+    ) -> Result<usize, Error> {
+        match size {
+            ArraySize::Constant(size) => Ok(size),
+            ArraySize::Identifier(id) => match self.generics.get(id) {
+                Some(size) => Ok(*size),
+                None => match self.get_constant(id) {
+                    Some(ConstantValue::FieldElement(n)) => self.check_usize(n.clone(), pos),
+                    Some(v) => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
+                            "Array size {} should be a field element, found {}",
+                            id,
+                            v.get_type()
+                        ),
+                    )
+                    .with_pos(Some(pos))),
+                    None => Err(Error::new(
+                        Code::UndefinedIdentifier,
+                        format!("Undefined array size {}", id),
+                    )
+                    .with_pos(Some(pos))),
+                },
+            },
+            ArraySize::Unsupported(e) => Err(Error::new(
+                Code::ExpectedConstant,
+                format!(
+                    "Array size should be a constant or an identifier, found {}",
+                    e
+                ),
+            )
+            .with_pos(Some(pos))),
+        }
+    }
+
+    fn check_usize(&self, n: T, pos: (Position, Position)) -> Result<usize, Error> {
+        n.to_dec_string().parse::<usize>().map_err(|_| {
+            Error::new(
+                Code::OutOfBounds,
+                format!("Value {} is too large to be used as a size or an index", n),
+            )
+            .with_pos(Some(pos))
+        })
+    }
+
+    fn check_type(
+        &mut self,
+        t: UnresolvedType<'ast>,
+        pos: (Position, Position),
+    ) -> Result<Type, Error> {
+        match t {
+            UnresolvedType::FieldElement => Ok(Type::FieldElement),
+            UnresolvedType::Boolean => Ok(Type::Boolean),
+            UnresolvedType::FieldElementArray(size) => {
+                Ok(Type::FieldElementArray(self.check_array_size(size, pos)?))
+            }
+            t @ UnresolvedType::BooleanArray(..) => Err(Error::new(
+                Code::Unsupported,
+                format!("Array elements should be field elements, found {}", t),
+            )
+            .with_pos(Some(pos))),
+        }
+    }
+
+    fn check_signature(
+        &mut self,
+        signature: &UnresolvedSignature<'ast>,
+        pos: (Position, Position),
+    ) -> Result<Signature, Error> {
+        let mut inputs = vec![];
+        for t in signature.inputs.iter() {
+            inputs.push(self.check_type(t.clone(), pos)?);
+        }
+        let mut outputs = vec![];
+        for t in signature.outputs.iter() {
+            outputs.push(self.check_type(t.clone(), pos)?);
+        }
+        Ok(Signature::new().inputs(inputs).outputs(outputs))
+    }
+
+    fn check_variable(&mut self, v: VariableNode<'ast>) -> Result<Variable<'ast>, Error> {
+        let pos = v.pos();
+        Ok(Variable::with_id_and_type(
+            v.value.id.into(),
+            self.check_type(v.value._type, pos)?,
+        ))
+    }
+
+    fn check_constant(
+        &mut self,
+        constant: ConstantNode<'ast, T>,
+    ) -> Result<(Identifier<'ast>, ConstantValue<T>), Error> {
+        let pos = constant.pos();
+        let constant = constant.value;
+
+        if self.constants.contains_key(constant.id) {
+            return Err(Error::new(
+                Code::DuplicateDefinition,
+                format!("Duplicate definition for constant {}", constant.id),
+            )
+            .with_pos(Some(pos)));
+        }
+
+        let ty = self.check_type(constant.ty, pos)?;
+        let value = self.check_constant_value(constant.expression)?;
+
+        match value.get_type() == ty {
+            true => {
+                let symbol = self.symbols.insert(Symbol {
+                    id: constant.id.to_string(),
+                    kind: SymbolKind::Constant,
+                    detail: format!("const {} {} = {}", ty, constant.id, value),
+                    pos: Some(pos),
+                    import: None,
+                    scope: None,
+                });
+                self.symbols.reference(pos, symbol);
+                Ok((constant.id, value))
+            }
+            false => Err(Error::new(
+                Code::TypeMismatch,
+                format!(
+                    "Value {} of type {} cannot be assigned to constant {} of type {}",
+                    value,
+                    value.get_type(),
+                    constant.id,
+                    ty
+                ),
+            )
+            .with_pos(Some(pos))),
+        }
+    }
+
+    /// Evaluates an expression which must be known at compile time
+    fn check_constant_value(
+        &mut self,
+        e: ExpressionNode<'ast, T>,
+    ) -> Result<ConstantValue<T>, Error> {
+        let pos = e.pos();
+
+        let e = Propagator::propagate_expression(self.check_expression(e)?);
+
+        ConstantValue::from_expression(&e).ok_or(
+            Error::new(
+                Code::ExpectedConstant,
+                format!("Expected a constant expression, found {}", e),
+            )
+            .with_pos(Some(pos)),
+        )
+    }
+
+    /// Evaluates a field element expression which must be known at compile time, such as a for loop bound
+    fn check_constant_expression(&mut self, e: ExpressionNode<'ast, T>) -> Result<T, Error> {
+        let pos = e.pos();
+
+        match self.check_constant_value(e)? {
+            ConstantValue::FieldElement(n) => Ok(n),
+            v => Err(Error::new(
+                Code::TypeMismatch,
+                format!(
+                    "Expected a constant of type {}, found {} of type {}",
+                    Type::FieldElement,
+                    v,
+                    v.get_type()
+                ),
+            )
+            .with_pos(Some(pos))),
+        }
+    }
+
+    fn check_function(
         &mut self,
         funct_node: FunctionNode<'ast, T>,
     ) -> Result<TypedFunction<'ast, T>, Vec<Error>> {
@@ -227,11 +772,14 @@ impl<'ast> Checker<'ast> {
 
         assert_eq!(funct.arguments.len(), funct.signature.inputs.len());
 
+        let signature = self
+            .check_signature(&funct.signature, pos)
+            .map_err(|e| vec![e])?;
+
         let query = FunctionQuery::new(
             funct.id,
-            &funct.signature.inputs,
-            &funct
-                .signature
+            &signature.inputs,
+            &signature
                 .outputs
                 .clone()
                 .into_iter()
@@ -243,52 +791,287 @@ impl<'ast> Checker<'ast> {
 
         match candidates.len() {
             1 => {
-                errors.push(Error {
-                    pos: Some(pos),
-                    message: format!(
-                        "Duplicate definition for function {} with signature {}",
-                        funct.id, funct.signature
-                    ),
-                });
+                errors.push(
+                    Error::new(
+                        Code::DuplicateDefinition,
+                        format!(
+                            "Duplicate definition for function {} with signature {}",
+                            funct.id, signature
+                        ),
+                    )
+                    .with_pos(Some(pos)),
+                );
             }
             0 => {}
             _ => panic!("duplicate function declaration should have been caught"),
         }
 
-        for arg in &funct.arguments {
-            self.insert_scope(arg.value.id.value.clone());
-        }
+        // the variables declared from here on are scoped to this function
+        let outer_function = std::mem::replace(&mut self.function, Some(pos));
 
-        let mut statements_checked = vec![];
+        let mut arguments_checked = vec![];
 
-        for stat in funct.statements.into_iter() {
-            match self.check_statement(stat, &funct.signature.outputs) {
-                Ok(statement) => {
-                    statements_checked.push(statement);
-                }
-                Err(e) => {
-                    errors.push(e);
+        for arg in funct.arguments {
+            let pos = arg.value.id.pos();
+            match self.check_variable(arg.value.id) {
+                Ok(var) => {
+                    // parameters are part of the signature, so they are not reported when unused
+                    self.used_variables.insert(var.id.id);
+                    if let Err(e) = self.declare(var.clone(), pos) {
+                        errors.push(e);
+                    }
+                    arguments_checked.push(crate::typed_absy::Parameter {
+                        id: var,
+                        private: arg.value.private,
+                    });
                 }
+                Err(e) => errors.push(e),
             }
         }
 
+        let statements_checked = match self.check_statements(funct.statements, &signature.outputs) {
+            Ok(statements) => statements,
+            Err(e) => {
+                errors.extend(e);
+                vec![]
+            }
+        };
+
+        self.function = outer_function;
+
         if errors.len() > 0 {
             return Err(errors);
         }
 
         Ok(TypedFunction {
             id: funct.id,
-            arguments: funct
-                .arguments
-                .into_iter()
-                .map(|a| a.value.into())
-                .collect(),
+            arguments: arguments_checked,
             statements: statements_checked,
-            signature: funct.signature,
+            signature,
         })
     }
 
-    fn check_statement<T: Field>(
+    /// Checks the generic function matching `query` if any, for the generic values inferred from `query`
+    fn instantiate(
+        &mut self,
+        query: &FunctionQuery<'ast>,
+        pos: (Position, Position),
+    ) -> Result<(), Error> {
+        let candidate = self.generic_functions[..self.visible_generic_functions]
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.value.id == query.id)
+            .filter_map(|(index, f)| {
+                query
+                    .infer_generics(&f.value.signature)
+                    .map(|generics| (index, f.clone(), generics))
+            })
+            .next();
+
+        let (index, function, generics) = match candidate {
+            Some(candidate) => candidate,
+            None => return Ok(()),
+        };
+
+        self.instantiated_generic_functions.insert(index);
+
+        let values = function
+            .value
+            .signature
+            .generics
+            .iter()
+            .map(|id| format!("{} = {}", id, generics[id]))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        // the instance is checked in a fresh scope, and can only call generic functions defined before it
+        let scope = std::mem::replace(&mut self.scope, HashSet::new());
+        let used_variables = std::mem::replace(&mut self.used_variables, HashSet::new());
+        let level = std::mem::replace(&mut self.level, 0);
+        let outer_generics = std::mem::replace(&mut self.generics, generics);
+        let visible_generic_functions =
+            std::mem::replace(&mut self.visible_generic_functions, index);
+        let in_branch = std::mem::replace(&mut self.in_branch, false);
+
+        let function_pos = function.pos();
+
+        self.enter_scope();
+        let res = self.check_function(function);
+        self.exit_scope();
+
+        self.scope = scope;
+        self.used_variables = used_variables;
+        self.level = level;
+        self.generics = outer_generics;
+        self.visible_generic_functions = visible_generic_functions;
+        self.in_branch = in_branch;
+
+        match res {
+            Ok(instance) => {
+                let declaration = FunctionDeclaration {
+                    id: instance.id.to_string(),
+                    signature: instance.signature.clone(),
+                };
+                if let Some(symbol) =
+                    self.symbols
+                        .find(&declaration.id, SymbolKind::Function, Some(function_pos))
+                {
+                    self.function_symbols.insert(declaration.clone(), symbol);
+                }
+                self.functions.insert(declaration);
+                self.instances.push(instance);
+                Ok(())
+            }
+            Err(errors) => Err(Error::new(
+                Code::GenericInstantiation,
+                format!(
+                    "Instantiation of function {} with {} failed:\n{}",
+                    query.id,
+                    values,
+                    errors
+                        .iter()
+                        .map(|e| e.to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                ),
+            )
+            .with_pos(Some(pos))),
+        }
+    }
+
+    /// Finds the functions matching `query`, instantiating a generic function if none is found
+    fn find_functions(
+        &mut self,
+        query: &FunctionQuery<'ast>,
+        pos: (Position, Position),
+    ) -> Result<Vec<FunctionDeclaration>, Error> {
+        self.used_symbols.insert(query.id.to_string());
+
+        match self.find_candidates(query).len() {
+            0 => {
+                self.instantiate(query, pos)?;
+                Ok(self.find_candidates(query))
+            }
+            _ => Ok(self.find_candidates(query)),
+        }
+    }
+
+    /// Checks `statements`, reporting the errors found in all of them
+    fn check_statements(
+        &mut self,
+        statements: Vec<StatementNode<'ast, T>>,
+        header_return_types: &Vec<Type>,
+    ) -> Result<Vec<TypedStatement<'ast, T>>, Vec<Error>> {
+        let mut errors = vec![];
+        let mut checked_statements = vec![];
+
+        for stat in statements {
+            let pos = stat.pos();
+            match self.check_statement(stat, header_return_types) {
+                Ok(statement) => self.push_statement(&mut checked_statements, statement, pos),
+                Err(e) => errors.extend(e),
+            }
+        }
+
+        match errors.len() {
+            0 => Ok(checked_statements),
+            _ => Err(errors),
+        }
+    }
+
+    // pushes a checked statement, preceded by its location in the source if locations are kept.
+    // Declarations compute nothing, so their location is not kept.
+    fn push_statement(
+        &self,
+        statements: &mut Vec<TypedStatement<'ast, T>>,
+        statement: TypedStatement<'ast, T>,
+        pos: (Position, Position),
+    ) {
+        match statement {
+            TypedStatement::Declaration(..) => {}
+            _ if self.locations => statements.push(TypedStatement::Location(pos)),
+            _ => {}
+        };
+        statements.push(statement);
+    }
+
+    fn check_statement(
+        &mut self,
+        stat: StatementNode<'ast, T>,
+        header_return_types: &Vec<Type>,
+    ) -> Result<TypedStatement<'ast, T>, Vec<Error>> {
+        let pos = stat.pos();
+
+        match stat.value {
+            Statement::For(var, from, to, statements) => {
+                self.enter_scope();
+                let res = self.check_for_loop(var, from, to, statements, header_return_types);
+                self.exit_scope();
+                res
+            }
+            Statement::IfElse(condition, consequence, alternative) => {
+                let checked_condition = self.check_expression(condition).and_then(|e| match e {
+                    TypedExpression::Boolean(e) => Ok(e),
+                    e => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
+                            "Expected if-else condition to be of type bool, found {} of type {}",
+                            e,
+                            e.get_type()
+                        ),
+                    )
+                    .with_pos(Some(pos))),
+                });
+                let checked_consequence = self.check_branch(consequence, header_return_types);
+                let checked_alternative = self.check_branch(alternative, header_return_types);
+
+                match (checked_condition, checked_consequence, checked_alternative) {
+                    (Ok(condition), Ok(consequence), Ok(alternative)) => {
+                        Ok(TypedStatement::IfElse(condition, consequence, alternative))
+                    }
+                    (condition, consequence, alternative) => Err(condition
+                        .err()
+                        .into_iter()
+                        .chain(consequence.err().into_iter().flatten())
+                        .chain(alternative.err().into_iter().flatten())
+                        .collect()),
+                }
+            }
+            value => self
+                .check_single_statement(Node::new(pos.0, pos.1, value), header_return_types)
+                .map_err(|e| vec![e]),
+        }
+    }
+
+    fn check_for_loop(
+        &mut self,
+        var: VariableNode<'ast>,
+        from: ExpressionNode<'ast, T>,
+        to: ExpressionNode<'ast, T>,
+        statements: Vec<StatementNode<'ast, T>>,
+        header_return_types: &Vec<Type>,
+    ) -> Result<TypedStatement<'ast, T>, Vec<Error>> {
+        let pos = var.pos();
+
+        self.check_for_var(&var).map_err(|e| vec![e])?;
+
+        let from = self.check_constant_expression(from).map_err(|e| vec![e])?;
+        let to = self.check_constant_expression(to).map_err(|e| vec![e])?;
+
+        let var = self.check_variable(var).map_err(|e| vec![e])?;
+
+        // the loop variable cannot be omitted, so it is not reported when unused
+        self.used_variables.insert(var.id.id);
+        self.insert_scope(var.clone(), Some(pos));
+        self.add_variable_symbol(&var, pos);
+
+        let checked_statements = self.check_statements(statements, header_return_types)?;
+
+        Ok(TypedStatement::For(var, from, to, checked_statements))
+    }
+
+    /// Checks a statement which does not contain blocks
+    fn check_single_statement(
         &mut self,
         stat: StatementNode<'ast, T>,
         header_return_types: &Vec<Type>,
@@ -310,9 +1093,9 @@ impl<'ast> Checker<'ast> {
 
                 match return_statement_types == *header_return_types {
                     true => Ok(TypedStatement::Return(expression_list_checked)),
-                    false => Err(Error {
-                        pos: Some(pos),
-                        message: format!(
+                    false => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
                             "Expected ({}) in return statement, found ({})",
                             header_return_types
                                 .iter()
@@ -325,16 +1108,16 @@ impl<'ast> Checker<'ast> {
                                 .collect::<Vec<_>>()
                                 .join(", ")
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
-            Statement::Declaration(var) => match self.insert_scope(var.clone().value) {
-                true => Ok(TypedStatement::Declaration(var.value.into())),
-                false => Err(Error {
-                    pos: Some(pos),
-                    message: format!("Duplicate declaration for variable named {}", var.value.id),
-                }),
-            },
+            Statement::Declaration(var) => {
+                let pos = var.pos();
+                let var = self.check_variable(var)?;
+                self.declare(var.clone(), pos)?;
+                Ok(TypedStatement::Declaration(var))
+            }
             Statement::Definition(assignee, expr) => {
                 // we create multidef when rhs is a function call to benefit from inference
                 // check rhs is not a function call here
@@ -355,13 +1138,14 @@ impl<'ast> Checker<'ast> {
                 // make sure the assignee has the same type as the rhs
                 match var_type == expression_type {
                     true => Ok(TypedStatement::Definition(var, checked_expr)),
-                    false => Err(Error {
-                        pos: Some(pos),
-                        message: format!(
+                    false => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
                             "Expression {} of type {} cannot be assigned to {} of type {}",
                             checked_expr, expression_type, var, var_type
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
             Statement::Condition(lhs, rhs) => {
@@ -372,48 +1156,81 @@ impl<'ast> Checker<'ast> {
                     (ref l, ref r) if r.get_type() == l.get_type() => {
                         Ok(TypedStatement::Condition(checked_lhs, checked_rhs))
                     }
-                    (e1, e2) => Err(Error {
-                        pos: Some(pos),
-                        message: format!(
+                    (e1, e2) => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
                             "Cannot compare {} of type {:?} to {} of type {:?}",
                             checked_lhs,
                             e1.get_type(),
                             checked_rhs,
                             e2.get_type(),
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
-            Statement::For(var, from, to, statements) => {
-                self.enter_scope();
-
-                self.check_for_var(&var)?;
-
-                self.insert_scope(var.clone().value);
-
-                let mut checked_statements = vec![];
-
-                for stat in statements {
-                    let checked_stat = self.check_statement(stat, header_return_types)?;
-                    checked_statements.push(checked_stat);
+            Statement::Log(format, arguments) => {
+                let placeholders = format.matches("{}").count();
+
+                if placeholders != arguments.len() {
+                    return Err(Error::new(
+                        Code::InvalidLog,
+                        format!(
+                            "Log format \"{}\" expects {} argument(s), found {}",
+                            format,
+                            placeholders,
+                            arguments.len()
+                        ),
+                    )
+                    .with_pos(Some(pos)));
                 }
 
-                self.exit_scope();
-                Ok(TypedStatement::For(
-                    var.value.into(),
-                    from,
-                    to,
-                    checked_statements,
+                let arguments = arguments
+                    .into_iter()
+                    .map(|a| {
+                        let pos = a.pos();
+                        let checked = self.check_expression(a)?;
+                        match is_log_argument(&checked) {
+                            true => Ok(checked),
+                            false => Err(Error::new(
+                                Code::InvalidLog,
+                                format!(
+                                    "Log argument {} would add constraints, only variables and linear expressions can be logged",
+                                    checked
+                                ),
+                            )
+                            .with_pos(Some(pos))),
+                        }
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                Ok(TypedStatement::Log(
+                    BooleanExpression::Value(true),
+                    format,
+                    arguments,
                 ))
             }
+            Statement::UnsupportedAssertion(e) => Err(Error::new(
+                Code::Unsupported,
+                format!(
+                    "Assertion statements should be an equality check, found {}",
+                    e
+                ),
+            )
+            .with_pos(Some(pos))),
+            Statement::For(..) | Statement::IfElse(..) => {
+                unreachable!("statements containing blocks are checked in check_statement")
+            }
             Statement::MultipleDefinition(assignees, rhs) => {
                 match rhs.value {
                     // Right side has to be a function call
                     Expression::FunctionCall(fun_id, arguments) => {
+                        self.check_call_outside_branch(fun_id, pos)?;
                         // find lhs types
                         let mut vars_types: Vec<Option<Type>> = vec![];
                         let mut var_names = vec![];
                         for assignee in assignees {
+                            let assignee_pos = assignee.pos();
                             let (name, t) = match assignee.value {
                     			Assignee::Identifier(name) => {
                     				Ok((name, match self.get_scope(&name) {
@@ -421,10 +1238,9 @@ impl<'ast> Checker<'ast> {
 					            		Some(sv) => Some(sv.id.get_type())
 					            	}))
                     			}
-                    			ref a => Err(Error {
-                                    pos: Some(pos),
- message: format!("Left hand side of function return assignment must be a list of identifiers, found {}", a)})
+                    			ref a => Err(Error::new(Code::Unsupported, format!("Left hand side of function return assignment must be a list of identifiers, found {}", a)).with_pos(Some(pos)))
                     		}?;
+                            self.reference_variable(&name, assignee_pos);
                             vars_types.push(t);
                             var_names.push(name);
                         }
@@ -439,44 +1255,81 @@ impl<'ast> Checker<'ast> {
                             arguments_checked.iter().map(|a| a.get_type()).collect();
 
                         let query = FunctionQuery::new(&fun_id, &arguments_types, &vars_types);
-                        let candidates = self.find_candidates(&query);
+                        let candidates = self.find_functions(&query, pos)?;
 
                         match candidates.len() {
                     		// the function has to be defined
                     		1 => {
                     			let f = &candidates[0];
+                                self.reference_function(f, pos);
 
                                 // we can infer the left hand side to be typed as the return values
                     			let lhs: Vec<_> = var_names.iter().enumerate().map(|(index, name)|
-                    				Variable::new(*name, f.signature.outputs[index].clone())
+                    				Variable::with_id_and_type((*name).into(), f.signature.outputs[index].clone())
                     			).collect();
 
-                                let assignees: Vec<_> = lhs.iter().map(|v| v.clone().into()).collect();
+                                let assignees: Vec<_> = lhs.clone();
 
                                 let call = TypedExpressionList::FunctionCall(f.id.to_string(), arguments_checked, f.signature.outputs.clone());
 
                                 for var in lhs {
-                                    self.insert_scope(var);
+                                    self.insert_scope(var, None);
                                 }
 
                                 Ok(TypedStatement::MultipleDefinition(assignees, call))
                     		},
-                    		0 => Err(Error {                         pos: Some(pos),
- message: format!("Function definition for function {} with signature {} not found.", fun_id, query) }),
-                    		_ => Err(Error {                         pos: Some(pos),
- message: format!("Function call for function {} with arguments {:?} is ambiguous.", fun_id, arguments_types) }),
+                    		0 => Err(Error::new(Code::UndefinedFunction, format!("Function definition for function {} with signature {} not found.", fun_id, query)).with_pos(Some(pos))),
+                    		_ => Err(Error::new(Code::TypeMismatch, format!("Function call for function {} with arguments {:?} is ambiguous.", fun_id, arguments_types)).with_pos(Some(pos))),
                     	}
                     }
-                    _ => Err(Error {
-                        pos: Some(pos),
-                        message: format!("{} should be a FunctionCall", rhs),
-                    }),
+                    _ => Err(Error::new(
+                        Code::Unsupported,
+                        format!("{} should be a FunctionCall", rhs),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
         }
     }
 
-    fn check_assignee<T: Field>(
+    fn check_branch(
+        &mut self,
+        statements: Vec<StatementNode<'ast, T>>,
+        header_return_types: &Vec<Type>,
+    ) -> Result<Vec<TypedStatement<'ast, T>>, Vec<Error>> {
+        self.enter_scope();
+        let in_branch = std::mem::replace(&mut self.in_branch, true);
+
+        let mut errors = vec![];
+        let mut checked_statements = vec![];
+
+        for stat in statements {
+            let pos = stat.pos();
+            let res = match stat.value {
+                Statement::Return(..) => Err(vec![Error::new(
+                    Code::Unsupported,
+                    format!("Return statements are not allowed inside if-else blocks"),
+                )
+                .with_pos(Some(pos))]),
+                _ => self.check_statement(stat, header_return_types),
+            };
+
+            match res {
+                Ok(statement) => self.push_statement(&mut checked_statements, statement, pos),
+                Err(e) => errors.extend(e),
+            }
+        }
+
+        self.in_branch = in_branch;
+        self.exit_scope();
+
+        match errors.len() {
+            0 => Ok(checked_statements),
+            _ => Err(errors),
+        }
+    }
+
+    fn check_assignee(
         &mut self,
         assignee: AssigneeNode<'ast, T>,
     ) -> Result<TypedAssignee<'ast, T>, Error> {
@@ -484,40 +1337,63 @@ impl<'ast> Checker<'ast> {
         // check that the assignee is declared
         match assignee.value {
             Assignee::Identifier(variable_name) => match self.get_scope(&variable_name) {
-                Some(var) => Ok(TypedAssignee::Identifier(
-                    crate::typed_absy::Variable::with_id_and_type(
-                        variable_name.into(),
-                        var.id.get_type(),
-                    ),
-                )),
-                None => Err(Error {
-                    pos: Some(assignee.pos()),
-                    message: format!("Undeclared variable: {:?}", variable_name),
-                }),
+                Some(var) => {
+                    let t = var.id.get_type();
+                    self.reference_variable(&variable_name, pos);
+                    Ok(TypedAssignee::Identifier(
+                        crate::typed_absy::Variable::with_id_and_type(variable_name.into(), t),
+                    ))
+                }
+                None => Err(Error::new(
+                    Code::UndefinedIdentifier,
+                    format!("Undeclared variable: {:?}", variable_name),
+                )
+                .with_pos(Some(assignee.pos()))),
             },
             Assignee::ArrayElement(box assignee, box index) => {
                 let checked_assignee = self.check_assignee(assignee)?;
+
+                // only one-dimensional arrays are supported
+                let size = match checked_assignee.get_type() {
+                    Type::FieldElementArray(size) => size,
+                    t => {
+                        return Err(Error::new(
+                            Code::TypeMismatch,
+                            format!(
+                                "Cannot access element {} on {} of type {}",
+                                index, checked_assignee, t
+                            ),
+                        )
+                        .with_pos(Some(pos)))
+                    }
+                };
+
                 let checked_index = match index {
                     RangeOrExpression::Expression(e) => self.check_expression(e)?,
-                    r => unimplemented!(
-                        "Using slices in assignments is not supported yet, found {}",
-                        r
-                    ),
+                    r => {
+                        return Err(Error::new(
+                            Code::Unsupported,
+                            format!("Using slices in assignments is not supported, found {}", r),
+                        )
+                        .with_pos(Some(pos)))
+                    }
                 };
 
                 let checked_typed_index = match checked_index {
                     TypedExpression::FieldElement(e) => Ok(e),
-                    e => Err(Error {
-                        pos: Some(pos),
-
-                        message: format!(
+                    e => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
                             "Expected array {} index to have type field, found {}",
                             checked_assignee,
                             e.get_type()
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }?;
 
+                self.check_index_bounds(&checked_typed_index, size, pos)?;
+
                 Ok(TypedAssignee::ArrayElement(
                     box checked_assignee,
                     box checked_typed_index,
@@ -526,7 +1402,7 @@ impl<'ast> Checker<'ast> {
         }
     }
 
-    fn check_spread_or_expression<T: Field>(
+    fn check_spread_or_expression(
         &mut self,
         spread_or_expression: SpreadOrExpression<'ast, T>,
     ) -> Result<Vec<TypedExpression<'ast, T>>, Error> {
@@ -548,21 +1424,21 @@ impl<'ast> Checker<'ast> {
                             })
                             .collect())
                     }
-                    e => Err(Error {
-                        pos: Some(pos),
-
-                        message: format!(
+                    e => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
                             "Expected spread operator to apply on field element array, found {}",
                             e.get_type()
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
             SpreadOrExpression::Expression(e) => self.check_expression(e).map(|r| vec![r]),
         }
     }
 
-    fn check_expression<T: Field>(
+    fn check_expression(
         &mut self,
         expr: ExpressionNode<'ast, T>,
     ) -> Result<TypedExpression<'ast, T>, Error> {
@@ -572,19 +1448,40 @@ impl<'ast> Checker<'ast> {
             Expression::Identifier(name) => {
                 // check that `id` is defined in the scope
                 match self.get_scope(&name) {
-                    Some(v) => match v.id.get_type() {
-                        Type::Boolean => Ok(BooleanExpression::Identifier(name.into()).into()),
-                        Type::FieldElement => {
-                            Ok(FieldElementExpression::Identifier(name.into()).into())
-                        }
-                        Type::FieldElementArray(n) => {
-                            Ok(FieldElementArrayExpression::Identifier(n, name.into()).into())
+                    Some(v) => {
+                        let t = v.id.get_type();
+                        self.used_variables.insert(name);
+                        self.reference_variable(&name, pos);
+                        match t {
+                            Type::Boolean => Ok(BooleanExpression::Identifier(name.into()).into()),
+                            Type::FieldElement => {
+                                Ok(FieldElementExpression::Identifier(name.into()).into())
+                            }
+                            Type::FieldElementArray(n) => {
+                                Ok(FieldElementArrayExpression::Identifier(n, name.into()).into())
+                            }
                         }
+                    }
+                    None => match self.generics.get(name) {
+                        // generic parameters can be used as constants
+                        Some(value) => Ok(FieldElementExpression::Number(T::from(*value)).into()),
+                        // global constants are replaced by their value
+                        None => match self.get_constant(name) {
+                            Some(value) => {
+                                if let Some(symbol) =
+                                    self.symbols.find_global(name, SymbolKind::Constant)
+                                {
+                                    self.symbols.reference(pos, symbol);
+                                }
+                                Ok(value.into())
+                            }
+                            None => Err(Error::new(
+                                Code::UndefinedIdentifier,
+                                format!("Identifier \"{}\" is undefined", name),
+                            )
+                            .with_pos(Some(pos))),
+                        },
                     },
-                    None => Err(Error {
-                        pos: Some(pos),
-                        message: format!("Identifier \"{}\" is undefined", name),
-                    }),
                 }
             }
             Expression::Add(box e1, box e2) => {
@@ -595,15 +1492,15 @@ impl<'ast> Checker<'ast> {
                     (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => {
                         Ok(FieldElementExpression::Add(box e1, box e2).into())
                     }
-                    (t1, t2) => Err(Error {
-                        pos: Some(pos),
-
-                        message: format!(
+                    (t1, t2) => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
                             "Expected only field elements, found {:?}, {:?}",
                             t1.get_type(),
                             t2.get_type()
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
             Expression::Sub(box e1, box e2) => {
@@ -614,15 +1511,15 @@ impl<'ast> Checker<'ast> {
                     (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => {
                         Ok(FieldElementExpression::Sub(box e1, box e2).into())
                     }
-                    (t1, t2) => Err(Error {
-                        pos: Some(pos),
-
-                        message: format!(
+                    (t1, t2) => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
                             "Expected only field elements, found {:?}, {:?}",
                             t1.get_type(),
                             t2.get_type()
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
             Expression::Mult(box e1, box e2) => {
@@ -633,15 +1530,15 @@ impl<'ast> Checker<'ast> {
                     (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => {
                         Ok(FieldElementExpression::Mult(box e1, box e2).into())
                     }
-                    (t1, t2) => Err(Error {
-                        pos: Some(pos),
-
-                        message: format!(
+                    (t1, t2) => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
                             "Expected only field elements, found {:?}, {:?}",
                             t1.get_type(),
                             t2.get_type()
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
             Expression::Div(box e1, box e2) => {
@@ -652,17 +1549,25 @@ impl<'ast> Checker<'ast> {
                     (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => {
                         Ok(FieldElementExpression::Div(box e1, box e2).into())
                     }
-                    (t1, t2) => Err(Error {
-                        pos: Some(pos),
-
-                        message: format!(
+                    (t1, t2) => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
                             "Expected only field elements, found {:?}, {:?}",
                             t1.get_type(),
                             t2.get_type()
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
+            Expression::IntegerDiv(box e1, box e2, bitwidth) => {
+                let (e1, e2) = self.check_integer_operands(e1, e2, bitwidth, pos)?;
+                Ok(FieldElementExpression::IntegerDiv(box e1, box e2, bitwidth).into())
+            }
+            Expression::Rem(box e1, box e2, bitwidth) => {
+                let (e1, e2) = self.check_integer_operands(e1, e2, bitwidth, pos)?;
+                Ok(FieldElementExpression::Rem(box e1, box e2, bitwidth).into())
+            }
             Expression::Pow(box e1, box e2) => {
                 let e1_checked = self.check_expression(e1)?;
                 let e2_checked = self.check_expression(e2)?;
@@ -671,15 +1576,15 @@ impl<'ast> Checker<'ast> {
                     (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => Ok(
                         TypedExpression::FieldElement(FieldElementExpression::Pow(box e1, box e2)),
                     ),
-                    (t1, t2) => Err(Error {
-                        pos: Some(pos),
-
-                        message: format!(
+                    (t1, t2) => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
                             "Expected only field elements, found {:?}, {:?}",
                             t1.get_type(),
                             t2.get_type()
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
             Expression::IfElse(box condition, box consequence, box alternative) => {
@@ -699,25 +1604,25 @@ impl<'ast> Checker<'ast> {
                                 (TypedExpression::FieldElementArray(consequence), TypedExpression::FieldElementArray(alternative)) => {
                                     Ok(FieldElementArrayExpression::IfElse(box condition, box consequence, box alternative).into())
                                 },
-                                _ => unimplemented!()
+                                _ => Err(Error::new(Code::Unsupported, format!("{{consequence}} and {{alternative}} in `if/else` expression should be field elements or arrays of field elements, found {}", consequence_type)).with_pos(Some(pos)))
                             }
-                            false => Err(Error {
-                                pos: Some(pos),
-                                message: format!("{{consequence}} and {{alternative}} in `if/else` expression should have the same type, found {}, {}", consequence_type, alternative_type)
-                            })
+                            false => Err(Error::new(Code::TypeMismatch, format!("{{consequence}} and {{alternative}} in `if/else` expression should have the same type, found {}, {}", consequence_type, alternative_type)).with_pos(Some(pos)))
                         }
                     }
-                    c => Err(Error {
-                        pos: Some(pos),
-                        message: format!(
+                    c => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
                             "{{condition}} after `if` should be a boolean, found {}",
                             c.get_type()
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
             Expression::Number(n) => Ok(FieldElementExpression::Number(n).into()),
             Expression::FunctionCall(fun_id, arguments) => {
+                self.check_call_outside_branch(fun_id, pos)?;
+
                 // check the arguments
                 let mut arguments_checked = vec![];
                 for arg in arguments {
@@ -734,12 +1639,13 @@ impl<'ast> Checker<'ast> {
                 // we use type inference to determine the type of the return, so we don't specify it
                 let query = FunctionQuery::new(&fun_id, &arguments_types, &vec![None]);
 
-                let candidates = self.find_candidates(&query);
+                let candidates = self.find_functions(&query, pos)?;
 
                 match candidates.len() {
                     // the function has to be defined
                     1 => {
                         let f = &candidates[0];
+                        self.reference_function(f, pos);
                         // the return count has to be 1
                         match f.signature.outputs.len() {
                             1 => match f.signature.outputs[0] {
@@ -756,26 +1662,25 @@ impl<'ast> Checker<'ast> {
                                     )
                                     .into())
                                 }
-                                _ => unimplemented!(),
+                                ref t => Err(Error::new(Code::Unsupported, format!(
+                                        "Calls to functions returning {} are only supported in multiple definitions, found a call to {}",
+                                        t, f.id
+                                    )).with_pos(Some(pos))),
                             },
-                            n => Err(Error {
-                                pos: Some(pos),
-
-                                message: format!(
+                            n => Err(Error::new(Code::Unsupported, format!(
                                     "{} returns {} values but is called outside of a definition",
                                     f.id, n
-                                ),
-                            }),
+                                )).with_pos(Some(pos))),
                         }
                     }
-                    0 => Err(Error {
-                        pos: Some(pos),
-
-                        message: format!(
+                    0 => Err(Error::new(
+                        Code::UndefinedFunction,
+                        format!(
                             "Function definition for function {} with signature {} not found.",
                             fun_id, query
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                     _ => panic!("duplicate definition should have been caught before the call"),
                 }
             }
@@ -786,16 +1691,17 @@ impl<'ast> Checker<'ast> {
                     (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => {
                         Ok(BooleanExpression::Lt(box e1, box e2).into())
                     }
-                    (e1, e2) => Err(Error {
-                        pos: Some(pos),
-                        message: format!(
+                    (e1, e2) => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
                             "Cannot compare {} of type {} to {} of type {}",
                             e1,
                             e1.get_type(),
                             e2,
                             e2.get_type()
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
             Expression::Le(box e1, box e2) => {
@@ -805,36 +1711,22 @@ impl<'ast> Checker<'ast> {
                     (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => {
                         Ok(BooleanExpression::Le(box e1, box e2).into())
                     }
-                    (e1, e2) => Err(Error {
-                        pos: Some(pos),
-                        message: format!(
+                    (e1, e2) => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
                             "Cannot compare {} of type {} to {} of type {}",
                             e1,
                             e1.get_type(),
                             e2,
                             e2.get_type()
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
-            Expression::Eq(box e1, box e2) => {
-                let e1_checked = self.check_expression(e1)?;
-                let e2_checked = self.check_expression(e2)?;
-                match (e1_checked, e2_checked) {
-                    (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => {
-                        Ok(BooleanExpression::Eq(box e1, box e2).into())
-                    }
-                    (e1, e2) => Err(Error {
-                        pos: Some(pos),
-                        message: format!(
-                            "Cannot compare {} of type {} to {} of type {}",
-                            e1,
-                            e1.get_type(),
-                            e2,
-                            e2.get_type()
-                        ),
-                    }),
-                }
+            Expression::Eq(box e1, box e2) => Ok(self.check_equality(e1, e2, pos)?.into()),
+            Expression::NotEq(box e1, box e2) => {
+                Ok(BooleanExpression::Not(box self.check_equality(e1, e2, pos)?).into())
             }
             Expression::Ge(box e1, box e2) => {
                 let e1_checked = self.check_expression(e1)?;
@@ -843,16 +1735,17 @@ impl<'ast> Checker<'ast> {
                     (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => {
                         Ok(BooleanExpression::Ge(box e1, box e2).into())
                     }
-                    (e1, e2) => Err(Error {
-                        pos: Some(pos),
-                        message: format!(
+                    (e1, e2) => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
                             "Cannot compare {} of type {} to {} of type {}",
                             e1,
                             e1.get_type(),
                             e2,
                             e2.get_type()
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
             Expression::Gt(box e1, box e2) => {
@@ -862,61 +1755,85 @@ impl<'ast> Checker<'ast> {
                     (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => {
                         Ok(BooleanExpression::Gt(box e1, box e2).into())
                     }
-                    (e1, e2) => Err(Error {
-                        pos: Some(pos),
-                        message: format!(
+                    (e1, e2) => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
                             "Cannot compare {} of type {} to {} of type {}",
                             e1,
                             e1.get_type(),
                             e2,
                             e2.get_type()
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
             Expression::Select(box array, box index) => {
                 let array = self.check_expression(array)?;
                 //let index = self.check_range_or_expression(index)?;
 
+                match array {
+                    TypedExpression::FieldElementArray(
+                        FieldElementArrayExpression::FunctionCall(..),
+                    ) => {
+                        return Err(Error::new(
+                            Code::Unsupported,
+                            format!(
+                            "Cannot access elements of {} directly, use an intermediate variable",
+                            array
+                        ),
+                        )
+                        .with_pos(Some(pos)))
+                    }
+                    _ => {}
+                };
+
                 match index {
                     RangeOrExpression::Range(r) => match array {
                         TypedExpression::FieldElementArray(array) => {
                             let array_size = array.size();
 
-                            let from = r
-                                .value
-                                .from
-                                .map(|v| v.to_dec_string().parse::<usize>().unwrap())
-                                .unwrap_or(0);
+                            let from = match r.value.from {
+                                Some(e) => {
+                                    let n = self.check_constant_expression(e)?;
+                                    self.check_usize(n, pos)?
+                                }
+                                None => 0,
+                            };
 
-                            let to = r
-                                .value
-                                .to
-                                .map(|v| v.to_dec_string().parse::<usize>().unwrap())
-                                .unwrap_or(array_size);
+                            let to = match r.value.to {
+                                Some(e) => {
+                                    let n = self.check_constant_expression(e)?;
+                                    self.check_usize(n, pos)?
+                                }
+                                None => array_size,
+                            };
 
                             match (from, to, array_size) {
-                                (f, _, s) if f > s => Err(Error {
-                                    pos: Some(pos),
-                                    message: format!(
+                                (f, _, s) if f > s => Err(Error::new(
+                                    Code::OutOfBounds,
+                                    format!(
                                         "Lower range bound {} is out of array bounds [0, {}]",
                                         f, s,
                                     ),
-                                }),
-                                (_, t, s) if t > s => Err(Error {
-                                    pos: Some(pos),
-                                    message: format!(
+                                )
+                                .with_pos(Some(pos))),
+                                (_, t, s) if t > s => Err(Error::new(
+                                    Code::OutOfBounds,
+                                    format!(
                                         "Higher range bound {} is out of array bounds [0, {}]",
                                         t, s,
                                     ),
-                                }),
-                                (f, t, _) if f > t => Err(Error {
-                                    pos: Some(pos),
-                                    message: format!(
+                                )
+                                .with_pos(Some(pos))),
+                                (f, t, _) if f > t => Err(Error::new(
+                                    Code::OutOfBounds,
+                                    format!(
                                         "Lower range bound {} is larger than higher range bound {}",
                                         f, t,
                                     ),
-                                }),
+                                )
+                                .with_pos(Some(pos))),
                                 (f, t, _) => Ok(FieldElementArrayExpression::Value(
                                     t - f,
                                     (f..t)
@@ -931,28 +1848,33 @@ impl<'ast> Checker<'ast> {
                                 .into()),
                             }
                         }
-                        _ => panic!(""),
+                        a => Err(Error::new(
+                            Code::TypeMismatch,
+                            format!("Cannot take a slice of expression of type {}", a.get_type()),
+                        )
+                        .with_pos(Some(pos))),
                     },
                     RangeOrExpression::Expression(e) => match (array, self.check_expression(e)?) {
                         (
                             TypedExpression::FieldElementArray(a),
                             TypedExpression::FieldElement(i),
-                        ) => Ok(FieldElementExpression::Select(box a, box i).into()),
-                        (a, e) => Err(Error {
-                            pos: Some(pos),
-                            message: format!(
+                        ) => {
+                            self.check_index_bounds(&i, a.size(), pos)?;
+                            Ok(FieldElementExpression::Select(box a, box i).into())
+                        }
+                        (a, e) => Err(Error::new(
+                            Code::TypeMismatch,
+                            format!(
                                 "Cannot access element {} on expression of type {}",
                                 e,
                                 a.get_type()
                             ),
-                        }),
+                        )
+                        .with_pos(Some(pos))),
                     },
                 }
             }
             Expression::InlineArray(expressions) => {
-                // we should have at least one expression
-                let size = expressions.len();
-                assert!(size > 0);
                 // check each expression, getting its type
                 let mut expressions_checked = vec![];
                 for e in expressions {
@@ -960,6 +1882,15 @@ impl<'ast> Checker<'ast> {
                     expressions_checked.extend(e_checked);
                 }
 
+                // we should have at least one expression
+                if expressions_checked.len() == 0 {
+                    return Err(Error::new(
+                        Code::Unsupported,
+                        format!("Empty arrays are not supported"),
+                    )
+                    .with_pos(Some(pos)));
+                }
+
                 // we infer the type to be the type of the first element
                 let inferred_type = expressions_checked.get(0).unwrap().get_type();
 
@@ -971,16 +1902,16 @@ impl<'ast> Checker<'ast> {
                         for e in expressions_checked {
                             let unwrapped_e = match e {
                                 TypedExpression::FieldElement(e) => Ok(e),
-                                e => Err(Error {
-                                    pos: Some(pos),
-
-                                    message: format!(
+                                e => Err(Error::new(
+                                    Code::TypeMismatch,
+                                    format!(
                                         "Expected {} to have type {}, but type is {}",
                                         e,
                                         inferred_type,
                                         e.get_type()
                                     ),
-                                }),
+                                )
+                                .with_pos(Some(pos))),
                             }?;
                             unwrapped_expressions.push(unwrapped_e);
                         }
@@ -991,15 +1922,15 @@ impl<'ast> Checker<'ast> {
                         )
                         .into())
                     }
-                    _ => Err(Error {
-                        pos: Some(pos),
-
-                        message: format!(
+                    _ => Err(Error::new(
+                        Code::Unsupported,
+                        format!(
                             "Only arrays of {} are supported, found {}",
                             Type::FieldElement,
                             inferred_type
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
             Expression::And(box e1, box e2) => {
@@ -1009,15 +1940,15 @@ impl<'ast> Checker<'ast> {
                     (TypedExpression::Boolean(e1), TypedExpression::Boolean(e2)) => {
                         Ok(BooleanExpression::And(box e1, box e2).into())
                     }
-                    (e1, e2) => Err(Error {
-                        pos: Some(pos),
-
-                        message: format!(
+                    (e1, e2) => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
                             "cannot apply boolean operators to {} and {}",
                             e1.get_type(),
                             e2.get_type()
                         ),
-                    }),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
             Expression::Or(box e1, box e2) => {
@@ -1027,41 +1958,238 @@ impl<'ast> Checker<'ast> {
                     (TypedExpression::Boolean(e1), TypedExpression::Boolean(e2)) => {
                         Ok(BooleanExpression::Or(box e1, box e2).into())
                     }
-                    (e1, e2) => Err(Error {
-                        pos: Some(pos),
-
-                        message: format!("cannot compare {} to {}", e1.get_type(), e2.get_type()),
-                    }),
+                    (e1, e2) => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!("cannot compare {} to {}", e1.get_type(), e2.get_type()),
+                    )
+                    .with_pos(Some(pos))),
+                }
+            }
+            Expression::Xor(box e1, box e2) => {
+                let e1_checked = self.check_expression(e1)?;
+                let e2_checked = self.check_expression(e2)?;
+                match (e1_checked, e2_checked) {
+                    (TypedExpression::Boolean(e1), TypedExpression::Boolean(e2)) => {
+                        Ok(BooleanExpression::Xor(box e1, box e2).into())
+                    }
+                    (e1, e2) => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!(
+                            "Expected only booleans, found {}, {}",
+                            e1.get_type(),
+                            e2.get_type()
+                        ),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
             Expression::Not(box e) => {
                 let e_checked = self.check_expression(e)?;
                 match e_checked {
                     TypedExpression::Boolean(e) => Ok(BooleanExpression::Not(box e).into()),
-                    e => Err(Error {
-                        pos: Some(pos),
-
-                        message: format!("cannot negate {}", e.get_type()),
-                    }),
+                    e => Err(Error::new(
+                        Code::TypeMismatch,
+                        format!("cannot negate {}", e.get_type()),
+                    )
+                    .with_pos(Some(pos))),
                 }
             }
         }
     }
 
+    /// Checks that `index` is within the bounds of an array of size `size` if it is a constant
+    fn check_index_bounds(
+        &self,
+        index: &FieldElementExpression<'ast, T>,
+        size: usize,
+        pos: (Position, Position),
+    ) -> Result<(), Error> {
+        match index {
+            FieldElementExpression::Number(n) if *n >= T::from(size) => Err(Error::new(
+                Code::OutOfBounds,
+                format!("Index {} is out of bounds for an array of size {}", n, size),
+            )
+            .with_pos(Some(pos))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks an equality between two expressions of the same type
+    fn check_equality(
+        &mut self,
+        e1: ExpressionNode<'ast, T>,
+        e2: ExpressionNode<'ast, T>,
+        pos: (Position, Position),
+    ) -> Result<BooleanExpression<'ast, T>, Error> {
+        let e1_checked = self.check_expression(e1)?;
+        let e2_checked = self.check_expression(e2)?;
+        match (e1_checked, e2_checked) {
+            (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => {
+                Ok(BooleanExpression::Eq(box e1, box e2))
+            }
+            (TypedExpression::Boolean(e1), TypedExpression::Boolean(e2)) => {
+                Ok(BooleanExpression::BoolEq(box e1, box e2))
+            }
+            (TypedExpression::FieldElementArray(e1), TypedExpression::FieldElementArray(e2))
+                if e1.size() == e2.size() =>
+            {
+                Ok(BooleanExpression::FieldElementArrayEq(box e1, box e2))
+            }
+            (e1, e2) => Err(Error::new(
+                Code::TypeMismatch,
+                format!(
+                    "Cannot compare {} of type {} to {} of type {}",
+                    e1,
+                    e1.get_type(),
+                    e2,
+                    e2.get_type()
+                ),
+            )
+            .with_pos(Some(pos))),
+        }
+    }
+
+    /// Checks the operands of an integer division or remainder on `bitwidth` bits
+    fn check_integer_operands(
+        &mut self,
+        e1: ExpressionNode<'ast, T>,
+        e2: ExpressionNode<'ast, T>,
+        bitwidth: usize,
+        pos: (Position, Position),
+    ) -> Result<
+        (
+            FieldElementExpression<'ast, T>,
+            FieldElementExpression<'ast, T>,
+        ),
+        Error,
+    > {
+        // the quotient times the divisor must not overflow
+        let max_bitwidth = (T::get_required_bits() - 3) / 2;
+
+        if bitwidth == 0 || bitwidth > max_bitwidth {
+            return Err(Error::new(
+                Code::OutOfBounds,
+                format!(
+                    "Bit width of integer operations should be between 1 and {}, found {}",
+                    max_bitwidth, bitwidth
+                ),
+            )
+            .with_pos(Some(pos)));
+        }
+
+        let e1_checked = self.check_expression(e1)?;
+        let e2_checked = self.check_expression(e2)?;
+
+        match (e1_checked, e2_checked) {
+            (TypedExpression::FieldElement(e1), TypedExpression::FieldElement(e2)) => Ok((e1, e2)),
+            (t1, t2) => Err(Error::new(
+                Code::TypeMismatch,
+                format!(
+                    "Expected only field elements, found {:?}, {:?}",
+                    t1.get_type(),
+                    t2.get_type()
+                ),
+            )
+            .with_pos(Some(pos))),
+        }
+    }
+
+    /// Returns the value of the global constant `id`, recording that it is used
+    fn get_constant(&mut self, id: &str) -> Option<ConstantValue<T>> {
+        self.used_symbols.insert(id.to_string());
+        self.constants.get(id).cloned()
+    }
+
     fn get_scope(&self, variable_name: &Identifier<'ast>) -> Option<&ScopedVariable> {
         self.scope.get(&ScopedVariable {
-            id: Variable::new(*variable_name, Type::FieldElement),
+            id: Variable::field_element((*variable_name).into()),
             level: 0,
+            pos: None,
         })
     }
 
-    fn insert_scope(&mut self, v: Variable<'ast>) -> bool {
+    fn insert_scope(&mut self, v: Variable<'ast>, pos: Option<(Position, Position)>) -> bool {
         self.scope.insert(ScopedVariable {
             id: v,
             level: self.level,
+            pos,
         })
     }
 
+    /// Declares `v` in the current scope, warning if it shadows a global constant or a generic parameter
+    fn declare(&mut self, v: Variable<'ast>, pos: (Position, Position)) -> Result<(), Error> {
+        let id = v.id.id;
+
+        if let Some(previous) = self.get_scope(&id) {
+            return Err(Error::new(
+                Code::DuplicateDefinition,
+                format!("Duplicate declaration for variable named {}", id),
+            )
+            .with_pos(Some(pos))
+            .with_note(previous.pos, "previous declaration here"));
+        }
+
+        if self.constants.contains_key(id) {
+            self.warnings.push(
+                Diagnostic::new(
+                    Code::ShadowedName,
+                    format!(
+                        "Variable {} shadows the global constant of the same name",
+                        id
+                    ),
+                )
+                .with_pos(Some(pos)),
+            );
+        }
+
+        if self.generics.contains_key(id) {
+            self.warnings.push(
+                Diagnostic::new(
+                    Code::ShadowedName,
+                    format!(
+                        "Variable {} shadows the generic parameter of the same name",
+                        id
+                    ),
+                )
+                .with_pos(Some(pos)),
+            );
+        }
+
+        self.add_variable_symbol(&v, pos);
+        self.insert_scope(v, Some(pos));
+        Ok(())
+    }
+
+    fn add_variable_symbol(&mut self, v: &Variable<'ast>, pos: (Position, Position)) {
+        let symbol = self.symbols.insert(Symbol {
+            id: v.id.id.to_string(),
+            kind: SymbolKind::Variable,
+            detail: v.to_string(),
+            pos: Some(pos),
+            import: None,
+            scope: self.function,
+        });
+        self.symbols.reference(pos, symbol);
+    }
+
+    /// Records that the variable `id` of the current scope is referred to at `pos`
+    fn reference_variable(&mut self, id: &Identifier<'ast>, pos: (Position, Position)) {
+        let declared = match self.get_scope(id) {
+            Some(v) => v.pos,
+            None => return,
+        };
+        if let Some(symbol) = self.symbols.find(id, SymbolKind::Variable, declared) {
+            self.symbols.reference(pos, symbol);
+        }
+    }
+
+    /// Records that the function `f` is called at `pos`
+    fn reference_function(&mut self, f: &FunctionDeclaration, pos: (Position, Position)) {
+        if let Some(symbol) = self.function_symbols.get(f) {
+            self.symbols.reference(pos, *symbol);
+        }
+    }
+
     fn find_candidates(&self, query: &FunctionQuery<'ast>) -> Vec<FunctionDeclaration> {
         query.match_funcs(&self.functions)
     }
@@ -1072,9 +2200,92 @@ impl<'ast> Checker<'ast> {
 
     fn exit_scope(&mut self) -> () {
         let current_level = self.level;
-        self.scope
-            .retain(|ref scoped_variable| scoped_variable.level < current_level);
+        let (exited, scope): (HashSet<_>, HashSet<_>) = self
+            .scope
+            .drain()
+            .partition(|scoped_variable| scoped_variable.level >= current_level);
+        self.scope = scope;
         self.level -= 1;
+
+        for scoped_variable in exited {
+            let id = scoped_variable.id.id.id;
+            // variables without a position are declared implicitly, and not reported
+            if !self.used_variables.remove(id) && scoped_variable.pos.is_some() {
+                self.warnings.push(
+                    Diagnostic::new(Code::UnusedVariable, format!("Unused variable {}", id))
+                        .with_pos(scoped_variable.pos),
+                );
+            }
+        }
+    }
+}
+
+// the declaration of `funct` as written in the source, for example `def foo<N>(field[N] a) -> (field)`
+fn function_detail<'ast, T: Field>(funct: &Function<'ast, T>) -> String {
+    format!(
+        "def {}{}({}) -> ({})",
+        funct.id,
+        match funct.signature.generics.len() {
+            0 => String::new(),
+            _ => format!("<{}>", funct.signature.generics.join(", ")),
+        },
+        funct
+            .arguments
+            .iter()
+            .map(|a| a.value.to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        funct
+            .signature
+            .outputs
+            .iter()
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+// whether `e` can be passed to a log without adding constraints, that is if it flattens to linear
+// combinations of variables
+fn is_log_argument<'ast, T: Field>(e: &TypedExpression<'ast, T>) -> bool {
+    // whether `e` flattens to a single variable or constant
+    fn is_variable<'ast, T: Field>(e: &FieldElementExpression<'ast, T>) -> bool {
+        match *e {
+            FieldElementExpression::Number(_) | FieldElementExpression::Identifier(_) => true,
+            FieldElementExpression::Select(ref array, ref index) => match (&**array, &**index) {
+                (
+                    &FieldElementArrayExpression::Identifier(..),
+                    &FieldElementExpression::Number(_),
+                ) => true,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn is_linear<'ast, T: Field>(e: &FieldElementExpression<'ast, T>) -> bool {
+        match *e {
+            FieldElementExpression::Add(ref l, ref r)
+            | FieldElementExpression::Sub(ref l, ref r) => is_linear(l) && is_linear(r),
+            FieldElementExpression::Mult(ref l, ref r) => match (&**l, &**r) {
+                (&FieldElementExpression::Number(_), e)
+                | (e, &FieldElementExpression::Number(_)) => is_variable(e),
+                _ => false,
+            },
+            ref e => is_variable(e),
+        }
+    }
+
+    match *e {
+        TypedExpression::Boolean(BooleanExpression::Identifier(_))
+        | TypedExpression::Boolean(BooleanExpression::Value(_)) => true,
+        TypedExpression::Boolean(_) => false,
+        TypedExpression::FieldElement(ref e) => is_linear(e),
+        TypedExpression::FieldElementArray(FieldElementArrayExpression::Identifier(..)) => true,
+        TypedExpression::FieldElementArray(FieldElementArrayExpression::Value(_, ref elements)) => {
+            elements.iter().all(is_linear)
+        }
+        TypedExpression::FieldElementArray(_) => false,
     }
 }
 
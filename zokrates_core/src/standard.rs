@@ -1,6 +1,7 @@
 use crate::flat_absy::{FlatExpression, FlatExpressionList, FlatFunction, FlatStatement};
 use crate::flat_absy::{FlatParameter, FlatVariable};
 use crate::helpers::{DirectiveStatement, Helper, RustHelper};
+use crate::ir::R1cs;
 use crate::types::{Signature, Type};
 use bellman::pairing::ff::ScalarEngine;
 use reduce::Reduce;
@@ -11,11 +12,20 @@ use zokrates_field::field::Field;
 fn flat_expression_from_vec<T: Field>(
     v: Vec<(usize, <<T as Field>::BellmanEngine as ScalarEngine>::Fr)>,
 ) -> FlatExpression<T> {
+    flat_expression_from_lc(
+        v.into_iter()
+            .map(|(key, val)| (key, T::from_bellman(val)))
+            .collect(),
+    )
+}
+
+// util to convert a vector of `(variable_id, coefficient)` in `T` to a flat_expression
+fn flat_expression_from_lc<T: Field>(v: Vec<(usize, T)>) -> FlatExpression<T> {
     match v
         .into_iter()
         .map(|(key, val)| {
             FlatExpression::Mult(
-                box FlatExpression::Number(T::from_bellman(val)),
+                box FlatExpression::Number(val),
                 box FlatExpression::Identifier(FlatVariable::new(key)),
             )
         })
@@ -147,6 +157,100 @@ pub fn sha_round<T: Field>() -> FlatFunction<T> {
     }
 }
 
+/// Returns a flat function which enforces an external constraint system
+///
+/// # Arguments
+///
+/// * `r1cs` - The constraint system, with wire `i` represented by `FlatVariable::new(i)`
+/// * `helper` - A helper computing the values of all wires from the public and private inputs
+///
+/// # Remarks
+///
+/// The function takes the public and private inputs of the constraint system as field elements
+/// and returns its public outputs. The variables inside the function are set in this order:
+/// - constraint system variables
+/// - arguments
+pub fn r1cs_gadget<T: Field>(r1cs: R1cs<T>, helper: Helper) -> FlatFunction<T> {
+    let variable_count = r1cs.wires.len();
+    let input_count = r1cs.public_inputs + r1cs.private_inputs;
+
+    // the inputs follow `~one` and the outputs in the constraint system
+    let input_indices = (1 + r1cs.public_outputs)..(1 + r1cs.public_outputs + input_count);
+    let output_indices = 1..(1 + r1cs.public_outputs);
+
+    // apply an offset of `variable_count` to get the indices of the arguments
+    let argument_indices = input_indices.clone().map(|i| i + variable_count);
+
+    let signature = Signature {
+        inputs: vec![Type::FieldElement; input_count],
+        outputs: vec![Type::FieldElement; r1cs.public_outputs],
+    };
+
+    let arguments = argument_indices
+        .clone()
+        .map(|i| FlatParameter {
+            id: FlatVariable::new(i),
+            private: true,
+        })
+        .collect();
+
+    // insert a directive to set the witness based on the helper and inputs
+    let directive_statement = FlatStatement::Directive(DirectiveStatement {
+        outputs: (0..variable_count).map(|i| FlatVariable::new(i)).collect(),
+        inputs: argument_indices
+            .clone()
+            .map(|i| FlatVariable::new(i).into())
+            .collect(),
+        helper,
+    });
+
+    // define a binding of the first variable in the constraint system to one
+    let one_binding_statement = FlatStatement::Condition(
+        FlatVariable::new(0).into(),
+        FlatExpression::Number(T::from(1)),
+    );
+
+    // bind the inputs of the constraint system to the arguments
+    let input_binding_statements = input_indices
+        .zip(argument_indices)
+        .map(|(cs_index, argument_index)| {
+            FlatStatement::Condition(
+                FlatVariable::new(cs_index).into(),
+                FlatVariable::new(argument_index).into(),
+            )
+        });
+
+    let constraint_statements = r1cs.constraints.into_iter().map(|(a, b, c)| {
+        FlatStatement::Condition(
+            flat_expression_from_lc(c),
+            FlatExpression::Mult(
+                box flat_expression_from_lc(a),
+                box flat_expression_from_lc(b),
+            ),
+        )
+    });
+
+    let return_statement = FlatStatement::Return(FlatExpressionList {
+        expressions: output_indices
+            .map(|o| FlatExpression::Identifier(FlatVariable::new(o)))
+            .collect(),
+    });
+
+    let statements = std::iter::once(directive_statement)
+        .chain(std::iter::once(one_binding_statement))
+        .chain(input_binding_statements)
+        .chain(constraint_statements)
+        .chain(std::iter::once(return_statement))
+        .collect();
+
+    FlatFunction {
+        id: "main".to_owned(),
+        arguments,
+        statements,
+        signature,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,10 +327,57 @@ mod tests {
         let prog = crate::ir::Prog {
             main: f,
             private: vec![true; 768],
+            committed: vec![],
+            names: crate::flat_absy::SourceNames::default(),
         };
 
         let input = (0..512).map(|_| 0).chain((0..256).map(|_| 1)).collect();
 
         prog.execute(&input).unwrap();
     }
+
+    #[test]
+    fn generate_r1cs_gadget() {
+        // out = a * b, with wires laid out as `~one | out | a | b`
+        let r1cs = R1cs {
+            wires: (0..4).map(FlatVariable::new).collect(),
+            public_outputs: 1,
+            public_inputs: 1,
+            private_inputs: 1,
+            constraints: vec![(
+                vec![(2, FieldPrime::from(1))],
+                vec![(3, FieldPrime::from(1))],
+                vec![(1, FieldPrime::from(1))],
+            )],
+        };
+
+        let compiled = r1cs_gadget(r1cs, Helper::Rust(RustHelper::Identity));
+
+        assert_eq!(
+            compiled.signature,
+            Signature::new()
+                .inputs(vec![Type::FieldElement, Type::FieldElement])
+                .outputs(vec![Type::FieldElement])
+        );
+
+        // arguments are offset by the wire count
+        assert_eq!(
+            compiled.arguments.iter().map(|a| a.id).collect::<Vec<_>>(),
+            vec![FlatVariable::new(4), FlatVariable::new(5)]
+        );
+
+        // the first input wire is bound to the first argument
+        assert_eq!(
+            compiled.statements[2],
+            FlatStatement::Condition(FlatVariable::new(2).into(), FlatVariable::new(4).into())
+        );
+
+        // the output wire is returned
+        assert_eq!(
+            compiled.statements.last().unwrap(),
+            &FlatStatement::Return(FlatExpressionList {
+                expressions: vec![FlatExpression::Identifier(FlatVariable::new(1))]
+            })
+        );
+    }
 }
@@ -0,0 +1,92 @@
+//! Module reporting the array elements accessed out of bounds
+//!
+//! Indices depending on loop variables are only known once loops are unrolled and constants are
+//! propagated, so they cannot be checked during the semantic analysis.
+
+use crate::diagnostics::Code;
+use crate::parser::Position;
+use crate::semantics::Error;
+use crate::typed_absy::folder::*;
+use crate::typed_absy::Folder;
+use crate::typed_absy::*;
+use crate::types::Type;
+use zokrates_field::field::Field;
+
+pub struct BoundsChecker {
+    // the location in the source of the statement being checked
+    location: Option<(Position, Position)>,
+    // the locations of the statements found to access an element out of bounds
+    reported: Vec<Option<(Position, Position)>>,
+    errors: Vec<Error>,
+}
+
+impl BoundsChecker {
+    fn new() -> Self {
+        BoundsChecker {
+            location: None,
+            reported: vec![],
+            errors: vec![],
+        }
+    }
+
+    pub fn check<T: Field>(p: TypedProg<T>) -> Result<TypedProg<T>, Vec<Error>> {
+        let mut checker = BoundsChecker::new();
+        let p = checker.fold_program(p);
+        match checker.errors.len() {
+            0 => Ok(p),
+            _ => Err(checker.errors),
+        }
+    }
+
+    fn check_index<T: Field>(&mut self, index: &FieldElementExpression<T>, size: usize) {
+        match *index {
+            // a statement unrolled from a loop is only reported once
+            FieldElementExpression::Number(ref n)
+                if *n >= T::from(size) && !self.reported.contains(&self.location) =>
+            {
+                self.reported.push(self.location);
+                self.errors.push(
+                    Error::new(
+                        Code::OutOfBounds,
+                        format!("Index {} is out of bounds for an array of size {}", n, size),
+                    )
+                    .with_pos(self.location),
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'ast, T: Field> Folder<'ast, T> for BoundsChecker {
+    fn fold_statement(&mut self, s: TypedStatement<'ast, T>) -> Vec<TypedStatement<'ast, T>> {
+        match s {
+            TypedStatement::Location(position) => self.location = Some(position),
+            TypedStatement::Definition(
+                TypedAssignee::ArrayElement(
+                    box TypedAssignee::Identifier(ref variable),
+                    box ref index,
+                ),
+                _,
+            ) => match variable.get_type() {
+                Type::FieldElementArray(size) => self.check_index(index, size),
+                _ => {}
+            },
+            _ => {}
+        };
+        fold_statement(self, s)
+    }
+
+    fn fold_field_expression(
+        &mut self,
+        e: FieldElementExpression<'ast, T>,
+    ) -> FieldElementExpression<'ast, T> {
+        match e {
+            FieldElementExpression::Select(ref array, ref index) => {
+                self.check_index(index, array.size())
+            }
+            _ => {}
+        };
+        fold_field_expression(self, e)
+    }
+}
@@ -0,0 +1,24 @@
+//! Module removing the locations in the source of the statements of a program, which are only
+//! kept in the compiled program for the debugger
+
+use crate::typed_absy::folder::*;
+use crate::typed_absy::Folder;
+use crate::typed_absy::*;
+use zokrates_field::field::Field;
+
+pub struct LocationStripper;
+
+impl LocationStripper {
+    pub fn strip<T: Field>(p: TypedProg<T>) -> TypedProg<T> {
+        LocationStripper.fold_program(p)
+    }
+}
+
+impl<'ast, T: Field> Folder<'ast, T> for LocationStripper {
+    fn fold_statement(&mut self, s: TypedStatement<'ast, T>) -> Vec<TypedStatement<'ast, T>> {
+        match s {
+            TypedStatement::Location(..) => vec![],
+            s => fold_statement(self, s),
+        }
+    }
+}
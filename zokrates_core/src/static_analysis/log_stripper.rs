@@ -0,0 +1,26 @@
+//! Module removing the log statements of a program
+//!
+//! Logs do not take part in the constraints of a program, so removing them before flattening
+//! guarantees that nothing they refer to ends up in the compiled program.
+
+use crate::typed_absy::folder::*;
+use crate::typed_absy::Folder;
+use crate::typed_absy::*;
+use zokrates_field::field::Field;
+
+pub struct LogStripper;
+
+impl LogStripper {
+    pub fn strip<T: Field>(p: TypedProg<T>) -> TypedProg<T> {
+        LogStripper.fold_program(p)
+    }
+}
+
+impl<'ast, T: Field> Folder<'ast, T> for LogStripper {
+    fn fold_statement(&mut self, s: TypedStatement<'ast, T>) -> Vec<TypedStatement<'ast, T>> {
+        match s {
+            TypedStatement::Log(..) => vec![],
+            s => fold_statement(self, s),
+        }
+    }
+}
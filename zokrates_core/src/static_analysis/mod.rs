@@ -4,17 +4,21 @@
 //! @author Thibaut Schaeffer <thibaut@schaeff.fr>
 //! @date 2018
 
+mod bounds;
 mod dead_code;
 mod flat_propagation;
 mod inline;
-mod power_check;
+mod location_stripper;
+mod log_stripper;
 mod propagation;
 mod unroll;
 
+pub use self::bounds::BoundsChecker;
 use self::dead_code::DeadCode;
 use self::inline::Inliner;
-use self::power_check::PowerChecker;
-use self::propagation::Propagator;
+pub use self::location_stripper::LocationStripper;
+pub use self::log_stripper::LogStripper;
+pub use self::propagation::Propagator;
 use self::unroll::Unroller;
 use crate::flat_absy::FlatProg;
 use crate::typed_absy::TypedProg;
@@ -26,9 +30,8 @@ pub trait Analyse {
 
 impl<'ast, T: Field> Analyse for TypedProg<'ast, T> {
     fn analyse(self) -> Self {
-        let r = PowerChecker::check(self);
         // unroll
-        let r = Unroller::unroll(r);
+        let r = Unroller::unroll(self);
         //propagate a first time for constants to reach function calls
         let r = Propagator::propagate(r);
         // apply inlining strategy
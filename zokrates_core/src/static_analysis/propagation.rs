@@ -4,6 +4,7 @@
 //! @author Thibaut Schaeffer <thibaut@schaeff.fr>
 //! @date 2018
 
+use crate::helpers::{Executable, RustHelper};
 use crate::typed_absy::folder::*;
 use crate::typed_absy::*;
 use std::collections::HashMap;
@@ -23,6 +24,50 @@ impl<'ast, T: Field> Propagator<'ast, T> {
     pub fn propagate(p: TypedProg<'ast, T>) -> TypedProg<'ast, T> {
         Propagator::new().fold_program(p)
     }
+
+    /// Computes the quotient and remainder of the integer division of `e1` by `e2` if both are constants in range
+    /// and `e2` is not zero. Otherwise, the folded operands are returned so that the error is caught at runtime.
+    fn fold_integer_division(
+        &mut self,
+        e1: FieldElementExpression<'ast, T>,
+        e2: FieldElementExpression<'ast, T>,
+        bitwidth: usize,
+    ) -> Result<
+        (
+            FieldElementExpression<'ast, T>,
+            FieldElementExpression<'ast, T>,
+        ),
+        (
+            FieldElementExpression<'ast, T>,
+            FieldElementExpression<'ast, T>,
+        ),
+    > {
+        match (
+            self.fold_field_expression(e1),
+            self.fold_field_expression(e2),
+        ) {
+            (FieldElementExpression::Number(n1), FieldElementExpression::Number(n2))
+                if n1 < T::from(2).pow(bitwidth) && n2 < T::from(2).pow(bitwidth) =>
+            {
+                match RustHelper::IntegerDiv.execute(&vec![n1.clone(), n2.clone()]) {
+                    Ok(res) => Ok((
+                        FieldElementExpression::Number(res[0].clone()),
+                        FieldElementExpression::Number(res[1].clone()),
+                    )),
+                    Err(_) => Err((
+                        FieldElementExpression::Number(n1),
+                        FieldElementExpression::Number(n2),
+                    )),
+                }
+            }
+            (e1, e2) => Err((e1, e2)),
+        }
+    }
+
+    /// Folds an expression which does not depend on any variable, such as the value of a global constant
+    pub fn propagate_expression(e: TypedExpression<'ast, T>) -> TypedExpression<'ast, T> {
+        Propagator::new().fold_expression(e)
+    }
 }
 
 impl<'ast, T: Field> Folder<'ast, T> for Propagator<'ast, T> {
@@ -68,6 +113,11 @@ impl<'ast, T: Field> Folder<'ast, T> for Propagator<'ast, T> {
 				let expr = self.fold_expression(expr);
 
 				match (index, expr) {
+					// a[42] = e with a of size 2
+					// -> keep the statement for the bounds checker to report
+					(FieldElementExpression::Number(ref n), ref expr) if *n >= T::from(var.get_type().get_primitive_count()) => {
+						Some(TypedStatement::Definition(TypedAssignee::ArrayElement(box TypedAssignee::Identifier(var), box FieldElementExpression::Number(n.clone())), expr.clone()))
+					},
 					(
 						FieldElementExpression::Number(n),
 						TypedExpression::FieldElement(expr @ FieldElementExpression::Number(..))
@@ -76,13 +126,9 @@ impl<'ast, T: Field> Folder<'ast, T> for Propagator<'ast, T> {
 						// -> store (a[42] -> 33) in the constants, possibly overwriting the previous entry
 						self.constants.entry(TypedAssignee::Identifier(var)).and_modify(|e| {
 							match *e {
-								TypedExpression::FieldElementArray(FieldElementArrayExpression::Value(size, ref mut v)) => {
+								TypedExpression::FieldElementArray(FieldElementArrayExpression::Value(_, ref mut v)) => {
 									let n_as_usize = n.to_dec_string().parse::<usize>().unwrap();
-									if n_as_usize < size {
-										v[n_as_usize] = expr;
-									} else {
-										panic!(format!("out of bounds index ({} >= {}) found during static analysis", n_as_usize, size));
-									}
+									v[n_as_usize] = expr;
 								},
 								_ => panic!("constants should only store constants")
 							}
@@ -105,10 +151,20 @@ impl<'ast, T: Field> Folder<'ast, T> for Propagator<'ast, T> {
 			},
 			// we unrolled for loops in the previous step
 			TypedStatement::For(..) => panic!("for loop is unexpected, it should have been unrolled"),
+			// we lowered if-else statements in the previous step
+			TypedStatement::IfElse(..) => panic!("if-else statement is unexpected, it should have been lowered"),
 			TypedStatement::MultipleDefinition(variables, expression_list) => {
 				let expression_list = self.fold_expression_list(expression_list);
 				Some(TypedStatement::MultipleDefinition(variables, expression_list))
 			}
+			// drop logs which are known not to be printed
+			TypedStatement::Log(condition, format, arguments) => {
+				match self.fold_boolean_expression(condition) {
+					BooleanExpression::Value(false) => None,
+					condition => Some(TypedStatement::Log(condition, format, arguments.into_iter().map(|a| self.fold_expression(a)).collect()))
+				}
+			}
+			TypedStatement::Location(position) => Some(TypedStatement::Location(position)),
 		};
         match res {
             Some(v) => vec![v],
@@ -172,6 +228,18 @@ impl<'ast, T: Field> Folder<'ast, T> for Propagator<'ast, T> {
                 }
                 (e1, e2) => FieldElementExpression::Div(box e1, box e2),
             },
+            FieldElementExpression::IntegerDiv(box e1, box e2, bitwidth) => {
+                match self.fold_integer_division(e1, e2, bitwidth) {
+                    Ok((q, _)) => q,
+                    Err((e1, e2)) => FieldElementExpression::IntegerDiv(box e1, box e2, bitwidth),
+                }
+            }
+            FieldElementExpression::Rem(box e1, box e2, bitwidth) => {
+                match self.fold_integer_division(e1, e2, bitwidth) {
+                    Ok((_, r)) => r,
+                    Err((e1, e2)) => FieldElementExpression::Rem(box e1, box e2, bitwidth),
+                }
+            }
             FieldElementExpression::Pow(box e1, box e2) => {
                 let e1 = self.fold_field_expression(e1);
                 let e2 = self.fold_field_expression(e2);
@@ -182,13 +250,7 @@ impl<'ast, T: Field> Folder<'ast, T> for Propagator<'ast, T> {
                     (FieldElementExpression::Number(n1), FieldElementExpression::Number(n2)) => {
                         FieldElementExpression::Number(n1.pow(n2))
                     }
-                    (e1, FieldElementExpression::Number(n2)) => {
-                        FieldElementExpression::Pow(box e1, box FieldElementExpression::Number(n2))
-                    }
-                    (_, e2) => panic!(format!(
-                        "non-constant exponent {} detected during static analysis",
-                        e2
-                    )),
+                    (e1, e2) => FieldElementExpression::Pow(box e1, box e2),
                 }
             }
             FieldElementExpression::IfElse(box condition, box consequence, box alternative) => {
@@ -213,10 +275,11 @@ impl<'ast, T: Field> Folder<'ast, T> for Propagator<'ast, T> {
                         if n_as_usize < size {
                             v[n_as_usize].clone()
                         } else {
-                            panic!(format!(
-                                "out of bounds index ({} >= {}) found during static analysis",
-                                n_as_usize, size
-                            ));
+                            // keep the selection for the bounds checker to report
+                            FieldElementExpression::Select(
+                                box FieldElementArrayExpression::Value(size, v),
+                                box FieldElementExpression::Number(n),
+                            )
                         }
                     }
                     (
@@ -291,6 +354,29 @@ impl<'ast, T: Field> Folder<'ast, T> for Propagator<'ast, T> {
                     (e1, e2) => BooleanExpression::Eq(box e1, box e2),
                 }
             }
+            BooleanExpression::BoolEq(box e1, box e2) => {
+                let e1 = self.fold_boolean_expression(e1);
+                let e2 = self.fold_boolean_expression(e2);
+
+                match (e1, e2) {
+                    (BooleanExpression::Value(v1), BooleanExpression::Value(v2)) => {
+                        BooleanExpression::Value(v1 == v2)
+                    }
+                    (e1, e2) => BooleanExpression::BoolEq(box e1, box e2),
+                }
+            }
+            BooleanExpression::FieldElementArrayEq(box e1, box e2) => {
+                let e1 = self.fold_field_array_expression(e1);
+                let e2 = self.fold_field_array_expression(e2);
+
+                match (
+                    ConstantValue::from_expression(&e1.clone().into()),
+                    ConstantValue::from_expression(&e2.clone().into()),
+                ) {
+                    (Some(v1), Some(v2)) => BooleanExpression::Value(v1 == v2),
+                    _ => BooleanExpression::FieldElementArrayEq(box e1, box e2),
+                }
+            }
             BooleanExpression::Lt(box e1, box e2) => {
                 let e1 = self.fold_field_expression(e1);
                 let e2 = self.fold_field_expression(e2);
@@ -335,6 +421,43 @@ impl<'ast, T: Field> Folder<'ast, T> for Propagator<'ast, T> {
                     (e1, e2) => BooleanExpression::Ge(box e1, box e2),
                 }
             }
+            BooleanExpression::Or(box e1, box e2) => {
+                let e1 = self.fold_boolean_expression(e1);
+                let e2 = self.fold_boolean_expression(e2);
+
+                match (e1, e2) {
+                    (BooleanExpression::Value(v1), BooleanExpression::Value(v2)) => {
+                        BooleanExpression::Value(v1 || v2)
+                    }
+                    (e1, e2) => BooleanExpression::Or(box e1, box e2),
+                }
+            }
+            BooleanExpression::And(box e1, box e2) => {
+                let e1 = self.fold_boolean_expression(e1);
+                let e2 = self.fold_boolean_expression(e2);
+
+                match (e1, e2) {
+                    (BooleanExpression::Value(v1), BooleanExpression::Value(v2)) => {
+                        BooleanExpression::Value(v1 && v2)
+                    }
+                    (e1, e2) => BooleanExpression::And(box e1, box e2),
+                }
+            }
+            BooleanExpression::Xor(box e1, box e2) => {
+                let e1 = self.fold_boolean_expression(e1);
+                let e2 = self.fold_boolean_expression(e2);
+
+                match (e1, e2) {
+                    (BooleanExpression::Value(v1), BooleanExpression::Value(v2)) => {
+                        BooleanExpression::Value(v1 != v2)
+                    }
+                    (e1, e2) => BooleanExpression::Xor(box e1, box e2),
+                }
+            }
+            BooleanExpression::Not(box e) => match self.fold_boolean_expression(e) {
+                BooleanExpression::Value(v) => BooleanExpression::Value(!v),
+                e => BooleanExpression::Not(box e),
+            },
             e => fold_boolean_expression(self, e),
         }
     }
@@ -405,6 +528,45 @@ mod tests {
                 );
             }
 
+            #[test]
+            fn integer_div() {
+                let e = FieldElementExpression::IntegerDiv(
+                    box FieldElementExpression::Number(FieldPrime::from(7)),
+                    box FieldElementExpression::Number(FieldPrime::from(2)),
+                    8,
+                );
+
+                assert_eq!(
+                    Propagator::new().fold_field_expression(e),
+                    FieldElementExpression::Number(FieldPrime::from(3))
+                );
+            }
+
+            #[test]
+            fn rem() {
+                let e = FieldElementExpression::Rem(
+                    box FieldElementExpression::Number(FieldPrime::from(7)),
+                    box FieldElementExpression::Number(FieldPrime::from(2)),
+                    8,
+                );
+
+                assert_eq!(
+                    Propagator::new().fold_field_expression(e),
+                    FieldElementExpression::Number(FieldPrime::from(1))
+                );
+            }
+
+            #[test]
+            fn integer_div_out_of_range() {
+                let e = FieldElementExpression::IntegerDiv(
+                    box FieldElementExpression::Number(FieldPrime::from(256)),
+                    box FieldElementExpression::Number(FieldPrime::from(2)),
+                    8,
+                );
+
+                assert_eq!(Propagator::new().fold_field_expression(e.clone()), e);
+            }
+
             #[test]
             fn pow() {
                 let e = FieldElementExpression::Pow(
@@ -418,6 +580,16 @@ mod tests {
                 );
             }
 
+            #[test]
+            fn pow_variable_exponent() {
+                let e = FieldElementExpression::Pow(
+                    box FieldElementExpression::Number(FieldPrime::from(2)),
+                    box FieldElementExpression::Identifier("a".into()),
+                );
+
+                assert_eq!(Propagator::new().fold_field_expression(e.clone()), e);
+            }
+
             #[test]
             fn if_else_true() {
                 let e = FieldElementExpression::IfElse(
@@ -583,6 +755,156 @@ mod tests {
                     BooleanExpression::Value(false)
                 );
             }
+
+            #[test]
+            fn and() {
+                let e_true = BooleanExpression::And(
+                    box BooleanExpression::Value(true),
+                    box BooleanExpression::Value(true),
+                );
+
+                let e_false = BooleanExpression::And(
+                    box BooleanExpression::Value(true),
+                    box BooleanExpression::Value(false),
+                );
+
+                assert_eq!(
+                    Propagator::<FieldPrime>::new().fold_boolean_expression(e_true),
+                    BooleanExpression::Value(true)
+                );
+                assert_eq!(
+                    Propagator::<FieldPrime>::new().fold_boolean_expression(e_false),
+                    BooleanExpression::Value(false)
+                );
+            }
+
+            #[test]
+            fn or() {
+                let e_true = BooleanExpression::Or(
+                    box BooleanExpression::Value(false),
+                    box BooleanExpression::Value(true),
+                );
+
+                let e_false = BooleanExpression::Or(
+                    box BooleanExpression::Value(false),
+                    box BooleanExpression::Value(false),
+                );
+
+                assert_eq!(
+                    Propagator::<FieldPrime>::new().fold_boolean_expression(e_true),
+                    BooleanExpression::Value(true)
+                );
+                assert_eq!(
+                    Propagator::<FieldPrime>::new().fold_boolean_expression(e_false),
+                    BooleanExpression::Value(false)
+                );
+            }
+
+            #[test]
+            fn xor() {
+                let e_true = BooleanExpression::Xor(
+                    box BooleanExpression::Value(true),
+                    box BooleanExpression::Value(false),
+                );
+
+                let e_false = BooleanExpression::Xor(
+                    box BooleanExpression::Value(true),
+                    box BooleanExpression::Value(true),
+                );
+
+                assert_eq!(
+                    Propagator::<FieldPrime>::new().fold_boolean_expression(e_true),
+                    BooleanExpression::Value(true)
+                );
+                assert_eq!(
+                    Propagator::<FieldPrime>::new().fold_boolean_expression(e_false),
+                    BooleanExpression::Value(false)
+                );
+            }
+
+            #[test]
+            fn bool_eq() {
+                let e_true = BooleanExpression::BoolEq(
+                    box BooleanExpression::Value(false),
+                    box BooleanExpression::Value(false),
+                );
+
+                let e_false = BooleanExpression::BoolEq(
+                    box BooleanExpression::Value(true),
+                    box BooleanExpression::Value(false),
+                );
+
+                assert_eq!(
+                    Propagator::<FieldPrime>::new().fold_boolean_expression(e_true),
+                    BooleanExpression::Value(true)
+                );
+                assert_eq!(
+                    Propagator::<FieldPrime>::new().fold_boolean_expression(e_false),
+                    BooleanExpression::Value(false)
+                );
+            }
+
+            #[test]
+            fn field_array_eq() {
+                let e_true = BooleanExpression::FieldElementArrayEq(
+                    box FieldElementArrayExpression::Value(
+                        2,
+                        vec![
+                            FieldElementExpression::Number(FieldPrime::from(1)),
+                            FieldElementExpression::Number(FieldPrime::from(2)),
+                        ],
+                    ),
+                    box FieldElementArrayExpression::Value(
+                        2,
+                        vec![
+                            FieldElementExpression::Number(FieldPrime::from(1)),
+                            FieldElementExpression::Number(FieldPrime::from(2)),
+                        ],
+                    ),
+                );
+
+                let e_false = BooleanExpression::FieldElementArrayEq(
+                    box FieldElementArrayExpression::Value(
+                        2,
+                        vec![
+                            FieldElementExpression::Number(FieldPrime::from(1)),
+                            FieldElementExpression::Number(FieldPrime::from(2)),
+                        ],
+                    ),
+                    box FieldElementArrayExpression::Value(
+                        2,
+                        vec![
+                            FieldElementExpression::Number(FieldPrime::from(2)),
+                            FieldElementExpression::Number(FieldPrime::from(1)),
+                        ],
+                    ),
+                );
+
+                assert_eq!(
+                    Propagator::<FieldPrime>::new().fold_boolean_expression(e_true),
+                    BooleanExpression::Value(true)
+                );
+                assert_eq!(
+                    Propagator::<FieldPrime>::new().fold_boolean_expression(e_false),
+                    BooleanExpression::Value(false)
+                );
+            }
+
+            #[test]
+            fn not() {
+                let e_true = BooleanExpression::Not(box BooleanExpression::Value(false));
+
+                let e_false = BooleanExpression::Not(box BooleanExpression::Value(true));
+
+                assert_eq!(
+                    Propagator::<FieldPrime>::new().fold_boolean_expression(e_true),
+                    BooleanExpression::Value(true)
+                );
+                assert_eq!(
+                    Propagator::<FieldPrime>::new().fold_boolean_expression(e_false),
+                    BooleanExpression::Value(false)
+                );
+            }
         }
     }
 
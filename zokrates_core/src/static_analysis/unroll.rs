@@ -1,4 +1,4 @@
-//! Module containing SSA reduction, including for-loop unrolling
+//! Module containing SSA reduction, including for-loop unrolling and if-else lowering
 //!
 //! @file unroll.rs
 //! @author Thibaut Schaeffer <thibaut@schaeff.fr>
@@ -7,37 +7,63 @@
 use crate::typed_absy::folder::*;
 use crate::typed_absy::*;
 use crate::types::Type;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use zokrates_field::field::Field;
 
+/// Name of the variables holding the conditions of if-else statements
+const IF_ELSE_CONDITION: &str = "#if_else_condition";
+
 pub struct Unroller<'ast> {
+    // the current version of each variable
     substitution: HashMap<Identifier<'ast>, usize>,
+    // the highest version issued for each variable, which differs from the current one
+    // after leaving the branch of an if-else statement
+    issued: HashMap<Identifier<'ast>, usize>,
+    types: HashMap<Identifier<'ast>, Type>,
+    // the conditions of the if-else branches we are in, with their polarity
+    conditions: Vec<(Identifier<'ast>, bool)>,
 }
 
 impl<'ast> Unroller<'ast> {
     fn new() -> Self {
         Unroller {
             substitution: HashMap::new(),
+            issued: HashMap::new(),
+            types: HashMap::new(),
+            conditions: vec![],
         }
     }
 
     fn issue_next_ssa_variable(&mut self, v: Variable<'ast>) -> Variable<'ast> {
-        let res = match self.substitution.get(&v.id) {
-            Some(i) => Variable {
-                id: Identifier {
-                    id: v.id.id,
-                    version: i + 1,
-                    stack: vec![],
-                },
-                ..v
+        let version = self.issued.get(&v.id).map(|i| i + 1).unwrap_or(0);
+        self.issued.insert(v.id.clone(), version);
+        self.substitution.insert(v.id.clone(), version);
+        self.types.insert(v.id.clone(), v.get_type());
+        Variable {
+            id: Identifier {
+                id: v.id.id,
+                version,
+                stack: vec![],
             },
-            None => Variable { ..v.clone() },
-        };
-        self.substitution
-            .entry(v.id)
-            .and_modify(|e| *e += 1)
-            .or_insert(0);
-        res
+            ..v
+        }
+    }
+
+    /// Returns the condition under which the current statement is executed
+    fn branch_condition<T: Field>(&self) -> Option<BooleanExpression<'ast, T>> {
+        self.conditions
+            .iter()
+            .map(|(id, polarity)| {
+                let c = BooleanExpression::Identifier(id.clone());
+                match *polarity {
+                    true => c,
+                    false => BooleanExpression::Not(box c),
+                }
+            })
+            .fold(None, |acc, c| match acc {
+                None => Some(c),
+                Some(acc) => Some(BooleanExpression::And(box acc, box c)),
+            })
     }
 
     pub fn unroll<T: Field>(p: TypedProg<T>) -> TypedProg<T> {
@@ -45,6 +71,82 @@ impl<'ast> Unroller<'ast> {
     }
 }
 
+/// Returns the variables declared in `statements`, which are local to them
+fn declared_variables<'ast, T: Field>(
+    statements: &Vec<TypedStatement<'ast, T>>,
+) -> HashSet<Identifier<'ast>> {
+    statements
+        .iter()
+        .flat_map(|s| match s {
+            TypedStatement::Declaration(v) => vec![v.id.clone()],
+            TypedStatement::For(v, _, _, statements) => {
+                let mut res: Vec<_> = declared_variables(statements).into_iter().collect();
+                res.push(v.id.clone());
+                res
+            }
+            TypedStatement::IfElse(_, consequence, alternative) => declared_variables(consequence)
+                .into_iter()
+                .chain(declared_variables(alternative))
+                .collect(),
+            _ => vec![],
+        })
+        .collect()
+}
+
+fn identifier_expression<'ast, T: Field>(
+    id: Identifier<'ast>,
+    t: Type,
+) -> TypedExpression<'ast, T> {
+    match t {
+        Type::FieldElement => FieldElementExpression::Identifier(id).into(),
+        Type::Boolean => BooleanExpression::Identifier(id).into(),
+        Type::FieldElementArray(size) => FieldElementArrayExpression::Identifier(size, id).into(),
+    }
+}
+
+/// Returns `e` if `condition` holds, `default` otherwise
+fn guard<'ast, T: Field>(
+    condition: BooleanExpression<'ast, T>,
+    e: FieldElementExpression<'ast, T>,
+    default: T,
+) -> FieldElementExpression<'ast, T> {
+    FieldElementExpression::IfElse(
+        box condition,
+        box e,
+        box FieldElementExpression::Number(default),
+    )
+}
+
+/// Returns an expression evaluating to `consequence` if `condition` holds, `alternative` otherwise
+fn select<'ast, T: Field>(
+    condition: BooleanExpression<'ast, T>,
+    consequence: TypedExpression<'ast, T>,
+    alternative: TypedExpression<'ast, T>,
+) -> TypedExpression<'ast, T> {
+    match (consequence, alternative) {
+        (
+            TypedExpression::FieldElement(consequence),
+            TypedExpression::FieldElement(alternative),
+        ) => FieldElementExpression::IfElse(box condition, box consequence, box alternative).into(),
+        (TypedExpression::Boolean(consequence), TypedExpression::Boolean(alternative)) => {
+            BooleanExpression::Or(
+                box BooleanExpression::And(box condition.clone(), box consequence),
+                box BooleanExpression::And(
+                    box BooleanExpression::Not(box condition),
+                    box alternative,
+                ),
+            )
+            .into()
+        }
+        (
+            TypedExpression::FieldElementArray(consequence),
+            TypedExpression::FieldElementArray(alternative),
+        ) => FieldElementArrayExpression::IfElse(box condition, box consequence, box alternative)
+            .into(),
+        _ => panic!("branches should have the same type"),
+    }
+}
+
 impl<'ast, T: Field> Folder<'ast, T> for Unroller<'ast> {
     fn fold_statement(&mut self, s: TypedStatement<'ast, T>) -> Vec<TypedStatement<'ast, T>> {
         match s {
@@ -153,14 +255,161 @@ impl<'ast, T: Field> Folder<'ast, T> for Unroller<'ast> {
 
                 res
             }
+            TypedStatement::IfElse(condition, consequence, alternative) => {
+                // each variable assigned to in a branch is assigned the selection of its values at the
+                // end of both branches. Assertions and divisions inside a branch are only enforced if
+                // the branch is taken. Function calls, whose assertions could not be guarded, are
+                // rejected inside branches by the semantic checker.
+                let condition = self.fold_boolean_expression(condition);
+                let condition_variable =
+                    self.issue_next_ssa_variable(Variable::boolean(IF_ELSE_CONDITION.into()));
+
+                let mut local_variables = declared_variables(&consequence);
+                local_variables.extend(declared_variables(&alternative));
+                local_variables.insert(IF_ELSE_CONDITION.into());
+
+                let mut res = vec![TypedStatement::Definition(
+                    TypedAssignee::Identifier(condition_variable.clone()),
+                    condition.into(),
+                )];
+
+                let before = self.substitution.clone();
+
+                self.conditions.push((condition_variable.id.clone(), true));
+                for s in consequence {
+                    res.extend(self.fold_statement(s));
+                }
+                self.conditions.pop();
+
+                let after_consequence = std::mem::replace(&mut self.substitution, before.clone());
+
+                self.conditions.push((condition_variable.id.clone(), false));
+                for s in alternative {
+                    res.extend(self.fold_statement(s));
+                }
+                self.conditions.pop();
+
+                let after_alternative = self.substitution.clone();
+
+                let mut assigned: Vec<_> = after_consequence
+                    .keys()
+                    .chain(after_alternative.keys())
+                    .filter(|id| !local_variables.contains(*id))
+                    .filter(|id| {
+                        after_consequence.get(*id) != before.get(*id)
+                            || after_alternative.get(*id) != before.get(*id)
+                    })
+                    .cloned()
+                    .collect();
+                assigned.sort_by(|a, b| a.id.cmp(b.id));
+                assigned.dedup();
+
+                for id in assigned {
+                    let t = self.types.get(&id).unwrap().clone();
+                    let consequence = Identifier {
+                        version: *after_consequence.get(&id).unwrap_or(&0),
+                        ..id.clone()
+                    };
+                    let alternative = Identifier {
+                        version: *after_alternative.get(&id).unwrap_or(&0),
+                        ..id.clone()
+                    };
+
+                    let variable =
+                        self.issue_next_ssa_variable(Variable::with_id_and_type(id, t.clone()));
+
+                    res.push(TypedStatement::Definition(
+                        TypedAssignee::Identifier(variable),
+                        select(
+                            BooleanExpression::Identifier(condition_variable.id.clone()),
+                            identifier_expression(consequence, t.clone()),
+                            identifier_expression(alternative, t),
+                        ),
+                    ));
+                }
+
+                res
+            }
+            TypedStatement::Condition(lhs, rhs) => {
+                let lhs = self.fold_expression(lhs);
+                let rhs = self.fold_expression(rhs);
+
+                // inside a branch, the assertion is replaced by a tautology if the branch is not taken
+                match self.branch_condition() {
+                    Some(condition) => vec![TypedStatement::Condition(
+                        select(condition, lhs, rhs.clone()),
+                        rhs,
+                    )],
+                    None => vec![TypedStatement::Condition(lhs, rhs)],
+                }
+            }
+            TypedStatement::Log(condition, format, arguments) => {
+                let condition = self.fold_boolean_expression(condition);
+                let arguments = arguments
+                    .into_iter()
+                    .map(|a| self.fold_expression(a))
+                    .collect();
+
+                // inside a branch, the message is only printed if the branch is taken
+                let condition = match (self.branch_condition(), condition) {
+                    (Some(branch_condition), BooleanExpression::Value(true)) => branch_condition,
+                    (Some(branch_condition), condition) => {
+                        BooleanExpression::And(box branch_condition, box condition)
+                    }
+                    (None, condition) => condition,
+                };
+
+                vec![TypedStatement::Log(condition, format, arguments)]
+            }
             s => fold_statement(self, s),
         }
     }
 
+    fn fold_field_expression(
+        &mut self,
+        e: FieldElementExpression<'ast, T>,
+    ) -> FieldElementExpression<'ast, T> {
+        // inside a branch, the operands of divisions are replaced by valid ones if the branch is not
+        // taken, so that dividing by zero in that case does not make the program unsatisfiable
+        match (self.branch_condition(), e) {
+            (Some(condition), FieldElementExpression::Div(box left, box right)) => {
+                let left = self.fold_field_expression(left);
+                let right = self.fold_field_expression(right);
+                FieldElementExpression::Div(box left, box guard(condition, right, T::one()))
+            }
+            (
+                Some(condition),
+                FieldElementExpression::IntegerDiv(box left, box right, bitwidth),
+            ) => {
+                let left = self.fold_field_expression(left);
+                let right = self.fold_field_expression(right);
+                FieldElementExpression::IntegerDiv(
+                    box guard(condition.clone(), left, T::zero()),
+                    box guard(condition, right, T::one()),
+                    bitwidth,
+                )
+            }
+            (Some(condition), FieldElementExpression::Rem(box left, box right, bitwidth)) => {
+                let left = self.fold_field_expression(left);
+                let right = self.fold_field_expression(right);
+                FieldElementExpression::Rem(
+                    box guard(condition.clone(), left, T::zero()),
+                    box guard(condition, right, T::one()),
+                    bitwidth,
+                )
+            }
+            (_, e) => fold_field_expression(self, e),
+        }
+    }
+
     fn fold_function(&mut self, f: TypedFunction<'ast, T>) -> TypedFunction<'ast, T> {
         self.substitution = HashMap::new();
+        self.issued = HashMap::new();
+        self.types = HashMap::new();
         for arg in &f.arguments {
             self.substitution.insert(arg.id.id.clone(), 0);
+            self.issued.insert(arg.id.id.clone(), 0);
+            self.types.insert(arg.id.id.clone(), arg.id.get_type());
         }
 
         fold_function(self, f)
@@ -513,5 +762,261 @@ mod tests {
                 )]
             );
         }
+
+        #[test]
+        fn if_else() {
+            // field a
+            // a = 1
+            // if a == 1 then
+            //     a = 2
+            // else
+            //     field b = 3
+            //     a = b
+            // endif
+
+            // should be turned into
+            // a_0 = 1
+            // #if_else_condition_0 = a_0 == 1
+            // a_1 = 2
+            // b_0 = 3
+            // a_2 = b_0
+            // a_3 = if #if_else_condition_0 then a_1 else a_2 fi
+
+            let mut u = Unroller::new();
+
+            let s: TypedStatement<FieldPrime> =
+                TypedStatement::Declaration(Variable::field_element("a".into()));
+            assert_eq!(u.fold_statement(s), vec![]);
+
+            let s = TypedStatement::Definition(
+                TypedAssignee::Identifier(Variable::field_element("a".into())),
+                FieldElementExpression::Number(FieldPrime::from(1)).into(),
+            );
+            assert_eq!(
+                u.fold_statement(s),
+                vec![TypedStatement::Definition(
+                    TypedAssignee::Identifier(Variable::field_element(
+                        Identifier::from("a").version(0)
+                    )),
+                    FieldElementExpression::Number(FieldPrime::from(1)).into()
+                )]
+            );
+
+            let s = TypedStatement::IfElse(
+                BooleanExpression::Eq(
+                    box FieldElementExpression::Identifier("a".into()),
+                    box FieldElementExpression::Number(FieldPrime::from(1)),
+                ),
+                vec![TypedStatement::Definition(
+                    TypedAssignee::Identifier(Variable::field_element("a".into())),
+                    FieldElementExpression::Number(FieldPrime::from(2)).into(),
+                )],
+                vec![
+                    TypedStatement::Declaration(Variable::field_element("b".into())),
+                    TypedStatement::Definition(
+                        TypedAssignee::Identifier(Variable::field_element("b".into())),
+                        FieldElementExpression::Number(FieldPrime::from(3)).into(),
+                    ),
+                    TypedStatement::Definition(
+                        TypedAssignee::Identifier(Variable::field_element("a".into())),
+                        FieldElementExpression::Identifier("b".into()).into(),
+                    ),
+                ],
+            );
+
+            let condition = Identifier::from(IF_ELSE_CONDITION).version(0);
+
+            assert_eq!(
+                u.fold_statement(s),
+                vec![
+                    TypedStatement::Definition(
+                        TypedAssignee::Identifier(Variable::boolean(condition.clone())),
+                        BooleanExpression::Eq(
+                            box FieldElementExpression::Identifier(
+                                Identifier::from("a").version(0)
+                            ),
+                            box FieldElementExpression::Number(FieldPrime::from(1)),
+                        )
+                        .into()
+                    ),
+                    TypedStatement::Definition(
+                        TypedAssignee::Identifier(Variable::field_element(
+                            Identifier::from("a").version(1)
+                        )),
+                        FieldElementExpression::Number(FieldPrime::from(2)).into()
+                    ),
+                    TypedStatement::Definition(
+                        TypedAssignee::Identifier(Variable::field_element(
+                            Identifier::from("b").version(0)
+                        )),
+                        FieldElementExpression::Number(FieldPrime::from(3)).into()
+                    ),
+                    TypedStatement::Definition(
+                        TypedAssignee::Identifier(Variable::field_element(
+                            Identifier::from("a").version(2)
+                        )),
+                        FieldElementExpression::Identifier(Identifier::from("b").version(0)).into()
+                    ),
+                    TypedStatement::Definition(
+                        TypedAssignee::Identifier(Variable::field_element(
+                            Identifier::from("a").version(3)
+                        )),
+                        FieldElementExpression::IfElse(
+                            box BooleanExpression::Identifier(condition),
+                            box FieldElementExpression::Identifier(
+                                Identifier::from("a").version(1)
+                            ),
+                            box FieldElementExpression::Identifier(
+                                Identifier::from("a").version(2)
+                            ),
+                        )
+                        .into()
+                    ),
+                ]
+            );
+        }
+
+        #[test]
+        fn if_else_condition() {
+            // if a == 1 then
+            //     a == 2
+            // endif
+
+            // should be turned into
+            // #if_else_condition_0 = a_0 == 1
+            // (if #if_else_condition_0 then a_0 else 2 fi) == 2
+
+            let mut u = Unroller::new();
+
+            let s: TypedStatement<FieldPrime> = TypedStatement::IfElse(
+                BooleanExpression::Eq(
+                    box FieldElementExpression::Identifier("a".into()),
+                    box FieldElementExpression::Number(FieldPrime::from(1)),
+                ),
+                vec![TypedStatement::Condition(
+                    FieldElementExpression::Identifier("a".into()).into(),
+                    FieldElementExpression::Number(FieldPrime::from(2)).into(),
+                )],
+                vec![],
+            );
+
+            let condition = Identifier::from(IF_ELSE_CONDITION).version(0);
+
+            assert_eq!(
+                u.fold_statement(s),
+                vec![
+                    TypedStatement::Definition(
+                        TypedAssignee::Identifier(Variable::boolean(condition.clone())),
+                        BooleanExpression::Eq(
+                            box FieldElementExpression::Identifier(
+                                Identifier::from("a").version(0)
+                            ),
+                            box FieldElementExpression::Number(FieldPrime::from(1)),
+                        )
+                        .into()
+                    ),
+                    TypedStatement::Condition(
+                        FieldElementExpression::IfElse(
+                            box BooleanExpression::Identifier(condition),
+                            box FieldElementExpression::Identifier(
+                                Identifier::from("a").version(0)
+                            ),
+                            box FieldElementExpression::Number(FieldPrime::from(2)),
+                        )
+                        .into(),
+                        FieldElementExpression::Number(FieldPrime::from(2)).into()
+                    ),
+                ]
+            );
+        }
+
+        #[test]
+        fn if_else_division() {
+            // if c then
+            //     field d = a / b
+            // else
+            //     field e = a \<8> b
+            // endif
+
+            // should be turned into
+            // #if_else_condition_0 = c_0
+            // d_0 = a_0 / (if #if_else_condition_0 then b_0 else 1 fi)
+            // e_0 = (if !#if_else_condition_0 then a_0 else 0 fi) \<8> (if !#if_else_condition_0 then b_0 else 1 fi)
+
+            let mut u = Unroller::new();
+
+            let s: TypedStatement<FieldPrime> = TypedStatement::IfElse(
+                BooleanExpression::Identifier("c".into()),
+                vec![
+                    TypedStatement::Declaration(Variable::field_element("d".into())),
+                    TypedStatement::Definition(
+                        TypedAssignee::Identifier(Variable::field_element("d".into())),
+                        FieldElementExpression::Div(
+                            box FieldElementExpression::Identifier("a".into()),
+                            box FieldElementExpression::Identifier("b".into()),
+                        )
+                        .into(),
+                    ),
+                ],
+                vec![
+                    TypedStatement::Declaration(Variable::field_element("e".into())),
+                    TypedStatement::Definition(
+                        TypedAssignee::Identifier(Variable::field_element("e".into())),
+                        FieldElementExpression::IntegerDiv(
+                            box FieldElementExpression::Identifier("a".into()),
+                            box FieldElementExpression::Identifier("b".into()),
+                            8,
+                        )
+                        .into(),
+                    ),
+                ],
+            );
+
+            let condition = Identifier::from(IF_ELSE_CONDITION).version(0);
+            let guard = |polarity: bool, id: &'static str, default: usize| {
+                let condition = BooleanExpression::Identifier(condition.clone());
+                FieldElementExpression::IfElse(
+                    box match polarity {
+                        true => condition,
+                        false => BooleanExpression::Not(box condition),
+                    },
+                    box FieldElementExpression::Identifier(Identifier::from(id).version(0)),
+                    box FieldElementExpression::Number(FieldPrime::from(default)),
+                )
+            };
+
+            assert_eq!(
+                u.fold_statement(s),
+                vec![
+                    TypedStatement::Definition(
+                        TypedAssignee::Identifier(Variable::boolean(condition.clone())),
+                        BooleanExpression::Identifier(Identifier::from("c").version(0)).into()
+                    ),
+                    TypedStatement::Definition(
+                        TypedAssignee::Identifier(Variable::field_element(
+                            Identifier::from("d").version(0)
+                        )),
+                        FieldElementExpression::Div(
+                            box FieldElementExpression::Identifier(
+                                Identifier::from("a").version(0)
+                            ),
+                            box guard(true, "b", 1),
+                        )
+                        .into()
+                    ),
+                    TypedStatement::Definition(
+                        TypedAssignee::Identifier(Variable::field_element(
+                            Identifier::from("e").version(0)
+                        )),
+                        FieldElementExpression::IntegerDiv(
+                            box guard(false, "a", 0),
+                            box guard(false, "b", 1),
+                            8,
+                        )
+                        .into()
+                    ),
+                ]
+            );
+        }
     }
 }
@@ -0,0 +1,181 @@
+//! Module containing the symbols of a program and the places they are used at.
+//!
+//! The symbols are recorded by the semantic checker, which knows what each identifier refers to.
+//! They let editors show the type of an identifier or jump to its definition.
+
+pub use crate::parser::Position;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolKind {
+    Variable,
+    Constant,
+    Function,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Symbol {
+    pub id: String,
+    pub kind: SymbolKind,
+    /// The declaration of the symbol with its type, for example `field[3] a` or `def foo(field a) -> (field)`
+    pub detail: String,
+    /// Where the symbol is declared. Imported symbols point to their import statement
+    pub pos: Option<(Position, Position)>,
+    /// The source of the import the symbol comes from, if it is imported
+    pub import: Option<String>,
+    /// The function the symbol is declared in. Global symbols have no scope
+    pub scope: Option<(Position, Position)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SymbolIndex {
+    pub symbols: Vec<Symbol>,
+    /// The spans referring to a symbol, along with the index of the symbol in `symbols`
+    pub references: Vec<((Position, Position), usize)>,
+}
+
+impl SymbolIndex {
+    /// Adds `symbol` to the index unless it is already there, returning its index
+    pub fn insert(&mut self, symbol: Symbol) -> usize {
+        match self.find(&symbol.id, symbol.kind, symbol.pos) {
+            Some(index) => index,
+            None => {
+                self.symbols.push(symbol);
+                self.symbols.len() - 1
+            }
+        }
+    }
+
+    /// Returns the index of the symbol named `id` declared at `pos`
+    pub fn find(
+        &self,
+        id: &str,
+        kind: SymbolKind,
+        pos: Option<(Position, Position)>,
+    ) -> Option<usize> {
+        self.symbols
+            .iter()
+            .position(|s| s.id == id && s.kind == kind && s.pos == pos)
+    }
+
+    /// Returns the index of the global symbol named `id`
+    pub fn find_global(&self, id: &str, kind: SymbolKind) -> Option<usize> {
+        self.symbols
+            .iter()
+            .position(|s| s.id == id && s.kind == kind && s.scope.is_none())
+    }
+
+    /// Records that the symbol at `index` is referred to at `pos`
+    pub fn reference(&mut self, pos: (Position, Position), index: usize) {
+        // functions can be checked several times, once for each of their generic instances
+        if !self.references.contains(&(pos, index)) {
+            self.references.push((pos, index));
+        }
+    }
+
+    /// Returns the symbol referred to at `pos`, choosing the innermost reference if several contain it
+    pub fn symbol_at(&self, pos: Position) -> Option<&Symbol> {
+        self.references
+            .iter()
+            .filter(|(span, _)| contains(*span, pos))
+            .max_by_key(|((start, end), _)| (key(*start), std::cmp::Reverse(key(*end))))
+            .map(|(_, index)| &self.symbols[*index])
+    }
+
+    /// Returns the symbols which can be used at `pos`
+    pub fn visible_at(&self, pos: Position) -> Vec<&Symbol> {
+        self.symbols
+            .iter()
+            .filter(|s| match (s.scope, s.pos) {
+                (None, _) => true,
+                (Some(scope), Some((start, _))) => contains(scope, pos) && key(start) <= key(pos),
+                (Some(_), None) => false,
+            })
+            .collect()
+    }
+}
+
+fn key(pos: Position) -> (usize, usize) {
+    (pos.line, pos.col)
+}
+
+// spans are considered to contain their end, so that a cursor placed right after an identifier points to it
+fn contains((start, end): (Position, Position), pos: Position) -> bool {
+    key(start) <= key(pos) && key(pos) <= key(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(line: usize, col: usize) -> Position {
+        Position { line, col }
+    }
+
+    fn span(line: usize, start: usize, end: usize) -> (Position, Position) {
+        (at(line, start), at(line, end))
+    }
+
+    fn variable(id: &str, pos: (Position, Position), scope: (Position, Position)) -> Symbol {
+        Symbol {
+            id: id.to_string(),
+            kind: SymbolKind::Variable,
+            detail: format!("field {}", id),
+            pos: Some(pos),
+            import: None,
+            scope: Some(scope),
+        }
+    }
+
+    #[test]
+    fn symbol_at() {
+        let mut index = SymbolIndex::default();
+        let foo = index.insert(Symbol {
+            id: "foo".to_string(),
+            kind: SymbolKind::Function,
+            detail: "def foo(field a) -> (field)".to_string(),
+            pos: Some(span(1, 1, 20)),
+            import: None,
+            scope: None,
+        });
+        let a = index.insert(variable("a", span(2, 2, 9), (at(1, 1), at(4, 1))));
+
+        // `foo(a)`, where the call contains the argument
+        index.reference(span(3, 12, 18), foo);
+        index.reference(span(3, 16, 17), a);
+
+        assert_eq!(index.symbol_at(at(3, 13)).unwrap().id, "foo");
+        assert_eq!(index.symbol_at(at(3, 16)).unwrap().id, "a");
+        assert_eq!(index.symbol_at(at(3, 17)).unwrap().id, "a");
+        assert!(index.symbol_at(at(3, 2)).is_none());
+    }
+
+    #[test]
+    fn insert_twice() {
+        let mut index = SymbolIndex::default();
+        let scope = (at(1, 1), at(5, 1));
+        let a = index.insert(variable("a", span(2, 2, 9), scope));
+        let b = index.insert(variable("b", span(3, 2, 9), scope));
+
+        assert_eq!(index.insert(variable("a", span(2, 2, 9), scope)), a);
+        assert_ne!(a, b);
+        assert_eq!(index.symbols.len(), 2);
+    }
+
+    #[test]
+    fn visible_at() {
+        let mut index = SymbolIndex::default();
+        let scope = (at(1, 1), at(5, 1));
+        index.insert(variable("a", span(2, 2, 9), scope));
+        index.insert(variable("b", span(4, 2, 9), scope));
+
+        let visible: Vec<_> = index
+            .visible_at(at(3, 2))
+            .into_iter()
+            .map(|s| s.id.clone())
+            .collect();
+        assert_eq!(visible, vec!["a".to_string()]);
+
+        assert!(index.visible_at(at(7, 2)).is_empty());
+    }
+}
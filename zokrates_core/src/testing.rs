@@ -0,0 +1,217 @@
+//! Module containing the runner of the tests written in ZoKrates.
+//!
+//! Tests are functions whose name starts with `test_`. Each of them is compiled in place of the
+//! `main` function of its program and executed without inputs, passing if all of its constraints
+//! are satisfied. Tests whose name starts with `test_fail_` pass if their execution fails instead.
+
+use absy::{Function, Prog};
+use compile::{compile_program, CompileConfig, CompileErrorInner, CompileErrors};
+use imports;
+use ir;
+use optimizer::Optimize;
+use std::io::BufRead;
+use zokrates_field::field::Field;
+use zokrates_pest_ast as pest;
+
+const TEST_PREFIX: &str = "test_";
+const FAIL_PREFIX: &str = "test_fail_";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestReport {
+    pub name: String,
+    /// Whether the execution of the test is expected to fail
+    pub should_fail: bool,
+    /// The number of constraints of the test, if it could be compiled
+    pub constraint_count: Option<usize>,
+    /// Why the test failed, if it did
+    pub failure: Option<String>,
+}
+
+impl TestReport {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Compiles and runs the tests of a program in the order they are defined in, failing if one of
+/// them does not compile
+pub fn run_tests<T: Field, R: BufRead, S: BufRead, E: Into<imports::Error>>(
+    reader: &mut R,
+    location: Option<String>,
+    resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
+) -> Result<Vec<TestReport>, CompileErrors> {
+    let mut source = String::new();
+    reader
+        .read_to_string(&mut source)
+        .map_err(|e| CompileErrors::from(CompileErrorInner::from(e).with_context(&location)))?;
+    let ast = pest::generate_ast(&source)
+        .map_err(|e| CompileErrors::from(CompileErrorInner::from(e).with_context(&location)))?;
+    let program: Prog<T> = Prog::from(ast);
+
+    program
+        .functions
+        .iter()
+        .map(|f| &f.value)
+        .filter(|f| f.id.starts_with(TEST_PREFIX))
+        .map(|f| run_test(&program, f, location.clone(), resolve_option))
+        .collect()
+}
+
+fn run_test<'ast, T: Field, S: BufRead, E: Into<imports::Error>>(
+    program: &Prog<'ast, T>,
+    test: &Function<'ast, T>,
+    location: Option<String>,
+    resolve_option: Option<fn(&Option<String>, &String) -> Result<(S, String, String), E>>,
+) -> Result<TestReport, CompileErrors> {
+    let should_fail = test.id.starts_with(FAIL_PREFIX);
+
+    let report = |constraint_count, failure| TestReport {
+        name: test.id.to_string(),
+        should_fail,
+        constraint_count,
+        failure,
+    };
+
+    if !test.arguments.is_empty() {
+        return Ok(report(
+            None,
+            Some(String::from("Test functions cannot take arguments")),
+        ));
+    }
+
+    // the test replaces the main function of the program, if there is one
+    let functions = program
+        .functions
+        .iter()
+        .filter(|f| f.value.id != "main")
+        .cloned()
+        .map(|mut f| {
+            if f.value.id == test.id {
+                f.value.id = "main";
+            }
+            f
+        })
+        .collect();
+
+    let (compiled, _, _) = compile_program(
+        Prog {
+            functions,
+            ..program.clone()
+        },
+        location,
+        resolve_option,
+        &CompileConfig::default(),
+    )?;
+
+    let compiled = ir::Prog::from(compiled).optimize();
+
+    let failure = match (compiled.execute(&Vec::<T>::new()), should_fail) {
+        (Ok(_), false) | (Err(_), true) => None,
+        (Ok(_), true) => Some(String::from(
+            "Expected the execution to fail, but it succeeded",
+        )),
+        (Err(e), false) => Some(format!("Execution failed: {}", e)),
+    };
+
+    Ok(report(Some(compiled.constraint_count()), failure))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::io::{BufReader, Empty};
+    use zokrates_field::field::FieldPrime;
+
+    fn run(source: &str) -> Result<Vec<TestReport>, CompileErrors> {
+        run_tests::<FieldPrime, _, _, _>(
+            &mut BufReader::new(source.as_bytes()),
+            Some(String::from("./path/to/file")),
+            None::<
+                fn(
+                    &Option<String>,
+                    &String,
+                ) -> Result<(BufReader<Empty>, String, String), io::Error>,
+            >,
+        )
+    }
+
+    #[test]
+    fn passing_and_failing() {
+        let reports = run(r#"
+			def double(field a) -> (field):
+			   return 2 * a
+
+			def main(field a) -> (field):
+			   return double(a)
+
+			def test_double() -> (field):
+			   double(3) == 6
+			   return 1
+
+			def test_wrong() -> (field):
+			   double(3) == 5
+			   return 1
+
+			def test_fail_wrong() -> (field):
+			   double(3) == 5
+			   return 1
+
+			def test_fail_right() -> (field):
+			   double(3) == 6
+			   return 1
+		"#)
+        .unwrap();
+
+        let outcomes: Vec<_> = reports
+            .iter()
+            .map(|r| (r.name.as_str(), r.should_fail, r.passed()))
+            .collect();
+
+        assert_eq!(
+            outcomes,
+            vec![
+                ("test_double", false, true),
+                ("test_wrong", false, false),
+                ("test_fail_wrong", true, true),
+                ("test_fail_right", true, false),
+            ]
+        );
+        assert!(reports.iter().all(|r| r.constraint_count.is_some()));
+    }
+
+    #[test]
+    fn without_main() {
+        let reports = run(r#"
+			def test_one() -> (field):
+			   1 == 1
+			   return 1
+		"#)
+        .unwrap();
+
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].passed());
+    }
+
+    #[test]
+    fn with_arguments() {
+        let reports = run(r#"
+			def test_argument(field a) -> (field):
+			   return a
+		"#)
+        .unwrap();
+
+        assert!(!reports[0].passed());
+        assert_eq!(reports[0].constraint_count, None);
+    }
+
+    #[test]
+    fn compile_error() {
+        let res = run(r#"
+			def test_undefined() -> (field):
+			   return a
+		"#);
+
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use crate::typed_absy::*;
+use crate::types::Type;
+use std::fmt;
+use zokrates_field::field::Field;
+
+/// The value of a global constant, evaluated at compile time
+#[derive(Clone, PartialEq)]
+pub enum ConstantValue<T: Field> {
+    FieldElement(T),
+    Boolean(bool),
+    FieldElementArray(Vec<T>),
+}
+
+impl<T: Field> ConstantValue<T> {
+    /// Returns the value of `e` if it is fully evaluated, `None` otherwise
+    pub fn from_expression<'ast>(e: &TypedExpression<'ast, T>) -> Option<Self> {
+        match e {
+            TypedExpression::FieldElement(FieldElementExpression::Number(n)) => {
+                Some(ConstantValue::FieldElement(n.clone()))
+            }
+            TypedExpression::Boolean(BooleanExpression::Value(b)) => {
+                Some(ConstantValue::Boolean(*b))
+            }
+            TypedExpression::FieldElementArray(FieldElementArrayExpression::Value(_, v)) => v
+                .iter()
+                .map(|e| match e {
+                    FieldElementExpression::Number(n) => Some(n.clone()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(ConstantValue::FieldElementArray),
+            _ => None,
+        }
+    }
+
+    pub fn get_type(&self) -> Type {
+        match self {
+            ConstantValue::FieldElement(_) => Type::FieldElement,
+            ConstantValue::Boolean(_) => Type::Boolean,
+            ConstantValue::FieldElementArray(v) => Type::FieldElementArray(v.len()),
+        }
+    }
+}
+
+impl<'ast, T: Field> From<ConstantValue<T>> for TypedExpression<'ast, T> {
+    fn from(v: ConstantValue<T>) -> TypedExpression<'ast, T> {
+        match v {
+            ConstantValue::FieldElement(n) => FieldElementExpression::Number(n).into(),
+            ConstantValue::Boolean(b) => BooleanExpression::Value(b).into(),
+            ConstantValue::FieldElementArray(v) => FieldElementArrayExpression::Value(
+                v.len(),
+                v.into_iter().map(FieldElementExpression::Number).collect(),
+            )
+            .into(),
+        }
+    }
+}
+
+impl<T: Field> fmt::Display for ConstantValue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConstantValue::FieldElement(n) => write!(f, "{}", n),
+            ConstantValue::Boolean(b) => write!(f, "{}", b),
+            ConstantValue::FieldElementArray(v) => write!(
+                f,
+                "[{}]",
+                v.iter()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl<T: Field> fmt::Debug for ConstantValue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ConstantValue({})", self)
+    }
+}
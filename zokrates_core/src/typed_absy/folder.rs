@@ -131,10 +131,30 @@ pub fn fold_statement<'ast, T: Field, F: Folder<'ast, T>>(
                 .flat_map(|s| f.fold_statement(s))
                 .collect(),
         ),
+        TypedStatement::IfElse(condition, consequence, alternative) => TypedStatement::IfElse(
+            f.fold_boolean_expression(condition),
+            consequence
+                .into_iter()
+                .flat_map(|s| f.fold_statement(s))
+                .collect(),
+            alternative
+                .into_iter()
+                .flat_map(|s| f.fold_statement(s))
+                .collect(),
+        ),
         TypedStatement::MultipleDefinition(variables, elist) => TypedStatement::MultipleDefinition(
             variables.into_iter().map(|v| f.fold_variable(v)).collect(),
             f.fold_expression_list(elist),
         ),
+        TypedStatement::Log(condition, format, arguments) => TypedStatement::Log(
+            f.fold_boolean_expression(condition),
+            format,
+            arguments
+                .into_iter()
+                .map(|e| f.fold_expression(e))
+                .collect(),
+        ),
+        TypedStatement::Location(position) => TypedStatement::Location(position),
     };
     vec![res]
 }
@@ -197,6 +217,16 @@ pub fn fold_field_expression<'ast, T: Field, F: Folder<'ast, T>>(
             let e2 = f.fold_field_expression(e2);
             FieldElementExpression::Div(box e1, box e2)
         }
+        FieldElementExpression::IntegerDiv(box e1, box e2, bitwidth) => {
+            let e1 = f.fold_field_expression(e1);
+            let e2 = f.fold_field_expression(e2);
+            FieldElementExpression::IntegerDiv(box e1, box e2, bitwidth)
+        }
+        FieldElementExpression::Rem(box e1, box e2, bitwidth) => {
+            let e1 = f.fold_field_expression(e1);
+            let e2 = f.fold_field_expression(e2);
+            FieldElementExpression::Rem(box e1, box e2, bitwidth)
+        }
         FieldElementExpression::Pow(box e1, box e2) => {
             let e1 = f.fold_field_expression(e1);
             let e2 = f.fold_field_expression(e2);
@@ -232,6 +262,16 @@ pub fn fold_boolean_expression<'ast, T: Field, F: Folder<'ast, T>>(
             let e2 = f.fold_field_expression(e2);
             BooleanExpression::Eq(box e1, box e2)
         }
+        BooleanExpression::BoolEq(box e1, box e2) => {
+            let e1 = f.fold_boolean_expression(e1);
+            let e2 = f.fold_boolean_expression(e2);
+            BooleanExpression::BoolEq(box e1, box e2)
+        }
+        BooleanExpression::FieldElementArrayEq(box e1, box e2) => {
+            let e1 = f.fold_field_array_expression(e1);
+            let e2 = f.fold_field_array_expression(e2);
+            BooleanExpression::FieldElementArrayEq(box e1, box e2)
+        }
         BooleanExpression::Lt(box e1, box e2) => {
             let e1 = f.fold_field_expression(e1);
             let e2 = f.fold_field_expression(e2);
@@ -262,6 +302,11 @@ pub fn fold_boolean_expression<'ast, T: Field, F: Folder<'ast, T>>(
             let e2 = f.fold_boolean_expression(e2);
             BooleanExpression::And(box e1, box e2)
         }
+        BooleanExpression::Xor(box e1, box e2) => {
+            let e1 = f.fold_boolean_expression(e1);
+            let e2 = f.fold_boolean_expression(e2);
+            BooleanExpression::Xor(box e1, box e2)
+        }
         BooleanExpression::Not(box e) => {
             let e = f.fold_boolean_expression(e);
             BooleanExpression::Not(box e)
@@ -0,0 +1,821 @@
+//! Module containing the interpreter of typed programs, which runs them on field elements
+//! without compiling them.
+//!
+//! The interpreter follows the semantics of the compiled program rather than the most natural
+//! ones, so that both can be compared to find miscompilations:
+//! - both branches of if-else expressions and both operands of boolean operators are evaluated
+//! - both branches of if-else statements are executed, but the assertions of the branch which is
+//! not taken are not enforced, unless they are made in a called function
+//! - operations which cannot be satisfied, such as divisions by zero, fail even in a branch which
+//! is not taken
+
+use crate::flat_absy::SourceNames;
+use crate::ir;
+use crate::typed_absy::*;
+use crate::types::{Signature, Type};
+use std::collections::HashMap;
+use std::fmt;
+use zokrates_field::field::Field;
+
+pub type ExecutionResult<T> = Result<Vec<T>, Error>;
+
+impl<'ast, T: Field> TypedProg<'ast, T> {
+    /// Executes the `main` function of the program, with inputs and outputs laid out as in the
+    /// compiled program
+    pub fn execute<U: Into<T> + Clone>(&self, inputs: &Vec<U>) -> ExecutionResult<T> {
+        let main = self
+            .functions
+            .iter()
+            .find(|f| f.id == "main")
+            .expect("program should have a main function");
+
+        let expected: usize = main
+            .arguments
+            .iter()
+            .map(|p| p.id.get_type().get_primitive_count())
+            .sum();
+
+        if expected != inputs.len() {
+            return Err(Error::WrongInputCount {
+                expected,
+                received: inputs.len(),
+            });
+        }
+
+        let mut inputs = inputs.iter().map(|i| -> T { i.clone().into() });
+
+        let arguments = main
+            .arguments
+            .iter()
+            .map(|p| Value::from_flat(&p.id.get_type(), &mut inputs))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let outputs = Interpreter { program: self }.call_function(main, arguments)?;
+
+        Ok(outputs.into_iter().flat_map(|v| v.into_flat()).collect())
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Value<T> {
+    FieldElement(T),
+    Boolean(bool),
+    FieldElementArray(Vec<T>),
+}
+
+impl<T: Field> Value<T> {
+    fn get_type(&self) -> Type {
+        match *self {
+            Value::FieldElement(_) => Type::FieldElement,
+            Value::Boolean(_) => Type::Boolean,
+            Value::FieldElementArray(ref v) => Type::FieldElementArray(v.len()),
+        }
+    }
+
+    /// Reads a value of type `t` from field elements, booleans being encoded as 0 or 1
+    fn from_flat<I: Iterator<Item = T>>(t: &Type, values: &mut I) -> Result<Self, Error> {
+        let mut next = || values.next().expect("there should be enough values");
+
+        match *t {
+            Type::FieldElement => Ok(Value::FieldElement(next())),
+            Type::Boolean => match next() {
+                ref v if *v == T::zero() => Ok(Value::Boolean(false)),
+                ref v if *v == T::one() => Ok(Value::Boolean(true)),
+                v => Err(Error::NotBoolean {
+                    value: v.to_dec_string(),
+                }),
+            },
+            Type::FieldElementArray(size) => Ok(Value::FieldElementArray(
+                (0..size).map(|_| next()).collect(),
+            )),
+        }
+    }
+
+    fn into_flat(self) -> Vec<T> {
+        match self {
+            Value::FieldElement(v) => vec![v],
+            Value::Boolean(b) => vec![if b { T::one() } else { T::zero() }],
+            Value::FieldElementArray(v) => v,
+        }
+    }
+
+    fn field_element(self) -> T {
+        match self {
+            Value::FieldElement(v) => v,
+            v => panic!("expected a field element, found {}", v),
+        }
+    }
+}
+
+impl<T: Field> fmt::Display for Value<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::FieldElement(ref v) => write!(f, "{}", v),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::FieldElementArray(ref v) => write!(
+                f,
+                "[{}]",
+                v.iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+/// The values of the variables of a function call
+type Environment<'ast, T> = HashMap<Identifier<'ast>, Value<T>>;
+
+struct Interpreter<'a, 'ast: 'a, T: Field + 'a> {
+    program: &'a TypedProg<'ast, T>,
+}
+
+impl<'a, 'ast, T: Field> Interpreter<'a, 'ast, T> {
+    fn call(
+        &self,
+        id: &str,
+        arguments: Vec<Value<T>>,
+        outputs: Vec<Type>,
+    ) -> Result<Vec<Value<T>>, Error> {
+        let signature = Signature::new()
+            .inputs(arguments.iter().map(|a| a.get_type()).collect())
+            .outputs(outputs);
+
+        if let Some(f) = self
+            .program
+            .functions
+            .iter()
+            .find(|f| f.id == id && f.signature == signature)
+        {
+            return self.call_function(f, arguments);
+        }
+
+        // imported functions are already flattened, so we run them as compiled programs
+        let f = self
+            .program
+            .imported_functions
+            .iter()
+            .find(|f| f.id == id && f.signature == signature)
+            .unwrap_or_else(|| panic!("function {} should exist", id));
+
+        let inputs: Vec<T> = arguments.into_iter().flat_map(|a| a.into_flat()).collect();
+
+        let prog = ir::Prog {
+            main: f.clone().into(),
+            private: vec![true; inputs.len()],
+            committed: vec![],
+            names: SourceNames::default(),
+        };
+
+        let witness = prog.execute(&inputs).map_err(|error| Error::Import {
+            id: id.to_string(),
+            error,
+        })?;
+
+        let mut values = witness.return_values().into_iter();
+
+        signature
+            .outputs
+            .iter()
+            .map(|t| Value::from_flat(t, &mut values))
+            .collect()
+    }
+
+    fn call_function(
+        &self,
+        f: &TypedFunction<'ast, T>,
+        arguments: Vec<Value<T>>,
+    ) -> Result<Vec<Value<T>>, Error> {
+        let mut environment: Environment<T> = f
+            .arguments
+            .iter()
+            .map(|p| p.id.id.clone())
+            .zip(arguments)
+            .collect();
+
+        // assertions made in a function are enforced wherever it is called
+        match self.execute_statements(&f.statements, &mut environment, true)? {
+            Some(values) => Ok(values),
+            None => panic!("function {} should return", f.id),
+        }
+    }
+
+    /// Executes `statements`, returning the values returned if they contain a return statement.
+    /// Assertions are only checked if `enforce` is true.
+    fn execute_statements(
+        &self,
+        statements: &Vec<TypedStatement<'ast, T>>,
+        environment: &mut Environment<'ast, T>,
+        enforce: bool,
+    ) -> Result<Option<Vec<Value<T>>>, Error> {
+        for statement in statements {
+            match *statement {
+                TypedStatement::Return(ref expressions) => {
+                    return expressions
+                        .iter()
+                        .map(|e| self.evaluate(e, environment))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map(Some);
+                }
+                TypedStatement::Definition(ref assignee, ref expression) => {
+                    let value = self.evaluate(expression, environment)?;
+                    match *assignee {
+                        TypedAssignee::Identifier(ref v) => {
+                            environment.insert(v.id.clone(), value);
+                        }
+                        TypedAssignee::ArrayElement(
+                            box TypedAssignee::Identifier(ref v),
+                            ref index,
+                        ) => {
+                            let index = self.evaluate_field(index, environment)?;
+                            match environment.get_mut(&v.id) {
+                                Some(&mut Value::FieldElementArray(ref mut elements)) => {
+                                    // writing out of bounds has no effect, as in the compiled program
+                                    if let Some(i) = to_index(&index, elements.len()) {
+                                        elements[i] = value.field_element();
+                                    }
+                                }
+                                _ => panic!("{} should be an array", v.id),
+                            }
+                        }
+                        TypedAssignee::ArrayElement(..) => {
+                            unreachable!("only elements of array variables can be assigned to")
+                        }
+                    }
+                }
+                TypedStatement::Declaration(_) => {}
+                TypedStatement::Condition(ref left, ref right) => {
+                    let left = self.evaluate(left, environment)?;
+                    let right = self.evaluate(right, environment)?;
+                    if enforce && left != right {
+                        return Err(Error::UnsatisfiedAssertion {
+                            left: left.to_string(),
+                            right: right.to_string(),
+                        });
+                    }
+                }
+                TypedStatement::For(ref v, ref from, ref to, ref statements) => {
+                    let mut current = from.clone();
+                    while current < *to {
+                        environment.insert(v.id.clone(), Value::FieldElement(current.clone()));
+                        if let Some(values) =
+                            self.execute_statements(statements, environment, enforce)?
+                        {
+                            return Ok(Some(values));
+                        }
+                        current = T::one() + &current;
+                    }
+                }
+                TypedStatement::IfElse(ref condition, ref consequence, ref alternative) => {
+                    let condition = self.evaluate_boolean(condition, environment)?;
+
+                    // both branches are executed, and the variables take their values in the branch
+                    // which is taken
+                    let mut after_consequence = environment.clone();
+                    let mut after_alternative = environment.clone();
+
+                    let consequence_values = self.execute_statements(
+                        consequence,
+                        &mut after_consequence,
+                        enforce && condition,
+                    )?;
+                    let alternative_values = self.execute_statements(
+                        alternative,
+                        &mut after_alternative,
+                        enforce && !condition,
+                    )?;
+
+                    let (after, values) = match condition {
+                        true => (after_consequence, consequence_values),
+                        false => (after_alternative, alternative_values),
+                    };
+
+                    *environment = after;
+
+                    if values.is_some() {
+                        return Ok(values);
+                    }
+                }
+                TypedStatement::Log(ref condition, _, ref arguments) => {
+                    // the arguments are evaluated as they are in the compiled program, where computing
+                    // them can fail, but the message is not printed
+                    self.evaluate_boolean(condition, environment)?;
+                    self.evaluate_all(arguments, environment)?;
+                }
+                TypedStatement::Location(_) => {}
+                TypedStatement::MultipleDefinition(
+                    ref variables,
+                    TypedExpressionList::FunctionCall(ref id, ref arguments, ref types),
+                ) => {
+                    let arguments = self.evaluate_all(arguments, environment)?;
+                    let values = self.call(id, arguments, types.clone())?;
+                    for (v, value) in variables.iter().zip(values) {
+                        environment.insert(v.id.clone(), value);
+                    }
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn evaluate_all(
+        &self,
+        expressions: &Vec<TypedExpression<'ast, T>>,
+        environment: &Environment<'ast, T>,
+    ) -> Result<Vec<Value<T>>, Error> {
+        expressions
+            .iter()
+            .map(|e| self.evaluate(e, environment))
+            .collect()
+    }
+
+    fn evaluate(
+        &self,
+        e: &TypedExpression<'ast, T>,
+        environment: &Environment<'ast, T>,
+    ) -> Result<Value<T>, Error> {
+        match *e {
+            TypedExpression::FieldElement(ref e) => {
+                self.evaluate_field(e, environment).map(Value::FieldElement)
+            }
+            TypedExpression::Boolean(ref e) => {
+                self.evaluate_boolean(e, environment).map(Value::Boolean)
+            }
+            TypedExpression::FieldElementArray(ref e) => self
+                .evaluate_field_array(e, environment)
+                .map(Value::FieldElementArray),
+        }
+    }
+
+    fn evaluate_field(
+        &self,
+        e: &FieldElementExpression<'ast, T>,
+        environment: &Environment<'ast, T>,
+    ) -> Result<T, Error> {
+        match *e {
+            FieldElementExpression::Number(ref n) => Ok(n.clone()),
+            FieldElementExpression::Identifier(ref id) => Ok(get(environment, id).field_element()),
+            FieldElementExpression::Add(ref left, ref right) => {
+                let left = self.evaluate_field(left, environment)?;
+                let right = self.evaluate_field(right, environment)?;
+                Ok(left + right)
+            }
+            FieldElementExpression::Sub(ref left, ref right) => {
+                let left = self.evaluate_field(left, environment)?;
+                let right = self.evaluate_field(right, environment)?;
+                Ok(left - right)
+            }
+            FieldElementExpression::Mult(ref left, ref right) => {
+                let left = self.evaluate_field(left, environment)?;
+                let right = self.evaluate_field(right, environment)?;
+                Ok(left * right)
+            }
+            FieldElementExpression::Div(ref left, ref right) => {
+                let left = self.evaluate_field(left, environment)?;
+                let right = self.evaluate_field(right, environment)?;
+                match right == T::zero() {
+                    true => Err(Error::DivisionByZero),
+                    false => Ok(left / right),
+                }
+            }
+            FieldElementExpression::IntegerDiv(ref left, ref right, bitwidth) => self
+                .evaluate_integer_division(left, right, bitwidth, environment)
+                .map(|(quotient, _)| quotient),
+            FieldElementExpression::Rem(ref left, ref right, bitwidth) => self
+                .evaluate_integer_division(left, right, bitwidth, environment)
+                .map(|(_, remainder)| remainder),
+            FieldElementExpression::Pow(ref base, ref exponent) => {
+                let base = self.evaluate_field(base, environment)?;
+                let exponent = self.evaluate_field(exponent, environment)?;
+                check_range(&exponent, T::get_required_bits() - 2)?;
+
+                // square and multiply over the bits of the exponent, from the most significant one
+                let bits = exponent
+                    .into_byte_vector()
+                    .into_iter()
+                    .rev()
+                    .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1));
+
+                Ok(bits.fold(T::one(), |acc, bit| {
+                    let square = acc.clone() * &acc;
+                    match bit {
+                        true => square * &base,
+                        false => square,
+                    }
+                }))
+            }
+            FieldElementExpression::IfElse(ref condition, ref consequence, ref alternative) => {
+                let condition = self.evaluate_boolean(condition, environment)?;
+                let consequence = self.evaluate_field(consequence, environment)?;
+                let alternative = self.evaluate_field(alternative, environment)?;
+                Ok(if condition { consequence } else { alternative })
+            }
+            FieldElementExpression::FunctionCall(ref id, ref arguments) => {
+                let arguments = self.evaluate_all(arguments, environment)?;
+                let mut values = self.call(id, arguments, vec![Type::FieldElement])?;
+                Ok(values.remove(0).field_element())
+            }
+            FieldElementExpression::Select(ref array, ref index) => {
+                let array = self.evaluate_field_array(array, environment)?;
+                let index = self.evaluate_field(index, environment)?;
+                match to_index(&index, array.len()) {
+                    Some(i) => Ok(array[i].clone()),
+                    None => Err(Error::IndexOutOfBounds {
+                        index: index.to_dec_string(),
+                        size: array.len(),
+                    }),
+                }
+            }
+        }
+    }
+
+    /// Returns the quotient and the remainder of the division of `left` by `right`, which must
+    /// both fit in `bitwidth` bits
+    fn evaluate_integer_division(
+        &self,
+        left: &FieldElementExpression<'ast, T>,
+        right: &FieldElementExpression<'ast, T>,
+        bitwidth: usize,
+        environment: &Environment<'ast, T>,
+    ) -> Result<(T, T), Error> {
+        let left = self.evaluate_field(left, environment)?;
+        let right = self.evaluate_field(right, environment)?;
+        check_range(&left, bitwidth)?;
+        check_range(&right, bitwidth)?;
+
+        if right == T::zero() {
+            return Err(Error::DivisionByZero);
+        }
+
+        // the bitwidth is at most 125, so both operands fit in a u128
+        let left: u128 = left.to_dec_string().parse().unwrap();
+        let right: u128 = right.to_dec_string().parse().unwrap();
+
+        Ok((
+            T::try_from_dec_str(&(left / right).to_string()).unwrap(),
+            T::try_from_dec_str(&(left % right).to_string()).unwrap(),
+        ))
+    }
+
+    fn evaluate_boolean(
+        &self,
+        e: &BooleanExpression<'ast, T>,
+        environment: &Environment<'ast, T>,
+    ) -> Result<bool, Error> {
+        match *e {
+            BooleanExpression::Identifier(ref id) => match get(environment, id) {
+                Value::Boolean(b) => Ok(b),
+                v => panic!("expected a boolean, found {}", v),
+            },
+            BooleanExpression::Value(b) => Ok(b),
+            BooleanExpression::Lt(ref left, ref right) => self
+                .evaluate_comparison(left, right, environment)
+                .map(|(left, right)| left < right),
+            BooleanExpression::Le(ref left, ref right) => self
+                .evaluate_comparison(left, right, environment)
+                .map(|(left, right)| left <= right),
+            BooleanExpression::Gt(ref left, ref right) => self
+                .evaluate_comparison(left, right, environment)
+                .map(|(left, right)| left > right),
+            BooleanExpression::Ge(ref left, ref right) => self
+                .evaluate_comparison(left, right, environment)
+                .map(|(left, right)| left >= right),
+            BooleanExpression::Eq(ref left, ref right) => {
+                let left = self.evaluate_field(left, environment)?;
+                let right = self.evaluate_field(right, environment)?;
+                Ok(left == right)
+            }
+            BooleanExpression::FieldElementArrayEq(ref left, ref right) => {
+                let left = self.evaluate_field_array(left, environment)?;
+                let right = self.evaluate_field_array(right, environment)?;
+                Ok(left == right)
+            }
+            BooleanExpression::BoolEq(ref left, ref right) => {
+                let left = self.evaluate_boolean(left, environment)?;
+                let right = self.evaluate_boolean(right, environment)?;
+                Ok(left == right)
+            }
+            BooleanExpression::Or(ref left, ref right) => {
+                let left = self.evaluate_boolean(left, environment)?;
+                let right = self.evaluate_boolean(right, environment)?;
+                Ok(left || right)
+            }
+            BooleanExpression::And(ref left, ref right) => {
+                let left = self.evaluate_boolean(left, environment)?;
+                let right = self.evaluate_boolean(right, environment)?;
+                Ok(left && right)
+            }
+            BooleanExpression::Xor(ref left, ref right) => {
+                let left = self.evaluate_boolean(left, environment)?;
+                let right = self.evaluate_boolean(right, environment)?;
+                Ok(left != right)
+            }
+            BooleanExpression::Not(ref e) => self.evaluate_boolean(e, environment).map(|b| !b),
+        }
+    }
+
+    /// Evaluates the operands of a comparison, which must fit in `bitwidth - 2` bits
+    fn evaluate_comparison(
+        &self,
+        left: &FieldElementExpression<'ast, T>,
+        right: &FieldElementExpression<'ast, T>,
+        environment: &Environment<'ast, T>,
+    ) -> Result<(T, T), Error> {
+        let left = self.evaluate_field(left, environment)?;
+        let right = self.evaluate_field(right, environment)?;
+        check_range(&left, T::get_required_bits() - 2)?;
+        check_range(&right, T::get_required_bits() - 2)?;
+        Ok((left, right))
+    }
+
+    fn evaluate_field_array(
+        &self,
+        e: &FieldElementArrayExpression<'ast, T>,
+        environment: &Environment<'ast, T>,
+    ) -> Result<Vec<T>, Error> {
+        match *e {
+            FieldElementArrayExpression::Identifier(_, ref id) => match get(environment, id) {
+                Value::FieldElementArray(v) => Ok(v),
+                v => panic!("expected an array, found {}", v),
+            },
+            FieldElementArrayExpression::Value(_, ref expressions) => expressions
+                .iter()
+                .map(|e| self.evaluate_field(e, environment))
+                .collect(),
+            FieldElementArrayExpression::FunctionCall(size, ref id, ref arguments) => {
+                let arguments = self.evaluate_all(arguments, environment)?;
+                let mut values = self.call(id, arguments, vec![Type::FieldElementArray(size)])?;
+                match values.remove(0) {
+                    Value::FieldElementArray(v) => Ok(v),
+                    v => panic!("expected an array, found {}", v),
+                }
+            }
+            FieldElementArrayExpression::IfElse(
+                ref condition,
+                ref consequence,
+                ref alternative,
+            ) => {
+                let condition = self.evaluate_boolean(condition, environment)?;
+                let consequence = self.evaluate_field_array(consequence, environment)?;
+                let alternative = self.evaluate_field_array(alternative, environment)?;
+                Ok(if condition { consequence } else { alternative })
+            }
+        }
+    }
+}
+
+fn get<'ast, T: Field>(environment: &Environment<'ast, T>, id: &Identifier<'ast>) -> Value<T> {
+    environment
+        .get(id)
+        .cloned()
+        .unwrap_or_else(|| panic!("{} should be defined", id))
+}
+
+/// Returns `index` as an index in an array of size `size`, if it is in bounds
+fn to_index<T: Field>(index: &T, size: usize) -> Option<usize> {
+    match *index < T::from(size) {
+        true => Some(index.to_dec_string().parse().unwrap()),
+        false => None,
+    }
+}
+
+fn check_range<T: Field>(value: &T, bitwidth: usize) -> Result<(), Error> {
+    match *value < T::from(2).pow(bitwidth) {
+        true => Ok(()),
+        false => Err(Error::OutOfRange {
+            value: value.to_dec_string(),
+            bitwidth,
+        }),
+    }
+}
+
+#[derive(PartialEq)]
+pub enum Error {
+    UnsatisfiedAssertion { left: String, right: String },
+    DivisionByZero,
+    OutOfRange { value: String, bitwidth: usize },
+    IndexOutOfBounds { index: String, size: usize },
+    NotBoolean { value: String },
+    Import { id: String, error: ir::Error },
+    WrongInputCount { expected: usize, received: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::UnsatisfiedAssertion {
+                ref left,
+                ref right,
+            } => write!(f, "Expected {} to equal {}", left, right),
+            Error::DivisionByZero => write!(f, "Division by zero"),
+            Error::OutOfRange {
+                ref value,
+                bitwidth,
+            } => write!(f, "Expected {} to fit in {} bits", value, bitwidth),
+            Error::IndexOutOfBounds { ref index, size } => write!(
+                f,
+                "Index {} is out of bounds for an array of size {}",
+                index, size
+            ),
+            Error::NotBoolean { ref value } => {
+                write!(f, "Expected a boolean input, found {}", value)
+            }
+            Error::Import { ref id, ref error } => write!(f, "Call to {} failed: {}", id, error),
+            Error::WrongInputCount { expected, received } => write!(
+                f,
+                "Program takes {} input{} but was passed {} value{}",
+                expected,
+                if expected == 1 { "" } else { "s" },
+                received,
+                if received == 1 { "" } else { "s" }
+            ),
+        }
+    }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zokrates_field::field::{FieldPrime, Pow};
+
+    fn main(
+        arguments: Vec<Parameter<'static>>,
+        statements: Vec<TypedStatement<'static, FieldPrime>>,
+        outputs: Vec<Type>,
+    ) -> TypedProg<'static, FieldPrime> {
+        TypedProg {
+            functions: vec![TypedFunction {
+                id: "main",
+                signature: Signature::new()
+                    .inputs(arguments.iter().map(|p| p.id.get_type()).collect())
+                    .outputs(outputs),
+                arguments,
+                statements,
+            }],
+            imports: vec![],
+            imported_functions: vec![],
+            constants: vec![],
+        }
+    }
+
+    #[test]
+    fn arithmetic() {
+        // def main(field a) -> (field):
+        //     return a * a / 2
+        let prog = main(
+            vec![Parameter::private(Variable::field_element("a".into()))],
+            vec![TypedStatement::Return(vec![FieldElementExpression::Div(
+                box FieldElementExpression::Mult(
+                    box FieldElementExpression::Identifier("a".into()),
+                    box FieldElementExpression::Identifier("a".into()),
+                ),
+                box FieldElementExpression::Number(FieldPrime::from(2)),
+            )
+            .into()])],
+            vec![Type::FieldElement],
+        );
+
+        assert_eq!(
+            prog.execute(&vec![FieldPrime::from(4)]),
+            Ok(vec![FieldPrime::from(8)])
+        );
+        assert_eq!(
+            prog.execute(&Vec::<FieldPrime>::new()),
+            Err(Error::WrongInputCount {
+                expected: 1,
+                received: 0
+            })
+        );
+    }
+
+    #[test]
+    fn if_else_statement() {
+        // def main(field a) -> (field):
+        //     field b = 0
+        //     if a == 1 then
+        //         b = 1
+        //         a == 1
+        //     else
+        //         b = 2 / a
+        //     endif
+        //     return b
+        let prog = main(
+            vec![Parameter::private(Variable::field_element("a".into()))],
+            vec![
+                TypedStatement::Definition(
+                    TypedAssignee::Identifier(Variable::field_element("b".into())),
+                    FieldElementExpression::Number(FieldPrime::from(0)).into(),
+                ),
+                TypedStatement::IfElse(
+                    BooleanExpression::Eq(
+                        box FieldElementExpression::Identifier("a".into()),
+                        box FieldElementExpression::Number(FieldPrime::from(1)),
+                    ),
+                    vec![
+                        TypedStatement::Definition(
+                            TypedAssignee::Identifier(Variable::field_element("b".into())),
+                            FieldElementExpression::Number(FieldPrime::from(1)).into(),
+                        ),
+                        TypedStatement::Condition(
+                            FieldElementExpression::Identifier("a".into()).into(),
+                            FieldElementExpression::Number(FieldPrime::from(1)).into(),
+                        ),
+                    ],
+                    vec![TypedStatement::Definition(
+                        TypedAssignee::Identifier(Variable::field_element("b".into())),
+                        FieldElementExpression::Div(
+                            box FieldElementExpression::Number(FieldPrime::from(2)),
+                            box FieldElementExpression::Identifier("a".into()),
+                        )
+                        .into(),
+                    )],
+                ),
+                TypedStatement::Return(vec![FieldElementExpression::Identifier("b".into()).into()]),
+            ],
+            vec![Type::FieldElement],
+        );
+
+        // the assertion of the branch which is not taken is not enforced
+        assert_eq!(
+            prog.execute(&vec![FieldPrime::from(1)]),
+            Ok(vec![FieldPrime::from(1)])
+        );
+        assert_eq!(
+            prog.execute(&vec![FieldPrime::from(2)]),
+            Ok(vec![FieldPrime::from(1)])
+        );
+        // the division in the branch which is not taken still fails
+        assert_eq!(
+            prog.execute(&vec![FieldPrime::from(0)]),
+            Err(Error::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn comparison_range() {
+        // def main(field a, field b) -> (bool):
+        //     return a < b
+        let prog = main(
+            vec![
+                Parameter::private(Variable::field_element("a".into())),
+                Parameter::private(Variable::field_element("b".into())),
+            ],
+            vec![TypedStatement::Return(vec![BooleanExpression::Lt(
+                box FieldElementExpression::Identifier("a".into()),
+                box FieldElementExpression::Identifier("b".into()),
+            )
+            .into()])],
+            vec![Type::Boolean],
+        );
+
+        let max = FieldPrime::from(2).pow(252);
+
+        assert_eq!(
+            prog.execute(&vec![
+                FieldPrime::from(0),
+                max.clone() - FieldPrime::from(1)
+            ]),
+            Ok(vec![FieldPrime::from(1)])
+        );
+        assert_eq!(
+            prog.execute(&vec![FieldPrime::from(0), max.clone()]),
+            Err(Error::OutOfRange {
+                value: max.to_dec_string(),
+                bitwidth: 252
+            })
+        );
+    }
+
+    #[test]
+    fn boolean_input() {
+        // def main(bool a) -> (bool):
+        //     return !a
+        let prog = main(
+            vec![Parameter::private(Variable::boolean("a".into()))],
+            vec![TypedStatement::Return(vec![BooleanExpression::Not(
+                box BooleanExpression::Identifier("a".into()),
+            )
+            .into()])],
+            vec![Type::Boolean],
+        );
+
+        assert_eq!(
+            prog.execute(&vec![FieldPrime::from(1)]),
+            Ok(vec![FieldPrime::from(0)])
+        );
+        assert_eq!(
+            prog.execute(&vec![FieldPrime::from(2)]),
+            Err(Error::NotBoolean {
+                value: String::from("2")
+            })
+        );
+    }
+}
@@ -5,16 +5,20 @@
 //! @author Jacob Eberhardt <jacob.eberhardt@tu-berlin.de>
 //! @date 2017
 
+mod constant;
 pub mod folder;
+mod interpreter;
 mod parameter;
 mod variable;
 
+pub use crate::typed_absy::constant::ConstantValue;
 pub use crate::typed_absy::parameter::Parameter;
 pub use crate::typed_absy::variable::Variable;
 use crate::types::Signature;
 
 use crate::flat_absy::*;
 use crate::imports::Import;
+use crate::parser::Position;
 use crate::types::Type;
 use std::fmt;
 use zokrates_field::field::Field;
@@ -60,6 +64,8 @@ pub struct TypedProg<'ast, T: Field> {
     pub functions: Vec<TypedFunction<'ast, T>>,
     pub imports: Vec<Import>,
     pub imported_functions: Vec<FlatFunction<T>>,
+    /// Constants declared in the program, which are visible to the programs importing it
+    pub constants: Vec<(String, ConstantValue<T>)>,
 }
 
 impl<'ast, T: Field> fmt::Display for TypedProg<'ast, T> {
@@ -71,6 +77,12 @@ impl<'ast, T: Field> fmt::Display for TypedProg<'ast, T> {
                 .map(|x| format!("{}", x))
                 .collect::<Vec<_>>(),
         );
+        res.extend(
+            self.constants
+                .iter()
+                .map(|(id, v)| format!("const {} {} = {}", v.get_type(), id, v))
+                .collect::<Vec<_>>(),
+        );
         res.extend(
             self.imported_functions
                 .iter()
@@ -211,7 +223,22 @@ pub enum TypedStatement<'ast, T: Field> {
     Declaration(Variable<'ast>),
     Condition(TypedExpression<'ast, T>, TypedExpression<'ast, T>),
     For(Variable<'ast>, T, T, Vec<TypedStatement<'ast, T>>),
+    IfElse(
+        BooleanExpression<'ast, T>,
+        Vec<TypedStatement<'ast, T>>,
+        Vec<TypedStatement<'ast, T>>,
+    ),
     MultipleDefinition(Vec<Variable<'ast>>, TypedExpressionList<'ast, T>),
+    /// A message printed if the condition holds, which only differs from `true` for logs inside an
+    /// if-else statement once it is unrolled
+    Log(
+        BooleanExpression<'ast, T>,
+        String,
+        Vec<TypedExpression<'ast, T>>,
+    ),
+    /// Marks the beginning of the statement found at this location in the source, to report the
+    /// errors found by the static analysis and for the debugger
+    Location((Position, Position)),
 }
 
 impl<'ast, T: Field> fmt::Debug for TypedStatement<'ast, T> {
@@ -241,9 +268,20 @@ impl<'ast, T: Field> fmt::Debug for TypedStatement<'ast, T> {
                 }
                 write!(f, "\tendfor")
             }
+            TypedStatement::IfElse(ref condition, ref consequence, ref alternative) => {
+                write!(
+                    f,
+                    "IfElse({:?}, {:?}, {:?})",
+                    condition, consequence, alternative
+                )
+            }
             TypedStatement::MultipleDefinition(ref lhs, ref rhs) => {
                 write!(f, "MultipleDefinition({:?}, {:?})", lhs, rhs)
             }
+            TypedStatement::Log(ref condition, ref format, ref arguments) => {
+                write!(f, "Log({:?}, {:?}, {:?})", condition, format, arguments)
+            }
+            TypedStatement::Location(ref position) => write!(f, "Location({:?})", position),
         }
     }
 }
@@ -271,6 +309,17 @@ impl<'ast, T: Field> fmt::Display for TypedStatement<'ast, T> {
                 }
                 write!(f, "\tendfor")
             }
+            TypedStatement::IfElse(ref condition, ref consequence, ref alternative) => {
+                r#try!(write!(f, "if {} then\n", condition));
+                for l in consequence {
+                    r#try!(write!(f, "\t\t{}\n", l));
+                }
+                r#try!(write!(f, "\telse\n"));
+                for l in alternative {
+                    r#try!(write!(f, "\t\t{}\n", l));
+                }
+                write!(f, "\tendif")
+            }
             TypedStatement::MultipleDefinition(ref ids, ref rhs) => {
                 for (i, id) in ids.iter().enumerate() {
                     r#try!(write!(f, "{}", id));
@@ -280,6 +329,17 @@ impl<'ast, T: Field> fmt::Display for TypedStatement<'ast, T> {
                 }
                 write!(f, " = {}", rhs)
             }
+            TypedStatement::Log(ref condition, ref format, ref arguments) => {
+                if *condition != BooleanExpression::Value(true) {
+                    r#try!(write!(f, "if {} then ", condition));
+                }
+                r#try!(write!(f, "log(\"{}\"", format));
+                for a in arguments {
+                    r#try!(write!(f, ", {}", a));
+                }
+                write!(f, ")")
+            }
+            TypedStatement::Location(ref position) => write!(f, "// {}", position.0),
         }
     }
 }
@@ -391,6 +451,16 @@ pub enum FieldElementExpression<'ast, T: Field> {
         Box<FieldElementExpression<'ast, T>>,
         Box<FieldElementExpression<'ast, T>>,
     ),
+    IntegerDiv(
+        Box<FieldElementExpression<'ast, T>>,
+        Box<FieldElementExpression<'ast, T>>,
+        usize,
+    ),
+    Rem(
+        Box<FieldElementExpression<'ast, T>>,
+        Box<FieldElementExpression<'ast, T>>,
+        usize,
+    ),
     Pow(
         Box<FieldElementExpression<'ast, T>>,
         Box<FieldElementExpression<'ast, T>>,
@@ -423,6 +493,14 @@ pub enum BooleanExpression<'ast, T: Field> {
         Box<FieldElementExpression<'ast, T>>,
         Box<FieldElementExpression<'ast, T>>,
     ),
+    BoolEq(
+        Box<BooleanExpression<'ast, T>>,
+        Box<BooleanExpression<'ast, T>>,
+    ),
+    FieldElementArrayEq(
+        Box<FieldElementArrayExpression<'ast, T>>,
+        Box<FieldElementArrayExpression<'ast, T>>,
+    ),
     Ge(
         Box<FieldElementExpression<'ast, T>>,
         Box<FieldElementExpression<'ast, T>>,
@@ -439,6 +517,10 @@ pub enum BooleanExpression<'ast, T: Field> {
         Box<BooleanExpression<'ast, T>>,
         Box<BooleanExpression<'ast, T>>,
     ),
+    Xor(
+        Box<BooleanExpression<'ast, T>>,
+        Box<BooleanExpression<'ast, T>>,
+    ),
     Not(Box<BooleanExpression<'ast, T>>),
 }
 
@@ -475,6 +557,12 @@ impl<'ast, T: Field> fmt::Display for FieldElementExpression<'ast, T> {
             FieldElementExpression::Sub(ref lhs, ref rhs) => write!(f, "({} - {})", lhs, rhs),
             FieldElementExpression::Mult(ref lhs, ref rhs) => write!(f, "({} * {})", lhs, rhs),
             FieldElementExpression::Div(ref lhs, ref rhs) => write!(f, "({} / {})", lhs, rhs),
+            FieldElementExpression::IntegerDiv(ref lhs, ref rhs, ref bitwidth) => {
+                write!(f, "({} \\<{}> {})", lhs, bitwidth, rhs)
+            }
+            FieldElementExpression::Rem(ref lhs, ref rhs, ref bitwidth) => {
+                write!(f, "({} %<{}> {})", lhs, bitwidth, rhs)
+            }
             FieldElementExpression::Pow(ref lhs, ref rhs) => write!(f, "{}**{}", lhs, rhs),
             FieldElementExpression::IfElse(ref condition, ref consequent, ref alternative) => {
                 write!(
@@ -505,10 +593,15 @@ impl<'ast, T: Field> fmt::Display for BooleanExpression<'ast, T> {
             BooleanExpression::Lt(ref lhs, ref rhs) => write!(f, "{} < {}", lhs, rhs),
             BooleanExpression::Le(ref lhs, ref rhs) => write!(f, "{} <= {}", lhs, rhs),
             BooleanExpression::Eq(ref lhs, ref rhs) => write!(f, "{} == {}", lhs, rhs),
+            BooleanExpression::BoolEq(ref lhs, ref rhs) => write!(f, "{} == {}", lhs, rhs),
+            BooleanExpression::FieldElementArrayEq(ref lhs, ref rhs) => {
+                write!(f, "{} == {}", lhs, rhs)
+            }
             BooleanExpression::Ge(ref lhs, ref rhs) => write!(f, "{} >= {}", lhs, rhs),
             BooleanExpression::Gt(ref lhs, ref rhs) => write!(f, "{} > {}", lhs, rhs),
             BooleanExpression::Or(ref lhs, ref rhs) => write!(f, "{} || {}", lhs, rhs),
             BooleanExpression::And(ref lhs, ref rhs) => write!(f, "{} && {}", lhs, rhs),
+            BooleanExpression::Xor(ref lhs, ref rhs) => write!(f, "{} ^ {}", lhs, rhs),
             BooleanExpression::Not(ref exp) => write!(f, "!{}", exp),
             BooleanExpression::Value(b) => write!(f, "{}", b),
         }
@@ -566,6 +659,12 @@ impl<'ast, T: Field> fmt::Debug for FieldElementExpression<'ast, T> {
                 write!(f, "Mult({:?}, {:?})", lhs, rhs)
             }
             FieldElementExpression::Div(ref lhs, ref rhs) => write!(f, "Div({:?}, {:?})", lhs, rhs),
+            FieldElementExpression::IntegerDiv(ref lhs, ref rhs, ref bitwidth) => {
+                write!(f, "IntegerDiv({:?}, {:?}, {})", lhs, rhs, bitwidth)
+            }
+            FieldElementExpression::Rem(ref lhs, ref rhs, ref bitwidth) => {
+                write!(f, "Rem({:?}, {:?}, {})", lhs, rhs, bitwidth)
+            }
             FieldElementExpression::Pow(ref lhs, ref rhs) => write!(f, "Pow({:?}, {:?})", lhs, rhs),
             FieldElementExpression::IfElse(ref condition, ref consequent, ref alternative) => {
                 write!(
@@ -1,4 +1,3 @@
-use crate::absy;
 use crate::typed_absy::Variable;
 use std::fmt;
 
@@ -30,12 +29,3 @@ impl<'ast> fmt::Debug for Parameter<'ast> {
         write!(f, "Parameter(variable: {:?})", self.id)
     }
 }
-
-impl<'ast> From<absy::Parameter<'ast>> for Parameter<'ast> {
-    fn from(p: absy::Parameter<'ast>) -> Parameter {
-        Parameter {
-            private: p.private,
-            id: p.id.value.into(),
-        }
-    }
-}
@@ -1,4 +1,3 @@
-use crate::absy;
 use crate::typed_absy::Identifier;
 use crate::types::Type;
 use std::fmt;
@@ -42,16 +41,3 @@ impl<'ast> fmt::Debug for Variable<'ast> {
         write!(f, "Variable(type: {:?}, id: {:?})", self._type, self.id,)
     }
 }
-
-impl<'ast> From<absy::Variable<'ast>> for Variable<'ast> {
-    fn from(v: absy::Variable) -> Variable {
-        Variable::with_id_and_type(
-            Identifier {
-                id: v.id,
-                version: 0,
-                stack: vec![],
-            },
-            v._type,
-        )
-    }
-}
@@ -107,6 +107,7 @@ pub fn split<T: Field>() -> FlatProg<T> {
             statements,
             signature,
         }],
+        names: SourceNames::default(),
     }
 }
 
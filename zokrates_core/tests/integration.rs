@@ -13,5 +13,35 @@ zokrates_test! {
     array_if,
     fact_up_to_4,
     split,
+    quadratic_assertion,
     spread_slice,
 }
+
+#[cfg(feature = "wasm")]
+zokrates_test! {
+    r1cs_identity,
+}
+
+#[cfg(feature = "wasm")]
+zokrates_compile_error_test! {
+    r1cs_truncated: "Invalid witness generator r1cs/truncated.wasm: Error decoding buffer",
+    r1cs_no_signature: "Invalid witness generator r1cs/no_signature.wasm: Could not find exported symbol `min_inputs` in module",
+}
+
+zokrates_compile_error_test! {
+    array_size_expression: "Array size should be a constant or an identifier, found 1 + 1",
+    boolean_array: "Array elements should be field elements, found bool[2]",
+    non_equality_assertion: "Assertion statements should be an equality check, found a < 2",
+    slice_assignment: "Using slices in assignments is not supported, found 0..2",
+    multidimensional_assignment: "Cannot access element 1 on",
+    multidimensional_select: "Cannot access element 1 on expression of type field",
+    slice_on_field: "Cannot take a slice of expression of type field",
+    if_else_booleans: "should be field elements or arrays of field elements, found bool",
+    boolean_function_call: "Calls to functions returning bool are only supported in multiple definitions",
+    select_on_function_call: "Cannot access elements of foo() directly, use an intermediate variable",
+    empty_array: "Empty arrays are not supported",
+    index_out_of_bounds: "Index 2 is out of bounds for an array of size 2",
+    index_out_of_bounds_in_loop: "Index 2 is out of bounds for an array of size 2",
+    integer_bit_width: "Bit width of integer operations should be between 1 and 125, found 0",
+    if_else_function_call: "Function call to foo is not allowed inside if-else statements",
+}
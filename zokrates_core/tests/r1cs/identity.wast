@@ -0,0 +1,26 @@
+;; Witness generator for identity.r1cs, whose wires are [~one, out, in, t]
+;; with the constraints in * ~one = t and t * ~one = out.
+;; The input is read at offset 0 and the witness is written at offset 64.
+(module
+  (memory (export "memory") 1)
+  (global (export "min_inputs") i32 (i32.const 1))
+  (global (export "min_outputs") i32 (i32.const 4))
+  (global (export "field_size") i32 (i32.const 32))
+  (data (i32.const 64) "\01")
+  (func (export "get_inputs_off") (result i32)
+    (i32.const 0))
+  (func (export "solve") (result i32)
+    ;; copy the 32 bytes of the input to `out`, `in` and `t`, 8 bytes at a time
+    (i64.store (i32.const 96) (i64.load (i32.const 0)))
+    (i64.store (i32.const 104) (i64.load (i32.const 8)))
+    (i64.store (i32.const 112) (i64.load (i32.const 16)))
+    (i64.store (i32.const 120) (i64.load (i32.const 24)))
+    (i64.store (i32.const 128) (i64.load (i32.const 0)))
+    (i64.store (i32.const 136) (i64.load (i32.const 8)))
+    (i64.store (i32.const 144) (i64.load (i32.const 16)))
+    (i64.store (i32.const 152) (i64.load (i32.const 24)))
+    (i64.store (i32.const 160) (i64.load (i32.const 0)))
+    (i64.store (i32.const 168) (i64.load (i32.const 8)))
+    (i64.store (i32.const 176) (i64.load (i32.const 16)))
+    (i64.store (i32.const 184) (i64.load (i32.const 24)))
+    (i32.const 64)))
@@ -1,7 +1,9 @@
 extern crate serde_json;
 extern crate zokrates_field;
 
-use std::io;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
 use zokrates_core::compile::{compile as generic_compile, CompileErrors};
 use zokrates_core::ir;
 use zokrates_field::field::{Field, FieldPrime};
@@ -71,9 +73,8 @@ pub fn compare(
 }
 
 pub fn read_file(path: &str) -> String {
-    use std::fs::File;
     use std::io::Read;
-    let mut file = File::open(format!("./tests/bench/{}", path)).expect("Unable to open the file");
+    let mut file = File::open(format!("./tests/{}", path)).expect("Unable to open the file");
     let mut contents = String::new();
     file.read_to_string(&mut contents)
         .expect("Unable to read the file");
@@ -81,8 +82,27 @@ pub fn read_file(path: &str) -> String {
     contents
 }
 
+// imports are resolved relative to the test directory
+fn resolve(
+    _: &Option<String>,
+    source: &String,
+) -> Result<(BufReader<File>, String, String), io::Error> {
+    let path = Path::new("./tests").join(source);
+    let alias = path.file_stem().unwrap().to_string_lossy().to_string();
+    let file = File::open(&path)?;
+    Ok((
+        BufReader::new(file),
+        path.to_string_lossy().to_string(),
+        alias,
+    ))
+}
+
 pub fn compile(code: &str) -> Result<ir::Prog<FieldPrime>, CompileErrors> {
-    generic_compile::<FieldPrime, &[u8], &[u8], io::Error>(&mut code.as_bytes(), None, None)
+    generic_compile::<FieldPrime, &[u8], BufReader<File>, io::Error>(
+        &mut code.as_bytes(),
+        None,
+        Some(resolve),
+    )
 }
 
 macro_rules! zokrates_test {
@@ -93,8 +113,8 @@ macro_rules! zokrates_test {
 
                 use zokrates_field::field::{Field, FieldPrime};
 
-                let code_string = $crate::utils::read_file(&format!("./{}.code", stringify!($name)));
-                let test_string = $crate::utils::read_file(&format!("./{}.json", stringify!($name)));
+                let code_string = $crate::utils::read_file(&format!("bench/{}.code", stringify!($name)));
+                let test_string = $crate::utils::read_file(&format!("bench/{}.json", stringify!($name)));
 
                 let bin = $crate::utils::compile(&code_string).unwrap();
 
@@ -119,3 +139,23 @@ Called with input ({})
           )*
     };
 }
+
+macro_rules! zokrates_compile_error_test {
+    ($($name:ident: $message:expr,)*) => {
+          $(
+            #[test]
+            fn $name() {
+                let code_string = $crate::utils::read_file(&format!("compile_errors/{}.code", stringify!($name)));
+
+                let error = match $crate::utils::compile(&code_string) {
+                    Ok(..) => panic!("\n{}\n\nExpected a compile error", code_string),
+                    Err(e) => format!("{}", e),
+                };
+
+                // the error should point to the offending code
+                assert!(!error.contains("???:?\n"), "\n{}\n\nError has no position: {}", code_string, error);
+                assert!(error.contains($message), "\n{}\n\nExpected error `{}` but found `{}`", code_string, $message, error);
+            }
+          )*
+    };
+}
@@ -20,6 +20,19 @@ fn resolve_with_location(
     location: &String,
     source: &String,
 ) -> Result<(BufReader<File>, String, String), io::Error> {
+    let path = resolve_path(location, source)?;
+
+    if path.is_dir() {
+        return Err(io::Error::new(io::ErrorKind::Other, "Not a file"));
+    }
+
+    let (next_location, alias) = generate_next_parameters(&path)?;
+
+    File::open(path).and_then(|f| Ok((BufReader::new(f), next_location, alias)))
+}
+
+/// Returns the path of the file `source` refers to when imported from `location`
+pub fn resolve_path(location: &String, source: &String) -> Result<PathBuf, io::Error> {
     let source = PathBuf::from(source);
 
     // paths starting with `./` or `../` are interpreted relative to the current file
@@ -31,15 +44,7 @@ fn resolve_with_location(
         ),
     };
 
-    let path = base.join(PathBuf::from(source));
-
-    if path.is_dir() {
-        return Err(io::Error::new(io::ErrorKind::Other, "Not a file"));
-    }
-
-    let (next_location, alias) = generate_next_parameters(&path)?;
-
-    File::open(path).and_then(|f| Ok((BufReader::new(f), next_location, alias)))
+    Ok(base.join(PathBuf::from(source)))
 }
 
 fn generate_next_parameters(path: &PathBuf) -> Result<(String, String), io::Error> {
@@ -70,6 +75,12 @@ mod tests {
         assert!(res.is_err());
     }
 
+    #[test]
+    fn path_relative_to_location() {
+        let path = resolve_path(&String::from("./src"), &String::from("../foo/bar.code")).unwrap();
+        assert_eq!(path, PathBuf::from("./src/../foo/bar.code"));
+    }
+
     #[test]
     fn non_existing_file() {
         let res = resolve(&Some(String::from("./src")), &String::from("./rubbish.rs"));
@@ -14,6 +14,11 @@ pub fn parse(input: &str) -> Result<Pairs<Rule>, Error<Rule>> {
     ZoKratesParser::parse(Rule::file, input)
 }
 
+/// Parses the comments of `input`, which `parse` skips
+pub fn parse_comments(input: &str) -> Result<Pairs<Rule>, Error<Rule>> {
+    ZoKratesParser::parse(Rule::file_comments, input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,5 +147,67 @@ mod tests {
             let parse = ZoKratesParser::parse(Rule::iteration_statement, input);
             assert!(parse.is_ok());
         }
+
+        #[test]
+        fn parse_postfix_expression() {
+            let parse = ZoKratesParser::parse(Rule::postfix_expression, "foo(a)[0][1]");
+            assert_eq!(parse.unwrap().as_str(), "foo(a)[0][1]");
+
+            // only the first access can be a function call
+            let parse = ZoKratesParser::parse(Rule::postfix_expression, "a[0](1)");
+            assert_eq!(parse.unwrap().as_str(), "a[0]");
+        }
+
+        #[test]
+        fn parse_integer_operators() {
+            let input = "a \\<32> b + a %<32> b";
+
+            let parse = ZoKratesParser::parse(Rule::expression, input);
+            assert!(parse.is_ok());
+        }
+
+        #[test]
+        fn parse_generic_function() {
+            let input = "def sum<N, M>(field[N] a, field[M] b) -> (field[N]):\n return a";
+
+            let parse = ZoKratesParser::parse(Rule::function_definition, input);
+            assert!(parse.is_ok());
+        }
+
+        #[test]
+        fn parse_constant_definition() {
+            let input = "const field[2] A = [1, N + 1]\n";
+
+            let parse = ZoKratesParser::parse(Rule::constant_definition, input);
+            assert!(parse.is_ok());
+        }
+
+        #[test]
+        fn parse_file_comments() {
+            let input =
+                "import \"./a//b\" // the import\n/* a\nblock */ def main() -> (field): return 1";
+
+            let comments: Vec<_> = crate::parse_comments(input)
+                .unwrap()
+                .flat_map(|pair| pair.into_inner())
+                .filter(|pair| pair.as_rule() == Rule::comment)
+                .map(|pair| pair.as_str())
+                .collect();
+
+            assert_eq!(comments, vec!["// the import", "/* a\nblock */"]);
+        }
+
+        #[test]
+        fn parse_if_else_statement() {
+            let input = "if a == 1 then \n b = 2 \n c = 3 \n else \n b = 3 \n endif";
+
+            let parse = ZoKratesParser::parse(Rule::if_else_statement, input);
+            assert!(parse.is_ok());
+
+            let input = "if a == 1 then \n b = 2 \n endif";
+
+            let parse = ZoKratesParser::parse(Rule::if_else_statement, input);
+            assert!(parse.is_ok());
+        }
     }
 }
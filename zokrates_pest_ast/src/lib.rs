@@ -1,5 +1,5 @@
 use from_pest::FromPest;
-use pest::error::Error as PestError;
+use pest::error::{Error as PestError, ErrorVariant, LineColLocation};
 use pest::iterators::Pairs;
 use std::fmt;
 use zokrates_parser::parse;
@@ -9,12 +9,13 @@ extern crate lazy_static;
 
 pub use ast::{
     Access, ArrayAccess, ArrayInitializerExpression, ArrayType, AssertionStatement, Assignee,
-    AssignmentStatement, BasicType, BinaryExpression, BinaryOperator, CallAccess,
-    ConstantExpression, DefinitionStatement, Expression, File, FromExpression, Function,
-    IdentifierExpression, ImportDirective, ImportSource, InlineArrayExpression, IterationStatement,
-    MultiAssignmentStatement, Parameter, PostfixExpression, Range, RangeOrExpression,
-    ReturnStatement, Span, Spread, SpreadOrExpression, Statement, TernaryExpression, ToExpression,
-    Type, UnaryExpression, UnaryOperator, Visibility,
+    AssignmentStatement, BasicType, BinaryExpression, BinaryOperator, CallAccess, Comment,
+    ConstantDefinition, ConstantExpression, DefinitionStatement, ElseStatements, Expression, File,
+    FromExpression, Function, GenericParameter, IdentifierExpression, IfElseStatement,
+    ImportDirective, ImportSource, ImportSymbol, InlineArrayExpression, IterationStatement,
+    LogFormat, LogStatement, MultiAssignmentStatement, Parameter, PostfixExpression, Range,
+    RangeOrExpression, ReturnStatement, Span, Spread, SpreadOrExpression, Statement,
+    TernaryExpression, ToExpression, Type, UnaryExpression, UnaryOperator, Visibility,
 };
 
 mod ast {
@@ -43,7 +44,10 @@ mod ast {
                 | Operator::new(Rule::op_lt, Assoc::Left)
                 | Operator::new(Rule::op_gt, Assoc::Left),
             Operator::new(Rule::op_add, Assoc::Left) | Operator::new(Rule::op_sub, Assoc::Left),
-            Operator::new(Rule::op_mul, Assoc::Left) | Operator::new(Rule::op_div, Assoc::Left),
+            Operator::new(Rule::op_mul, Assoc::Left)
+                | Operator::new(Rule::op_div, Assoc::Left)
+                | Operator::new(Rule::op_integer_div, Assoc::Left)
+                | Operator::new(Rule::op_rem, Assoc::Left),
             Operator::new(Rule::op_pow, Assoc::Left),
         ])
     }
@@ -65,6 +69,12 @@ mod ast {
             Rule::op_sub => Expression::binary(BinaryOperator::Sub, lhs, rhs, span),
             Rule::op_mul => Expression::binary(BinaryOperator::Mul, lhs, rhs, span),
             Rule::op_div => Expression::binary(BinaryOperator::Div, lhs, rhs, span),
+            Rule::op_integer_div => {
+                Expression::binary(BinaryOperator::IntegerDiv(bit_width(pair)), lhs, rhs, span)
+            }
+            Rule::op_rem => {
+                Expression::binary(BinaryOperator::Rem(bit_width(pair)), lhs, rhs, span)
+            }
             Rule::op_pow => Expression::binary(BinaryOperator::Pow, lhs, rhs, span),
             Rule::op_equal => Expression::binary(BinaryOperator::Eq, lhs, rhs, span),
             Rule::op_not_equal => Expression::binary(BinaryOperator::NotEq, lhs, rhs, span),
@@ -79,6 +89,17 @@ mod ast {
         })
     }
 
+    // Read the bit width of an integer operator such as `\<32>`
+    // Widths which do not fit a `usize` are mapped to `usize::max_value()`, and are rejected during semantic checking
+    fn bit_width(pair: Pair<Rule>) -> usize {
+        pair.into_inner()
+            .next()
+            .unwrap()
+            .as_str()
+            .parse()
+            .unwrap_or(usize::max_value())
+    }
+
     // Create an Expression from an `expression`. `build_factor` turns each term into an `Expression` and `infix_rule` turns each (Expression, operator, Expression) into an Expression
     pub fn climb(pair: Pair<Rule>) -> Box<Expression> {
         PREC_CLIMBER.climb(pair.into_inner(), build_factor, infix_rule)
@@ -155,16 +176,41 @@ mod ast {
     #[pest_ast(rule(Rule::file))]
     pub struct File<'ast> {
         pub imports: Vec<ImportDirective<'ast>>,
+        pub constants: Vec<ConstantDefinition<'ast>>,
         pub functions: Vec<Function<'ast>>,
         pub eoi: EOI,
+        /// The comments of the file, which are skipped when parsing its other parts
+        #[pest_ast(outer(with(comments)))]
+        pub comments: Vec<Comment<'ast>>,
         #[pest_ast(outer())]
         pub span: Span<'ast>,
     }
 
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct Comment<'ast> {
+        pub value: String,
+        pub span: Span<'ast>,
+    }
+
+    // Collect the comments in the span of a file
+    // The span of a file starts at the beginning of the input, so the spans of the comments are the same in both
+    fn comments(span: Span) -> Vec<Comment> {
+        zokrates_parser::parse_comments(span.as_str())
+            .expect("`file_comments` matches any input")
+            .flat_map(|pair| pair.into_inner())
+            .filter(|pair| pair.as_rule() == Rule::comment)
+            .map(|pair| Comment {
+                value: pair.as_str().to_string(),
+                span: pair.as_span(),
+            })
+            .collect()
+    }
+
     #[derive(Debug, FromPest, PartialEq, Clone)]
     #[pest_ast(rule(Rule::function_definition))]
     pub struct Function<'ast> {
         pub id: IdentifierExpression<'ast>,
+        pub generics: Vec<GenericParameter<'ast>>,
         pub parameters: Vec<Parameter<'ast>>,
         pub returns: Vec<Type<'ast>>,
         pub statements: Vec<Statement<'ast>>,
@@ -172,11 +218,31 @@ mod ast {
         pub span: Span<'ast>,
     }
 
+    #[derive(Debug, FromPest, PartialEq, Clone)]
+    #[pest_ast(rule(Rule::generic_parameter))]
+    pub struct GenericParameter<'ast> {
+        pub id: IdentifierExpression<'ast>,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
     #[derive(Debug, FromPest, PartialEq, Clone)]
     #[pest_ast(rule(Rule::import_directive))]
     pub struct ImportDirective<'ast> {
         pub source: ImportSource<'ast>,
         pub alias: Option<IdentifierExpression<'ast>>,
+        /// The constants imported by name with `from "source" import ...`, in which case the main
+        /// function is not imported
+        pub symbols: Vec<ImportSymbol<'ast>>,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
+    #[derive(Debug, FromPest, PartialEq, Clone)]
+    #[pest_ast(rule(Rule::import_symbol))]
+    pub struct ImportSymbol<'ast> {
+        pub id: IdentifierExpression<'ast>,
+        pub alias: Option<IdentifierExpression<'ast>>,
         #[pest_ast(outer())]
         pub span: Span<'ast>,
     }
@@ -190,6 +256,16 @@ mod ast {
         pub span: Span<'ast>,
     }
 
+    #[derive(Debug, FromPest, PartialEq, Clone)]
+    #[pest_ast(rule(Rule::constant_definition))]
+    pub struct ConstantDefinition<'ast> {
+        pub ty: Type<'ast>,
+        pub id: IdentifierExpression<'ast>,
+        pub expression: Expression<'ast>,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
     #[derive(Debug, FromPest, PartialEq, Clone)]
     #[pest_ast(rule(Rule::ty))]
     pub enum Type<'ast> {
@@ -256,8 +332,10 @@ mod ast {
         Definition(DefinitionStatement<'ast>),
         Assertion(AssertionStatement<'ast>),
         Iteration(IterationStatement<'ast>),
+        IfElse(IfElseStatement<'ast>),
         Assignment(AssignmentStatement<'ast>),
         MultiAssignment(MultiAssignmentStatement<'ast>),
+        Log(LogStatement<'ast>),
     }
 
     #[derive(Debug, FromPest, PartialEq, Clone)]
@@ -299,6 +377,24 @@ mod ast {
         pub span: Span<'ast>,
     }
 
+    #[derive(Debug, FromPest, PartialEq, Clone)]
+    #[pest_ast(rule(Rule::if_else_statement))]
+    pub struct IfElseStatement<'ast> {
+        pub condition: Expression<'ast>,
+        pub consequence: Vec<Statement<'ast>>,
+        pub alternative: Option<ElseStatements<'ast>>,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
+    #[derive(Debug, FromPest, PartialEq, Clone)]
+    #[pest_ast(rule(Rule::else_statements))]
+    pub struct ElseStatements<'ast> {
+        pub statements: Vec<Statement<'ast>>,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
     #[derive(Debug, FromPest, PartialEq, Clone)]
     #[pest_ast(rule(Rule::multi_assignment_statement))]
     pub struct MultiAssignmentStatement<'ast> {
@@ -309,6 +405,24 @@ mod ast {
         pub span: Span<'ast>,
     }
 
+    #[derive(Debug, FromPest, PartialEq, Clone)]
+    #[pest_ast(rule(Rule::log_statement))]
+    pub struct LogStatement<'ast> {
+        pub format: LogFormat<'ast>,
+        pub arguments: Vec<Expression<'ast>>,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
+    #[derive(Debug, FromPest, PartialEq, Clone)]
+    #[pest_ast(rule(Rule::log_format))]
+    pub struct LogFormat<'ast> {
+        #[pest_ast(outer(with(span_into_unquoted_str)))]
+        pub value: String,
+        #[pest_ast(outer())]
+        pub span: Span<'ast>,
+    }
+
     #[derive(Debug, FromPest, PartialEq, Clone)]
     #[pest_ast(rule(Rule::return_statement))]
     pub struct ReturnStatement<'ast> {
@@ -326,6 +440,8 @@ mod ast {
         Sub,
         Mul,
         Div,
+        IntegerDiv(usize),
+        Rem(usize),
         Eq,
         NotEq,
         Lt,
@@ -581,6 +697,11 @@ mod ast {
         span.as_str().to_string()
     }
 
+    fn span_into_unquoted_str(span: Span) -> String {
+        let s = span.as_str();
+        s[1..s.len() - 1].to_string()
+    }
+
     #[derive(Debug, FromPest, PartialEq, Clone)]
     #[pest_ast(rule(Rule::EOI))]
     pub struct EOI;
@@ -603,6 +724,52 @@ impl fmt::Display for Error {
     }
 }
 
+impl Error {
+    /// The lines and columns where the error starts and ends
+    pub fn line_col(&self) -> ((usize, usize), (usize, usize)) {
+        match self.0.line_col {
+            LineColLocation::Pos(pos) => (pos, (pos.0, pos.1 + 1)),
+            LineColLocation::Span(start, end) => (start, end),
+        }
+    }
+
+    /// The message of the error, without the source snippet
+    pub fn message(&self) -> String {
+        match self.0.variant {
+            ErrorVariant::ParsingError {
+                ref positives,
+                ref negatives,
+            } => match (negatives.is_empty(), positives.is_empty()) {
+                (false, false) => format!(
+                    "unexpected {}; expected {}",
+                    enumerate(negatives),
+                    enumerate(positives)
+                ),
+                (false, true) => format!("unexpected {}", enumerate(negatives)),
+                (true, false) => format!("expected {}", enumerate(positives)),
+                (true, true) => "unknown parsing error".to_string(),
+            },
+            ErrorVariant::CustomError { ref message } => message.clone(),
+        }
+    }
+}
+
+fn enumerate(rules: &[Rule]) -> String {
+    match rules.len() {
+        1 => format!("{:?}", rules[0]),
+        2 => format!("{:?} or {:?}", rules[0], rules[1]),
+        l => format!(
+            "{}, or {:?}",
+            rules[..l - 1]
+                .iter()
+                .map(|r| format!("{:?}", r))
+                .collect::<Vec<_>>()
+                .join(", "),
+            rules[l - 1]
+        ),
+    }
+}
+
 pub fn generate_ast(input: &str) -> Result<ast::File, Error> {
     let parse_tree = parse(input).map_err(|e| Error(e))?;
     Ok(Prog::from(parse_tree).0)
@@ -680,6 +847,7 @@ mod tests {
                         value: String::from("main"),
                         span: Span::new(&source, 33, 37).unwrap()
                     },
+                    generics: vec![],
                     parameters: vec![],
                     returns: vec![Type::Basic(BasicType::Field(FieldType {}))],
                     statements: vec![Statement::Return(ReturnStatement {
@@ -698,15 +866,18 @@ mod tests {
                     })],
                     span: Span::new(&source, 29, source.len()).unwrap(),
                 }],
+                constants: vec![],
                 imports: vec![ImportDirective {
                     source: ImportSource {
                         value: String::from("foo"),
                         span: Span::new(&source, 8, 11).unwrap()
                     },
                     alias: None,
+                    symbols: vec![],
                     span: Span::new(&source, 0, 29).unwrap()
                 }],
                 eoi: EOI {},
+                comments: vec![],
                 span: Span::new(&source, 0, 65).unwrap()
             })
         );
@@ -725,6 +896,7 @@ mod tests {
                         value: String::from("main"),
                         span: Span::new(&source, 33, 37).unwrap()
                     },
+                    generics: vec![],
                     parameters: vec![],
                     returns: vec![Type::Basic(BasicType::Field(FieldType {}))],
                     statements: vec![Statement::Return(ReturnStatement {
@@ -757,15 +929,18 @@ mod tests {
                     })],
                     span: Span::new(&source, 29, 74).unwrap(),
                 }],
+                constants: vec![],
                 imports: vec![ImportDirective {
                     source: ImportSource {
                         value: String::from("foo"),
                         span: Span::new(&source, 8, 11).unwrap()
                     },
                     alias: None,
+                    symbols: vec![],
                     span: Span::new(&source, 0, 29).unwrap()
                 }],
                 eoi: EOI {},
+                comments: vec![],
                 span: Span::new(&source, 0, 74).unwrap()
             })
         );
@@ -784,6 +959,7 @@ mod tests {
                         value: String::from("main"),
                         span: Span::new(&source, 33, 37).unwrap()
                     },
+                    generics: vec![],
                     parameters: vec![],
                     returns: vec![Type::Basic(BasicType::Field(FieldType {}))],
                     statements: vec![Statement::Return(ReturnStatement {
@@ -806,20 +982,85 @@ mod tests {
                     })],
                     span: Span::new(&source, 29, 81).unwrap(),
                 }],
+                constants: vec![],
                 imports: vec![ImportDirective {
                     source: ImportSource {
                         value: String::from("foo"),
                         span: Span::new(&source, 8, 11).unwrap()
                     },
                     alias: None,
+                    symbols: vec![],
                     span: Span::new(&source, 0, 29).unwrap()
                 }],
                 eoi: EOI {},
+                comments: vec![],
                 span: Span::new(&source, 0, 81).unwrap()
             })
         );
     }
 
+    #[test]
+    fn from_import() {
+        let source = r#"from "foo" import A, B as C
+def main() -> (field): return A
+"#;
+        assert_eq!(
+            generate_ast(&source),
+            Ok(File {
+                functions: vec![Function {
+                    id: IdentifierExpression {
+                        value: String::from("main"),
+                        span: Span::new(&source, 32, 36).unwrap()
+                    },
+                    generics: vec![],
+                    parameters: vec![],
+                    returns: vec![Type::Basic(BasicType::Field(FieldType {}))],
+                    statements: vec![Statement::Return(ReturnStatement {
+                        expressions: vec![Expression::Identifier(IdentifierExpression {
+                            value: String::from("A"),
+                            span: Span::new(&source, 58, 59).unwrap()
+                        })],
+                        span: Span::new(&source, 51, 59).unwrap(),
+                    })],
+                    span: Span::new(&source, 28, 60).unwrap(),
+                }],
+                constants: vec![],
+                imports: vec![ImportDirective {
+                    source: ImportSource {
+                        value: String::from("foo"),
+                        span: Span::new(&source, 6, 9).unwrap()
+                    },
+                    alias: None,
+                    symbols: vec![
+                        ImportSymbol {
+                            id: IdentifierExpression {
+                                value: String::from("A"),
+                                span: Span::new(&source, 18, 19).unwrap()
+                            },
+                            alias: None,
+                            span: Span::new(&source, 18, 19).unwrap()
+                        },
+                        ImportSymbol {
+                            id: IdentifierExpression {
+                                value: String::from("B"),
+                                span: Span::new(&source, 21, 22).unwrap()
+                            },
+                            alias: Some(IdentifierExpression {
+                                value: String::from("C"),
+                                span: Span::new(&source, 26, 27).unwrap()
+                            }),
+                            span: Span::new(&source, 21, 27).unwrap()
+                        }
+                    ],
+                    span: Span::new(&source, 0, 28).unwrap()
+                }],
+                eoi: EOI {},
+                comments: vec![],
+                span: Span::new(&source, 0, 60).unwrap()
+            })
+        );
+    }
+
     #[test]
     fn parentheses() {
         let source = r#"def main() -> (field): return (1)
@@ -832,6 +1073,7 @@ mod tests {
                         value: String::from("main"),
                         span: Span::new(&source, 4, 8).unwrap()
                     },
+                    generics: vec![],
                     parameters: vec![],
                     returns: vec![Type::Basic(BasicType::Field(FieldType {}))],
                     statements: vec![Statement::Return(ReturnStatement {
@@ -843,8 +1085,10 @@ mod tests {
                     })],
                     span: Span::new(&source, 0, 34).unwrap(),
                 }],
+                constants: vec![],
                 imports: vec![],
                 eoi: EOI {},
+                comments: vec![],
                 span: Span::new(&source, 0, 34).unwrap()
             })
         );
@@ -862,6 +1106,7 @@ mod tests {
                         value: String::from("main"),
                         span: Span::new(&source, 4, 8).unwrap()
                     },
+                    generics: vec![],
                     parameters: vec![],
                     returns: vec![Type::Basic(BasicType::Field(FieldType {}))],
                     statements: vec![Statement::MultiAssignment(MultiAssignmentStatement {
@@ -908,13 +1153,57 @@ mod tests {
                     })],
                     span: Span::new(&source, 0, 50).unwrap(),
                 }],
+                constants: vec![],
                 imports: vec![],
                 eoi: EOI {},
+                comments: vec![],
                 span: Span::new(&source, 0, 50).unwrap()
             })
         );
     }
 
+    #[test]
+    fn retains_comments() {
+        let source = "// header\ndef main() -> (field):\n\treturn 1 // one\n";
+        assert_eq!(
+            generate_ast(&source).unwrap().comments,
+            vec![
+                Comment {
+                    value: String::from("// header"),
+                    span: Span::new(&source, 0, 9).unwrap()
+                },
+                Comment {
+                    value: String::from("// one"),
+                    span: Span::new(&source, 43, 49).unwrap()
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn log() {
+        let source =
+            "def main(field a) -> (field):\n\tlog(\" a = {} // not a comment\", a)\n\treturn a\n";
+        match &generate_ast(&source).unwrap().functions[0].statements[0] {
+            Statement::Log(s) => {
+                // spaces and slashes are kept in the format
+                assert_eq!(s.format.value, " a = {} // not a comment");
+                assert_eq!(s.arguments.len(), 1);
+            }
+            s => panic!("expected a log statement, found {:?}", s),
+        }
+    }
+
+    #[test]
+    fn error() {
+        let source = "def main() -> (field):\n\treturn 1 +\n";
+        let error = generate_ast(&source).unwrap_err();
+
+        // the error is reported on the second line, and its message does not quote the source
+        assert_eq!((error.line_col().0).0, 2);
+        assert!(!error.message().contains("-->"));
+    }
+
     #[test]
     fn playground() {
         let source = r#"import "heyman" as yo